@@ -1,5 +1,106 @@
 use super::{data, error, io, ui, ui::Screen};
 use ratatui::prelude::*;
+use std::{collections::HashMap, path};
+
+/// The index of a vault together with the select screen and file system access working on it.
+/// Vaults the user switched away from are kept, so their watchers keep collecting file events and switching back is instant.
+struct Vault {
+    /// The select screen showing the notes of this vault.
+    select: ui::screen::SelectScreen,
+    /// The index of the notes of this vault.
+    index: data::NoteIndexContainer,
+    /// Handles the file events of the index in the background.
+    watcher: data::IndexWatcher,
+    /// The file manager working on this vault.
+    manager: io::FileManager,
+    /// The HtmlBuilder working on this vault.
+    builder: io::HtmlBuilder,
+}
+
+impl Vault {
+    /// Indexes the vault at the given path and creates a select screen for it.
+    /// Also returns all errors that happened during creation that did not prevent the creation.
+    fn open(
+        config: &crate::Config,
+        vault_path: path::PathBuf,
+        styles: ui::UiStyles,
+    ) -> (Self, Vec<error::RucolaError>) {
+        // Gather errors
+        let mut errors = vec![];
+
+        let history = match ui::FilterHistory::load(&vault_path) {
+            Ok(history) => history,
+            Err(e) => {
+                errors.push(e);
+                Default::default()
+            }
+        };
+
+        let pinned = match data::PinnedNotes::load(&vault_path) {
+            Ok(pinned) => pinned,
+            Err(e) => {
+                errors.push(e);
+                Default::default()
+            }
+        };
+
+        let columns = match data::ColumnLayout::load(&vault_path) {
+            Ok(columns) => columns,
+            Err(e) => {
+                errors.push(e);
+                Default::default()
+            }
+        };
+
+        let builder = io::HtmlBuilder::new(config, vault_path.clone());
+
+        let manager = io::FileManager::new(config, vault_path.clone());
+
+        let cache = match data::IndexCache::load(&vault_path) {
+            Ok(cache) => cache,
+            Err(e) => {
+                errors.push(e);
+                Default::default()
+            }
+        };
+
+        let tracker = match io::FileTracker::new(config, vault_path) {
+            Ok(config) => config,
+            Err(e) => {
+                errors.push(e);
+                Default::default()
+            }
+        };
+
+        // Index all files in path, re-using the cached notes that did not change
+        let (index, index_errors) =
+            data::NoteIndex::new_with_cache(tracker, builder.clone(), cache);
+        errors.extend(index_errors);
+
+        let index = data::NoteIndexContainer::new(index);
+        let watcher = data::IndexWatcher::start(&index);
+
+        (
+            Self {
+                select: ui::screen::SelectScreen::new(
+                    index.clone(),
+                    manager.clone(),
+                    builder.clone(),
+                    styles,
+                    config,
+                    history,
+                    pinned,
+                    columns,
+                ),
+                index,
+                watcher,
+                manager,
+                builder,
+            },
+            errors,
+        )
+    }
+}
 
 /// The main state of the application.
 /// Consists of a select screen that is always existent, a stack of notes the user has navigated through and that he can navigate through by popping, reversing its navigation. Lastly, there is a display screen of the currently displayed note, which should always correspond to the top of the stack.
@@ -9,12 +110,33 @@ pub struct App {
     select: ui::screen::SelectScreen,
     /// The top of the display stack, if present.
     display: Option<ui::screen::DisplayScreen>,
-    /// The ids of note on the display stack
-    display_stack: Vec<String>,
+    /// The stats screen, if opened. Shown above the select screen, but below the display stack.
+    stats: Option<ui::screen::StatsScreen>,
+    /// The vault screen, if opened. Shown above the select screen.
+    vaults: Option<ui::screen::VaultScreen>,
+    /// The trash screen, if opened. Shown above the select screen.
+    trash: Option<ui::screen::TrashScreen>,
+    /// The graph screen, if opened. Shown above the select screen, but below the display stack.
+    graph: Option<ui::screen::GraphScreen>,
+    /// The display stacks of all open tabs, each holding the ids of the notes displayed in that tab.
+    /// Always contains at least one tab, only the active one may be empty.
+    tabs: Vec<Vec<String>>,
+    /// The index of the active tab, whose display stack determines the display screen.
+    tab: usize,
+    /// Whether the keybindings of the active screen are shown above it.
+    help: bool,
+    /// The path of the note last opened in the editor, to read it again once the editor is closed.
+    edited: Option<std::path::PathBuf>,
 
     // === DATA ===
     /// Index note data
     index: data::NoteIndexContainer,
+    /// Handles the file events of the index in the background.
+    watcher: data::IndexWatcher,
+    /// The vaults opened before, but not currently shown, by path.
+    parked: HashMap<path::PathBuf, Vault>,
+    /// The new index of the current vault being built in the background, if a rescan was requested.
+    rescan: Option<data::IndexRescan>,
 
     // === CONFIG ===
     /// The file manager this app's screens use to enact the user's file system requests on the file system.
@@ -23,6 +145,12 @@ pub struct App {
     builder: io::HtmlBuilder,
     /// The styles used by this app's screens.
     styles: ui::UiStyles,
+    /// The reading speed used to estimate reading times of displayed notes.
+    words_per_minute: usize,
+    /// The settings of the stats screen.
+    stats_config: ui::screen::StatsConfig,
+    /// The config the app was created from, used to open further vaults.
+    config: crate::Config,
 }
 
 impl App {
@@ -51,108 +179,265 @@ impl App {
             }
         };
 
-        let builder = io::HtmlBuilder::new(&config, vault_path.clone());
-
-        let manager = io::FileManager::new(&config, vault_path.clone());
-
-        let tracker = match io::FileTracker::new(&config, vault_path) {
-            Ok(config) => config,
-            Err(e) => {
-                errors.push(e);
-                Default::default()
-            }
-        };
-
-        // Index all files in path
-        let (index, index_errors) = data::NoteIndex::new(tracker, builder.clone());
-        errors.extend(index_errors);
+        let (stats_config, stats_config_error) = ui::screen::StatsConfig::new(&config);
+        errors.extend(stats_config_error);
 
-        let index = std::rc::Rc::new(std::cell::RefCell::new(index));
+        let (vault, vault_errors) = Vault::open(&config, vault_path, styles);
+        errors.extend(vault_errors);
 
         // Initialize app state
         (
             Self {
-                select: ui::screen::SelectScreen::new(
-                    index.clone(),
-                    manager.clone(),
-                    builder.clone(),
-                    styles,
-                    config.stats_show,
-                ),
+                select: vault.select,
                 display: None,
-                display_stack: Vec::new(),
-                index,
+                stats: None,
+                vaults: None,
+                trash: None,
+                graph: None,
+                edited: None,
+                help: false,
+                tabs: vec![Vec::new()],
+                tab: 0,
+                index: vault.index,
+                watcher: vault.watcher,
+                parked: HashMap::new(),
+                rescan: None,
                 styles,
-                manager,
-                builder,
+                manager: vault.manager,
+                builder: vault.builder,
+                words_per_minute: config.words_per_minute,
+                stats_config,
+                config,
             },
             errors,
         )
     }
 
+    /// Switches to the vault at the given path, indexing it if it was not opened before, and parks the current one.
+    /// Returns the first error that happened while indexing the new vault, after switching to it.
+    fn switch_vault(&mut self, vault_path: path::PathBuf) -> error::Result<()> {
+        let current_path = self.index.borrow().vault_path().to_path_buf();
+        if vault_path == current_path {
+            return Ok(());
+        }
+
+        let (mut vault, errors) = match self.parked.remove(&vault_path) {
+            Some(vault) => (vault, vec![]),
+            None => Vault::open(&self.config, vault_path, self.styles),
+        };
+
+        // exchange the current vault with the new one and park it
+        std::mem::swap(&mut self.select, &mut vault.select);
+        std::mem::swap(&mut self.index, &mut vault.index);
+        std::mem::swap(&mut self.watcher, &mut vault.watcher);
+        std::mem::swap(&mut self.manager, &mut vault.manager);
+        std::mem::swap(&mut self.builder, &mut vault.builder);
+        self.parked.insert(current_path, vault);
+
+        // notes of the previous vault can no longer be shown
+        self.tabs = vec![Vec::new()];
+        self.tab = 0;
+        self.display = None;
+        self.stats = None;
+        self.vaults = None;
+        self.trash = None;
+        self.graph = None;
+        // a rescan of the previous vault is no longer of interest
+        self.rescan = None;
+
+        match errors.into_iter().next() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Closes the tab at the given position, keeping the active tab in place if possible.
+    /// If the last tab was closed, a new empty one is opened.
+    fn close_tab(&mut self, position: usize) {
+        self.tabs.remove(position);
+        if self.tabs.is_empty() {
+            self.tabs.push(Vec::new());
+        }
+        if self.tab > position || self.tab >= self.tabs.len() {
+            self.tab = self.tab.saturating_sub(1);
+        }
+    }
+
+    /// Closes all tabs but the active one that no longer display any notes.
+    fn close_empty_tabs(&mut self) {
+        let mut position = 0;
+        while position < self.tabs.len() {
+            if position != self.tab && self.tabs[position].is_empty() {
+                self.close_tab(position);
+            } else {
+                position += 1;
+            }
+        }
+    }
+
     /// Reads the top of the display stack, creates a new display screen from it and sets that as the currently active display screen.
     /// If the display stack is empty, clears the display screen.
     fn set_display_to_top(&mut self) -> error::Result<()> {
-        self.display = match self.display_stack.last() {
+        self.display = match self.tabs[self.tab].last() {
             Some(id) => Some(ui::screen::DisplayScreen::new(
                 id,
                 self.index.clone(),
                 self.manager.clone(),
                 self.builder.clone(),
                 self.styles,
+                self.words_per_minute,
             )?),
             None => None,
         };
         Ok(())
     }
 
+    /// Replaces the index of the current vault with the rescanned one, if the rescan is done, and refreshes all screens showing it.
+    /// The new index replaces the old one within the same container, so all screens keep working on it.
+    /// Returns the first error that happened while indexing.
+    fn finish_rescan(&mut self) -> error::Result<()> {
+        if !self
+            .rescan
+            .as_ref()
+            .is_some_and(|rescan| rescan.is_finished())
+        {
+            return Ok(());
+        }
+        let Some(rescan) = self.rescan.take() else {
+            return Ok(());
+        };
+
+        let (index, errors) = rescan.finish()?;
+        *self.index.borrow_mut() = index;
+
+        // notes might have disappeared
+        let index = self.index.borrow();
+        for stack in self.tabs.iter_mut() {
+            stack.retain(|id| index.get(id).is_some());
+        }
+        drop(index);
+        self.close_empty_tabs();
+
+        self.select.refresh_env_stats();
+        self.set_display_to_top()?;
+        if self.stats.is_some() {
+            self.stats = Some(ui::screen::StatsScreen::new(
+                self.select.local_stats().clone(),
+                self.select.global_stats().clone(),
+                self.index.clone(),
+                self.manager.clone(),
+                self.styles,
+                self.stats_config.clone(),
+            ));
+        }
+
+        match errors.into_iter().next() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
     // Updates the app with the given key.
     pub fn update(
         &mut self,
         key: Option<crossterm::event::KeyEvent>,
     ) -> error::Result<ui::TerminalMessage> {
-        // Check for file changes
-        let mut index = self.index.borrow_mut();
-        let (modifications, id_changes) = index.handle_file_events()?;
-        drop(index);
+        // Swap in a finished rescan
+        self.finish_rescan()?;
+
+        // Read a note closed in the editor right away instead of waiting for its file events
+        if let Some(path) = self.edited.take() {
+            if path.exists() {
+                self.watcher.reload(path);
+            }
+        }
+
+        // Apply file changes read in the background
+        let (modifications, id_changes, errors) = self.watcher.apply(&self.index);
+        let changed = if modifications {
+            self.index.borrow_mut().take_changed()
+        } else {
+            Default::default()
+        };
 
-        // synchronize display stack with id changes from file events
-        'changes: for (old_id, maybe_new_id) in id_changes {
-            // if an id was changed, update all displays referring to it
-            if let Some(new_id) = maybe_new_id {
-                for display_id in self.display_stack.iter_mut() {
-                    if *display_id == old_id {
-                        *display_id = new_id;
-                        continue 'changes;
+        // synchronize the display stacks of all tabs with id changes from file events
+        for (old_id, maybe_new_id) in id_changes {
+            for stack in self.tabs.iter_mut() {
+                match &maybe_new_id {
+                    // if an id was changed, update all displays referring to it
+                    Some(new_id) => {
+                        for display_id in stack.iter_mut() {
+                            if *display_id == old_id {
+                                *display_id = new_id.clone();
+                            }
+                        }
                     }
+                    // if an id was deleted, remove all such displays from the stack
+                    None => stack.retain(|display_id| *display_id != old_id),
                 }
-            } else {
-                // if an id was deleted, remove all such displays from the stack
-                self.display_stack
-                    .retain(|display_id| *display_id != old_id);
             }
         }
 
         // remove 'empty' ids, indicating that
-        self.display_stack
-            .retain(|display_id| !display_id.is_empty());
+        for stack in self.tabs.iter_mut() {
+            stack.retain(|display_id| !display_id.is_empty());
+        }
+        self.close_empty_tabs();
 
         if modifications {
-            // if anything happened in the file system, better refresh the filters
-            self.select.refresh_env_stats();
+            // if anything happened in the file system, update the statistics of the changed notes
+            self.select.update_env_stats(&changed);
             // also refresh the display by setting it to none
             self.set_display_to_top()?;
+            // and the stats screen, if opened
+            if self.stats.is_some() {
+                self.stats = Some(ui::screen::StatsScreen::new(
+                    self.select.local_stats().clone(),
+                    self.select.global_stats().clone(),
+                    self.index.clone(),
+                    self.manager.clone(),
+                    self.styles,
+                    self.stats_config.clone(),
+                ));
+            }
+        }
+
+        // report problems reading the changed files once the changes that could be read are shown
+        if let Some(e) = errors.into_iter().next() {
+            return Err(e);
         }
 
+        // apply filters typed since the last update once the user stops typing
+        self.select.apply_pending_filter();
+
         let key = if let Some(key) = key {
             key
         } else {
             return Ok(ui::TerminalMessage::None);
         };
 
+        // While the keybindings are shown, keys only close them again
+        if self.help {
+            if matches!(
+                key.code,
+                crossterm::event::KeyCode::Esc | crossterm::event::KeyCode::Char('?')
+            ) {
+                self.help = false;
+            }
+            return Ok(ui::TerminalMessage::None);
+        }
+
         // Update appropriate screen
         let msg = if let Some(display) = &mut self.display {
             display.update(key)
+        } else if let Some(vaults) = &mut self.vaults {
+            vaults.update(key)
+        } else if let Some(trash) = &mut self.trash {
+            trash.update(key)
+        } else if let Some(graph) = &mut self.graph {
+            graph.update(key)
+        } else if let Some(stats) = &mut self.stats {
+            stats.update(key)
         } else {
             self.select.update(key)
         };
@@ -163,31 +448,292 @@ impl App {
         match &msg {
             // Message that do not modify the app trigger no immediate effect and are later passed up.
             ui::Message::None | ui::Message::Quit | ui::Message::OpenExternalCommand(_) => {}
+            ui::Message::DisplayHelp => {
+                self.help = true;
+            }
+            ui::Message::EditNote(_, path) => {
+                // The editor is opened by the terminal, the note is read again on the next update
+                self.edited = Some(path.clone());
+            }
             ui::Message::DisplayStackClear => {
                 // Clear the display stack and remove the current display screen, if there is one.
-                self.display_stack.clear();
+                self.tabs[self.tab].clear();
                 self.display = None;
             }
             ui::Message::DisplayStackPop => {
                 // Pop the top of the stack - which should correspond to the currently displayed note.
-                self.display_stack.pop();
+                self.tabs[self.tab].pop();
+                // A tab without notes is closed, unless it is the only one left.
+                if self.tabs[self.tab].is_empty() && self.tabs.len() > 1 {
+                    self.close_tab(self.tab);
+                }
                 self.set_display_to_top()?;
             }
             ui::Message::DisplayStackPush(new_id) => {
                 // Push a new id on top of the display stack.
-                self.display_stack.push(new_id.clone());
+                self.tabs[self.tab].push(new_id.clone());
+                self.set_display_to_top()?;
+            }
+            ui::Message::DisplayAnchor(new_id, anchor) => {
+                // Push a new id on top of the display stack and let its display know where to look.
+                self.tabs[self.tab].push(new_id.clone());
+                self.set_display_to_top()?;
+                if let Some(display) = &mut self.display {
+                    display.set_anchor(anchor.clone());
+                }
+            }
+            ui::Message::DisplayRandom(ids) => {
+                // Push a randomly chosen id on top of the display stack, if there is any to choose from.
+                if !ids.is_empty() {
+                    self.tabs[self.tab].push(ids[fastrand::usize(..ids.len())].clone());
+                    self.set_display_to_top()?;
+                }
+            }
+            ui::Message::TabOpen(new_id, anchor) => {
+                // Open a new tab right of the current one, unless the current one is empty and can be reused.
+                if !self.tabs[self.tab].is_empty() {
+                    self.tab += 1;
+                    self.tabs.insert(self.tab, Vec::new());
+                }
+                self.tabs[self.tab].push(new_id.clone());
+                self.set_display_to_top()?;
+                if let (Some(display), Some(anchor)) = (&mut self.display, anchor) {
+                    display.set_anchor(anchor.clone());
+                }
+            }
+            ui::Message::TabNext => {
+                self.tab = (self.tab + 1) % self.tabs.len();
+                self.set_display_to_top()?;
+            }
+            ui::Message::TabPrevious => {
+                self.tab = (self.tab + self.tabs.len() - 1) % self.tabs.len();
                 self.set_display_to_top()?;
             }
+            ui::Message::TabClose => {
+                self.close_tab(self.tab);
+                self.set_display_to_top()?;
+            }
+            ui::Message::TabMove(offset) => {
+                // Move the current tab, keeping it within the tab bar.
+                let target = self
+                    .tab
+                    .saturating_add_signed(*offset)
+                    .min(self.tabs.len() - 1);
+                let stack = self.tabs.remove(self.tab);
+                self.tabs.insert(target, stack);
+                self.tab = target;
+            }
+            ui::Message::DisplayStats => {
+                // Open the stats screen with the statistics currently shown on the select screen.
+                self.stats = Some(ui::screen::StatsScreen::new(
+                    self.select.local_stats().clone(),
+                    self.select.global_stats().clone(),
+                    self.index.clone(),
+                    self.manager.clone(),
+                    self.styles,
+                    self.stats_config.clone(),
+                ));
+            }
+            ui::Message::CloseStats => {
+                self.stats = None;
+            }
+            ui::Message::DisplayVaults => {
+                // Open the vault screen with all configured vaults.
+                self.vaults = Some(ui::screen::VaultScreen::new(
+                    self.config.vaults()?,
+                    self.index.borrow().vault_path(),
+                    self.parked.keys().cloned().collect(),
+                    self.styles,
+                ));
+            }
+            ui::Message::CloseVaults => {
+                self.vaults = None;
+            }
+            ui::Message::SwitchVault(vault_path) => {
+                self.switch_vault(vault_path.clone())?;
+            }
+            ui::Message::Rescan => {
+                // Start indexing the vault again, unless already doing so.
+                if self.rescan.is_none() {
+                    self.rescan = Some(data::IndexRescan::start(&self.config, &self.index));
+                }
+            }
+            ui::Message::DisplayTrash => {
+                // Open the trash screen with all notes in the trash of the current vault.
+                self.trash = Some(ui::screen::TrashScreen::new(
+                    self.manager.clone(),
+                    self.styles,
+                ));
+            }
+            ui::Message::CloseTrash => {
+                self.trash = None;
+            }
+            ui::Message::DisplayGraph => {
+                // Open the graph screen with the notes currently shown on the select screen.
+                self.graph = Some(ui::screen::GraphScreen::new(
+                    self.select.local_stats(),
+                    self.index.clone(),
+                    self.styles,
+                ));
+            }
+            ui::Message::CloseGraph => {
+                self.graph = None;
+            }
         }
 
         Ok(msg.into())
     }
 
+    /// Returns the caches of the notes of all vaults opened during this session, to speed up indexing in the next session.
+    pub fn index_caches(&self) -> Vec<data::IndexCache> {
+        std::iter::once(&self.index)
+            .chain(self.parked.values().map(|vault| &vault.index))
+            .map(|index| data::IndexCache::new(&index.borrow()))
+            .collect()
+    }
+
+    /// Returns the statistics of the whole vault to be cached for the next session.
+    pub fn stats_cache(&self) -> data::StatsCache {
+        data::StatsCache::new(
+            self.index.borrow().vault_path(),
+            self.select.global_stats().summary(),
+        )
+    }
+
     pub fn draw(&self, area: Rect, buf: &mut Buffer) {
         if let Some(display) = &self.display {
-            display.draw(area, buf);
+            if self.tabs.len() > 1 {
+                let areas =
+                    Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).split(area);
+                self.draw_tab_bar(areas[0], buf);
+                display.draw(areas[1], buf);
+            } else {
+                display.draw(area, buf);
+            }
+        } else if let Some(vaults) = &self.vaults {
+            vaults.draw(area, buf);
+        } else if let Some(trash) = &self.trash {
+            trash.draw(area, buf);
+        } else if let Some(graph) = &self.graph {
+            graph.draw(area, buf);
+        } else if let Some(stats) = &self.stats {
+            stats.draw(area, buf);
         } else {
             self.select.draw(area, buf);
         }
+
+        if self.help {
+            self.draw_help(area, buf);
+        }
+
+        // Show that the index is being rebuilt in the top right corner
+        if self.rescan.is_some() {
+            Widget::render(
+                Line::styled(" Rescanning vault... ", self.styles.subtitle_style)
+                    .alignment(Alignment::Right),
+                Rect { height: 1, ..area },
+                buf,
+            );
+        }
+    }
+
+    /// Returns the screen that is currently shown and receives the user's input.
+    fn active_screen(&self) -> &dyn ui::Screen {
+        if let Some(display) = &self.display {
+            display
+        } else if let Some(vaults) = &self.vaults {
+            vaults
+        } else if let Some(trash) = &self.trash {
+            trash
+        } else if let Some(graph) = &self.graph {
+            graph
+        } else if let Some(stats) = &self.stats {
+            stats
+        } else {
+            &self.select
+        }
+    }
+
+    /// Draws a popup listing the keybindings currently active on the active screen.
+    fn draw_help(&self, area: Rect, buf: &mut Buffer) {
+        use ratatui::widgets::{block, Block, Cell, Clear, Row, Table};
+
+        let keybindings = self.active_screen().keybindings();
+
+        let keys_width = keybindings
+            .iter()
+            .map(|(keys, _)| keys.len())
+            .max()
+            .unwrap_or_default() as u16;
+
+        let help_height = keybindings.len() as u16 + 2;
+
+        let help_rows = keybindings.into_iter().map(|(keys, effect)| {
+            Row::new(vec![
+                Cell::from(keys).style(self.styles.hotkey_style),
+                Cell::from(effect).style(self.styles.text_style),
+            ])
+        });
+
+        let help_table = Table::new(
+            help_rows,
+            [Constraint::Length(keys_width), Constraint::Min(0)],
+        )
+        .column_spacing(2)
+        .block(
+            Block::bordered()
+                .title(ratatui::style::Styled::set_style(
+                    "Keybindings",
+                    self.styles.title_style,
+                ))
+                .title(
+                    block::Title::from(Line::from(vec![
+                        Span::styled("Esc", self.styles.hotkey_style),
+                        Span::styled(": Close", self.styles.text_style),
+                    ]))
+                    .position(block::Position::Bottom)
+                    .alignment(Alignment::Right),
+                ),
+        );
+
+        let popup_areas = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(help_height),
+            Constraint::Fill(1),
+        ])
+        .split(area);
+
+        let center_area = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(72),
+            Constraint::Fill(1),
+        ])
+        .split(popup_areas[1])[1];
+
+        // Clear the area and then render the keybindings on top.
+        Widget::render(Clear, center_area, buf);
+        Widget::render(help_table, center_area, buf);
+    }
+
+    /// Draws a bar listing the notes currently shown in all open tabs, highlighting the active one.
+    fn draw_tab_bar(&self, area: Rect, buf: &mut Buffer) {
+        let index = self.index.borrow();
+        let titles = self.tabs.iter().map(|stack| {
+            stack
+                .last()
+                .map(|id| index.get(id).map(|note| note.title()).unwrap_or(id))
+                .unwrap_or_default()
+                .to_owned()
+        });
+
+        Widget::render(
+            ratatui::widgets::Tabs::new(titles)
+                .style(self.styles.text_style)
+                .highlight_style(self.styles.selected_style)
+                .select(self.tab)
+                .divider(Span::styled("|", self.styles.subtitle_style)),
+            area,
+            buf,
+        );
     }
 }
@@ -1,16 +1,37 @@
-use std::path;
+use std::{collections::HashMap, path};
 
-use crate::{error, ui};
+use crate::{data, error, ui};
 
 /// Groups data passed by the user in the config file.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct Config {
     /// Path to the vault to index.
     pub(crate) vault_path: Option<path::PathBuf>,
+    /// Further vaults to switch to while running, by name.
+    pub(crate) vaults: HashMap<String, path::PathBuf>,
     /// File types to consider notes
     /// See the [default list](https://docs.rs/ignore/latest/src/ignore/default_types.rs.html) of the ignore crate for possible options.
     /// The "all" option matches all files.
     pub(crate) file_types: Vec<String>,
+    /// Gitignore-style patterns of files and folders to exclude from the vault, in addition to those ignored by `.gitignore` files.
+    pub(crate) exclude: Vec<String>,
+    /// The folder of note templates relative to the vault, excluded from the vault and offered when creating notes. Empty to disable.
+    pub(crate) templates: String,
+    /// The folder relative to the vault deleted notes are moved to, excluded from the vault. Empty to delete notes permanently.
+    pub(crate) trash: String,
+    /// The folder the undo journals are stored in. None to use the config directory.
+    pub(crate) journal_folder: Option<path::PathBuf>,
+    /// How to derive the ids of notes, by which they are linked.
+    pub(crate) id_scheme: data::IdScheme,
+    /// Where tags are read from in the body of notes.
+    pub(crate) tag_syntax: data::TagSyntax,
+    /// How words and characters of notes are counted.
+    pub(crate) text_counting: data::TextCounting,
+    /// The size in kilobytes above which only the beginning of a note is read when indexing. 0 to always read whole notes.
+    pub(crate) max_note_size: u64,
+    /// File extensions of attachments such as images that notes can link to.
+    pub(crate) attachment_extensions: Vec<String>,
     /// Default file ending for newly created notes
     pub(crate) default_extension: String,
     /// Selected theme
@@ -31,6 +52,24 @@ pub struct Config {
     pub(crate) katex: bool,
     /// A list of strings to replace in math mode to mimic latex commands
     pub(crate) math_replacements: Vec<(String, String)>,
+    /// Named filters that can be inserted into the filter bar via `@name`.
+    pub(crate) filters: HashMap<String, String>,
+    /// How to weigh title, tag and link matches when scoring notes against a filter.
+    pub(crate) score_weights: data::ScoreWeights,
+    /// Wether to ignore diacritics and plural endings when fuzzy matching note titles.
+    pub(crate) normalize_titles: bool,
+    /// The reading speed used to estimate reading times of notes.
+    pub(crate) words_per_minute: usize,
+    /// The amount of words to relate the amount of links of a note to, e.g. 100 for links per 100 words.
+    pub(crate) link_density_words: usize,
+    /// How many folders deep to group notes by on the statistics screen.
+    pub(crate) directory_depth: usize,
+    /// The file name of daily notes, with `YYYY`, `MM` and `DD` in place of the date.
+    pub(crate) daily_note_pattern: String,
+    /// When notes are considered important but stale.
+    pub(crate) stale: data::StaleCriteria,
+    /// How to weigh the problems of an environment when computing its health score.
+    pub(crate) health: data::HealthCriteria,
 }
 
 impl Default for Config {
@@ -39,10 +78,22 @@ impl Default for Config {
             enable_html: true,
             katex: true,
             vault_path: None,
+            vaults: HashMap::new(),
             theme: "default_dark".to_string(),
             stats_show: ui::screen::StatsShow::Both,
             editor: None,
             file_types: vec![String::from("markdown")],
+            exclude: vec![],
+            templates: String::from("templates"),
+            trash: String::from(".trash"),
+            journal_folder: None,
+            id_scheme: data::IdScheme::Name,
+            tag_syntax: data::TagSyntax::default(),
+            text_counting: data::TextCounting::Whitespace,
+            max_note_size: 1024,
+            attachment_extensions: ["png", "jpg", "jpeg", "gif", "svg", "webp", "pdf"]
+                .map(String::from)
+                .to_vec(),
             default_extension: String::from("md"),
             html_prepend: None,
             css: None,
@@ -51,6 +102,15 @@ impl Default for Config {
                 ("\\field".to_string(), "\\mathbb".to_string()),
                 ("\\liealg".to_string(), "\\mathfrak".to_string()),
             ],
+            filters: HashMap::new(),
+            score_weights: data::ScoreWeights::default(),
+            normalize_titles: false,
+            words_per_minute: 200,
+            link_density_words: 100,
+            directory_depth: 1,
+            daily_note_pattern: String::from("YYYY-MM-DD"),
+            stale: data::StaleCriteria::default(),
+            health: data::HealthCriteria::default(),
         }
     }
 }
@@ -65,24 +125,73 @@ impl Config {
         // get current dir
         let pwd = std::env::current_dir()?;
 
-        // Extract vault path. Expanduser expands `~` to the correct user home directory and similar.
-        let mut full_vault_path = args
-            .target_folder
-            // first attempt to extend the command line given path if one was passed
-            .and_then(|arg_string| expanduser::expanduser(arg_string).ok())
-            // if none was given, expand the path given from the config file
-            .or_else(|| {
-                config.vault_path.take().and_then(|conf_path_buf| {
-                    expanduser::expanduser(conf_path_buf.to_string_lossy()).ok()
+        // Extract vault path.
+        let full_vault_path =
+            args.target_folder
+                // first attempt to extend the command line given path if one was passed
+                .and_then(|arg_string| expand_path(&arg_string, &pwd))
+                // if none was given, expand the path given from the config file
+                .or_else(|| {
+                    config.vault_path.take().and_then(|conf_path_buf| {
+                        expand_path(&conf_path_buf.to_string_lossy(), &pwd)
+                    })
                 })
+                .unwrap_or_else(|| pwd.clone());
+
+        Ok((config, full_vault_path))
+    }
+
+    /// Returns the names and expanded, absolute paths of all configured vaults, sorted by name.
+    /// Vaults whose paths cannot be expanded are skipped.
+    pub fn vaults(&self) -> error::Result<Vec<(String, path::PathBuf)>> {
+        let pwd = std::env::current_dir()?;
+        let mut vaults = self
+            .vaults
+            .iter()
+            .filter_map(|(name, path)| {
+                expand_path(&path.to_string_lossy(), &pwd).map(|path| (name.clone(), path))
             })
-            .unwrap_or_else(|| pwd.clone());
+            .collect::<Vec<_>>();
+        vaults.sort();
+        Ok(vaults)
+    }
+}
 
-        // make sure path is absolute
-        if !full_vault_path.is_absolute() {
-            full_vault_path = pwd.join(full_vault_path);
-        }
+/// Expands `~` and similar in the given path to the correct user home directory and makes it absolute by prepending the given working directory if neccessary.
+fn expand_path(path: &str, pwd: &path::Path) -> Option<path::PathBuf> {
+    let path = expanduser::expanduser(path).ok()?;
 
-        Ok((config, full_vault_path))
+    // make sure path is absolute
+    if path.is_absolute() {
+        Some(path)
+    } else {
+        Some(pwd.join(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_vaults() {
+        let mut config = super::Config::default();
+        config.vaults.insert(
+            String::from("work"),
+            std::path::PathBuf::from("/work/notes"),
+        );
+        config
+            .vaults
+            .insert(String::from("personal"), std::path::PathBuf::from("notes"));
+
+        let vaults = config.vaults().unwrap();
+        assert_eq!(vaults.len(), 2);
+        assert_eq!(vaults[0].0, "personal");
+        assert_eq!(vaults[0].1, std::env::current_dir().unwrap().join("notes"));
+        assert_eq!(
+            vaults[1],
+            (
+                String::from("work"),
+                std::path::PathBuf::from("/work/notes")
+            )
+        );
     }
 }
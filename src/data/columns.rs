@@ -0,0 +1,176 @@
+use std::path;
+
+use crate::{data, error};
+
+/// A column of the note list on the select screen next to the note names, which are always shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Column {
+    Words,
+    Chars,
+    GlobalOutLinks,
+    LocalOutLinks,
+    GlobalInLinks,
+    LocalInLinks,
+    Broken,
+    Modified,
+    Reading,
+}
+
+impl Column {
+    /// All columns, in the order they are shown in.
+    pub const ALL: [Self; 9] = [
+        Self::Words,
+        Self::Chars,
+        Self::GlobalOutLinks,
+        Self::LocalOutLinks,
+        Self::GlobalInLinks,
+        Self::LocalInLinks,
+        Self::Broken,
+        Self::Modified,
+        Self::Reading,
+    ];
+
+    /// The mode sorting the note list by the values of this column.
+    pub fn sorting_mode(self) -> data::SortingMode {
+        match self {
+            Self::Words | Self::Reading => data::SortingMode::Words,
+            Self::Chars => data::SortingMode::Chars,
+            Self::GlobalOutLinks => data::SortingMode::GlobalOutLinks,
+            Self::LocalOutLinks => data::SortingMode::LocalOutLinks,
+            Self::GlobalInLinks => data::SortingMode::GlobalInLinks,
+            Self::LocalInLinks => data::SortingMode::LocalInLinks,
+            Self::Broken => data::SortingMode::Broken,
+            Self::Modified => data::SortingMode::Modified,
+        }
+    }
+
+    /// The header of this column in the note list, split around the letter of the key toggling it and sorting by it.
+    pub fn header(self) -> [&'static str; 3] {
+        match self {
+            Self::Words => ["  ", "W", "ords"],
+            Self::Chars => ["  ", "C", "hars"],
+            Self::GlobalOutLinks => ["Global", "O", "ut"],
+            Self::LocalOutLinks => ["LocalO", "u", "t"],
+            Self::GlobalInLinks => ["Global", "I", "n"],
+            Self::LocalInLinks => ["LocalI", "n", ""],
+            Self::Broken => [" ", "B", "roken"],
+            Self::Modified => ["", "M", "odified"],
+            Self::Reading => ["  ", "R", "eading"],
+        }
+    }
+
+    /// The width of this column in the note list.
+    pub fn width(self) -> u16 {
+        match self {
+            Self::Words | Self::Chars | Self::Broken => 8,
+            Self::GlobalOutLinks
+            | Self::LocalOutLinks
+            | Self::GlobalInLinks
+            | Self::LocalInLinks
+            | Self::Modified => 10,
+            Self::Reading => 9,
+        }
+    }
+}
+
+/// Which columns the note list on the select screen shows and how it is sorted while no filter is active.
+/// The layout is persisted between sessions in a state file of each vault in the config directory, see [data::vault_file_name].
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ColumnLayout {
+    /// The shown columns, in the order of [Column::ALL].
+    columns: Vec<Column>,
+    /// The mode the note list is sorted by.
+    pub sorting: data::SortingMode,
+    /// Wether the note list is sorted ascendingly.
+    pub ascending: bool,
+}
+
+impl Default for ColumnLayout {
+    fn default() -> Self {
+        Self {
+            columns: vec![
+                Column::Words,
+                Column::Chars,
+                Column::GlobalOutLinks,
+                Column::LocalOutLinks,
+                Column::GlobalInLinks,
+                Column::LocalInLinks,
+                Column::Reading,
+            ],
+            sorting: data::SortingMode::Name,
+            ascending: true,
+        }
+    }
+}
+
+impl ColumnLayout {
+    /// Loads the column layout of the vault at the given path from its state file, using the default layout if none exists.
+    pub fn load(vault: &path::Path) -> error::Result<Self> {
+        Ok(confy::load(
+            "rucola",
+            data::vault_file_name("columns", vault).as_str(),
+        )?)
+    }
+
+    /// Writes the column layout to the state file of the vault at the given path.
+    pub fn store(&self, vault: &path::Path) -> error::Result<()> {
+        Ok(confy::store(
+            "rucola",
+            data::vault_file_name("columns", vault).as_str(),
+            self,
+        )?)
+    }
+
+    /// Returns the shown columns, in the order they are shown in.
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// Shows the given column if it was hidden, and hides it otherwise.
+    /// Returns wether the column is now shown.
+    pub fn toggle(&mut self, column: Column) -> bool {
+        let shown = !self.columns.contains(&column);
+        self.columns = Column::ALL
+            .into_iter()
+            .filter(|&other| {
+                if other == column {
+                    shown
+                } else {
+                    self.columns.contains(&other)
+                }
+            })
+            .collect();
+        shown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Column, ColumnLayout};
+
+    #[test]
+    fn test_toggle() {
+        let mut layout = ColumnLayout::default();
+
+        assert!(!layout.toggle(Column::Chars));
+        assert!(!layout.columns().contains(&Column::Chars));
+        assert!(layout.toggle(Column::Modified));
+        assert!(layout.toggle(Column::Broken));
+        assert_eq!(
+            layout.columns(),
+            [
+                Column::Words,
+                Column::GlobalOutLinks,
+                Column::LocalOutLinks,
+                Column::GlobalInLinks,
+                Column::LocalInLinks,
+                Column::Broken,
+                Column::Modified,
+                Column::Reading,
+            ]
+        );
+        assert!(layout.toggle(Column::Chars));
+        assert_eq!(layout.columns()[1], Column::Chars);
+    }
+}
@@ -0,0 +1,402 @@
+use std::{cmp, iter::Peekable, str::Chars};
+
+/// A small expression evaluated per note, as entered via `expr:` in the filter string.
+/// Expressions support
+///  - number and string literals as well as `true` and `false`,
+///  - the note variables `name`, `path`, `words`, `chars`, `tags`, `links`, `aliases`, `inlinks`, `outlinks`, `broken`, `todos` and `done`,
+///  - any other identifier, which is looked up in the frontmatter of the note,
+///  - the methods `contains(x)`, `starts_with(x)`, `ends_with(x)` and `len()`,
+///  - arithmetic `+ - * /`, comparisons `== != < <= > >=` and the boolean operators `&& || !`.
+///
+/// Example: `words > 300 && tags.contains("#draft")`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression(Node);
+
+/// A value an expression can evaluate to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    List(Vec<String>),
+}
+
+/// A node of the syntax tree of an expression.
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    /// A fixed value.
+    Literal(Value),
+    /// A property of the note, looked up on evaluation.
+    Variable(String),
+    /// A method called on the value of the first node with the values of the other nodes as arguments.
+    Method(Box<Node>, String, Vec<Node>),
+    /// Logical negation.
+    Not(Box<Node>),
+    /// A binary operator with its two operands.
+    Binary(&'static str, Box<Node>, Box<Node>),
+}
+
+/// A token of the expression string.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    Identifier(String),
+    Operator(&'static str),
+}
+
+/// All operators, longer ones first so they are preferred when tokenizing.
+const OPERATORS: [&str; 17] = [
+    "&&", "||", "==", "!=", "<=", ">=", "<", ">", "!", "+", "-", "*", "/", "(", ")", ".", ",",
+];
+
+impl Expression {
+    /// Parses an expression from the given string.
+    /// Returns `None` if the string is not a valid expression, e.g. because it is still being typed.
+    pub fn parse(expression: &str) -> Option<Self> {
+        let tokens = tokenize(expression)?;
+        let mut tokens = tokens.into_iter().peekable();
+        let node = parse_or(&mut tokens)?;
+        // all tokens need to be used up
+        if tokens.next().is_some() {
+            return None;
+        }
+        Some(Self(node))
+    }
+
+    /// Evaluates this expression for the given note.
+    /// Returns `None` if the expression cannot be evaluated, e.g. because types do not match.
+    pub fn evaluate(&self, note: &super::Note, index: &super::NoteIndex) -> Option<Value> {
+        self.0.evaluate(note, index)
+    }
+
+    /// Checks wether this expression evaluates to `true` for the given note.
+    pub fn matches(&self, note: &super::Note, index: &super::NoteIndex) -> bool {
+        self.evaluate(note, index) == Some(Value::Bool(true))
+    }
+}
+
+impl Node {
+    /// Recursively evaluates this node for the given note.
+    fn evaluate(&self, note: &super::Note, index: &super::NoteIndex) -> Option<Value> {
+        match self {
+            Node::Literal(value) => Some(value.clone()),
+            Node::Variable(name) => Some(variable(name, note, index)),
+            Node::Not(inner) => match inner.evaluate(note, index)? {
+                Value::Bool(b) => Some(Value::Bool(!b)),
+                _ => None,
+            },
+            Node::Method(target, method, arguments) => {
+                let target = target.evaluate(note, index)?;
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| argument.evaluate(note, index))
+                    .collect::<Option<Vec<_>>>()?;
+                call(target, method, &arguments)
+            }
+            Node::Binary(operator, left, right) => {
+                // short-circuit boolean operators
+                if matches!(*operator, "&&" | "||") {
+                    let Value::Bool(left) = left.evaluate(note, index)? else {
+                        return None;
+                    };
+                    if left == (*operator == "||") {
+                        return Some(Value::Bool(left));
+                    }
+                    return match right.evaluate(note, index)? {
+                        Value::Bool(right) => Some(Value::Bool(right)),
+                        _ => None,
+                    };
+                }
+                binary(
+                    operator,
+                    left.evaluate(note, index)?,
+                    right.evaluate(note, index)?,
+                )
+            }
+        }
+    }
+}
+
+/// Looks up the value of the variable with the given name for the given note.
+fn variable(name: &str, note: &super::Note, index: &super::NoteIndex) -> Value {
+    match name {
+        "name" => Value::Text(note.name.clone()),
+        "title" => Value::Text(note.title().to_owned()),
+        "path" => Value::Text(index.relative_path(note).to_string_lossy().to_string()),
+        "words" => Value::Number(note.words as f64),
+        "chars" | "characters" => Value::Number(note.characters as f64),
+        "tags" => Value::List(note.tags.clone()),
+        "links" => Value::List(note.links.clone()),
+        "aliases" => Value::List(note.aliases.clone()),
+        "inlinks" => Value::Number(index.inlinks_count(&note.id) as f64),
+        "outlinks" => Value::Number(index.outlinks_count(note) as f64),
+        "broken" => Value::Number(index.broken_links_count(note) as f64),
+        "todos" => Value::Number(note.open_tasks as f64),
+        "done" => Value::Number(note.done_tasks as f64),
+        // everything else is a frontmatter field, missing fields are empty
+        _ => Value::Text(
+            note.frontmatter
+                .get(&name.to_lowercase())
+                .cloned()
+                .unwrap_or_default(),
+        ),
+    }
+}
+
+/// Calls the method of the given name on the target value.
+fn call(target: Value, method: &str, arguments: &[Value]) -> Option<Value> {
+    match (method, target, arguments) {
+        ("contains", Value::List(list), [Value::Text(item)]) => {
+            Some(Value::Bool(list.contains(item)))
+        }
+        ("contains", Value::Text(text), [Value::Text(part)]) => {
+            Some(Value::Bool(text.contains(part.as_str())))
+        }
+        ("starts_with", Value::Text(text), [Value::Text(part)]) => {
+            Some(Value::Bool(text.starts_with(part.as_str())))
+        }
+        ("ends_with", Value::Text(text), [Value::Text(part)]) => {
+            Some(Value::Bool(text.ends_with(part.as_str())))
+        }
+        ("len", Value::List(list), []) => Some(Value::Number(list.len() as f64)),
+        ("len", Value::Text(text), []) => Some(Value::Number(text.chars().count() as f64)),
+        _ => None,
+    }
+}
+
+/// Applies a non-boolean binary operator to two values.
+fn binary(operator: &str, left: Value, right: Value) -> Option<Value> {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => match operator {
+            "+" => Some(Value::Number(left + right)),
+            "-" => Some(Value::Number(left - right)),
+            "*" => Some(Value::Number(left * right)),
+            "/" => Some(Value::Number(left / right)),
+            _ => compare(operator, left.partial_cmp(&right)?),
+        },
+        (Value::Text(left), Value::Text(right)) => match operator {
+            "+" => Some(Value::Text(left + &right)),
+            _ => compare(operator, left.cmp(&right)),
+        },
+        (Value::Bool(left), Value::Bool(right)) if matches!(operator, "==" | "!=") => {
+            compare(operator, left.cmp(&right))
+        }
+        (Value::List(left), Value::List(right)) if matches!(operator, "==" | "!=") => {
+            Some(Value::Bool((left == right) == (operator == "==")))
+        }
+        _ => None,
+    }
+}
+
+/// Converts the result of a comparison to a boolean value according to the given comparison operator.
+fn compare(operator: &str, ordering: cmp::Ordering) -> Option<Value> {
+    let result = match operator {
+        "==" => ordering.is_eq(),
+        "!=" => ordering.is_ne(),
+        "<" => ordering.is_lt(),
+        "<=" => ordering.is_le(),
+        ">" => ordering.is_gt(),
+        ">=" => ordering.is_ge(),
+        _ => return None,
+    };
+    Some(Value::Bool(result))
+}
+
+/// Splits the expression string into tokens.
+fn tokenize(expression: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars: Peekable<Chars> = expression.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() {
+            let mut number = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+                number.push(c);
+            }
+            tokens.push(Token::Number(number.parse().ok()?));
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next()? {
+                    quote if quote == c => break,
+                    other => text.push(other),
+                }
+            }
+            tokens.push(Token::Text(text));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut identifier = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || matches!(c, '_' | '-')) {
+                identifier.push(c);
+            }
+            tokens.push(Token::Identifier(identifier));
+        } else {
+            let rest = chars.clone().collect::<String>();
+            let operator = OPERATORS
+                .iter()
+                .find(|operator| rest.starts_with(*operator))?;
+            for _ in 0..operator.len() {
+                chars.next();
+            }
+            tokens.push(Token::Operator(operator));
+        }
+    }
+
+    Some(tokens)
+}
+
+/// The token stream an expression is parsed from.
+type Tokens = Peekable<std::vec::IntoIter<Token>>;
+
+/// Parses a chain of left-associative binary operators from the given set, with operands parsed by the given function.
+fn parse_binary(
+    tokens: &mut Tokens,
+    operators: &[&'static str],
+    operand: fn(&mut Tokens) -> Option<Node>,
+) -> Option<Node> {
+    let mut left = operand(tokens)?;
+    while let Some(Token::Operator(operator)) = tokens
+        .next_if(|token| matches!(token, Token::Operator(operator) if operators.contains(operator)))
+    {
+        let right = operand(tokens)?;
+        left = Node::Binary(operator, Box::new(left), Box::new(right));
+    }
+    Some(left)
+}
+
+/// Parses a disjunction, binding weakest.
+fn parse_or(tokens: &mut Tokens) -> Option<Node> {
+    parse_binary(tokens, &["||"], parse_and)
+}
+
+/// Parses a conjunction.
+fn parse_and(tokens: &mut Tokens) -> Option<Node> {
+    parse_binary(tokens, &["&&"], parse_comparison)
+}
+
+/// Parses a comparison.
+fn parse_comparison(tokens: &mut Tokens) -> Option<Node> {
+    parse_binary(tokens, &["==", "!=", "<=", ">=", "<", ">"], parse_sum)
+}
+
+/// Parses a sum or difference.
+fn parse_sum(tokens: &mut Tokens) -> Option<Node> {
+    parse_binary(tokens, &["+", "-"], parse_product)
+}
+
+/// Parses a product or quotient.
+fn parse_product(tokens: &mut Tokens) -> Option<Node> {
+    parse_binary(tokens, &["*", "/"], parse_unary)
+}
+
+/// Parses a negation or a value with possible method calls.
+fn parse_unary(tokens: &mut Tokens) -> Option<Node> {
+    if tokens.next_if_eq(&Token::Operator("!")).is_some() {
+        return Some(Node::Not(Box::new(parse_unary(tokens)?)));
+    }
+
+    let mut node = parse_atom(tokens)?;
+    // method calls
+    while tokens.next_if_eq(&Token::Operator(".")).is_some() {
+        let Some(Token::Identifier(method)) = tokens.next() else {
+            return None;
+        };
+        if tokens.next() != Some(Token::Operator("(")) {
+            return None;
+        }
+        let mut arguments = Vec::new();
+        if tokens.next_if_eq(&Token::Operator(")")).is_none() {
+            loop {
+                arguments.push(parse_or(tokens)?);
+                match tokens.next()? {
+                    Token::Operator(",") => {}
+                    Token::Operator(")") => break,
+                    _ => return None,
+                }
+            }
+        }
+        node = Node::Method(Box::new(node), method, arguments);
+    }
+    Some(node)
+}
+
+/// Parses a literal, variable or parenthesized expression.
+fn parse_atom(tokens: &mut Tokens) -> Option<Node> {
+    match tokens.next()? {
+        Token::Number(number) => Some(Node::Literal(Value::Number(number))),
+        Token::Text(text) => Some(Node::Literal(Value::Text(text))),
+        Token::Identifier(identifier) => Some(match identifier.as_str() {
+            "true" => Node::Literal(Value::Bool(true)),
+            "false" => Node::Literal(Value::Bool(false)),
+            _ => Node::Variable(identifier),
+        }),
+        Token::Operator("(") => {
+            let inner = parse_or(tokens)?;
+            (tokens.next()? == Token::Operator(")")).then_some(inner)
+        }
+        Token::Operator(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Expression, Value};
+    use crate::{data, io};
+
+    #[test]
+    fn test_parsing() {
+        assert!(Expression::parse("words > 300 && tags.contains(\"#draft\")").is_some());
+        assert!(Expression::parse("!(words < 3 || chars >= 2 * 10)").is_some());
+        assert!(Expression::parse("name.len() == 5").is_some());
+
+        // incomplete expressions
+        assert!(Expression::parse("").is_none());
+        assert!(Expression::parse("words >").is_none());
+        assert!(Expression::parse("tags.contains(\"#dra").is_none());
+        assert!(Expression::parse("(words > 3").is_none());
+        assert!(Expression::parse("words 3").is_none());
+        assert!(Expression::parse("words # 3").is_none());
+    }
+
+    #[test]
+    fn test_evaluation() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let chart = index.get("chart").unwrap();
+        let os = index.get("operating-systems").unwrap();
+
+        let eval = |expression: &str, note| {
+            Expression::parse(expression)
+                .unwrap()
+                .evaluate(note, &index)
+        };
+
+        assert_eq!(eval("words", chart), Some(Value::Number(115.0)));
+        assert_eq!(eval("words * 2 + 1", chart), Some(Value::Number(231.0)));
+        assert_eq!(
+            eval("name + \"!\"", chart),
+            Some(Value::Text("Chart!".into()))
+        );
+        assert_eq!(eval("tags.len()", chart), Some(Value::Number(2.0)));
+        assert_eq!(eval("words > \"a\"", chart), None);
+        assert_eq!(eval("tags.nonsense()", chart), None);
+        assert_eq!(eval("status", chart), Some(Value::Text(String::new())));
+
+        let matches =
+            |expression: &str, note| Expression::parse(expression).unwrap().matches(note, &index);
+
+        assert!(matches("words > 100 && tags.contains('#topology')", chart));
+        assert!(!matches("words > 100 && tags.contains('#topology')", os));
+        assert!(matches("words > 1000 || broken == 1", chart));
+        assert!(matches("!(inlinks > 0) && outlinks >= 3", os));
+        assert!(matches("path.starts_with('common/notes/math')", chart));
+        assert!(matches("status != 'draft'", chart));
+        assert!(!matches("words", chart));
+    }
+}
@@ -1,4 +1,70 @@
 use fuzzy_matcher::FuzzyMatcher;
+use std::{
+    cmp,
+    collections::{HashMap, HashSet},
+    sync, time,
+};
+
+/// How deep named filters may refer to other named filters before expansion stops.
+const MAX_NAMED_DEPTH: usize = 8;
+
+/// The match score assigned to notes whose title matches a regular expression or an exact title search.
+const FIXED_MATCH_SCORE: i64 = 100;
+
+/// Describes how the title part of a filter is matched against note titles.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TitleMode {
+    /// Fuzzy matching, scoring notes by match quality.
+    #[default]
+    Fuzzy,
+    /// The title must contain the searched words exactly, ignoring case.
+    Exact,
+    /// The title must contain the searched words exactly, respecting case.
+    CaseSensitive,
+}
+
+impl TitleMode {
+    /// Returns the mode to switch to when cycling through modes.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Fuzzy => Self::Exact,
+            Self::Exact => Self::CaseSensitive,
+            Self::CaseSensitive => Self::Fuzzy,
+        }
+    }
+
+    /// A short description of this mode for display in the UI.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Fuzzy => "fuzzy",
+            Self::Exact => "exact",
+            Self::CaseSensitive => "case-sensitive",
+        }
+    }
+}
+
+/// Weights used to combine the different parts of a filter into a single match score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ScoreWeights {
+    /// Factor applied to the title match score.
+    pub title: i64,
+    /// Score added for every fulfilled tag inclusion condition.
+    pub tags: i64,
+    /// Score added for every fulfilled link inclusion condition.
+    pub links: i64,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        Self {
+            title: 1,
+            tags: 0,
+            links: 0,
+        }
+    }
+}
+
 /// Describes a way to filter notes by their contained tags and/or title
 #[derive(Debug, Default, Clone)]
 pub struct Filter {
@@ -6,30 +72,164 @@ pub struct Filter {
     pub any: bool,
     /// The tags to include and exclude by, hash included.
     pub tags: Vec<(String, bool)>,
+    /// The tags that need to be contained exactly, without their subtags matching, hash included.
+    pub exact_tags: Vec<(String, bool)>,
+    /// The tags to fuzzy match with the tags of the note, hash included.
+    pub fuzzy_tags: Vec<(String, bool)>,
     /// The links to look for or exclude, already converted to ids.
     pub links: Vec<(String, bool)>,
     /// The backlinks to look for or exclude, already converted to ids.
     pub blinks: Vec<(String, bool)>,
-    /// The words to search the note title for. Will be fuzzy matched with the note title.
+    /// The words to search the note title for. Will be matched with the note title according to the title mode.
     pub title: String,
+    /// How to match the title words with the note title.
+    pub title_mode: TitleMode,
+    /// Words that must not fuzzy match the note title.
+    pub title_excluded: Vec<String>,
+    /// A regular expression to match the note title with instead of fuzzy matching.
+    pub title_regex: Option<regex::Regex>,
+    /// A condition on the creation time of the note file.
+    pub created: Option<TimeCondition>,
+    /// A condition on the last modification time of the note file.
+    pub modified: Option<TimeCondition>,
+    /// A condition on the amount of words in the note.
+    pub words: Option<CountCondition>,
+    /// A condition on the amount of characters in the note.
+    pub chars: Option<CountCondition>,
+    /// A condition on the amount of open tasks in the note.
+    pub todos: Option<CountCondition>,
+    /// A condition on the amount of links pointing to the note.
+    pub inlinks: Option<CountCondition>,
+    /// A condition on the amount of valid links going out from the note.
+    pub outlinks: Option<CountCondition>,
+    /// Prefixes of the path relative to the vault that the note must or must not have.
+    pub paths: Vec<(String, bool)>,
+    /// Languages, as ISO 639-1 codes, the note must or must not be written in.
+    pub languages: Vec<(String, bool)>,
+    /// Notes the note must or must not be close to in the link graph.
+    pub near: Vec<(DistanceCondition, bool)>,
+    /// Frontmatter fields as pairs of lowercase key and value the note must or must not have.
+    pub fields: Vec<((String, String), bool)>,
+    /// Properties the note must or must not have.
+    pub properties: Vec<(NoteProperty, bool)>,
     /// Everything to be searched for in the full text of the notes, in lowercase.
     pub full_text: Option<String>,
+    /// An expression that needs to evaluate to `true` for the note, entered after `expr:`.
+    pub expression: Option<super::expression::Expression>,
+    /// A boolean combination of further filters, if the filter string made use of `AND`, `OR`, `NOT` or parentheses.
+    pub query: Option<Box<Query>>,
+    /// How to weigh the different conditions when computing the match score.
+    pub weights: ScoreWeights,
+    /// Wether to fold diacritics and strip simple plural endings before fuzzy matching titles.
+    pub normalize: bool,
+    /// Sorting modes and directions (ascending or not) to sort the matching notes by, most important first.
+    pub sorting: Vec<(super::SortingMode, bool)>,
 }
 
 impl Filter {
+    /// Creates a new filter from the given filter string.
     pub fn new(filter_string: &str, any: bool) -> Self {
+        Self::new_with_named(filter_string, any, &HashMap::new())
+    }
+
+    /// Creates a new filter from the given filter string, after replacing every word `@name` in front of the full text search with the named filter of that name.
+    pub fn new_with_named(filter_string: &str, any: bool, named: &HashMap<String, String>) -> Self {
         let mut tags = Vec::new();
+        let mut exact_tags = Vec::new();
+        let mut fuzzy_tags = Vec::new();
         let mut links = Vec::new();
         let mut blinks = Vec::new();
         let mut title = String::new();
+        let mut title_mode = TitleMode::Fuzzy;
+        let mut title_excluded = Vec::new();
+        let mut properties = Vec::new();
+        let mut paths = Vec::new();
+        let mut languages = Vec::new();
+        let mut near = Vec::new();
+        let mut fields = Vec::new();
+        let mut named_filters = Vec::new();
+        let mut created = None;
+        let mut modified = None;
+        let mut words = None;
+        let mut chars = None;
+        let mut todos = None;
+        let mut inlinks = None;
+        let mut outlinks = None;
 
-        let (filters, full_text) = filter_string
-            .split_once('|')
-            .map(|(filters, rest)| (filters, Some(rest.to_lowercase())))
+        // An expression takes up the rest of the filter string, as it may contain spaces and `|`
+        let (filter_string, expression) = filter_string
+            .split_once("expr:")
+            .map(|(filters, expression)| {
+                (filters, super::expression::Expression::parse(expression))
+            })
             .unwrap_or((filter_string, None));
 
+        // Regexes may contain `|` and parentheses, so they are taken out before the full text search and boolean operators are looked for
+        let (filters, title_regex, full_text) = split_regex(filter_string);
+        let title_regex = title_regex.and_then(|pattern| regex::Regex::new(&pattern).ok());
+        let full_text = full_text.map(|rest| rest.to_lowercase());
+
+        // Named filters are only expanded here, so their words stay in front of the full text search and expression
+        let filters = expand_named(&filters, named, 0);
+
+        // Sort directives apply to the filter as a whole, so they are extracted before anything else
+        let (sort_words, filter_words): (Vec<_>, Vec<_>) = filters
+            .split_whitespace()
+            .partition(|word| word.starts_with("sort:"));
+        let sorting = sort_words
+            .into_iter()
+            .filter_map(|word| {
+                let name = word.trim_start_matches("sort:");
+                match name.strip_prefix('-') {
+                    Some(name) => super::SortingMode::from_name(name).map(|mode| (mode, false)),
+                    None => super::SortingMode::from_name(name).map(|mode| (mode, true)),
+                }
+            })
+            .collect();
+        let filters = filter_words.join(" ");
+        let filters = filters.as_str();
+
+        // If boolean operators are used, delegate everything but the full text search to a query
+        if Query::is_query(filters) {
+            return Self {
+                any,
+                full_text,
+                expression,
+                query: Some(Box::new(Query::parse(filters, any, named))),
+                title_regex,
+                sorting,
+                ..Default::default()
+            };
+        }
+
         // Go through words
         for word in filters.split_whitespace() {
+            if let Some((name, expansion)) = word
+                .strip_prefix('@')
+                .and_then(|name| named.get_key_value(name))
+            {
+                // named filters that were not expanded in place are applied as a whole, without themselves to avoid loops
+                let mut others = named.clone();
+                others.remove(name);
+                named_filters.push(Query::Leaf(Self::new_with_named(expansion, any, &others)));
+                continue;
+            }
+            if let Some(tag) = word.strip_prefix("!#=") {
+                exact_tags.push((format!("#{tag}"), false));
+                continue;
+            }
+            if let Some(tag) = word.strip_prefix("#=") {
+                exact_tags.push((format!("#{tag}"), true));
+                continue;
+            }
+            if let Some(tag) = word.strip_prefix("!~#") {
+                fuzzy_tags.push((format!("#{tag}"), false));
+                continue;
+            }
+            if let Some(tag) = word.strip_prefix("~#") {
+                fuzzy_tags.push((format!("#{tag}"), true));
+                continue;
+            }
             if word.starts_with("!#") {
                 tags.push((word.trim_start_matches('!').to_string(), false));
                 continue;
@@ -66,7 +266,99 @@ impl Filter {
                 ));
                 continue;
             }
-            // if nothing else fits
+            if let Some(value) = word.strip_prefix("!path:") {
+                paths.push((value.to_string(), false));
+                continue;
+            }
+            if let Some(value) = word.strip_prefix("path:") {
+                paths.push((value.to_string(), true));
+                continue;
+            }
+            if let Some(value) = word.strip_prefix("!lang:") {
+                languages.push((value.to_lowercase(), false));
+                continue;
+            }
+            if let Some(value) = word.strip_prefix("lang:") {
+                languages.push((value.to_lowercase(), true));
+                continue;
+            }
+            if let Some(value) = word.strip_prefix("!near:") {
+                near.push((DistanceCondition::new(value), false));
+                continue;
+            }
+            if let Some(value) = word.strip_prefix("near:") {
+                near.push((DistanceCondition::new(value), true));
+                continue;
+            }
+            if let Some(value) = word
+                .strip_prefix("!has:")
+                .or_else(|| word.strip_prefix("!is:"))
+            {
+                properties.extend(NoteProperty::new(value).map(|property| (property, false)));
+                continue;
+            }
+            if let Some(value) = word
+                .strip_prefix("has:")
+                .or_else(|| word.strip_prefix("is:"))
+            {
+                properties.extend(NoteProperty::new(value).map(|property| (property, true)));
+                continue;
+            }
+            if let Some(excluded) = word.strip_prefix('!') {
+                if let Some(field) = split_field(excluded) {
+                    fields.push((field, false));
+                } else if !excluded.is_empty() {
+                    title_excluded.push(excluded.to_string());
+                }
+                continue;
+            }
+            if let Some(value) = word.strip_prefix("created:") {
+                created = TimeCondition::new(value);
+                continue;
+            }
+            if let Some(value) = word.strip_prefix("modified:") {
+                modified = TimeCondition::new(value);
+                continue;
+            }
+            if let Some(value) = word.strip_prefix("words:") {
+                words = CountCondition::new(value);
+                continue;
+            }
+            if let Some(value) = word.strip_prefix("chars:") {
+                chars = CountCondition::new(value);
+                continue;
+            }
+            if let Some(value) = word.strip_prefix("todo:") {
+                todos = CountCondition::new(value);
+                continue;
+            }
+            if let Some(value) = word.strip_prefix("inlinks:") {
+                inlinks = CountCondition::new(value);
+                continue;
+            }
+            if let Some(value) = word.strip_prefix("outlinks:") {
+                outlinks = CountCondition::new(value);
+                continue;
+            }
+            // unknown key:value pairs are constraints on the frontmatter
+            if let Some(field) = split_field(word) {
+                fields.push((field, true));
+                continue;
+            }
+            // if nothing else fits, match against the title, possibly exactly
+            let word = if let Some(word) = word.strip_prefix("==") {
+                title_mode = TitleMode::CaseSensitive;
+                word
+            } else if let Some(word) = word.strip_prefix('=') {
+                title_mode = TitleMode::Exact;
+                word
+            } else {
+                word
+            };
+            // the words are separated for exact matching, fuzzy matching concatenates them as before
+            if !title.is_empty() {
+                title.push(' ');
+            }
             title.push_str(word);
         }
 
@@ -74,11 +366,176 @@ impl Filter {
         Self {
             any,
             tags,
+            exact_tags,
+            fuzzy_tags,
             links,
             blinks,
             title,
+            title_mode,
+            title_excluded,
+            title_regex,
+            created,
+            modified,
+            words,
+            chars,
+            todos,
+            inlinks,
+            outlinks,
+            paths,
+            languages,
+            near,
+            fields,
+            properties,
             full_text,
+            expression,
+            query: named_filters
+                .into_iter()
+                .reduce(|left, right| {
+                    if any {
+                        Query::Or(Box::new(left), Box::new(right))
+                    } else {
+                        Query::And(Box::new(left), Box::new(right))
+                    }
+                })
+                .map(Box::new),
+            weights: ScoreWeights::default(),
+            normalize: false,
+            sorting,
+        }
+    }
+
+    /// Sets the given title mode for this filter and all filters of its query, unless they already specified a non-fuzzy mode themselves.
+    pub fn with_title_mode(mut self, mode: TitleMode) -> Self {
+        if self.title_mode == TitleMode::Fuzzy {
+            self.title_mode = mode;
+        }
+        if let Some(query) = self.query.take() {
+            self.query = Some(Box::new(query.with_title_mode(mode)));
+        }
+        self
+    }
+
+    /// Sets the given score weights for this filter and all filters of its query.
+    pub fn with_weights(mut self, weights: ScoreWeights) -> Self {
+        self.weights = weights;
+        if let Some(query) = self.query.take() {
+            self.query = Some(Box::new(query.with_weights(weights)));
+        }
+        self
+    }
+
+    /// Enables or disables normalization of titles before fuzzy matching for this filter and all filters of its query.
+    pub fn with_normalization(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        if let Some(query) = self.query.take() {
+            self.query = Some(Box::new(query.with_normalization(normalize)));
+        }
+        self
+    }
+
+    /// Forgets everything this filter remembered about the index it was applied to, so it can be applied to a changed index.
+    pub fn forget_index(&mut self) {
+        for (condition, _) in self.near.iter_mut() {
+            condition.forget_reachable();
+        }
+        if let Some(query) = self.query.as_mut() {
+            query.forget_index();
+        }
+    }
+
+    /// Checks wether applying this filter reads the notes from disk or searches the link graph, so it should not be applied on every keystroke.
+    pub fn is_expensive(&self) -> bool {
+        self.full_text.is_some()
+            || !self.near.is_empty()
+            || self
+                .query
+                .as_ref()
+                .is_some_and(|query| query.is_expensive())
+    }
+
+    /// Checks wether matching a note against this filter may depend on other notes of the index, e.g. on links pointing to it or on aliases resolving its links.
+    /// If not, the filter only needs to be re-applied to notes that changed themselves.
+    pub fn depends_on_other_notes(&self) -> bool {
+        !self.links.is_empty()
+            || !self.blinks.is_empty()
+            || !self.near.is_empty()
+            || self.inlinks.is_some()
+            || self.outlinks.is_some()
+            || self.expression.is_some()
+            || self
+                .properties
+                .iter()
+                .any(|(property, _)| *property != NoteProperty::Todo)
+            || self
+                .query
+                .as_ref()
+                .is_some_and(|query| query.depends_on_other_notes())
+    }
+
+    /// Checks wether the filter created from the string `new` can only match notes also matched by the filter created from `old`, with all other settings being equal.
+    /// This is judged conservatively from the strings alone and only valid for filters requiring all conditions to be fulfilled.
+    /// It holds when `new` appends further conditions to `old`, or extends its last title word or its full text search.
+    pub fn narrows(old: &str, new: &str) -> bool {
+        let Some(appended) = new.strip_prefix(old) else {
+            return false;
+        };
+        // boolean operators, named filters and expressions can widen a filter arbitrarily
+        if Query::is_query(new) || new.contains('@') || new.contains("expr:") {
+            return false;
+        }
+        // extending the full text search only adds conditions
+        if split_regex(old).2.is_some() {
+            return true;
+        }
+        // conditions that can only be given once are replaced by a later word, which can widen the filter
+        let (appended_filters, appended_regex, _full_text) = split_regex(appended);
+        if appended_regex.is_some()
+            || appended_filters
+                .split_whitespace()
+                .any(|word| SINGLE_VALUED_KEYS.iter().any(|key| word.starts_with(key)))
+        {
+            return false;
         }
+        // adding new words only adds conditions
+        if old.is_empty()
+            || old.ends_with(char::is_whitespace)
+            || appended.starts_with(char::is_whitespace)
+        {
+            return true;
+        }
+        // otherwise, the last word was extended, which is only safe for plain title words
+        let old_word = old.split_whitespace().last().unwrap_or_default();
+        let new_word = new.split_whitespace().last().unwrap_or_default();
+        let is_plain =
+            |word: &str| !word.starts_with(['#', '!', '>', '<', '~', '=']) && !word.contains(':');
+        is_plain(old_word) && is_plain(new_word)
+    }
+
+    /// Wether this filter contains no conditions at all and thus matches every note.
+    fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+            && self.exact_tags.is_empty()
+            && self.fuzzy_tags.is_empty()
+            && self.links.is_empty()
+            && self.blinks.is_empty()
+            && self.full_text.is_none()
+            && self.expression.is_none()
+            && self.title.is_empty()
+            && self.title_excluded.is_empty()
+            && self.title_regex.is_none()
+            && self.created.is_none()
+            && self.modified.is_none()
+            && self.words.is_none()
+            && self.chars.is_none()
+            && self.todos.is_none()
+            && self.inlinks.is_none()
+            && self.outlinks.is_none()
+            && self.paths.is_empty()
+            && self.languages.is_empty()
+            && self.near.is_empty()
+            && self.fields.is_empty()
+            && self.properties.is_empty()
+            && self.query.is_none()
     }
 
     pub fn apply(&self, note: &super::Note, index: &super::NoteIndex) -> Option<i64> {
@@ -86,22 +543,31 @@ impl Filter {
 
         let mut any = false;
         let mut all = true;
+        // the amount of fulfilled inclusion conditions, used to boost the score
+        let mut tag_hits = 0;
+        let mut link_hits = 0;
         for (tag, included) in self.tags.iter() {
-            if note
-                // go over all tags
-                .tags
-                .iter()
-                // split each tag into..
-                .flat_map(|tag| {
-                    // an iterator of substring starting at 0 and going to every appearance to /
-                    tag.match_indices('/')
-                        .map(|(index, _match)| &tag[0..index])
-                        // and appended just a substring that is the whole tag
-                        .chain(std::iter::once(tag.as_str()))
-                    // flatten this so we have just an iterator over (sub)strs
-                })
-                // check if any of these substring is the searched tag
-                .any(|subtag| subtag == tag)
+            // tags containing wildcards are matched against the full tags of the note
+            let found = if tag.contains('*') {
+                note.tags.iter().any(|note_tag| glob_match(tag, note_tag))
+            } else {
+                note
+                    // go over all tags
+                    .tags
+                    .iter()
+                    // split each tag into..
+                    .flat_map(|tag| {
+                        // an iterator of substring starting at 0 and going to every appearance to /
+                        tag.match_indices('/')
+                            .map(|(index, _match)| &tag[0..index])
+                            // and appended just a substring that is the whole tag
+                            .chain(std::iter::once(tag.as_str()))
+                        // flatten this so we have just an iterator over (sub)strs
+                    })
+                    // check if any of these substring is the searched tag
+                    .any(|subtag| subtag == tag)
+            };
+            if found
             // now compare this to our expectation
             //  - inclusion: We _want_ one of them to be equal
             //  - exclusion: We _dont_ want one of them to be equal
@@ -110,6 +576,9 @@ impl Filter {
                 // this did match our expectation (one of them is equal in case of inclusion or none of them is equal in case of exclusion)
                 // so at least one condition (this one) is true
                 any = true;
+                if *included {
+                    tag_hits += 1;
+                }
             } else {
                 // this did not match our expectation (none of them is equal in case of inclusion or one of them is equal in case of exclusion)
                 // so not all conditions can be true
@@ -117,14 +586,55 @@ impl Filter {
             }
         }
 
+        // go through all exact tags
+        for (tag, included) in self.exact_tags.iter() {
+            if note.tags.contains(tag) == *included {
+                any = true;
+                if *included {
+                    tag_hits += 1;
+                }
+            } else {
+                all = false;
+            }
+        }
+
+        // go through all fuzzy tags
+        if !self.fuzzy_tags.is_empty() {
+            let matcher = fuzzy_matcher::skim::SkimMatcherV2::default();
+            for (tag, included) in self.fuzzy_tags.iter() {
+                // a fuzzy tag is found if it matches any of the note's tags
+                let found = note
+                    .tags
+                    .iter()
+                    .any(|note_tag| matcher.fuzzy_match(note_tag, tag).is_some());
+                if found == *included {
+                    any = true;
+                    if *included {
+                        tag_hits += 1;
+                    }
+                } else {
+                    all = false;
+                }
+            }
+        }
+
         // === === LINKS === ===
 
         // go through all links
         for (link, included) in self.links.iter() {
             // if the links is contained and we want it to be contained or not contained and we want it to be not contained
-            if note.links.contains(link) == *included {
+            // both the searched link and the links of the note might be aliases
+            let link = index.resolve_id(link);
+            let found = note
+                .links
+                .iter()
+                .any(|other| index.resolve_link(note, other) == link);
+            if found == *included {
                 // at least one condition (this one) is true
                 any = true;
+                if *included {
+                    link_hits += 1;
+                }
             } else {
                 // else, at least one condition is false, so not all of them are true
                 all = false;
@@ -134,11 +644,8 @@ impl Filter {
         // go through all backlinks
         for (blink, included) in self.blinks.iter() {
             // check if the note with the blink-ID links to the main one passed to this function
-            let exists_and_contains = if let Some(other_note) = index.inner.get(blink) {
-                other_note.links.contains(&super::name_to_id(&note.name))
-            } else {
-                false
-            };
+            let blink = index.resolve_id(blink);
+            let exists_and_contains = index.backlinks(&note.id).any(|source| source == blink);
 
             // if the backlink exists and we want that, set any/all as above
             if exists_and_contains == *included {
@@ -148,6 +655,112 @@ impl Filter {
             }
         }
 
+        // === === TIMES === ===
+
+        for (condition, time) in [
+            (&self.created, note.created),
+            (&self.modified, note.modified),
+        ] {
+            if let Some(condition) = condition {
+                if condition.matches(time) {
+                    any = true;
+                } else {
+                    all = false;
+                }
+            }
+        }
+
+        // === === COUNTS === ===
+
+        for (condition, count) in [
+            (&self.words, note.words),
+            (&self.chars, note.characters),
+            (&self.todos, note.open_tasks),
+        ] {
+            if let Some(condition) = condition {
+                if condition.matches(count) {
+                    any = true;
+                } else {
+                    all = false;
+                }
+            }
+        }
+
+        // link counts are only calculated if needed, as counting inlinks requires going over the whole index
+        if let Some(condition) = &self.inlinks {
+            if condition.matches(index.inlinks_count(&note.id)) {
+                any = true;
+            } else {
+                all = false;
+            }
+        }
+
+        if let Some(condition) = &self.outlinks {
+            if condition.matches(index.outlinks_count(note)) {
+                any = true;
+            } else {
+                all = false;
+            }
+        }
+
+        // === === PATHS === ===
+
+        if !self.paths.is_empty() {
+            // compare whole folder names, so `projects` does not match `projects-old`
+            let relative_path = index.relative_path(note);
+            for (prefix, included) in self.paths.iter() {
+                if relative_path.starts_with(std::path::Path::new(prefix)) == *included {
+                    any = true;
+                } else {
+                    all = false;
+                }
+            }
+        }
+
+        // === === LANGUAGES === ===
+
+        for (language, included) in self.languages.iter() {
+            if (note.language.as_ref() == Some(language)) == *included {
+                any = true;
+            } else {
+                all = false;
+            }
+        }
+
+        // === === DISTANCES === ===
+
+        for (condition, included) in self.near.iter() {
+            if condition.matches(note, index) == *included {
+                any = true;
+            } else {
+                all = false;
+            }
+        }
+
+        // === === FRONTMATTER === ===
+
+        for ((key, value), included) in self.fields.iter() {
+            let found = note
+                .frontmatter
+                .get(key)
+                .is_some_and(|field| value == "*" || field.to_lowercase() == *value);
+            if found == *included {
+                any = true;
+            } else {
+                all = false;
+            }
+        }
+
+        // === === PROPERTIES === ===
+
+        for (property, included) in self.properties.iter() {
+            if property.holds(note, index) == *included {
+                any = true;
+            } else {
+                all = false;
+            }
+        }
+
         if let Some(text) = &self.full_text {
             if std::fs::read_to_string(&note.path)
                 .map(|content| content.to_lowercase().contains(text))
@@ -159,11 +772,49 @@ impl Filter {
             }
         }
 
+        if let Some(expression) = &self.expression {
+            if expression.matches(note, index) {
+                any = true;
+            } else {
+                all = false;
+            }
+        }
+
+        // === === QUERY === ===
+
+        let query_match = self
+            .query
+            .as_ref()
+            .and_then(|query| query.apply(note, index));
+        if self.query.is_some() {
+            if query_match.is_some() {
+                any = true;
+            } else {
+                all = false;
+            }
+        }
+
+        // Titles are matched against both the displayed title and the file name, keeping the better match
+        let names = [note.title(), note.name.as_str()];
+
         let fuz_match = if self.title.is_empty() {
             None
         } else {
-            let matcher = fuzzy_matcher::skim::SkimMatcherV2::default();
-            let fuzzy_match = matcher.fuzzy_match(&note.name, &self.title);
+            // Fuzzy matching ignores the spaces between the title words, exact matching needs them
+            let fuzzy_title = self.title.replace(' ', "");
+            let title_match = |name: &str| match self.title_mode {
+                TitleMode::Fuzzy if self.normalize => fuzzy_matcher::skim::SkimMatcherV2::default()
+                    .fuzzy_match(&normalize(name), &normalize(&fuzzy_title)),
+                TitleMode::Fuzzy => {
+                    fuzzy_matcher::skim::SkimMatcherV2::default().fuzzy_match(name, &fuzzy_title)
+                }
+                TitleMode::Exact => name
+                    .to_lowercase()
+                    .contains(&self.title.to_lowercase())
+                    .then_some(FIXED_MATCH_SCORE),
+                TitleMode::CaseSensitive => name.contains(&self.title).then_some(FIXED_MATCH_SCORE),
+            };
+            let fuzzy_match = names.iter().filter_map(|name| title_match(name)).max();
             if fuzzy_match.is_some() {
                 any = true;
             } else {
@@ -171,60 +822,664 @@ impl Filter {
             }
             fuzzy_match
         };
+
+        // go through all excluded title words
+        if !self.title_excluded.is_empty() {
+            let matcher = fuzzy_matcher::skim::SkimMatcherV2::default();
+            for excluded in self.title_excluded.iter() {
+                if names
+                    .iter()
+                    .all(|name| matcher.fuzzy_match(name, excluded).is_none())
+                {
+                    any = true;
+                } else {
+                    all = false;
+                }
+            }
+        }
+
+        let regex_match = if let Some(regex) = &self.title_regex {
+            if names.iter().any(|name| regex.is_match(name)) {
+                any = true;
+                Some(FIXED_MATCH_SCORE)
+            } else {
+                all = false;
+                None
+            }
+        } else {
+            None
+        };
         // if all conditions are empty, return match score (only title search)
-        if self.tags.is_empty() && self.links.is_empty() && self.blinks.is_empty() && self.full_text.is_none() && self.title.is_empty()  ||
+        if self.is_empty() ||
             // also return match score if the required amount of conditions are fulfilled
             (!self.any && all || self.any && any)
         {
-            fuz_match.or(Some(0))
+            // query scores are already weighted by the filters within the query
+            let title_score = fuz_match
+                .or(regex_match)
+                .map(|score| score * self.weights.title)
+                .or(query_match)
+                .unwrap_or(0);
+            Some(title_score + tag_hits * self.weights.tags + link_hits * self.weights.links)
         } else {
             // else, an exclusion criterion was triggered
             None
         }
     }
 }
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{data, io};
 
-    #[test]
-    fn test_filter() {
-        let config = crate::Config::default();
-        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
-        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
-        let index = data::NoteIndex::new(tracker, builder).0;
+/// The prefixes of filter words whose condition can only be given once, with later words replacing earlier ones.
+const SINGLE_VALUED_KEYS: [&str; 8] = [
+    "created:",
+    "modified:",
+    "words:",
+    "chars:",
+    "todo:",
+    "inlinks:",
+    "outlinks:",
+    "re:",
+];
 
-        assert_eq!(index.inner.len(), 11);
+/// Splits the given filter string into
+///  - the filter words before the first `|`, without any `re:` words,
+///  - the pattern of the last `re:` word, which may contain `|`,
+///  - and the full text search after the first `|` outside of a regex.
+fn split_regex(filter_string: &str) -> (String, Option<String>, Option<&str>) {
+    let mut filters = Vec::new();
+    let mut pattern = None;
+    let mut rest = filter_string.trim_start();
+    while !rest.is_empty() {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let word = &rest[..end];
+        if let Some(regex) = word.strip_prefix("re:") {
+            pattern = Some(regex.to_owned());
+        } else if let Some(bar) = word.find('|') {
+            filters.push(&word[..bar]);
+            return (filters.join(" "), pattern, Some(&rest[bar + 1..]));
+        } else {
+            filters.push(word);
+        }
+        rest = rest[end..].trim_start();
+    }
+    (filters.join(" "), pattern, None)
+}
 
-        let linux = index.inner.get("linux").unwrap();
-        let win = index.inner.get("windows").unwrap();
-        let osx = index.inner.get("osx").unwrap();
+/// Replaces every word of the form `@name` with the named filter of that name, if one exists.
+/// Named filters using boolean operators are wrapped in parentheses to keep their meaning.
+/// Named filters with a full text search, an expression or a regex are kept as they are, as these only apply to a filter as a whole.
+fn expand_named(filter_string: &str, named: &HashMap<String, String>, depth: usize) -> String {
+    if depth >= MAX_NAMED_DEPTH || !filter_string.contains('@') {
+        return filter_string.to_owned();
+    }
 
-        // === Filter 1 ===
+    filter_string
+        .split_whitespace()
+        .map(|word| {
+            match word
+                .strip_prefix('@')
+                .and_then(|name| named.get(name))
+                .map(|expansion| expand_named(expansion, named, depth + 1))
+            {
+                Some(expansion) if is_self_contained(&expansion) => word.to_owned(),
+                Some(expansion) if Query::is_query(&expansion) => format!("({})", expansion),
+                Some(expansion) => expansion,
+                None => word.to_owned(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-        let filter1 = Filter {
-            any: false,
-            tags: vec![("#os".to_string(), true), ("#os/win".to_string(), false)],
-            links: vec![],
-            blinks: vec![],
-            title: String::new(),
-            full_text: None,
-        };
+/// Checks wether the given filter string contains a full text search, an expression or a regex.
+fn is_self_contained(filter_string: &str) -> bool {
+    filter_string.contains('|')
+        || filter_string.contains("expr:")
+        || filter_string
+            .split_whitespace()
+            .any(|word| word.starts_with("re:"))
+}
 
-        assert!(filter1.apply(linux, &index).is_some());
-        assert!(filter1.apply(osx, &index).is_some());
-        assert!(filter1.apply(win, &index).is_none());
+/// Normalizes a text for matching by
+///  - turning it to lowercase,
+///  - transliterating common diacritics to plain ASCII (`ö` becomes `o`, `ß` becomes `ss`),
+///  - and stripping plural endings from words (`graphs` becomes `graph`).
+fn normalize(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.to_lowercase().chars() {
+        match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' => folded.push('a'),
+            'ç' | 'č' | 'ć' => folded.push('c'),
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ę' => folded.push('e'),
+            'ì' | 'í' | 'î' | 'ï' | 'ī' => folded.push('i'),
+            'ñ' | 'ń' => folded.push('n'),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' => folded.push('o'),
+            'ù' | 'ú' | 'û' | 'ü' | 'ū' => folded.push('u'),
+            'ý' | 'ÿ' => folded.push('y'),
+            'š' | 'ś' => folded.push('s'),
+            'ž' | 'ź' | 'ż' => folded.push('z'),
+            'ł' => folded.push('l'),
+            'ß' => folded.push_str("ss"),
+            'æ' => folded.push_str("ae"),
+            'œ' => folded.push_str("oe"),
+            c => folded.push(c),
+        }
     }
 
-    #[test]
-    fn test_filter_from_string() {
-        let config = crate::Config::default();
-        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
-        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
-        let index = data::NoteIndex::new(tracker, builder).0;
+    folded
+        .split_whitespace()
+        .map(|word| {
+            // strip a plural 's', but leave short words and singular endings like 'ss', 'us' and 'is' alone
+            if word.len() > 3
+                && word.ends_with('s')
+                && !["ss", "us", "is"]
+                    .iter()
+                    .any(|ending| word.ends_with(ending))
+            {
+                &word[..word.len() - 1]
+            } else {
+                word
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-        assert_eq!(index.inner.len(), 11);
+/// Matches a text against a pattern in which every `*` stands for any (possibly empty) sequence of characters.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // the part before the first wildcard must be a prefix
+    let Some(mut rest) = text.strip_prefix(parts.next().unwrap_or_default()) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    match parts.split_last() {
+        // no wildcard: the prefix must have been the entire text
+        None => rest.is_empty(),
+        Some((last, middle)) => {
+            // all parts between wildcards must appear in order
+            for part in middle {
+                match rest.find(part) {
+                    Some(index) => rest = &rest[index + part.len()..],
+                    None => return false,
+                }
+            }
+            // the part after the last wildcard must be a suffix
+            rest.ends_with(last)
+        }
+    }
+}
+
+/// Splits a leading comparison operator (`>`, `<` or `=`) off a filter value.
+/// Values without an operator are compared for equality.
+fn split_comparison(value: &str) -> (cmp::Ordering, &str) {
+    if let Some(rest) = value.strip_prefix('>') {
+        (cmp::Ordering::Greater, rest)
+    } else if let Some(rest) = value.strip_prefix('<') {
+        (cmp::Ordering::Less, rest)
+    } else {
+        (cmp::Ordering::Equal, value.trim_start_matches('='))
+    }
+}
+
+/// A property of a note that can be checked for by filters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteProperty {
+    /// The note contains at least one link whose target is missing from the index.
+    BrokenLinks,
+    /// No other note links to the note.
+    Orphan,
+    /// The note contains at least one open task list item.
+    Todo,
+}
+
+impl NoteProperty {
+    /// Parses a property from its name as used in the filter string, e.g. `brokenlinks` for `has:brokenlinks`.
+    pub fn new(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "brokenlinks" | "broken" => Some(Self::BrokenLinks),
+            "orphan" => Some(Self::Orphan),
+            "todo" | "todos" | "tasks" => Some(Self::Todo),
+            _ => None,
+        }
+    }
+
+    /// Checks wether the given note has this property.
+    pub fn holds(&self, note: &super::Note, index: &super::NoteIndex) -> bool {
+        match self {
+            Self::BrokenLinks => index.broken_links_count(note) > 0,
+            Self::Orphan => !index.backlinks(&note.id).any(|source| *source != note.id),
+            Self::Todo => note.open_tasks > 0,
+        }
+    }
+}
+
+/// A condition comparing some count of a note, such as its amount of words, to a fixed value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CountCondition {
+    /// Wether the count of the note must be greater than, less than or equal to the value.
+    ordering: cmp::Ordering,
+    /// The value to compare to.
+    value: usize,
+}
+
+impl CountCondition {
+    /// Parses a count condition from a value like `>500`, `<100` or `0`.
+    pub fn new(value: &str) -> Option<Self> {
+        let (ordering, value) = split_comparison(value);
+        Some(Self {
+            ordering,
+            value: value.parse().ok()?,
+        })
+    }
+
+    /// Checks if the given count fulfills this condition.
+    pub fn matches(&self, count: usize) -> bool {
+        count.cmp(&self.value) == self.ordering
+    }
+}
+
+/// A condition on the link distance of a note to another note.
+#[derive(Debug, Clone)]
+pub struct DistanceCondition {
+    /// The id of the note to measure the distance from.
+    id: String,
+    /// The maximum amount of link hops, in either direction.
+    distance: usize,
+    /// The ids of all notes within the maximum distance, found once when the condition is first checked.
+    reachable: sync::OnceLock<HashSet<String>>,
+}
+
+impl PartialEq for DistanceCondition {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.distance == other.distance
+    }
+}
+
+impl Eq for DistanceCondition {}
+
+impl DistanceCondition {
+    /// Parses a distance condition from a value like `manifold:2`.
+    /// If no distance is given, only direct neighbours are considered close.
+    pub fn new(value: &str) -> Self {
+        let (name, distance) = value
+            .rsplit_once(':')
+            .and_then(|(name, distance)| Some((name, distance.parse().ok()?)))
+            .unwrap_or((value, 1));
+        Self {
+            id: super::name_to_id(name),
+            distance,
+            reachable: sync::OnceLock::new(),
+        }
+    }
+
+    /// Checks if the given note is within the maximum distance of the target note.
+    /// The notes within the distance are only searched for on the first check, so all checks need to use the same index.
+    pub fn matches(&self, note: &super::Note, index: &super::NoteIndex) -> bool {
+        self.reachable
+            .get_or_init(|| index.within_distance(index.resolve_id(&self.id), self.distance))
+            .contains(&note.id)
+    }
+
+    /// Forgets the notes found within the distance, so they are searched for again on the next check.
+    fn forget_reachable(&mut self) {
+        self.reachable = sync::OnceLock::new();
+    }
+}
+
+/// Splits a word of the form `key:value` into its lowercase key and value, if both are non-empty and the key is a plain identifier.
+fn split_field(word: &str) -> Option<(String, String)> {
+    let (key, value) = word.split_once(':')?;
+    if key.is_empty()
+        || value.is_empty()
+        || !key
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    {
+        return None;
+    }
+    Some((key.to_lowercase(), value.to_lowercase()))
+}
+
+/// Converts a date of the proleptic gregorian calendar to the number of days since the unix epoch.
+pub(super) fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Converts a number of days since the unix epoch to a date of the proleptic gregorian calendar, as year, month and day.
+pub(super) fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// A condition on a point in time, such as the creation or modification time of a note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeCondition {
+    /// Wether the time of the note must be after (`Greater`), before (`Less`) or on the same day (`Equal`) as the reference time.
+    ordering: cmp::Ordering,
+    /// The reference time.
+    time: time::SystemTime,
+}
+
+impl TimeCondition {
+    /// Length of a day in seconds.
+    const DAY: u64 = 24 * 60 * 60;
+
+    /// Parses a time condition from a value like `>2024-01-01` or `<7d`.
+    /// Absolute dates are given as `YYYY-MM-DD`, relative ages as a number followed by `d`(ays), `w`(eeks), `m`(onths) or `y`(ears).
+    /// For ages, the comparison refers to the age and is thus reversed: `<7d` matches all times within the last week.
+    pub fn new(value: &str) -> Option<Self> {
+        let (ordering, value) = split_comparison(value);
+
+        // Relative ages
+        let unit = match value.chars().last()? {
+            'd' => Some(1),
+            'w' => Some(7),
+            'm' => Some(30),
+            'y' => Some(365),
+            _ => None,
+        };
+        if let Some(unit) = unit {
+            let amount = value[..value.len() - 1].parse::<u64>().ok()?;
+            return Some(Self {
+                ordering: ordering.reverse(),
+                time: time::SystemTime::now()
+                    .checked_sub(time::Duration::from_secs(amount * unit * Self::DAY))?,
+            });
+        }
+
+        // Absolute dates
+        let mut parts = value.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+        let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+        let days = days_from_civil(year, month, day);
+        let offset = time::Duration::from_secs(days.unsigned_abs() * Self::DAY);
+        Some(Self {
+            ordering,
+            time: if days >= 0 {
+                time::UNIX_EPOCH.checked_add(offset)?
+            } else {
+                time::UNIX_EPOCH.checked_sub(offset)?
+            },
+        })
+    }
+
+    /// Checks if the given time fulfills this condition. Missing times never do.
+    pub fn matches(&self, time: Option<time::SystemTime>) -> bool {
+        let Some(time) = time else {
+            return false;
+        };
+        match self.ordering {
+            cmp::Ordering::Equal => time
+                .duration_since(self.time)
+                .is_ok_and(|diff| diff.as_secs() < Self::DAY),
+            ordering => time.cmp(&self.time) == ordering,
+        }
+    }
+}
+
+/// A boolean combination of filters, as created from a filter string containing `AND`, `OR`, `NOT` or parentheses.
+/// The set operators `&` (intersection), `+` (union) and `-` (difference) can be used as well, e.g. to combine named filters.
+/// Consecutive words without an operator in between are grouped into a single leaf filter.
+#[derive(Debug, Clone)]
+pub enum Query {
+    /// A plain filter without any boolean operators.
+    Leaf(Filter),
+    /// Both sub-queries need to match.
+    And(Box<Query>, Box<Query>),
+    /// At least one of the sub-queries needs to match.
+    Or(Box<Query>, Box<Query>),
+    /// The sub-query must not match.
+    Not(Box<Query>),
+}
+
+/// The token stream a query is parsed from.
+type Tokens<'a> = std::iter::Peekable<std::str::SplitWhitespace<'a>>;
+
+impl Query {
+    /// Checks wether the given filter string makes use of the boolean query syntax.
+    fn is_query(filter_string: &str) -> bool {
+        filter_string
+            .split_whitespace()
+            // parentheses within a regex do not group anything
+            .filter(|word| !word.starts_with("re:"))
+            .any(|word| word.contains(['(', ')']) || Self::is_operator(word))
+    }
+
+    /// Checks wether the given word is a boolean or set operator.
+    fn is_operator(word: &str) -> bool {
+        matches!(word, "AND" | "OR" | "NOT" | "&" | "+" | "-")
+    }
+
+    /// Parses a query from the given string. The `any` flag and the named filters are passed on to all leaf filters.
+    /// Parsing is lenient, as the string is usually still being typed: Missing closing parentheses are implied and operators without operands match everything.
+    fn parse(query_string: &str, any: bool, named: &HashMap<String, String>) -> Self {
+        // make sure parentheses are always their own token
+        let padded = query_string.replace('(', " ( ").replace(')', " ) ");
+        Self::parse_or(&mut padded.split_whitespace().peekable(), any, named)
+    }
+
+    /// Parses a disjunction of conjunctions. `OR`, as well as the set union `+` and difference `-`, bind weakest and are evaluated left to right.
+    fn parse_or(tokens: &mut Tokens, any: bool, named: &HashMap<String, String>) -> Self {
+        let mut left = Self::parse_and(tokens, any, named);
+        while let Some(operator) = tokens.next_if(|word| matches!(*word, "OR" | "+" | "-")) {
+            let right = Self::parse_and(tokens, any, named);
+            left = if operator == "-" {
+                // the difference contains all notes of the left side that are not contained in the right side
+                Self::And(Box::new(left), Box::new(Self::Not(Box::new(right))))
+            } else {
+                Self::Or(Box::new(left), Box::new(right))
+            };
+        }
+        left
+    }
+
+    /// Parses a conjunction of unary expressions. The `AND` keyword or intersection `&` is optional between parenthesized groups.
+    fn parse_and(tokens: &mut Tokens, any: bool, named: &HashMap<String, String>) -> Self {
+        let mut left = Self::parse_unary(tokens, any, named);
+        loop {
+            match tokens.peek().copied() {
+                None | Some("OR" | "+" | "-" | ")") => break,
+                Some("AND" | "&") => {
+                    tokens.next();
+                }
+                // implicit AND
+                Some(_) => {}
+            }
+            let right = Self::parse_unary(tokens, any, named);
+            left = Self::And(Box::new(left), Box::new(right));
+        }
+        left
+    }
+
+    /// Parses a negation, a parenthesized group or a leaf filter.
+    fn parse_unary(tokens: &mut Tokens, any: bool, named: &HashMap<String, String>) -> Self {
+        match tokens.peek().copied() {
+            Some("NOT") => {
+                tokens.next();
+                Self::Not(Box::new(Self::parse_unary(tokens, any, named)))
+            }
+            Some("(") => {
+                tokens.next();
+                let inner = Self::parse_or(tokens, any, named);
+                // closing parenthesis is optional
+                tokens.next_if_eq(&")");
+                inner
+            }
+            _ => {
+                // collect all words until the next operator
+                let mut words = Vec::new();
+                while let Some(word) =
+                    tokens.next_if(|word| !Self::is_operator(word) && !matches!(*word, "(" | ")"))
+                {
+                    words.push(word);
+                }
+                Self::Leaf(Filter::new_with_named(&words.join(" "), any, named))
+            }
+        }
+    }
+
+    /// Sets the given title mode for all filters within this query.
+    fn with_title_mode(self, mode: TitleMode) -> Self {
+        match self {
+            Self::Leaf(filter) => Self::Leaf(filter.with_title_mode(mode)),
+            Self::And(left, right) => Self::And(
+                Box::new(left.with_title_mode(mode)),
+                Box::new(right.with_title_mode(mode)),
+            ),
+            Self::Or(left, right) => Self::Or(
+                Box::new(left.with_title_mode(mode)),
+                Box::new(right.with_title_mode(mode)),
+            ),
+            Self::Not(inner) => Self::Not(Box::new(inner.with_title_mode(mode))),
+        }
+    }
+
+    /// Checks wether any filter of this query is expensive to apply, see [Filter::is_expensive].
+    fn is_expensive(&self) -> bool {
+        match self {
+            Self::Leaf(filter) => filter.is_expensive(),
+            Self::And(left, right) | Self::Or(left, right) => {
+                left.is_expensive() || right.is_expensive()
+            }
+            Self::Not(inner) => inner.is_expensive(),
+        }
+    }
+
+    /// Checks wether any filter of this query depends on other notes, see [Filter::depends_on_other_notes].
+    fn depends_on_other_notes(&self) -> bool {
+        match self {
+            Self::Leaf(filter) => filter.depends_on_other_notes(),
+            Self::And(left, right) | Self::Or(left, right) => {
+                left.depends_on_other_notes() || right.depends_on_other_notes()
+            }
+            Self::Not(inner) => inner.depends_on_other_notes(),
+        }
+    }
+
+    /// Enables or disables title normalization for all filters of this query.
+    fn with_normalization(self, normalize: bool) -> Self {
+        match self {
+            Self::Leaf(filter) => Self::Leaf(filter.with_normalization(normalize)),
+            Self::And(left, right) => Self::And(
+                Box::new(left.with_normalization(normalize)),
+                Box::new(right.with_normalization(normalize)),
+            ),
+            Self::Or(left, right) => Self::Or(
+                Box::new(left.with_normalization(normalize)),
+                Box::new(right.with_normalization(normalize)),
+            ),
+            Self::Not(inner) => Self::Not(Box::new(inner.with_normalization(normalize))),
+        }
+    }
+
+    /// Forgets everything the filters of this query remembered about the index they were applied to.
+    fn forget_index(&mut self) {
+        match self {
+            Self::Leaf(filter) => filter.forget_index(),
+            Self::And(left, right) | Self::Or(left, right) => {
+                left.forget_index();
+                right.forget_index();
+            }
+            Self::Not(inner) => inner.forget_index(),
+        }
+    }
+
+    /// Sets the given score weights for all filters of this query.
+    fn with_weights(self, weights: ScoreWeights) -> Self {
+        match self {
+            Self::Leaf(filter) => Self::Leaf(filter.with_weights(weights)),
+            Self::And(left, right) => Self::And(
+                Box::new(left.with_weights(weights)),
+                Box::new(right.with_weights(weights)),
+            ),
+            Self::Or(left, right) => Self::Or(
+                Box::new(left.with_weights(weights)),
+                Box::new(right.with_weights(weights)),
+            ),
+            Self::Not(inner) => Self::Not(Box::new(inner.with_weights(weights))),
+        }
+    }
+
+    /// Checks the given note against this query, returning a match score if it matches.
+    /// Conjunctions add up the scores of their parts, while disjunctions take the better one.
+    pub fn apply(&self, note: &super::Note, index: &super::NoteIndex) -> Option<i64> {
+        match self {
+            Self::Leaf(filter) => filter.apply(note, index),
+            Self::And(left, right) => left
+                .apply(note, index)
+                .zip(right.apply(note, index))
+                .map(|(left, right)| left + right),
+            Self::Or(left, right) => left.apply(note, index).max(right.apply(note, index)),
+            Self::Not(inner) => match inner.apply(note, index) {
+                Some(_) => None,
+                None => Some(0),
+            },
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data, io};
+
+    #[test]
+    fn test_filter() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        assert_eq!(index.inner.len(), 11);
+
+        let linux = index.inner.get("linux").unwrap();
+        let win = index.inner.get("windows").unwrap();
+        let osx = index.inner.get("osx").unwrap();
+
+        // === Filter 1 ===
+
+        let filter1 = Filter {
+            any: false,
+            tags: vec![("#os".to_string(), true), ("#os/win".to_string(), false)],
+            links: vec![],
+            blinks: vec![],
+            title: String::new(),
+            full_text: None,
+            ..Default::default()
+        };
+
+        assert!(filter1.apply(linux, &index).is_some());
+        assert!(filter1.apply(osx, &index).is_some());
+        assert!(filter1.apply(win, &index).is_none());
+    }
+
+    #[test]
+    fn test_filter_from_string() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        assert_eq!(index.inner.len(), 11);
 
         // === Filter 2 ===
 
@@ -294,4 +1549,1057 @@ mod tests {
         assert_eq!(filter4.blinks, vec![("atlas".to_string(), true)]);
         assert_eq!(filter4.title, "");
     }
+
+    #[test]
+    fn test_filter_query() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let linux = index.inner.get("linux").unwrap();
+        let win = index.inner.get("windows").unwrap();
+        let osx = index.inner.get("osx").unwrap();
+        let os = index.inner.get("operating-systems").unwrap();
+
+        // === Filter 5 ===
+        let filter5 = Filter::new("(#os AND !#os/win) OR >linux", false);
+
+        assert!(filter5.query.is_some());
+        assert!(filter5.tags.is_empty());
+
+        assert!(filter5.apply(linux, &index).is_some());
+        assert!(filter5.apply(osx, &index).is_some());
+        assert!(filter5.apply(win, &index).is_none());
+        assert!(filter5.apply(os, &index).is_some());
+
+        // === Filter 6 ===
+        let filter6 = Filter::new("#os NOT (>linux OR #os/win) |equivalent", false);
+
+        assert_eq!(filter6.full_text, Some(String::from("equivalent")));
+        assert!(filter6.apply(win, &index).is_none());
+        assert!(filter6.apply(os, &index).is_none());
+    }
+
+    #[test]
+    fn test_filter_query_lenient() {
+        // unclosed parentheses and dangling operators must not panic
+        for query in ["(#os AND", "NOT", "OR OR )", "((>linux) #os", ") AND ("] {
+            assert!(Filter::new(query, false).query.is_some());
+        }
+        // lowercase words are not operators
+        assert!(Filter::new("lie and group", false).query.is_none());
+    }
+
+    #[test]
+    fn test_filter_regex() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let linux = index.inner.get("linux").unwrap();
+        let liegroup = index.inner.get("lie-group").unwrap();
+        let smoothmap = index.inner.get("smooth-map").unwrap();
+
+        // === Filter 7 ===
+        let filter7 = Filter::new("re:^L.*x$", false);
+
+        assert!(filter7.title_regex.is_some());
+        assert_eq!(filter7.title, "");
+        assert_eq!(filter7.apply(linux, &index), Some(FIXED_MATCH_SCORE));
+        assert!(filter7.apply(liegroup, &index).is_none());
+
+        // === Filter 8 ===
+        let filter8 = Filter::new("#diffgeo re:(?i)^s", false);
+
+        assert!(filter8.apply(smoothmap, &index).is_some());
+        assert!(filter8.apply(liegroup, &index).is_none());
+
+        // regexes may contain `|`, which does not start a full text search
+        let filter9 = Filter::new("re:^(Lin|Smo) | manifold", false);
+
+        assert!(filter9.query.is_none());
+        assert_eq!(filter9.full_text.as_deref(), Some(" manifold"));
+        assert!(filter9.title_regex.unwrap().is_match("Smooth Map"));
+
+        // invalid regexes are ignored, without turning the filter into a query
+        let invalid = Filter::new("#diffgeo re:(unclosed", false);
+
+        assert!(invalid.title_regex.is_none());
+        assert!(invalid.query.is_none());
+        assert!(invalid.apply(smoothmap, &index).is_some());
+    }
+
+    #[test]
+    fn test_time_conditions() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2024, 1, 1), 19723);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19723), (2024, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), (2024, 2, 29));
+
+        let new_year = TimeCondition::new(">2024-01-01").unwrap();
+        assert_eq!(
+            new_year.time,
+            time::UNIX_EPOCH + time::Duration::from_secs(19723 * 24 * 60 * 60)
+        );
+        assert!(new_year.matches(Some(time::SystemTime::now())));
+        assert!(!new_year.matches(Some(time::UNIX_EPOCH)));
+        assert!(!new_year.matches(None));
+
+        let week = TimeCondition::new("<7d").unwrap();
+        assert_eq!(week.ordering, cmp::Ordering::Greater);
+        assert!(week.matches(Some(time::SystemTime::now())));
+        assert!(!week.matches(Some(time::UNIX_EPOCH)));
+
+        assert!(TimeCondition::new(">2024-13-01").is_none());
+        assert!(TimeCondition::new("<7x").is_none());
+        assert!(TimeCondition::new("").is_none());
+    }
+
+    #[test]
+    fn test_filter_dates() {
+        let tmp = testdir::testdir!();
+        let fm = io::FileManager::new(&crate::Config::default(), tmp.clone());
+        fm.create_note_file("Fresh").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let fresh = data::Note::from_path(&tmp.join("Fresh.md"), Default::default()).unwrap();
+
+        assert!(Filter::new("modified:<1d", false)
+            .apply(&fresh, &index)
+            .is_some());
+        assert!(Filter::new("modified:>2100-01-01", false)
+            .apply(&fresh, &index)
+            .is_none());
+        assert!(Filter::new("modified:<2100-01-01 #diffgeo", true)
+            .apply(&fresh, &index)
+            .is_some());
+    }
+
+    #[test]
+    fn test_filter_counts() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let chart = index.inner.get("chart").unwrap();
+        let os = index.inner.get("operating-systems").unwrap();
+
+        // === Filter 9 ===
+        let filter9 = Filter::new("words:>100 chars:<1000", false);
+
+        assert_eq!(
+            filter9.words,
+            Some(CountCondition {
+                ordering: cmp::Ordering::Greater,
+                value: 100
+            })
+        );
+        assert!(filter9.apply(chart, &index).is_some());
+        assert!(filter9.apply(os, &index).is_none());
+
+        // === Filter 10 ===
+        let filter10 = Filter::new("words:41", false);
+
+        assert!(filter10.apply(os, &index).is_some());
+        assert!(filter10.apply(chart, &index).is_none());
+
+        assert!(CountCondition::new(">").is_none());
+        assert!(CountCondition::new("<-3").is_none());
+    }
+
+    #[test]
+    fn test_filter_blinks_apply() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let topology = index.inner.get("topology").unwrap();
+        let liegroup = index.inner.get("lie-group").unwrap();
+        let chart = index.inner.get("chart").unwrap();
+        let manifold = index.inner.get("manifold").unwrap();
+
+        // === Filter 11: All notes that Manifold links to ===
+        let filter11 = Filter::new("<Manifold", false);
+
+        assert!(filter11.apply(topology, &index).is_some());
+        assert!(filter11.apply(liegroup, &index).is_some());
+        assert!(filter11.apply(chart, &index).is_none());
+        assert!(filter11.apply(manifold, &index).is_none());
+
+        // === Filter 12: All notes that Manifold does not link to ===
+        let filter12 = Filter::new("!<Manifold", false);
+
+        assert!(filter12.apply(topology, &index).is_none());
+        assert!(filter12.apply(chart, &index).is_some());
+    }
+
+    #[test]
+    fn test_filter_named() {
+        let named = HashMap::from([
+            (String::from("os"), String::from("#os !#os/win")),
+            (String::from("either"), String::from(">linux OR @os")),
+            (String::from("loop"), String::from("@loop")),
+            (String::from("kernel"), String::from("#os | kernel")),
+            (String::from("long"), String::from("expr: words > 300")),
+        ]);
+
+        // === Filter 13 ===
+        let filter13 = Filter::new_with_named("@os >linux", false, &named);
+
+        assert_eq!(
+            filter13.tags,
+            vec![("#os".to_string(), true), ("#os/win".to_string(), false)]
+        );
+        assert_eq!(filter13.links, vec![("linux".to_string(), true)]);
+
+        // === Filter 14 ===
+        assert_eq!(
+            expand_named("@either #math", &named, 0),
+            "(>linux OR #os !#os/win) #math"
+        );
+        assert!(Filter::new_with_named("@either", false, &named)
+            .query
+            .is_some());
+
+        // Unknown names are kept, self-references do not loop forever
+        assert_eq!(expand_named("@unknown", &named, 0), "@unknown");
+        assert_eq!(expand_named("@loop", &named, 0), "@loop");
+
+        // Named filters with a full text search or expression do not swallow the following words
+        let filter = Filter::new_with_named("@kernel >linux", false, &named);
+
+        assert!(filter.full_text.is_none());
+        assert_eq!(filter.links, vec![("linux".to_string(), true)]);
+        let Some(Query::Leaf(kernel)) = filter.query.as_deref() else {
+            panic!("named filter was not applied as a whole");
+        };
+        assert_eq!(kernel.full_text.as_deref(), Some(" kernel"));
+
+        let filter = Filter::new_with_named("@long #os | manifold", false, &named);
+
+        assert!(filter.expression.is_none());
+        assert_eq!(filter.tags, vec![("#os".to_string(), true)]);
+        assert_eq!(filter.full_text.as_deref(), Some(" manifold"));
+        assert!(
+            matches!(filter.query.as_deref(), Some(Query::Leaf(long)) if long.expression.is_some())
+        );
+
+        // Words after the full text search are not expanded
+        let filter = Filter::new_with_named("#math | @os", false, &named);
+
+        assert_eq!(filter.full_text.as_deref(), Some(" @os"));
+        assert!(filter.query.is_none());
+    }
+
+    #[test]
+    fn test_filter_title_excluded() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let linux = index.inner.get("linux").unwrap();
+        let win = index.inner.get("windows").unwrap();
+        let osx = index.inner.get("osx").unwrap();
+
+        // === Filter 15 ===
+        let filter15 = Filter::new("#os !windows !#os/osx !", false);
+
+        assert_eq!(filter15.title_excluded, vec!["windows".to_string()]);
+        assert_eq!(filter15.tags.len(), 2);
+
+        assert!(filter15.apply(linux, &index).is_some());
+        assert!(filter15.apply(win, &index).is_none());
+        assert!(filter15.apply(osx, &index).is_none());
+    }
+
+    #[test]
+    fn test_filter_title_modes() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let liegroup = index.inner.get("lie-group").unwrap();
+        let smoothmap = index.inner.get("smooth-map").unwrap();
+
+        // === Filter 16 ===
+        let filter16 = Filter::new("lie group", false);
+        assert_eq!(filter16.title, "lie group");
+        assert_eq!(filter16.title_mode, TitleMode::Fuzzy);
+        assert!(filter16.apply(liegroup, &index).is_some());
+        // fuzzy matching treats the title words as if they were typed without spaces
+        assert_eq!(
+            filter16.apply(liegroup, &index),
+            Filter::new("liegroup", false).apply(liegroup, &index)
+        );
+        assert_eq!(
+            filter16.apply(smoothmap, &index),
+            Filter::new("liegroup", false).apply(smoothmap, &index)
+        );
+
+        // === Filter 17 ===
+        let filter17 = Filter::new("=lie gr", false);
+        assert_eq!(filter17.title_mode, TitleMode::Exact);
+        assert_eq!(filter17.apply(liegroup, &index), Some(FIXED_MATCH_SCORE));
+        assert!(filter17.apply(smoothmap, &index).is_none());
+
+        // === Filter 18 ===
+        assert!(Filter::new("==lie", false)
+            .apply(liegroup, &index)
+            .is_none());
+        assert!(Filter::new("==Lie", false)
+            .apply(liegroup, &index)
+            .is_some());
+
+        // === Filter 19 ===
+        let filter19 = Filter::new("sm OR lie", false).with_title_mode(TitleMode::CaseSensitive);
+        assert!(filter19.apply(liegroup, &index).is_none());
+        assert!(filter19.apply(smoothmap, &index).is_none());
+        assert!(Filter::new("sm OR lie", false)
+            .apply(smoothmap, &index)
+            .is_some());
+
+        assert_eq!(TitleMode::CaseSensitive.next(), TitleMode::Fuzzy);
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("#os/*", "#os/win"));
+        assert!(glob_match("#os/*", "#os/win/xp"));
+        assert!(!glob_match("#os/*", "#os"));
+        assert!(glob_match("#*meeting*", "#team/meeting-notes"));
+        assert!(glob_match("#*meeting*", "#meeting"));
+        assert!(!glob_match("#*meeting*", "#meet"));
+        assert!(glob_match("#a*b*a", "#aba"));
+        assert!(!glob_match("#a*b*a", "#ab"));
+        assert!(glob_match("#os", "#os"));
+        assert!(!glob_match("#os", "#os/win"));
+    }
+
+    #[test]
+    fn test_filter_tag_wildcards() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let linux = index.inner.get("linux").unwrap();
+        let os = index.inner.get("operating-systems").unwrap();
+        let chart = index.inner.get("chart").unwrap();
+
+        // === Filter 20 ===
+        let filter20 = Filter::new("#os/*", false);
+
+        assert!(filter20.apply(linux, &index).is_some());
+        assert!(filter20.apply(os, &index).is_none());
+
+        // === Filter 21 ===
+        let filter21 = Filter::new("#*geo*", false);
+
+        assert!(filter21.apply(chart, &index).is_some());
+        assert!(filter21.apply(linux, &index).is_none());
+    }
+
+    #[test]
+    fn test_filter_broken_links() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let chart = index.inner.get("chart").unwrap();
+        let liegroup = index.inner.get("lie-group").unwrap();
+
+        // === Filter 22 ===
+        let filter22 = Filter::new("has:brokenlinks", false);
+
+        assert_eq!(filter22.properties, vec![(NoteProperty::BrokenLinks, true)]);
+        assert!(filter22.apply(chart, &index).is_some());
+        assert!(filter22.apply(liegroup, &index).is_none());
+
+        // === Filter 23 ===
+        let filter23 = Filter::new("!has:brokenlinks has:nonsense", false);
+
+        assert_eq!(filter23.properties.len(), 1);
+        assert!(filter23.apply(chart, &index).is_none());
+        assert!(filter23.apply(liegroup, &index).is_some());
+    }
+
+    #[test]
+    fn test_filter_orphans() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let os = index.inner.get("operating-systems").unwrap();
+        let linux = index.inner.get("linux").unwrap();
+
+        // === Filter 24 ===
+        let filter24 = Filter::new("is:orphan", false);
+
+        assert_eq!(filter24.properties, vec![(NoteProperty::Orphan, true)]);
+        assert!(filter24.apply(os, &index).is_some());
+        assert!(filter24.apply(linux, &index).is_none());
+
+        // === Filter 25 ===
+        let filter25 = Filter::new("!is:orphan", false);
+
+        assert!(filter25.apply(os, &index).is_none());
+        assert!(filter25.apply(linux, &index).is_some());
+    }
+
+    #[test]
+    fn test_filter_paths() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let chart = index.inner.get("chart").unwrap();
+        let linux = index.inner.get("linux").unwrap();
+
+        // === Filter 26 ===
+        let filter26 = Filter::new("path:common/notes/math/", false);
+
+        assert_eq!(
+            filter26.paths,
+            vec![("common/notes/math/".to_string(), true)]
+        );
+        assert!(filter26.apply(chart, &index).is_some());
+        assert!(filter26.apply(linux, &index).is_none());
+
+        // === Filter 27 ===
+        let filter27 = Filter::new("!path:common/notes/math", false);
+
+        assert!(filter27.apply(chart, &index).is_none());
+        assert!(filter27.apply(linux, &index).is_some());
+    }
+
+    #[test]
+    fn test_filter_paths_sibling_folders() {
+        let tmp = testdir::testdir!();
+        std::fs::create_dir_all(tmp.join("projects")).unwrap();
+        std::fs::create_dir_all(tmp.join("projects-old")).unwrap();
+        std::fs::write(tmp.join("projects/Current.md"), "Current").unwrap();
+        std::fs::write(tmp.join("projects-old/Archived.md"), "Archived").unwrap();
+        std::fs::write(tmp.join("projectsX.md"), "Loose").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let current = index.inner.get("current").unwrap();
+        let archived = index.inner.get("archived").unwrap();
+        let loose = index.inner.get("projectsx").unwrap();
+
+        // folders sharing the prefix of the given folder are not part of it
+        let filter = Filter::new("path:projects", false);
+        assert!(filter.apply(current, &index).is_some());
+        assert!(filter.apply(archived, &index).is_none());
+        assert!(filter.apply(loose, &index).is_none());
+
+        let filter = Filter::new("!path:projects/", false);
+        assert!(filter.apply(current, &index).is_none());
+        assert!(filter.apply(archived, &index).is_some());
+        assert!(filter.apply(loose, &index).is_some());
+    }
+
+    #[test]
+    fn test_filter_heading_title() {
+        let tmp = testdir::testdir!();
+        std::fs::write(tmp.join("20240131.md"), "# Meeting Notes\n").unwrap();
+        std::fs::write(tmp.join("Other.md"), "Nothing").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let meeting = index.inner.get("20240131").unwrap();
+        let other = index.inner.get("other").unwrap();
+
+        // both the heading and the file name are matched
+        assert!(Filter::new("meeting", false)
+            .apply(meeting, &index)
+            .is_some());
+        assert!(Filter::new("2024", false).apply(meeting, &index).is_some());
+        assert!(Filter::new("meeting", false).apply(other, &index).is_none());
+        assert!(Filter::new("!meeting", false)
+            .apply(meeting, &index)
+            .is_none());
+    }
+
+    #[test]
+    fn test_filter_languages() {
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("English.md"),
+            "This is a note that is written in English and it is short.",
+        )
+        .unwrap();
+        std::fs::write(
+            tmp.join("German.md"),
+            "Das ist eine Notiz, die auf Deutsch geschrieben ist und auch kurz ist.",
+        )
+        .unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let english = index.inner.get("english").unwrap();
+        let german = index.inner.get("german").unwrap();
+
+        let filter = Filter::new("lang:DE", false);
+        assert_eq!(filter.languages, vec![("de".to_string(), true)]);
+        assert!(filter.apply(german, &index).is_some());
+        assert!(filter.apply(english, &index).is_none());
+
+        let filter = Filter::new("!lang:de", false);
+        assert!(filter.apply(german, &index).is_none());
+        assert!(filter.apply(english, &index).is_some());
+    }
+
+    #[test]
+    fn test_filter_weights() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let chart = index.inner.get("chart").unwrap();
+        let smooth = index.inner.get("smooth-map").unwrap();
+        let manifold = index.inner.get("manifold").unwrap();
+
+        // === Default weights ===
+        let filter28 = Filter::new("#diffgeo #topology", true);
+
+        assert_eq!(filter28.apply(chart, &index), Some(0));
+        assert_eq!(filter28.apply(smooth, &index), Some(0));
+
+        // === Tag weights ===
+        let weights = ScoreWeights {
+            title: 1,
+            tags: 10,
+            links: 5,
+        };
+        let filter29 = Filter::new("#diffgeo #topology", true).with_weights(weights);
+
+        assert_eq!(filter29.apply(chart, &index), Some(20));
+        assert_eq!(filter29.apply(smooth, &index), Some(10));
+
+        // === Link weights, combined with title ===
+        let filter30 = Filter::new(">atlas >topology", true).with_weights(weights);
+
+        assert_eq!(filter30.apply(manifold, &index), Some(10));
+
+        let filter31 = Filter::new("#topology man", false).with_weights(weights);
+        let title_only = Filter::new("man", false).apply(manifold, &index).unwrap();
+
+        assert_eq!(filter31.apply(manifold, &index), Some(title_only + 10));
+
+        // === Weights in queries ===
+        let filter32 = Filter::new("#diffgeo OR #lietheo", false).with_weights(weights);
+
+        assert_eq!(filter32.apply(chart, &index), Some(10));
+    }
+
+    #[test]
+    fn test_filter_expensive() {
+        assert!(!Filter::new("#diffgeo lie", false).is_expensive());
+        assert!(!Filter::new("#os OR >chart", false).is_expensive());
+        assert!(Filter::new("#diffgeo | manifold", false).is_expensive());
+        assert!(Filter::new("near:chart", false).is_expensive());
+        assert!(Filter::new("#os OR near:chart", false).is_expensive());
+    }
+
+    #[test]
+    fn test_filter_near() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let chart = index.inner.get("chart").unwrap();
+        let topology = index.inner.get("topology").unwrap();
+        let linux = index.inner.get("linux").unwrap();
+
+        // === Filter 33 ===
+        let filter33 = Filter::new("near:chart", false);
+
+        assert_eq!(
+            filter33.near,
+            vec![(DistanceCondition::new("chart:1"), true)]
+        );
+        assert!(filter33.apply(chart, &index).is_some());
+        assert!(filter33.apply(topology, &index).is_none());
+        assert!(filter33.apply(linux, &index).is_none());
+
+        // === Filter 34 ===
+        let filter34 = Filter::new("near:chart:2", false);
+
+        assert!(filter34.apply(topology, &index).is_some());
+        assert!(filter34.apply(linux, &index).is_none());
+
+        // === Filter 35 ===
+        let filter35 = Filter::new("!near:operating-systems:1", false);
+
+        assert!(filter35.apply(chart, &index).is_some());
+        assert!(filter35.apply(linux, &index).is_none());
+    }
+
+    #[test]
+    fn test_filter_near_changed_index() {
+        let tmp = testdir::testdir!();
+        std::fs::write(tmp.join("Hub.md"), "[[Spoke]]").unwrap();
+        std::fs::write(tmp.join("Spoke.md"), "Nothing").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = data::NoteIndexContainer::new(data::NoteIndex::new(tracker, builder).0);
+        let watcher = data::IndexWatcher::start(&index);
+
+        let mut filter = Filter::new("near:hub", false);
+        assert!(filter
+            .apply(index.borrow().inner.get("spoke").unwrap(), &index.borrow())
+            .is_some());
+
+        // the notes close to the hub are only found again once the filter forgets the old index
+        std::fs::write(tmp.join("Hub.md"), "No more links").unwrap();
+        watcher.reload(tmp.join("Hub.md"));
+        for _ in 0..100 {
+            if watcher.apply(&index).0 {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        let index = index.borrow();
+        assert_eq!(index.inner.get("hub").unwrap().links.len(), 0);
+        assert!(filter
+            .apply(index.inner.get("spoke").unwrap(), &index)
+            .is_some());
+        filter.forget_index();
+        assert!(filter
+            .apply(index.inner.get("spoke").unwrap(), &index)
+            .is_none());
+    }
+
+    #[test]
+    fn test_filter_fields() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("Draft.md"),
+            "---\nstatus: Draft\nauthor: me\n---\n# Draft\n",
+        )
+        .unwrap();
+        let draft = data::Note::from_path(&tmp.join("Draft.md"), Default::default()).unwrap();
+        let chart = index.inner.get("chart").unwrap();
+
+        // === Filter 36 ===
+        let filter36 = Filter::new("status:draft author:me", false);
+
+        assert_eq!(
+            filter36.fields,
+            vec![
+                (("status".to_string(), "draft".to_string()), true),
+                (("author".to_string(), "me".to_string()), true)
+            ]
+        );
+        assert!(filter36.title.is_empty());
+        assert!(filter36.apply(&draft, &index).is_some());
+        assert!(filter36.apply(chart, &index).is_none());
+
+        // === Filter 37 ===
+        let filter37 = Filter::new("!status:draft", false);
+
+        assert!(filter37.apply(&draft, &index).is_none());
+        assert!(filter37.apply(chart, &index).is_some());
+
+        // === Filter 38 ===
+        let filter38 = Filter::new("author:*", false);
+
+        assert!(filter38.apply(&draft, &index).is_some());
+        assert!(filter38.apply(chart, &index).is_none());
+    }
+
+    #[test]
+    fn test_filter_fuzzy_tags() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let chart = index.inner.get("chart").unwrap();
+        let lg = index.inner.get("lie-group").unwrap();
+        let linux = index.inner.get("linux").unwrap();
+
+        // === Filter 39 ===
+        let filter39 = Filter::new("~#dffgeo", false);
+
+        assert_eq!(filter39.fuzzy_tags, vec![("#dffgeo".to_string(), true)]);
+        assert!(filter39.apply(chart, &index).is_some());
+        assert!(filter39.apply(lg, &index).is_none());
+
+        // exact tags still require equality
+        assert!(Filter::new("#dffgeo", false).apply(chart, &index).is_none());
+
+        // === Filter 40 ===
+        let filter40 = Filter::new("~#lie", false);
+
+        assert!(filter40.apply(lg, &index).is_some());
+        assert!(filter40.apply(linux, &index).is_none());
+
+        // === Filter 41 ===
+        let filter41 = Filter::new("!~#linx", false);
+
+        assert!(filter41.apply(linux, &index).is_none());
+        assert!(filter41.apply(chart, &index).is_some());
+    }
+
+    #[test]
+    fn test_filter_link_counts() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let manifold = index.inner.get("manifold").unwrap();
+        let topology = index.inner.get("topology").unwrap();
+        let os = index.inner.get("operating-systems").unwrap();
+
+        // === Filter 42 ===
+        let filter42 = Filter::new("inlinks:>3", false);
+
+        assert_eq!(filter42.inlinks, CountCondition::new(">3"));
+        assert!(filter42.apply(manifold, &index).is_some());
+        assert!(filter42.apply(os, &index).is_none());
+
+        // === Filter 43 ===
+        let filter43 = Filter::new("outlinks:0", false);
+
+        assert!(filter43.apply(topology, &index).is_some());
+        assert!(filter43.apply(manifold, &index).is_none());
+
+        // === Filter 44 ===
+        let filter44 = Filter::new("inlinks:0 outlinks:>2", false);
+
+        assert!(filter44.apply(os, &index).is_some());
+        assert!(filter44.apply(manifold, &index).is_none());
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("Möbius Strip"), "mobius strip");
+        assert_eq!(normalize("Graphs"), "graph");
+        assert_eq!(normalize("Straße"), "strasse");
+        assert_eq!(normalize("Glass  is"), "glass is");
+        assert_eq!(normalize("Analysis of Manifolds"), "analysis of manifold");
+    }
+
+    #[test]
+    fn test_filter_normalization() {
+        let mobius = data::Note {
+            name: "Möbius Strip".to_string(),
+            ..Default::default()
+        };
+        let graph = data::Note {
+            name: "Graph".to_string(),
+            ..Default::default()
+        };
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        // === Without normalization ===
+        assert!(Filter::new("mobius", false)
+            .apply(&mobius, &index)
+            .is_none());
+        assert!(Filter::new("graphs", false).apply(&graph, &index).is_none());
+
+        // === With normalization ===
+        let filter45 = Filter::new("mobius", false).with_normalization(true);
+        assert!(filter45.apply(&mobius, &index).is_some());
+
+        let filter46 = Filter::new("graphs", false).with_normalization(true);
+        assert!(filter46.apply(&graph, &index).is_some());
+        assert!(filter46.apply(&mobius, &index).is_none());
+
+        let filter47 = Filter::new("graphs OR #topology", false).with_normalization(true);
+        assert!(filter47.apply(&graph, &index).is_some());
+    }
+
+    #[test]
+    fn test_filter_sorting() {
+        use data::SortingMode;
+
+        // === Filter 48 ===
+        let filter48 = Filter::new("#os sort:words sort:-inlinks sort:nonsense", false);
+
+        assert_eq!(
+            filter48.sorting,
+            vec![
+                (SortingMode::Words, true),
+                (SortingMode::GlobalInLinks, false)
+            ]
+        );
+        assert_eq!(filter48.tags, vec![("#os".to_string(), true)]);
+        assert!(filter48.fields.is_empty());
+
+        // === Filter 49 ===
+        let filter49 = Filter::new("#os OR #topology sort:-chars", false);
+
+        assert_eq!(filter49.sorting, vec![(SortingMode::Chars, false)]);
+        assert!(filter49.query.is_some());
+
+        // sorting alone does not restrict the notes
+        assert!(Filter::new("sort:name", false).is_empty());
+    }
+
+    #[test]
+    fn test_filter_aliases() {
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("Machine Learning.md"),
+            "---\naliases:\n  - ML\n---\nSee [[Notes]].",
+        )
+        .unwrap();
+        std::fs::write(tmp.join("Notes.md"), "See [[ML]].").unwrap();
+        std::fs::write(tmp.join("Other.md"), "Nothing.").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let ml = index.inner.get("machine-learning").unwrap();
+        let notes = index.inner.get("notes").unwrap();
+        let other = index.inner.get("other").unwrap();
+
+        // === Links ===
+        for filter_string in [">ML", ">machine-learning"] {
+            let filter = Filter::new(filter_string, false);
+            assert!(filter.apply(notes, &index).is_some());
+            assert!(filter.apply(other, &index).is_none());
+        }
+
+        // === Backlinks ===
+        let filter50 = Filter::new("<ML", false);
+
+        assert!(filter50.apply(notes, &index).is_some());
+        assert!(filter50.apply(other, &index).is_none());
+
+        let filter51 = Filter::new("<notes", false);
+
+        assert!(filter51.apply(ml, &index).is_some());
+    }
+
+    #[test]
+    fn test_filter_set_operations() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let named = HashMap::from([
+            ("math".to_string(), "#diffgeo".to_string()),
+            ("topo".to_string(), "#topology".to_string()),
+            ("os".to_string(), "#os".to_string()),
+        ]);
+
+        let matches = |filter_string: &str| {
+            let filter = Filter::new_with_named(filter_string, false, &named);
+            let mut ids = index
+                .inner
+                .iter()
+                .filter(|(_id, note)| filter.apply(note, &index).is_some())
+                .map(|(id, _note)| id.as_str())
+                .collect::<Vec<_>>();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(matches("@math - @topo"), vec!["atlas", "smooth-map"]);
+        assert_eq!(matches("@math & @topo"), vec!["chart", "manifold"]);
+        assert_eq!(
+            matches("@topo + @os"),
+            vec![
+                "chart",
+                "linux",
+                "manifold",
+                "operating-systems",
+                "osx",
+                "topology",
+                "windows"
+            ]
+        );
+        // set operators are evaluated left to right
+        assert_eq!(
+            matches("@math + @os - @topo - #os/linux"),
+            vec!["atlas", "operating-systems", "osx", "smooth-map", "windows"]
+        );
+    }
+
+    #[test]
+    fn test_filter_exact_tags() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let os = index.inner.get("operating-systems").unwrap();
+        let linux = index.inner.get("linux").unwrap();
+
+        // === Filter 52 ===
+        let filter52 = Filter::new("#=os", false);
+
+        assert_eq!(filter52.exact_tags, vec![("#os".to_string(), true)]);
+        assert!(filter52.tags.is_empty());
+        assert!(filter52.apply(os, &index).is_some());
+        assert!(filter52.apply(linux, &index).is_none());
+
+        // subtag matching still applies without the =
+        assert!(Filter::new("#os", false).apply(linux, &index).is_some());
+
+        // === Filter 53 ===
+        let filter53 = Filter::new("#os !#=os", false);
+
+        assert!(filter53.apply(os, &index).is_none());
+        assert!(filter53.apply(linux, &index).is_some());
+
+        // === Filter 54 ===
+        let filter54 = Filter::new("#=os/linux", false);
+
+        assert!(filter54.apply(linux, &index).is_some());
+    }
+
+    #[test]
+    fn test_filter_todos() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let tasks = data::Note {
+            name: "Tasks".to_string(),
+            open_tasks: 4,
+            done_tasks: 1,
+            ..Default::default()
+        };
+        let done = data::Note {
+            name: "Done".to_string(),
+            done_tasks: 2,
+            ..Default::default()
+        };
+
+        // === Filter 55 ===
+        let filter55 = Filter::new("has:todo", false);
+
+        assert_eq!(filter55.properties, vec![(NoteProperty::Todo, true)]);
+        assert!(filter55.apply(&tasks, &index).is_some());
+        assert!(filter55.apply(&done, &index).is_none());
+
+        // === Filter 56 ===
+        let filter56 = Filter::new("todo:>3", false);
+
+        assert!(filter56.fields.is_empty());
+        assert!(filter56.apply(&tasks, &index).is_some());
+        assert!(filter56.apply(&done, &index).is_none());
+
+        // === Filter 57 ===
+        let filter57 = Filter::new("todo:0", false);
+
+        assert!(filter57.apply(&tasks, &index).is_none());
+        assert!(filter57.apply(&done, &index).is_some());
+    }
+
+    #[test]
+    fn test_filter_expression() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        // === Filter 58 ===
+        let filter58 = Filter::new(
+            "#diffgeo expr: words > 100 || tags.contains('#lietheo')",
+            false,
+        );
+
+        assert_eq!(filter58.tags, vec![("#diffgeo".to_string(), true)]);
+        assert!(filter58.expression.is_some());
+        assert!(filter58.full_text.is_none());
+
+        let atlas = index.inner.get("atlas").unwrap();
+        let smooth_map = index.inner.get("smooth-map").unwrap();
+        let lie_group = index.inner.get("lie-group").unwrap();
+
+        assert!(filter58.apply(atlas, &index).is_some());
+        assert!(filter58.apply(smooth_map, &index).is_none());
+        assert!(filter58.apply(lie_group, &index).is_none());
+
+        // === Filter 59 ===
+        let filter59 = Filter::new("expr: words >", false);
+
+        assert!(filter59.expression.is_none());
+        assert!(filter59.is_empty());
+    }
+
+    #[test]
+    fn test_filter_narrows() {
+        // appended conditions
+        assert!(Filter::narrows("", "#os"));
+        assert!(Filter::narrows("#os", "#os lin"));
+        assert!(Filter::narrows("#os ", "#os !#os/win"));
+        assert!(Filter::narrows("lin", "linux"));
+        assert!(Filter::narrows("lin", "lin|kernel"));
+        assert!(Filter::narrows("lin|ker", "lin|kernel"));
+
+        // not an extension
+        assert!(!Filter::narrows("#os", "#o"));
+        assert!(!Filter::narrows("linux", "lie"));
+
+        // extensions of special words can widen the filter
+        assert!(!Filter::narrows("#o", "#os"));
+        assert!(!Filter::narrows("!lin", "!linux"));
+        assert!(!Filter::narrows("words:>1", "words:>10"));
+        assert!(!Filter::narrows("sort", "sort:words"));
+        assert!(!Filter::narrows("re:a|b", "re:a|b*"));
+
+        // single-valued conditions are replaced by later words
+        assert!(!Filter::narrows("words:>1000", "words:>1000 words:>10"));
+        assert!(!Filter::narrows("created:2024", "created:2024 created:x"));
+        assert!(!Filter::narrows("#os re:^L", "#os re:^L re:."));
+        assert!(!Filter::narrows("#os", "#os inlinks:>2 | kernel"));
+        assert!(Filter::narrows("#os | ker", "#os | ker words:>10"));
+
+        // operators and named filters
+        assert!(!Filter::narrows("#os", "#os OR #diffgeo"));
+        assert!(!Filter::narrows("#os", "#os @inbox"));
+        assert!(!Filter::narrows(
+            "expr: words > 3",
+            "expr: words > 3 || true"
+        ));
+    }
 }
@@ -0,0 +1,777 @@
+use std::collections::{HashMap, VecDeque};
+
+/// The damping factor used when computing PageRank.
+const PAGERANK_DAMPING: f64 = 0.85;
+/// The maximum amount of power iterations when computing PageRank.
+const PAGERANK_ITERATIONS: usize = 100;
+/// PageRank iteration stops early once the total change of all ranks falls below this value.
+const PAGERANK_TOLERANCE: f64 = 1e-9;
+/// The amount of iterations of the force-directed layout.
+const LAYOUT_ITERATIONS: usize = 100;
+
+/// The link graph of a set of notes, only containing links between notes of that set.
+/// Notes are referred to by their position in the `ids` vector.
+#[derive(Debug, Clone, Default)]
+pub struct LinkGraph {
+    /// The ids of all notes in the graph.
+    pub ids: Vec<String>,
+    /// For each note, the positions of the notes it links to, without duplicates and self-links.
+    pub outgoing: Vec<Vec<usize>>,
+}
+
+/// Centrality metrics of a single note within a link graph.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Centrality {
+    /// The PageRank of the note. The ranks of all notes in a graph add up to 1.
+    pub pagerank: f64,
+    /// The betweenness centrality of the note, i.e. the fraction of shortest paths between other notes passing through it, normalized to [0, 1].
+    pub betweenness: f64,
+    /// The degree centrality of the note, i.e. the amount of notes linking to or linked from it, relative to the amount of other notes.
+    pub degree: f64,
+}
+
+impl LinkGraph {
+    /// Creates the link graph of the notes of the given index with the given ids.
+    pub fn new<'a>(index: &super::NoteIndex, ids: impl IntoIterator<Item = &'a String>) -> Self {
+        let ids = ids
+            .into_iter()
+            .filter(|id| index.inner.contains_key(*id))
+            .cloned()
+            .collect::<Vec<_>>();
+        let positions = ids
+            .iter()
+            .enumerate()
+            .map(|(position, id)| (id.as_str(), position))
+            .collect::<HashMap<_, _>>();
+
+        let outgoing = ids
+            .iter()
+            .enumerate()
+            .map(|(source, id)| {
+                let mut targets = index
+                    .get(id)
+                    .map(|note| {
+                        note.links
+                            .iter()
+                            .filter_map(|link| {
+                                positions.get(index.resolve_link(note, link)).copied()
+                            })
+                            .filter(|target| *target != source)
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                targets.sort_unstable();
+                targets.dedup();
+                targets
+            })
+            .collect();
+
+        Self { ids, outgoing }
+    }
+
+    /// Returns the amount of notes in this graph.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Computes PageRank, betweenness and degree centrality for all notes of the graph, by their id.
+    pub fn centrality(&self) -> HashMap<String, Centrality> {
+        let pagerank = self.pagerank();
+        let betweenness = self.betweenness();
+
+        // Count distinct neighbours in both directions
+        let mut neighbours = vec![std::collections::HashSet::new(); self.len()];
+        for (source, targets) in self.outgoing.iter().enumerate() {
+            for &target in targets {
+                neighbours[source].insert(target);
+                neighbours[target].insert(source);
+            }
+        }
+        let others = self.len().saturating_sub(1).max(1) as f64;
+
+        self.ids
+            .iter()
+            .enumerate()
+            .map(|(position, id)| {
+                (
+                    id.clone(),
+                    Centrality {
+                        pagerank: pagerank[position],
+                        betweenness: betweenness[position],
+                        degree: neighbours[position].len() as f64 / others,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Counts for every note the notes it links to that also link back to it, by its id.
+    pub fn reciprocal_links(&self) -> HashMap<String, usize> {
+        self.outgoing
+            .iter()
+            .enumerate()
+            .map(|(source, targets)| {
+                (
+                    self.ids[source].clone(),
+                    targets
+                        .iter()
+                        .filter(|&&target| self.outgoing[target].binary_search(&source).is_ok())
+                        .count(),
+                )
+            })
+            .collect()
+    }
+
+    /// Computes the connected components of the graph, ignoring the direction of links.
+    /// Returns the sizes of all components, largest first, and the component of every note by its id, as the position within the sizes.
+    pub fn components(&self) -> (Vec<usize>, HashMap<String, usize>) {
+        let n = self.len();
+
+        // Undirected adjacency
+        let mut neighbours = vec![Vec::new(); n];
+        for (source, targets) in self.outgoing.iter().enumerate() {
+            for &target in targets {
+                neighbours[source].push(target);
+                neighbours[target].push(source);
+            }
+        }
+
+        // Breadth first search from every note not yet assigned to a component
+        let mut component = vec![usize::MAX; n];
+        let mut sizes = Vec::new();
+        for start in 0..n {
+            if component[start] != usize::MAX {
+                continue;
+            }
+            let current_component = sizes.len();
+            let mut size = 0;
+            component[start] = current_component;
+            let mut queue = VecDeque::from([start]);
+            while let Some(current) = queue.pop_front() {
+                size += 1;
+                for &next in &neighbours[current] {
+                    if component[next] == usize::MAX {
+                        component[next] = current_component;
+                        queue.push_back(next);
+                    }
+                }
+            }
+            sizes.push(size);
+        }
+
+        // Renumber the components by descending size, ties by first appearance
+        let mut order = (0..sizes.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&c| std::cmp::Reverse(sizes[c]));
+        let mut renumbered = vec![0; sizes.len()];
+        for (new, &old) in order.iter().enumerate() {
+            renumbered[old] = new;
+        }
+
+        (
+            order.iter().map(|&c| sizes[c]).collect(),
+            self.ids
+                .iter()
+                .zip(component)
+                .map(|(id, c)| (id.clone(), renumbered[c]))
+                .collect(),
+        )
+    }
+
+    /// Computes the diameter, i.e. the longest shortest path, and the average shortest path length of the largest connected component, ignoring the direction of links.
+    /// Returns `None` if the graph is empty.
+    pub fn distances(&self) -> Option<(usize, f64)> {
+        let (_, components) = self.components();
+        // Component 0 is the largest one
+        let members = self
+            .ids
+            .iter()
+            .map(|id| components.get(id) == Some(&0))
+            .collect::<Vec<_>>();
+        if !members.contains(&true) {
+            return None;
+        }
+
+        let mut neighbours = vec![Vec::new(); self.len()];
+        for (source, targets) in self.outgoing.iter().enumerate() {
+            for &target in targets {
+                neighbours[source].push(target);
+                neighbours[target].push(source);
+            }
+        }
+
+        let mut diameter = 0;
+        let mut total = 0;
+        let mut pairs = 0;
+        for start in (0..self.len()).filter(|&start| members[start]) {
+            // Breadth first search from every member
+            let mut distance = vec![usize::MAX; self.len()];
+            distance[start] = 0;
+            let mut queue = VecDeque::from([start]);
+            while let Some(current) = queue.pop_front() {
+                for &next in &neighbours[current] {
+                    if distance[next] == usize::MAX {
+                        distance[next] = distance[current] + 1;
+                        diameter = diameter.max(distance[next]);
+                        total += distance[next];
+                        pairs += 1;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        Some((diameter, total as f64 / pairs.max(1) as f64))
+    }
+
+    /// Computes the strongly connected components of the graph, following links in their direction, with Kosaraju's algorithm.
+    /// Only components with more than one note, i.e. those containing link cycles, are returned, each sorted by id, largest first.
+    pub fn cycles(&self) -> Vec<Vec<String>> {
+        let n = self.len();
+
+        // First pass: order notes by the time their depth first search finishes
+        let mut visited = vec![false; n];
+        let mut order = Vec::with_capacity(n);
+        for start in 0..n {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            // Stack of notes and the position of the next link to follow
+            let mut stack = vec![(start, 0)];
+            while let Some((current, next)) = stack.pop() {
+                if let Some(&target) = self.outgoing[current].get(next) {
+                    stack.push((current, next + 1));
+                    if !visited[target] {
+                        visited[target] = true;
+                        stack.push((target, 0));
+                    }
+                } else {
+                    order.push(current);
+                }
+            }
+        }
+
+        // Second pass: collect components on the transposed graph in reverse finishing order
+        let mut incoming = vec![Vec::new(); n];
+        for (source, targets) in self.outgoing.iter().enumerate() {
+            for &target in targets {
+                incoming[target].push(source);
+            }
+        }
+
+        let mut assigned = vec![false; n];
+        let mut components = Vec::new();
+        for &start in order.iter().rev() {
+            if assigned[start] {
+                continue;
+            }
+            assigned[start] = true;
+            let mut component = Vec::new();
+            let mut stack = vec![start];
+            while let Some(current) = stack.pop() {
+                component.push(self.ids[current].clone());
+                for &previous in &incoming[current] {
+                    if !assigned[previous] {
+                        assigned[previous] = true;
+                        stack.push(previous);
+                    }
+                }
+            }
+            if component.len() > 1 {
+                component.sort();
+                components.push(component);
+            }
+        }
+
+        components.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        components
+    }
+
+    /// Computes the PageRank of every note by power iteration.
+    /// The rank of notes without outgoing links is distributed evenly among all notes.
+    fn pagerank(&self) -> Vec<f64> {
+        let n = self.len();
+        if n == 0 {
+            return Vec::new();
+        }
+
+        let mut ranks = vec![1. / n as f64; n];
+        for _ in 0..PAGERANK_ITERATIONS {
+            let dangling = self
+                .outgoing
+                .iter()
+                .zip(ranks.iter())
+                .filter(|(targets, _)| targets.is_empty())
+                .map(|(_, rank)| rank)
+                .sum::<f64>();
+
+            let base = (1. - PAGERANK_DAMPING + PAGERANK_DAMPING * dangling) / n as f64;
+            let mut next = vec![base; n];
+            for (source, targets) in self.outgoing.iter().enumerate() {
+                for &target in targets {
+                    next[target] += PAGERANK_DAMPING * ranks[source] / targets.len() as f64;
+                }
+            }
+
+            let change = next
+                .iter()
+                .zip(ranks.iter())
+                .map(|(a, b)| (a - b).abs())
+                .sum::<f64>();
+            ranks = next;
+            if change < PAGERANK_TOLERANCE {
+                break;
+            }
+        }
+
+        ranks
+    }
+
+    /// Computes the betweenness centrality of every note with Brandes' algorithm, following links in their direction.
+    fn betweenness(&self) -> Vec<f64> {
+        let n = self.len();
+        let mut betweenness = vec![0.; n];
+
+        for source in 0..n {
+            // Breadth first search counting shortest paths
+            let mut stack = Vec::new();
+            let mut predecessors = vec![Vec::new(); n];
+            let mut paths = vec![0.; n];
+            let mut distance = vec![usize::MAX; n];
+            paths[source] = 1.;
+            distance[source] = 0;
+
+            let mut queue = VecDeque::from([source]);
+            while let Some(current) = queue.pop_front() {
+                stack.push(current);
+                for &next in &self.outgoing[current] {
+                    if distance[next] == usize::MAX {
+                        distance[next] = distance[current] + 1;
+                        queue.push_back(next);
+                    }
+                    if distance[next] == distance[current] + 1 {
+                        paths[next] += paths[current];
+                        predecessors[next].push(current);
+                    }
+                }
+            }
+
+            // Accumulate dependencies in order of decreasing distance
+            let mut dependency = vec![0.; n];
+            while let Some(current) = stack.pop() {
+                for &previous in &predecessors[current] {
+                    dependency[previous] +=
+                        paths[previous] / paths[current] * (1. + dependency[current]);
+                }
+                if current != source {
+                    betweenness[current] += dependency[current];
+                }
+            }
+        }
+
+        // Normalize by the amount of ordered pairs of other notes
+        if n > 2 {
+            let pairs = ((n - 1) * (n - 2)) as f64;
+            betweenness.iter_mut().for_each(|value| *value /= pairs);
+        }
+
+        betweenness
+    }
+
+    /// Returns the subgraph only containing the notes with the given ids, in their current order.
+    pub fn restrict(&self, ids: &[String]) -> Self {
+        let keep = ids.iter().collect::<std::collections::HashSet<_>>();
+        let positions = self
+            .ids
+            .iter()
+            .enumerate()
+            .filter(|(_, id)| keep.contains(id))
+            .enumerate()
+            .map(|(new, (old, _))| (old, new))
+            .collect::<HashMap<_, _>>();
+
+        let mut ids = vec![String::new(); positions.len()];
+        let mut outgoing = vec![Vec::new(); positions.len()];
+        for (&old, &new) in positions.iter() {
+            ids[new] = self.ids[old].clone();
+            outgoing[new] = self.outgoing[old]
+                .iter()
+                .filter_map(|target| positions.get(target).copied())
+                .collect();
+        }
+
+        Self { ids, outgoing }
+    }
+
+    /// Serializes the graph to the DOT language, labelling every note with its name.
+    pub fn to_dot(&self, index: &super::NoteIndex) -> String {
+        let mut dot = String::from("digraph notes {\n");
+        for id in self.ids.iter() {
+            dot.push_str(&format!(
+                "  \"{}\" [label=\"{}\"];\n",
+                dot_escape(id),
+                dot_escape(index.get(id).map(|note| note.name.as_str()).unwrap_or(id)),
+            ));
+        }
+        for (source, targets) in self.outgoing.iter().enumerate() {
+            for &target in targets {
+                dot.push_str(&format!(
+                    "  \"{}\" -> \"{}\";\n",
+                    dot_escape(&self.ids[source]),
+                    dot_escape(&self.ids[target]),
+                ));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Serializes the graph to GraphML, storing the name of every note in the `name` attribute.
+    pub fn to_graphml(&self, index: &super::NoteIndex) -> String {
+        let mut xml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"name\" for=\"node\" attr.name=\"name\" attr.type=\"string\"/>\n",
+            "  <graph id=\"notes\" edgedefault=\"directed\">\n",
+        ));
+        for id in self.ids.iter() {
+            xml.push_str(&format!(
+                "    <node id=\"{}\"><data key=\"name\">{}</data></node>\n",
+                xml_escape(id),
+                xml_escape(index.get(id).map(|note| note.name.as_str()).unwrap_or(id)),
+            ));
+        }
+        for (source, targets) in self.outgoing.iter().enumerate() {
+            for &target in targets {
+                xml.push_str(&format!(
+                    "    <edge source=\"{}\" target=\"{}\"/>\n",
+                    xml_escape(&self.ids[source]),
+                    xml_escape(&self.ids[target]),
+                ));
+            }
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+
+    /// Returns the pairs of notes linked in either direction, each pair once with the smaller position first.
+    pub fn undirected_edges(&self) -> Vec<(usize, usize)> {
+        let mut edges = self
+            .outgoing
+            .iter()
+            .enumerate()
+            .flat_map(|(source, targets)| {
+                targets
+                    .iter()
+                    .map(move |&target| (source.min(target), source.max(target)))
+            })
+            .collect::<Vec<_>>();
+        edges.sort_unstable();
+        edges.dedup();
+        edges
+    }
+
+    /// Places all notes of the graph in the plane with a force-directed layout (Fruchterman-Reingold), so linked notes end up close to each other.
+    /// Notes start evenly spaced on a circle, so the same graph is always laid out the same way.
+    /// The layout is centered on the origin and scaled to lie within [-1, 1] in both directions.
+    pub fn layout(&self) -> Vec<(f64, f64)> {
+        let n = self.len();
+        let mut positions = (0..n)
+            .map(|position| {
+                let angle = std::f64::consts::TAU * position as f64 / n as f64;
+                (angle.cos(), angle.sin())
+            })
+            .collect::<Vec<_>>();
+        let edges = self.undirected_edges();
+
+        // The ideal distance between notes, so all of them fit into an area of size 4
+        let ideal = (4. / n.max(1) as f64).sqrt();
+
+        for iteration in 0..LAYOUT_ITERATIONS {
+            let mut forces = vec![(0., 0.); n];
+
+            // All notes repel each other
+            for a in 0..n {
+                for b in (a + 1)..n {
+                    let (dx, dy) = (
+                        positions[a].0 - positions[b].0,
+                        positions[a].1 - positions[b].1,
+                    );
+                    let distance = dx.hypot(dy).max(0.01);
+                    let force = ideal * ideal / distance;
+                    forces[a].0 += dx / distance * force;
+                    forces[a].1 += dy / distance * force;
+                    forces[b].0 -= dx / distance * force;
+                    forces[b].1 -= dy / distance * force;
+                }
+            }
+
+            // Linked notes attract each other
+            for &(a, b) in edges.iter() {
+                let (dx, dy) = (
+                    positions[a].0 - positions[b].0,
+                    positions[a].1 - positions[b].1,
+                );
+                let distance = dx.hypot(dy).max(0.01);
+                let force = distance * distance / ideal;
+                forces[a].0 -= dx / distance * force;
+                forces[a].1 -= dy / distance * force;
+                forces[b].0 += dx / distance * force;
+                forces[b].1 += dy / distance * force;
+            }
+
+            // Move every note along its force, at most by a distance that cools down over time
+            let temperature = 0.1 * (1. - iteration as f64 / LAYOUT_ITERATIONS as f64);
+            for (position, (fx, fy)) in positions.iter_mut().zip(forces) {
+                let length = fx.hypot(fy);
+                if length > 0. {
+                    let step = length.min(temperature);
+                    position.0 += fx / length * step;
+                    position.1 += fy / length * step;
+                }
+            }
+        }
+
+        // Center on the origin and scale into [-1, 1]
+        let (cx, cy) = positions
+            .iter()
+            .fold((0., 0.), |(sx, sy), (x, y)| (sx + x, sy + y));
+        let (cx, cy) = (cx / n.max(1) as f64, cy / n.max(1) as f64);
+        let extent = positions
+            .iter()
+            .map(|(x, y)| (x - cx).abs().max((y - cy).abs()))
+            .fold(0., f64::max);
+        positions
+            .into_iter()
+            .map(|(x, y)| {
+                if extent > 0. {
+                    ((x - cx) / extent, (y - cy) / extent)
+                } else {
+                    (0., 0.)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Escapes a string for use within a quoted DOT identifier.
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Escapes a string for use within XML text or attribute values.
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data, io};
+
+    #[test]
+    fn test_graph_layout() {
+        // two pairs of linked notes
+        let graph = LinkGraph {
+            ids: ["a", "b", "c", "d"].map(String::from).to_vec(),
+            outgoing: vec![vec![1], vec![0], vec![3], vec![]],
+        };
+        assert_eq!(graph.undirected_edges(), vec![(0, 1), (2, 3)]);
+
+        let positions = graph.layout();
+        assert_eq!(positions.len(), 4);
+        assert!(positions
+            .iter()
+            .all(|(x, y)| x.abs() <= 1. + 1e-9 && y.abs() <= 1. + 1e-9));
+
+        // linked notes are closer to each other than to the other pair
+        let distance = |a: usize, b: usize| {
+            (positions[a].0 - positions[b].0).hypot(positions[a].1 - positions[b].1)
+        };
+        assert!(distance(0, 1) < distance(0, 2));
+        assert!(distance(0, 1) < distance(1, 3));
+        assert!(distance(2, 3) < distance(1, 3));
+
+        // the layout does not change between runs
+        assert_eq!(graph.layout(), positions);
+
+        // a single note is placed in the center
+        let single = LinkGraph {
+            ids: vec![String::from("a")],
+            outgoing: vec![vec![]],
+        };
+        assert_eq!(single.layout(), vec![(0., 0.)]);
+        assert!(LinkGraph::default().layout().is_empty());
+    }
+
+    #[test]
+    fn test_graph_centrality() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let graph = LinkGraph::new(&index, index.inner.keys());
+
+        assert_eq!(graph.len(), 11);
+
+        let centrality = graph.centrality();
+
+        // ranks add up to one
+        let total = centrality.values().map(|c| c.pagerank).sum::<f64>();
+        assert!((total - 1.).abs() < 1e-6);
+
+        // manifold is the hub of the math notes
+        let manifold = centrality["manifold"];
+        let chart = centrality["chart"];
+        let books = centrality["books"];
+        assert!(manifold.pagerank > chart.pagerank);
+        assert!(manifold.betweenness > chart.betweenness);
+        assert!(manifold.degree > chart.degree);
+
+        // unconnected notes are not between anything
+        assert_eq!(books.betweenness, 0.);
+        assert_eq!(books.degree, 0.);
+    }
+
+    #[test]
+    fn test_graph_components() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let (sizes, components) = LinkGraph::new(&index, index.inner.keys()).components();
+
+        // math notes, operating systems and books
+        assert_eq!(sizes, vec![6, 4, 1]);
+        assert_eq!(components["chart"], 0);
+        assert_eq!(components["topology"], 0);
+        assert_eq!(components["linux"], 1);
+        assert_eq!(components["books"], 2);
+    }
+
+    #[test]
+    fn test_graph_reciprocal_links() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let reciprocal = LinkGraph::new(&index, index.inner.keys()).reciprocal_links();
+
+        // manifold links back and forth with atlas, lie group and smooth map
+        assert_eq!(reciprocal["manifold"], 3);
+        assert_eq!(reciprocal["atlas"], 1);
+        assert_eq!(reciprocal["lie-group"], 1);
+        assert_eq!(reciprocal["smooth-map"], 1);
+        assert_eq!(reciprocal["chart"], 0);
+        assert_eq!(reciprocal["operating-systems"], 0);
+    }
+
+    #[test]
+    fn test_graph_cycles() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let cycles = LinkGraph::new(&index, index.inner.keys()).cycles();
+
+        // topology does not link back and the os notes only link one way
+        assert_eq!(
+            cycles,
+            vec![vec![
+                "atlas",
+                "chart",
+                "lie-group",
+                "manifold",
+                "smooth-map"
+            ]]
+        );
+
+        // a -> b -> c -> a and d <-> e, f on its own
+        let graph = LinkGraph {
+            ids: ["a", "b", "c", "d", "e", "f"]
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+            outgoing: vec![vec![1], vec![2], vec![0, 5], vec![4], vec![3], vec![]],
+        };
+        assert_eq!(graph.cycles(), vec![vec!["a", "b", "c"], vec!["d", "e"]]);
+    }
+
+    #[test]
+    fn test_graph_distances() {
+        // a - b - c - d and e - f
+        let graph = LinkGraph {
+            ids: ["a", "b", "c", "d", "e", "f"]
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+            outgoing: vec![vec![1], vec![], vec![1, 3], vec![], vec![5], vec![]],
+        };
+
+        let (diameter, average) = graph.distances().unwrap();
+        assert_eq!(diameter, 3);
+        // 3 pairs at distance 1, 2 at 2, 1 at 3, all counted in both directions
+        assert_eq!(average, 10. / 6.);
+
+        assert_eq!(LinkGraph::default().distances(), None);
+    }
+
+    #[test]
+    fn test_graph_betweenness_path() {
+        // a -> b -> c: only b lies between two other notes
+        let graph = LinkGraph {
+            ids: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            outgoing: vec![vec![1], vec![2], vec![]],
+        };
+
+        let centrality = graph.centrality();
+
+        assert_eq!(centrality["a"].betweenness, 0.);
+        assert_eq!(centrality["b"].betweenness, 0.5);
+        assert_eq!(centrality["c"].betweenness, 0.);
+        assert_eq!(centrality["b"].degree, 1.);
+        assert_eq!(centrality["a"].degree, 0.5);
+        assert!(centrality["c"].pagerank > centrality["a"].pagerank);
+    }
+
+    #[test]
+    fn test_graph_export() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let graph = index.to_graph();
+        assert_eq!(graph.len(), 11);
+        assert!(graph.ids.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let restricted = graph.restrict(&[
+            String::from("chart"),
+            String::from("atlas"),
+            String::from("none"),
+        ]);
+        assert_eq!(restricted.ids, vec!["atlas", "chart"]);
+        assert_eq!(restricted.outgoing, vec![vec![1], vec![]]);
+
+        let dot = restricted.to_dot(&index);
+        assert_eq!(
+            dot,
+            "digraph notes {\n  \"atlas\" [label=\"Atlas\"];\n  \"chart\" [label=\"Chart\"];\n  \"atlas\" -> \"chart\";\n}\n"
+        );
+
+        let graphml = restricted.to_graphml(&index);
+        assert!(graphml.contains("<node id=\"chart\"><data key=\"name\">Chart</data></node>"));
+        assert!(graphml.contains("<edge source=\"atlas\" target=\"chart\"/>"));
+
+        assert_eq!(dot_escape("a \"b\" \\"), "a \\\"b\\\" \\\\");
+        assert_eq!(xml_escape("<a & 'b'>"), "&lt;a &amp; &apos;b&apos;&gt;");
+    }
+}
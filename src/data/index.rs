@@ -1,11 +1,37 @@
-use std::{borrow::BorrowMut, collections::HashMap};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path,
+    sync::{self, mpsc},
+};
+
+use itertools::Itertools;
 
 use crate::{error, io};
 
 use super::Note;
 
-/// Contains a NoteIndex and wraps it to provide easy mutable access from different areas of the code.
-pub type NoteIndexContainer = std::rc::Rc<std::cell::RefCell<NoteIndex>>;
+/// Contains a NoteIndex and wraps it to provide easy access from different areas of the code and from background threads.
+/// Any number of readers can access the index at once, while changing it waits for them to finish.
+#[derive(Debug, Clone)]
+pub struct NoteIndexContainer(sync::Arc<sync::RwLock<NoteIndex>>);
+
+impl NoteIndexContainer {
+    /// Wraps the given index.
+    pub fn new(index: NoteIndex) -> Self {
+        Self(sync::Arc::new(sync::RwLock::new(index)))
+    }
+
+    /// Locks the index for reading.
+    /// A panic of another holder of the lock does not leave the index half-changed, so the lock is used regardless.
+    pub fn borrow(&self) -> sync::RwLockReadGuard<'_, NoteIndex> {
+        self.0.read().unwrap_or_else(sync::PoisonError::into_inner)
+    }
+
+    /// Locks the index for writing, waiting for all readers to finish.
+    pub fn borrow_mut(&self) -> sync::RwLockWriteGuard<'_, NoteIndex> {
+        self.0.write().unwrap_or_else(sync::PoisonError::into_inner)
+    }
+}
 
 /// Indicates a note with old id .0 has changed id to .1.unwrap() or was deleted (.1 = None).
 pub type IdChange = (String, Option<String>);
@@ -14,10 +40,22 @@ pub type IdChange = (String, Option<String>);
 pub struct NoteIndex {
     /// The wrapped HashMap, available only in the data module.
     pub(super) inner: HashMap<String, Note>,
+    /// Maps the ids of aliases declared by notes to the ids of these notes.
+    aliases: HashMap<String, String>,
+    /// Maps the ids derived from file names to the ids of all notes with that file name, in case several notes in different folders share one.
+    names: HashMap<String, Vec<String>>,
+    /// Maps link targets, as written in the linking notes, to the ids of the notes linking to them, once per link.
+    backlinks: HashMap<String, Vec<String>>,
+    /// The sorted paths of all attachments in the vault.
+    attachments: Vec<path::PathBuf>,
+    /// The ids of all notes created, modified or deleted by file events since they were last taken.
+    changed: HashSet<String>,
 
     /// === Config ===
     /// The file tracker that sends file events and watches the structure of the vault of this index.
-    tracker: io::FileTracker,
+    /// Shared with the [IndexWatcher] handling its file events, which uses it without locking the index.
+    /// Available only in the data module.
+    pub(super) tracker: sync::Arc<io::FileTracker>,
     /// The HtmlBuilder this index uses to create its HTML files.
     builder: io::HtmlBuilder,
 }
@@ -36,33 +74,68 @@ impl NoteIndex {
     ///
     /// All IO errors that happeded during the creation or the (potential) HTML conversion are returned alongside.
     pub fn new(
+        tracker: io::FileTracker,
+        builder: io::HtmlBuilder,
+    ) -> (Self, Vec<error::RucolaError>) {
+        Self::new_with_cache(tracker, builder, super::IndexCache::default())
+    }
+
+    /// Like [Self::new], but takes notes whose files were not modified since they were cached from the given cache instead of parsing them again.
+    /// HTML files of cached notes are only created if they do not exist yet.
+    pub fn new_with_cache(
         mut tracker: io::FileTracker,
         builder: io::HtmlBuilder,
+        mut cache: super::IndexCache,
     ) -> (Self, Vec<error::RucolaError>) {
+        cache.discard_unless_read_with(tracker.read_settings());
         // create an error struct
         let mut errors = vec![];
-        // collect all the notes from the vault folder
-        let inner = tracker
-            .get_walker() // Check only OKs
-            .flatten()
-            // Convert tiles to notes and skip errors
-            .filter(|entry| entry.metadata().is_ok_and(|md| md.is_file()))
-            .flat_map(|entry| match Note::from_path(entry.path()) {
+        // collect all the notes from the vault folder, remembering which ones need a new html
+        let mut outdated = HashSet::new();
+        let mut notes = tracker
+            .note_paths()
+            .into_iter()
+            // Convert files to notes and skip errors
+            .map(|path| {
+                let modified = std::fs::metadata(&path)
+                    .ok()
+                    .and_then(|metadata| metadata.modified().ok());
+                match cache.take(&path, modified) {
+                    Some(note) => Ok(note),
+                    None => {
+                        let note = tracker.read_note(&path);
+                        outdated.insert(path);
+                        note
+                    }
+                }
+            })
+            .flat_map(|res| match res {
                 Ok(note) => Some(note),
                 Err(e) => {
                     errors.push(e);
                     None
                 }
             })
-            // Extract name and convert to id
-            .map(|note| (super::name_to_id(&note.name), note))
-            // Collect into hash map
-            .collect::<HashMap<_, _>>();
+            .collect::<Vec<_>>();
+
+        // Assign the ids according to the configured scheme, also to cached notes.
+        // Notes are inserted in order of their paths, so colliding ids are disambiguated the same way on every start.
+        notes.sort_by(|a, b| a.path.cmp(&b.path));
+        let mut inner = HashMap::new();
+        for mut note in notes {
+            note.id = unique_id(&inner, tracker.note_id(&note), &note.path);
+            inner.insert(note.id.clone(), note);
+        }
 
         // create htmls and save errors
         errors.extend(
             inner
                 .values()
+                // large notes only get their html when viewed
+                .filter(|note| !note.truncated)
+                .filter(|note| {
+                    outdated.contains(&note.path) || !builder.name_to_html_path(&note.name).exists()
+                })
                 .map(|note| builder.create_html(note, false))
                 .flat_map(|res| match res {
                     Ok(_) => None,
@@ -76,14 +149,38 @@ impl NoteIndex {
             Err(e) => errors.push(e.into()),
         };
 
-        (
-            Self {
-                inner,
-                tracker,
-                builder,
-            },
-            errors,
-        )
+        let mut index = Self {
+            inner,
+            aliases: HashMap::new(),
+            names: HashMap::new(),
+            backlinks: HashMap::new(),
+            attachments: tracker.attachments(),
+            changed: HashSet::new(),
+            tracker: sync::Arc::new(tracker),
+            builder,
+        };
+        index.rebuild_aliases();
+        index.rebuild_names();
+        let notes = index
+            .inner
+            .iter()
+            .map(|(id, note)| (id.clone(), note.links.clone()))
+            .collect::<Vec<_>>();
+        for (id, links) in notes {
+            index.add_backlinks(&id, &links);
+        }
+
+        // warn about notes that had to be given another id
+        let renamed = index
+            .id_collisions()
+            .iter()
+            .map(|(_base, notes)| notes.len() - 1)
+            .sum::<usize>();
+        if renamed > 0 {
+            errors.push(error::RucolaError::IdCollisions(renamed));
+        }
+
+        (index, errors)
     }
 
     /// Wrapper of the HashMap::get() Function
@@ -91,130 +188,483 @@ impl NoteIndex {
         self.inner.get(key)
     }
 
-    /// Handle all file events on notes, as found by the contained tracker.
-    ///  - Renames and moves are tracked
-    ///  - new file creations with in the vault folder are checked for notes and added if appropriate
-    ///  - removed files are removed from the index (if they were present)
-    ///  - Modifications of files are checked for being notes and if so, the respective index entries are updated with the new data.
+    /// Returns the note with the given id, reading it completely if only its beginning was indexed because of its size.
+    /// The index itself keeps the partially read note.
+    pub fn get_full(&self, key: &str) -> error::Result<Option<Note>> {
+        match self.inner.get(key) {
+            Some(note) if note.truncated => {
+                let mut full = self.tracker.read_full_note(&note.path)?;
+                full.id = note.id.clone();
+                Ok(Some(full))
+            }
+            note => Ok(note.cloned()),
+        }
+    }
+
+    /// Returns an iterator over the ids of all notes in this index.
+    pub fn ids(&self) -> impl Iterator<Item = &String> {
+        self.inner.keys()
+    }
+
+    /// Returns the link graph of all notes in this index, ordered by id.
+    pub fn to_graph(&self) -> super::LinkGraph {
+        super::LinkGraph::new(self, self.inner.keys().sorted())
+    }
+
+    /// Applies the given changes, as read from file events by an [IndexWatcher], to the index.
     /// Returns wether the index has changed, and a list of all IdChanges.
-    pub fn handle_file_events(&mut self) -> error::Result<(bool, Vec<IdChange>)> {
+    fn apply_file_changes(&mut self, changes: Vec<FileChange>) -> (bool, Vec<IdChange>) {
         let mut modifications = false;
         let mut id_changes = vec![];
-        for event in self.tracker.try_events_iter().flatten() {
-            match event.kind {
-                notify::EventKind::Create(kind) => {
-                    // Creations:
-                    // - Check if a file was created (we don't care about folders)
-                    // - Check for each path if we are interested in it (gitignore + extensions from config)
-                    // - Try to load the note and index it
-                    if kind == notify::event::CreateKind::File {
-                        for path in event.paths {
-                            if self.tracker.is_tracked(&path) {
-                                if let Ok(note) = super::Note::from_path(&path) {
-                                    // create html on creation
-                                    self.builder.create_html(&note, false)?;
-                                    // insert the note
-                                    self.inner.insert(super::name_to_id(&note.name), note);
-                                    modifications = true;
-                                }
-                            }
-                        }
+        for change in changes {
+            match change {
+                FileChange::Insert(notes) => {
+                    for note in notes {
+                        self.insert_note(note);
+                        modifications = true;
                     }
                 }
-                notify::EventKind::Modify(kind) => {
-                    // Modifications
-                    // - For renames, remove all notes at a path coinciding with a rename source, then insert all notes from a rename target.
-                    // - For (meta)data modifications, reload the entire note
-                    match kind {
-                        // Renames and moves
-                        notify::event::ModifyKind::Name(mode) => match mode {
-                            notify::event::RenameMode::From => {}
-                            notify::event::RenameMode::To => {}
-                            notify::event::RenameMode::Both => {
-                                let from = event.paths.first().ok_or_else(|| {
-                                    error::RucolaError::NotifyEventError(event.clone())
-                                })?;
-                                let to = event.paths.get(1).ok_or_else(|| {
-                                    error::RucolaError::NotifyEventError(event.clone())
-                                })?;
-                                // find the id of the first note with this path
-                                if let Some(old_id) = self
-                                    .inner
-                                    .iter()
-                                    .find(|(_id, note)| note.path.to_path_buf() == *from)
-                                    .map(|(id, _n)| id.to_owned())
-                                {
-                                    // remove old note
-                                    self.inner.remove(&old_id);
-                                    modifications = true;
-                                    // add new note
-                                    if self.tracker.is_tracked(to) {
-                                        if let Ok(note) = super::Note::from_path(to) {
-                                            // create html on creation
-                                            self.builder.create_html(&note, false)?;
-                                            // insert the note from the new location
-                                            let new_id = super::name_to_id(&note.name);
-                                            self.inner.insert(new_id.clone(), note);
-                                            if old_id != new_id {
-                                                id_changes.push((old_id, Some(new_id)));
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                            notify::event::RenameMode::Any => {}
-                            notify::event::RenameMode::Other => {}
-                        },
-                        // General edits
-                        notify::event::ModifyKind::Data(_)
-                        | notify::event::ModifyKind::Metadata(_) => {
-                            for (_id, note) in self.inner.borrow_mut().iter_mut() {
-                                if event.paths.contains(&note.path) {
-                                    if let Ok(new_note) = Note::from_path(&note.path) {
-                                        // create html on creation
-                                        self.builder.create_html(&new_note, false)?;
-                                        // replace the index entry
-                                        *note = new_note;
-                                        modifications = true;
-                                    }
-                                }
+                FileChange::Remove(path) => {
+                    for old_id in self.remove_path(&path) {
+                        modifications = true;
+                        id_changes.push((old_id, None));
+                    }
+                }
+                FileChange::Rename(from, notes) => {
+                    let old_ids = self.remove_path(&from);
+                    let new_ids = notes
+                        .into_iter()
+                        .map(|note| self.insert_note(note))
+                        .collect::<Vec<_>>();
+                    modifications |= !old_ids.is_empty() || !new_ids.is_empty();
+
+                    match (old_ids.as_slice(), new_ids.as_slice()) {
+                        // a single note was renamed - displays can follow it
+                        ([old_id], [new_id]) => {
+                            if old_id != new_id {
+                                id_changes.push((old_id.clone(), Some(new_id.clone())));
                             }
                         }
-                        notify::event::ModifyKind::Any => {}
-                        notify::event::ModifyKind::Other => {}
+                        // otherwise, only report the notes that did not reappear
+                        _ => id_changes.extend(
+                            old_ids
+                                .into_iter()
+                                .filter(|old_id| !new_ids.contains(old_id))
+                                .map(|old_id| (old_id, None)),
+                        ),
                     }
                 }
-                // Remove events: Keep only those notes whose path was not removed
-                notify::EventKind::Remove(kind) => {
-                    if kind == notify::event::RemoveKind::File {
-                        let deleted_path = event
-                            .paths
-                            .first()
-                            .ok_or_else(|| error::RucolaError::NotifyEventError(event.clone()))?;
-                        if let Some(old_id) = self
-                            .inner
-                            .iter()
-                            .find(|(_id, note)| note.path.to_path_buf() == *deleted_path)
-                            .map(|(id, _n)| id.to_owned())
-                        {
-                            self.inner.remove(&old_id);
-                            modifications = true;
-                            id_changes.push((old_id, None));
-                        }
+                FileChange::Touch(path, modified) => {
+                    if let Some(note) = self.inner.values_mut().find(|note| note.path == path) {
+                        note.modified = modified;
                     }
                 }
-                // Do nothing in the other cases
-                notify::EventKind::Access(_) => {}
-                notify::EventKind::Other => {}
-                notify::EventKind::Any => {}
+                FileChange::Attachments(attachments) => {
+                    self.attachments = attachments;
+                    modifications = true;
+                }
             }
         }
         // just to be sure
         modifications |= !id_changes.is_empty();
-        Ok((modifications, id_changes))
+        (modifications, id_changes)
+    }
+
+    /// Removes the note at the given path, or all notes below it if it is a folder, from the index.
+    /// Returns the ids of all removed notes.
+    fn remove_path(&mut self, path: &path::Path) -> Vec<String> {
+        let ids = self
+            .inner
+            .iter()
+            .filter(|(_id, note)| note.path.starts_with(path))
+            .map(|(id, _note)| id.to_owned())
+            .collect::<Vec<_>>();
+        for id in &ids {
+            self.remove_note(id);
+        }
+        ids
+    }
+
+    /// Re-parses the single note file at the given path, (re-)creates its HTML and inserts it into the index, replacing its previous version.
+    /// Links and backlinks are read from the notes themselves, so only the alias table needs to be patched.
+    /// The note is read while the index is borrowed, see [IndexWatcher::reload] to read it in the background instead.
+    /// Returns the id of the updated note.
+    pub fn update_note(&mut self, path: &path::Path) -> error::Result<String> {
+        let note = self.tracker.read_note(path)?;
+        // large notes only get their html when viewed
+        if !note.truncated {
+            self.builder.create_html(&note, false)?;
+        }
+        Ok(self.insert_note(note))
+    }
+
+    /// Removes the note with the given id from the index and patches the alias table.
+    /// Returns the removed note, if there was one.
+    pub fn remove_note(&mut self, id: &str) -> Option<Note> {
+        let note = self.inner.remove(id)?;
+        self.changed.insert(id.to_owned());
+        self.remove_backlinks(id, &note.links);
+
+        // the note no longer shares its name with others
+        let name = super::name_to_id(&note.name);
+        if let Some(ids) = self.names.get_mut(&name) {
+            ids.retain(|other| other != id);
+            if ids.is_empty() {
+                self.names.remove(&name);
+            }
+        }
+
+        // aliases of the removed note are gone
+        self.aliases.retain(|_alias, target| *target != id);
+        // and aliases of other notes that were shadowed by its id can now be used
+        if let Some(target) = self.inner.iter().find_map(|(other_id, other)| {
+            other
+                .alias_ids()
+                .any(|alias| alias == id)
+                .then(|| other_id.clone())
+        }) {
+            self.aliases.insert(id.to_owned(), target);
+        }
+
+        Some(note)
+    }
+
+    /// Inserts the given note into the index, replacing any previous version, then patches the alias table.
+    /// Returns the id of the inserted note.
+    fn insert_note(&mut self, mut note: Note) -> String {
+        note.id = unique_id(&self.inner, self.tracker.note_id(&note), &note.path);
+        let id = note.id.clone();
+
+        // if the id of the note changed, e.g. through its front matter, its previous version is no longer found under the new one
+        if let Some(previous_id) = self
+            .inner
+            .iter()
+            .find(|(other_id, other)| other.path == note.path && **other_id != id)
+            .map(|(other_id, _other)| other_id.clone())
+        {
+            self.remove_note(&previous_id);
+        }
+
+        // the aliases of the previous version might have changed, and the id itself now shadows any equal alias
+        self.aliases
+            .retain(|alias, target| *target != id && *alias != id);
+        self.aliases.extend(
+            note.alias_ids()
+                .map(|alias| (alias, id.clone()))
+                .filter(|(alias, _id)| !self.inner.contains_key(alias)),
+        );
+
+        let names = self.names.entry(super::name_to_id(&note.name)).or_default();
+        if !names.contains(&id) {
+            names.push(id.clone());
+        }
+
+        self.changed.insert(id.clone());
+        self.add_backlinks(&id, &note.links);
+        if let Some(old_note) = self.inner.insert(id.clone(), note) {
+            self.remove_backlinks(&id, &old_note.links);
+        }
+        id
+    }
+
+    /// Registers the given links of the note with the given id in the backlink table.
+    fn add_backlinks(&mut self, id: &str, links: &[String]) {
+        for link in links {
+            self.backlinks
+                .entry(link.clone())
+                .or_default()
+                .push(id.to_owned());
+        }
+    }
+
+    /// Removes the given links of the note with the given id from the backlink table.
+    fn remove_backlinks(&mut self, id: &str, links: &[String]) {
+        for link in links {
+            if let Some(sources) = self.backlinks.get_mut(link) {
+                if let Some(position) = sources.iter().position(|source| source == id) {
+                    sources.swap_remove(position);
+                }
+                if sources.is_empty() {
+                    self.backlinks.remove(link);
+                }
+            }
+        }
+    }
+
+    /// Returns the ids of all notes linking to the note with the given id, directly or through one of its aliases, once per link.
+    /// Links to a file name shared by several notes only count for the note they resolve to, see [Self::resolve_link].
+    pub fn backlinks<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a String> + 'a {
+        let aliases = self
+            .inner
+            .get(id)
+            .map(|note| {
+                note.alias_ids()
+                    .filter(|alias| {
+                        self.resolve_id(alias) == id
+                            || self
+                                .names
+                                .get(alias)
+                                .is_some_and(|ids| ids.iter().any(|other| other == id))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        std::iter::once(id.to_owned())
+            .chain(aliases)
+            .flat_map(move |target| self.backlinks.get_key_value(&target))
+            .flat_map(move |(target, sources)| {
+                sources.iter().filter(move |source| {
+                    self.inner
+                        .get(source.as_str())
+                        .map_or(true, |note| self.resolve_link(note, target) == id)
+                })
+            })
+    }
+
+    /// Returns the ids of all notes created, modified or deleted by file events since the last call, see [IndexWatcher].
+    pub fn take_changed(&mut self) -> HashSet<String> {
+        std::mem::take(&mut self.changed)
+    }
+
+    /// Re-creates the alias table from the aliases declared by the indexed notes.
+    /// Aliases that coincide with the id of an existing note are ignored.
+    fn rebuild_aliases(&mut self) {
+        self.aliases = self
+            .inner
+            .iter()
+            .flat_map(|(id, note)| note.alias_ids().map(|alias| (alias, id.clone())))
+            .filter(|(alias, _id)| !self.inner.contains_key(alias))
+            .collect();
+    }
+
+    /// Re-creates the table of notes sharing their file name from the indexed notes.
+    fn rebuild_names(&mut self) {
+        self.names = HashMap::new();
+        for (id, note) in self
+            .inner
+            .iter()
+            .sorted_by(|(_, a), (_, b)| a.path.cmp(&b.path))
+        {
+            self.names
+                .entry(super::name_to_id(&note.name))
+                .or_default()
+                .push(id.clone());
+        }
+    }
+
+    /// Resolves a link of the given note to the id of the note it points to.
+    /// If several notes share the file name the link points to, the one in the folder of the linking note is preferred, as in Obsidian.
+    /// Otherwise, the link is resolved vault-wide through [Self::resolve_id].
+    pub fn resolve_link<'a>(&'a self, source: &Note, link: &'a str) -> &'a str {
+        self.names
+            .get(link)
+            .filter(|ids| ids.len() > 1)
+            .and_then(|ids| {
+                ids.iter().find(|id| {
+                    self.inner
+                        .get(id.as_str())
+                        .is_some_and(|note| note.path.parent() == source.path.parent())
+                })
+            })
+            .map(|id| id.as_str())
+            .unwrap_or_else(|| self.resolve_id(link))
+    }
+
+    /// Resolves the given id through the alias table.
+    /// Returns the id itself if it belongs to a note or is no known alias.
+    pub fn resolve_id<'a>(&'a self, id: &'a str) -> &'a str {
+        if self.inner.contains_key(id) {
+            id
+        } else {
+            self.aliases.get(id).map(|id| id.as_str()).unwrap_or(id)
+        }
+    }
+
+    /// Returns all ids that more than one note of the vault would have according to the configured id scheme, sorted.
+    /// For each such id, lists the ids the notes were given instead with their paths relative to the vault.
+    pub fn id_collisions(&self) -> Vec<(String, Vec<(&str, &path::Path)>)> {
+        let mut collisions = HashMap::<String, Vec<(&str, &path::Path)>>::new();
+        for (id, note) in self.inner.iter() {
+            let base = self.tracker.note_id(note);
+            if *id != base {
+                collisions
+                    .entry(base)
+                    .or_default()
+                    .push((id.as_str(), self.relative_path(note)));
+            }
+        }
+
+        collisions
+            .into_iter()
+            .map(|(base, mut notes)| {
+                // the note that kept the id
+                if let Some((id, note)) = self.inner.get_key_value(&base) {
+                    notes.push((id.as_str(), self.relative_path(note)));
+                }
+                notes.sort();
+                (base, notes)
+            })
+            // a disambiguated note whose counterpart was removed no longer collides
+            .filter(|(_base, notes)| notes.len() > 1)
+            .sorted()
+            .collect()
+    }
+
+    /// Returns the path of the vault this index was created from.
+    pub fn vault_path(&self) -> &path::Path {
+        self.tracker.vault_path()
+    }
+
+    /// Returns the path of the given note relative to the vault, or the full path if it lies outside the vault.
+    pub fn relative_path<'a>(&self, note: &'a Note) -> &'a path::Path {
+        note.path
+            .strip_prefix(self.tracker.vault_path())
+            .unwrap_or(&note.path)
+    }
+
+    /// Returns the file references of the given note to attachments that do not exist in the vault.
+    /// References are matched with attachments by file name, so they may point to any folder.
+    pub fn missing_attachments<'a>(&self, note: &'a Note) -> Vec<&'a str> {
+        note.file_references
+            .iter()
+            .filter(|reference| {
+                self.tracker
+                    .is_attachment(path::Path::new(reference.as_str()))
+            })
+            .filter(|reference| {
+                let name = super::reference_file_name(reference);
+                !self
+                    .attachments
+                    .iter()
+                    .any(|attachment| attachment.file_name().is_some_and(|file| *file == *name))
+            })
+            .map(|reference| reference.as_str())
+            .collect()
+    }
+
+    /// Returns the path of the attachment with the given id, see [super::attachment_id].
+    pub fn attachment_path(&self, id: &str) -> Option<&path::Path> {
+        self.attachments
+            .iter()
+            .find(|attachment| super::attachment_id(&attachment.to_string_lossy()) == id)
+            .map(|attachment| attachment.as_path())
     }
 
-    /// Returns an iterator over pairs of (id, name) of notes linked from this note.
+    /// Returns pairs of (id, file name) of the attachments in the vault referenced by the note with the given id, once each.
+    pub fn attachments_vec(&self, source_id: &str) -> Vec<(String, String)> {
+        self.inner
+            .get(source_id)
+            .map(|source| {
+                source
+                    .file_references
+                    .iter()
+                    .map(|reference| super::attachment_id(reference))
+                    .unique()
+                    .flat_map(|id| {
+                        self.attachment_path(&id)
+                            .and_then(|path| path.file_name())
+                            .map(|name| name.to_string_lossy().to_string())
+                            .map(|name| (id, name))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns pairs of (id, title) of the notes referencing the attachment with the given id.
+    pub fn attachment_blinks_vec(&self, attachment_id: &str) -> Vec<(String, String)> {
+        self.inner
+            .iter()
+            .filter(|(_id, note)| {
+                note.file_references
+                    .iter()
+                    .any(|reference| super::attachment_id(reference) == attachment_id)
+            })
+            .map(|(id, note)| (id.clone(), note.title().to_owned()))
+            .sorted()
+            .collect()
+    }
+
+    /// Returns the paths, relative to the vault, of all attachments in the vault that no note references, sorted.
+    pub fn unreferenced_attachments(&self) -> Vec<&path::Path> {
+        let referenced = self
+            .inner
+            .values()
+            .flat_map(|note| note.file_references.iter())
+            .map(|reference| super::reference_file_name(reference))
+            .collect::<HashSet<_>>();
+
+        self.attachments
+            .iter()
+            .filter(|attachment| {
+                !attachment
+                    .file_name()
+                    .is_some_and(|name| referenced.contains(&*name.to_string_lossy()))
+            })
+            .map(|attachment| {
+                attachment
+                    .strip_prefix(self.tracker.vault_path())
+                    .unwrap_or(attachment)
+            })
+            .collect()
+    }
+
+    /// Returns the amount of links of the given note whose target is not contained in this index, even through an alias.
+    pub fn broken_links_count(&self, note: &Note) -> usize {
+        note.links
+            .iter()
+            .filter(|link| !self.inner.contains_key(self.resolve_link(note, link)))
+            .count()
+    }
+
+    /// Returns the amount of links pointing to the note with the given id from anywhere in the index.
+    pub fn inlinks_count(&self, id: &str) -> usize {
+        self.backlinks(id).count()
+    }
+
+    /// Returns the amount of links of the given note whose target is contained in this index.
+    pub fn outlinks_count(&self, note: &Note) -> usize {
+        note.links.len() - self.broken_links_count(note)
+    }
+
+    /// Returns the ids of all notes reachable from the note with the given id within the given amount of link hops, following links in either direction.
+    /// The note itself is always contained, as long as it exists.
+    pub fn within_distance(&self, id: &str, distance: usize) -> HashSet<String> {
+        let mut reached = HashSet::new();
+        if !self.inner.contains_key(id) {
+            return reached;
+        }
+
+        // build an undirected adjacency list of all valid links
+        let mut neighbours: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (source, note) in self.inner.iter() {
+            for target in note.links.iter().map(|link| self.resolve_link(note, link)) {
+                if self.inner.contains_key(target) {
+                    neighbours.entry(source).or_default().push(target);
+                    neighbours.entry(target).or_default().push(source);
+                }
+            }
+        }
+
+        // breadth first search from the start note
+        let mut queue = VecDeque::from([(id, 0)]);
+        reached.insert(id.to_string());
+        while let Some((current, hops)) = queue.pop_front() {
+            if hops >= distance {
+                continue;
+            }
+            for next in neighbours.get(current).into_iter().flatten() {
+                if reached.insert(next.to_string()) {
+                    queue.push_back((next, hops + 1));
+                }
+            }
+        }
+
+        reached
+    }
+
+    /// Returns an iterator over pairs of (id, title) of notes linked from this note.
     pub fn links_vec(&self, source_id: &str) -> Vec<(String, String)> {
         self.inner
             .get(source_id)
@@ -222,28 +672,428 @@ impl NoteIndex {
                 source
                     .links
                     .iter()
+                    .map(|link_id| self.resolve_link(source, link_id))
                     .flat_map(|link_id| {
                         self.inner
                             .get(link_id)
-                            .map(|note| note.name.clone())
-                            .map(|name| (link_id.to_owned(), name))
+                            .map(|note| (link_id.to_owned(), note.title().to_owned()))
                     })
                     .collect()
             })
             .unwrap_or_default()
     }
 
-    /// Returns an iterator over pairs of (id, name) of notes linking to this note.
-    pub fn blinks_vec(&self, target_id: &str) -> Vec<(String, String)> {
-        let id_copy = target_id.to_string();
+    /// Returns pairs of (id, title) of the existing notes embedded in the note with the given id.
+    pub fn embeds_vec(&self, source_id: &str) -> Vec<(String, String)> {
         self.inner
+            .get(source_id)
+            .map(|source| {
+                source
+                    .embeds
+                    .iter()
+                    .map(|embed_id| self.resolve_link(source, embed_id))
+                    .flat_map(|embed_id| {
+                        self.inner
+                            .get(embed_id)
+                            .map(|note| (embed_id.to_owned(), note.title().to_owned()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the tags and valid link targets of the given note, by which notes are compared for similarity.
+    fn similarity_features<'a>(&'a self, note: &'a Note) -> HashSet<&'a str> {
+        note.tags
             .iter()
-            .filter(|(_other_id, note)| note.links.contains(&id_copy))
-            .map(|(id, note)| (id.to_owned(), note.name.to_owned()))
+            .map(|tag| tag.as_str())
+            .chain(
+                note.links
+                    .iter()
+                    .map(|link| self.resolve_link(note, link))
+                    .filter(|id| self.inner.contains_key(*id)),
+            )
+            .collect()
+    }
+
+    /// Returns up to the given amount of notes among the candidates that are most similar to the note with the given id, with their similarity, most similar first.
+    /// Similarity is the Jaccard index of the tags and valid link targets of two notes.
+    /// Notes that already link to or are linked from the note, or share nothing with it, are not suggested.
+    pub fn related_notes<'a>(
+        &self,
+        id: &str,
+        candidates: impl IntoIterator<Item = &'a String>,
+        amount: usize,
+    ) -> Vec<(String, f64)> {
+        let Some(note) = self.inner.get(id) else {
+            return Vec::new();
+        };
+        let features = self.similarity_features(note);
+
+        let mut related = candidates
+            .into_iter()
+            .filter(|other_id| other_id.as_str() != id)
+            .filter_map(|other_id| self.inner.get_key_value(other_id))
+            // skip notes already linked in either direction
+            .filter(|(other_id, other)| {
+                !features.contains(other_id.as_str())
+                    && !other
+                        .links
+                        .iter()
+                        .any(|link| self.resolve_link(other, link) == id)
+            })
+            .filter_map(|(other_id, other)| {
+                let other_features = self.similarity_features(other);
+                let shared = features.intersection(&other_features).count();
+                (shared > 0).then(|| {
+                    let total = features.union(&other_features).count();
+                    (other_id.clone(), shared as f64 / total as f64)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        related.sort_by(|(id_a, a), (id_b, b)| b.total_cmp(a).then_with(|| id_a.cmp(id_b)));
+        related.truncate(amount);
+        related
+    }
+
+    /// Returns an iterator over pairs of (id, title) of notes linking to this note.
+    pub fn blinks_vec(&self, target_id: &str) -> Vec<(String, String)> {
+        self.backlinks(target_id)
+            .unique()
+            .flat_map(|id| {
+                self.inner
+                    .get(id)
+                    .map(|note| (id.to_owned(), note.title().to_owned()))
+            })
+            .collect()
+    }
+
+    /// Returns the id of each note linking to the note with the given id together with the sentence the link appears in, once per link.
+    pub fn backlink_contexts(&self, target_id: &str) -> Vec<(String, String)> {
+        self.backlinks(target_id)
+            .unique()
+            .flat_map(|id| self.inner.get(id).map(|note| (id, note)))
+            .flat_map(|(id, note)| {
+                note.link_contexts
+                    .iter()
+                    .filter(|(target, _context)| self.resolve_link(note, target) == target_id)
+                    .map(|(_target, context)| (id.to_owned(), context.to_owned()))
+            })
             .collect()
     }
 }
 
+/// A new index of a vault being built on a background thread, so the current index stays usable until it can be swapped out.
+pub struct IndexRescan {
+    /// The thread building the new index.
+    handle: std::thread::JoinHandle<error::Result<(NoteIndex, Vec<error::RucolaError>)>>,
+}
+
+impl IndexRescan {
+    /// Starts indexing the given index's vault again on a background thread, with a new file tracker created from the given config.
+    /// Notes whose files did not change are taken from a cache of the given index instead of being parsed again.
+    /// The cache is also built on the background thread, which only locks the given index for reading meanwhile.
+    pub fn start(config: &crate::Config, index: &NoteIndexContainer) -> Self {
+        let config = config.clone();
+        let index = index.clone();
+
+        Self {
+            handle: std::thread::spawn(move || {
+                let (vault_path, cache) = {
+                    let index = index.borrow();
+                    (
+                        index.vault_path().to_path_buf(),
+                        super::IndexCache::new(&index),
+                    )
+                };
+                let tracker = io::FileTracker::new(&config, vault_path.clone())?;
+                let builder = io::HtmlBuilder::new(&config, vault_path);
+                Ok(NoteIndex::new_with_cache(tracker, builder, cache))
+            }),
+        }
+    }
+
+    /// Wether the new index is done and [Self::finish] will not block.
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Waits for the new index and returns it together with all errors that happened during indexing.
+    pub fn finish(self) -> error::Result<(NoteIndex, Vec<error::RucolaError>)> {
+        self.handle
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
+}
+
+/// A change to an index caused by file events, with all affected notes already read, so applying it only needs a brief lock of the index.
+enum FileChange {
+    /// Notes that were created or modified, to be inserted into the index, replacing their previous versions.
+    Insert(Vec<Note>),
+    /// A path whose note, or all notes below it if it was a folder, was removed.
+    Remove(path::PathBuf),
+    /// A path that was renamed or moved, together with all notes found at its target.
+    Rename(path::PathBuf, Vec<Note>),
+    /// The path of a note file that was touched without changing its content, together with its new modification time.
+    Touch(path::PathBuf, Option<std::time::SystemTime>),
+    /// The rescanned attachments of the vault.
+    Attachments(Vec<path::PathBuf>),
+}
+
+/// How long the background thread of an [IndexWatcher] waits for new file events or reload requests before looking again.
+const WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Handles the file events of an index on a background thread.
+/// Notes affected by file events are read and get their HTML there, so the index is only locked for writing while the finished changes are applied.
+pub struct IndexWatcher {
+    /// The batches of changes read by the background thread, together with the errors that happened while reading them.
+    changes: mpsc::Receiver<(Vec<FileChange>, Vec<error::RucolaError>)>,
+    /// Paths of notes the background thread should read again right away.
+    reloads: mpsc::Sender<path::PathBuf>,
+}
+
+impl IndexWatcher {
+    /// Starts handling the file events of the given index on a background thread, which ends once the index is dropped.
+    /// As the tracker is taken from the index for every batch of events, the watcher keeps working when a rescanned index is swapped in.
+    pub fn start(index: &NoteIndexContainer) -> Self {
+        let weak = sync::Arc::downgrade(&index.0);
+        let (change_sender, changes) = mpsc::channel();
+        let (reloads, reload_receiver) = mpsc::channel::<path::PathBuf>();
+
+        std::thread::spawn(move || {
+            let mut paths = vec![];
+            while let Some(index) = weak.upgrade().map(NoteIndexContainer) {
+                let (tracker, builder) = {
+                    let index = index.borrow();
+                    (index.tracker.clone(), index.builder.clone())
+                };
+
+                let (mut changes, mut errors) = read_file_events(&index, &tracker, &builder);
+                let notes = load_notes(&tracker, &builder, paths.drain(..), &mut errors);
+                if !notes.is_empty() {
+                    changes.push(FileChange::Insert(notes));
+                }
+                // do not keep the index alive while waiting
+                drop(index);
+
+                if (!changes.is_empty() || !errors.is_empty())
+                    && change_sender.send((changes, errors)).is_err()
+                {
+                    break;
+                }
+
+                match reload_receiver.recv_timeout(WATCH_INTERVAL) {
+                    Ok(path) => paths.push(path),
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+                paths.extend(reload_receiver.try_iter());
+            }
+        });
+
+        Self { changes, reloads }
+    }
+
+    /// Requests the note at the given path to be read again right away, e.g. after it was closed in the editor, instead of waiting for its file events.
+    pub fn reload(&self, path: path::PathBuf) {
+        // the background thread only stops once the index is gone, and then there is nothing to reload
+        let _ = self.reloads.send(path);
+    }
+
+    /// Applies all changes read by the background thread since the last call to the given index.
+    /// Returns wether the index has changed, a list of all IdChanges and all errors that happened while reading the changes.
+    pub fn apply(
+        &self,
+        index: &NoteIndexContainer,
+    ) -> (bool, Vec<IdChange>, Vec<error::RucolaError>) {
+        let mut modifications = false;
+        let mut id_changes = vec![];
+        let mut errors = vec![];
+        for (changes, batch_errors) in self.changes.try_iter() {
+            let (batch_modifications, batch_id_changes) =
+                index.borrow_mut().apply_file_changes(changes);
+            modifications |= batch_modifications;
+            id_changes.extend(batch_id_changes);
+            errors.extend(batch_errors);
+        }
+        (modifications, id_changes, errors)
+    }
+}
+
+/// Reads all file events found by the given tracker since the last call and loads the affected notes, creating their HTML.
+///  - Renames and moves are tracked, also when reported as separate events for source and target or when moving entire folders
+///  - new file creations with in the vault folder are checked for notes and added if appropriate
+///  - removed files and folders are removed from the index (if they were present)
+///  - Modifications of files are checked for being notes and if so, the respective index entries are updated with the new data.
+///    This includes editors saving atomically by renaming a temporary file onto the note.
+///
+/// The index is only locked for reading briefly, to look up the notes at the affected paths.
+fn read_file_events(
+    index: &NoteIndexContainer,
+    tracker: &io::FileTracker,
+    builder: &io::HtmlBuilder,
+) -> (Vec<FileChange>, Vec<error::RucolaError>) {
+    let mut changes = vec![];
+    let mut errors = vec![];
+    let mut attachments_changed = false;
+    for event in tracker.try_events().into_iter().flatten() {
+        // Attachments are rescanned entirely once all events are handled
+        if !matches!(event.kind, notify::EventKind::Access(_))
+            && event.paths.iter().any(|path| tracker.is_attachment(path))
+        {
+            attachments_changed = true;
+        }
+
+        match event.kind {
+            // Creations and the targets of moves into the vault: Load all notes at the new paths.
+            notify::EventKind::Create(_)
+            | notify::EventKind::Modify(notify::event::ModifyKind::Name(
+                notify::event::RenameMode::To,
+            )) => {
+                for path in &event.paths {
+                    let paths = note_paths_at(index, tracker, path);
+                    changes.push(FileChange::Insert(load_notes(
+                        tracker,
+                        builder,
+                        paths,
+                        &mut errors,
+                    )));
+                }
+            }
+            // Removals and the sources of moves out of the vault: Remove all notes at the old paths.
+            notify::EventKind::Remove(_)
+            | notify::EventKind::Modify(notify::event::ModifyKind::Name(
+                notify::event::RenameMode::From,
+            )) => {
+                changes.extend(event.paths.iter().cloned().map(FileChange::Remove));
+            }
+            // Renames and moves within the vault: Remove all notes at the source, then insert all notes from the target.
+            notify::EventKind::Modify(notify::event::ModifyKind::Name(
+                notify::event::RenameMode::Both,
+            )) => match (event.paths.first(), event.paths.get(1)) {
+                (Some(from), Some(to)) => {
+                    let paths = note_paths_at(index, tracker, to);
+                    changes.push(FileChange::Rename(
+                        from.clone(),
+                        load_notes(tracker, builder, paths, &mut errors),
+                    ));
+                }
+                _ => errors.push(error::RucolaError::NotifyEventError(event.clone())),
+            },
+            // General edits: Reload the notes at the edited paths
+            notify::EventKind::Modify(
+                notify::event::ModifyKind::Data(_)
+                | notify::event::ModifyKind::Metadata(_)
+                | notify::event::ModifyKind::Any,
+            ) => {
+                for path in &event.paths {
+                    // Files touched without changing their content need not be parsed again
+                    match unchanged_modification_time(index, path) {
+                        Some(modified) => changes.push(FileChange::Touch(path.clone(), modified)),
+                        None => {
+                            let paths = note_paths_at(index, tracker, path);
+                            changes.push(FileChange::Insert(load_notes(
+                                tracker,
+                                builder,
+                                paths,
+                                &mut errors,
+                            )));
+                        }
+                    }
+                }
+            }
+            // Do nothing in the other cases
+            notify::EventKind::Modify(_) => {}
+            notify::EventKind::Access(_) => {}
+            notify::EventKind::Other => {}
+            notify::EventKind::Any => {}
+        }
+    }
+    if attachments_changed {
+        changes.push(FileChange::Attachments(tracker.attachments()));
+    }
+    (changes, errors)
+}
+
+/// Returns the path of the note file at the given path, or of all note files below it if it is a folder.
+/// Only paths tracked by the file tracker or holding an indexed note are considered.
+fn note_paths_at(
+    index: &NoteIndexContainer,
+    tracker: &io::FileTracker,
+    path: &path::Path,
+) -> Vec<path::PathBuf> {
+    if path.is_dir() {
+        tracker
+            .note_paths()
+            .into_iter()
+            .filter(|entry_path| entry_path.starts_with(path))
+            .collect()
+    } else if index.borrow().inner.values().any(|note| note.path == path)
+        || tracker.is_tracked(&path.to_path_buf())
+    {
+        vec![path.to_path_buf()]
+    } else {
+        vec![]
+    }
+}
+
+/// Reads the notes at the given paths and creates their HTML, large notes only get theirs when viewed.
+/// Files that can no longer be read are skipped, errors while creating HTML are collected.
+fn load_notes(
+    tracker: &io::FileTracker,
+    builder: &io::HtmlBuilder,
+    paths: impl IntoIterator<Item = path::PathBuf>,
+    errors: &mut Vec<error::RucolaError>,
+) -> Vec<Note> {
+    let mut notes = vec![];
+    for path in paths {
+        if let Ok(note) = tracker.read_note(&path) {
+            if !note.truncated {
+                if let Err(e) = builder.create_html(&note, false) {
+                    errors.push(e);
+                }
+            }
+            notes.push(note);
+        }
+    }
+    notes
+}
+
+/// Checks if the note file at the given path still has the content its indexed note was parsed from, as happens when sync tools touch files without changing them.
+/// If so, returns its new modification time.
+fn unchanged_modification_time(
+    index: &NoteIndexContainer,
+    path: &path::Path,
+) -> Option<Option<std::time::SystemTime>> {
+    let checksum = index
+        .borrow()
+        .inner
+        .values()
+        .find(|note| note.path == path)?
+        .checksum?;
+
+    let content = std::fs::read_to_string(path).ok()?;
+    (super::note::content_checksum(&content) == checksum).then(|| {
+        std::fs::metadata(path)
+            .ok()
+            .and_then(|metadata| metadata.modified().ok())
+    })
+}
+
+/// Returns the given id if it is free or already used by the note at the given path.
+/// Otherwise, returns the first free id of the form `id-2`, `id-3`, ... instead.
+fn unique_id(inner: &HashMap<String, Note>, base: String, path: &path::Path) -> String {
+    let is_free = |id: &str| inner.get(id).map_or(true, |other| other.path == path);
+    let mut id = base.clone();
+    let mut counter = 1;
+    while !is_free(&id) {
+        counter += 1;
+        id = format!("{base}-{counter}");
+    }
+    id
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -318,4 +1168,599 @@ mod tests {
             vec![("manifold".to_string(), "Manifold".to_string()),]
         );
     }
+
+    #[test]
+    fn test_broken_links_count() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = NoteIndex::new(tracker, builder).0;
+
+        let chart = index.inner.get("chart").unwrap();
+        let lg = index.inner.get("lie-group").unwrap();
+
+        assert_eq!(index.broken_links_count(chart), 1);
+        assert_eq!(index.broken_links_count(lg), 0);
+    }
+
+    #[test]
+    fn test_link_counts() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = NoteIndex::new(tracker, builder).0;
+
+        let chart = index.inner.get("chart").unwrap();
+        let manifold = index.inner.get("manifold").unwrap();
+
+        assert_eq!(index.inlinks_count("manifold"), 4);
+        assert_eq!(index.inlinks_count("operating-systems"), 0);
+        assert_eq!(index.inlinks_count("diffeomorphism"), 1);
+        assert_eq!(index.outlinks_count(chart), 1);
+        assert_eq!(index.outlinks_count(manifold), 4);
+    }
+
+    #[test]
+    fn test_relative_path() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = NoteIndex::new(tracker, builder).0;
+
+        let chart = index.inner.get("chart").unwrap();
+
+        assert_eq!(
+            index.relative_path(chart),
+            path::Path::new("common/notes/math/Chart.md")
+        );
+    }
+
+    #[test]
+    fn test_related_notes() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = NoteIndex::new(tracker, builder).0;
+
+        let related = index.related_notes("chart", index.inner.keys(), 10);
+
+        // manifold and atlas are already linked, the os notes share nothing
+        assert_eq!(
+            related
+                .iter()
+                .map(|(id, _)| id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["smooth-map", "topology", "lie-group"]
+        );
+        assert_eq!(related[0].1, 0.5);
+
+        assert_eq!(index.related_notes("chart", index.inner.keys(), 1).len(), 1);
+        assert!(index
+            .related_notes("books", index.inner.keys(), 10)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_within_distance() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = NoteIndex::new(tracker, builder).0;
+
+        let set = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<HashSet<_>>();
+
+        assert_eq!(index.within_distance("chart", 0), set(&["chart"]));
+        assert_eq!(
+            index.within_distance("chart", 1),
+            set(&["chart", "manifold", "atlas"])
+        );
+        assert_eq!(
+            index.within_distance("linux", 1),
+            set(&["linux", "operating-systems"])
+        );
+        assert_eq!(
+            index.within_distance("linux", 2),
+            set(&["linux", "operating-systems", "windows", "osx"])
+        );
+        assert_eq!(index.within_distance("linux", 5).len(), 4);
+        assert!(index.within_distance("diffeomorphism", 3).is_empty());
+    }
+
+    #[test]
+    fn test_aliases() {
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("Machine Learning.md"),
+            "---\naliases: [ML, AI Stuff]\n---\n",
+        )
+        .unwrap();
+        std::fs::write(tmp.join("Notes.md"), "See [[ML]].").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = NoteIndex::new(tracker, builder).0;
+
+        assert_eq!(index.resolve_id("ml"), "machine-learning");
+        assert_eq!(index.resolve_id("ai-stuff"), "machine-learning");
+        assert_eq!(index.resolve_id("machine-learning"), "machine-learning");
+        assert_eq!(index.resolve_id("notes"), "notes");
+        assert_eq!(index.resolve_id("unknown"), "unknown");
+
+        // links through aliases are valid links to the note declaring the alias
+        let notes = index.get("notes").unwrap();
+        assert_eq!(index.broken_links_count(notes), 0);
+        assert_eq!(index.outlinks_count(notes), 1);
+        assert_eq!(
+            index.links_vec("notes"),
+            vec![(
+                String::from("machine-learning"),
+                String::from("Machine Learning")
+            )]
+        );
+        assert_eq!(index.inlinks_count("machine-learning"), 1);
+        assert!(index
+            .within_distance("notes", 1)
+            .contains("machine-learning"));
+    }
+
+    #[test]
+    fn test_update_remove_note() {
+        let tmp = testdir::testdir!();
+        std::fs::write(tmp.join("Machine Learning.md"), "---\naliases: [ML]\n---\n").unwrap();
+        std::fs::write(tmp.join("Notes.md"), "See [[ML]].").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let mut index = NoteIndex::new(tracker, builder).0;
+        assert_eq!(index.resolve_id("ml"), "machine-learning");
+
+        // changing the aliases of a single note patches the alias table
+        std::fs::write(
+            tmp.join("Machine Learning.md"),
+            "---\naliases: [AI]\n---\nword",
+        )
+        .unwrap();
+        assert_eq!(
+            index.update_note(&tmp.join("Machine Learning.md")).unwrap(),
+            "machine-learning"
+        );
+        assert_eq!(index.get("machine-learning").unwrap().words, 1);
+        assert_eq!(index.resolve_id("ml"), "ml");
+        assert_eq!(index.resolve_id("ai"), "machine-learning");
+
+        // a new note shadows an alias of the same id
+        std::fs::write(tmp.join("AI.md"), "").unwrap();
+        assert_eq!(index.update_note(&tmp.join("AI.md")).unwrap(), "ai");
+        assert_eq!(index.resolve_id("ai"), "ai");
+        assert_eq!(index.blinks_vec("notes").len(), 0);
+
+        // removing it makes the alias available again
+        assert!(index.remove_note("ai").is_some());
+        assert!(index.remove_note("ai").is_none());
+        assert_eq!(index.resolve_id("ai"), "machine-learning");
+
+        assert!(index.update_note(&tmp.join("Missing.md")).is_err());
+        assert_eq!(
+            index.take_changed(),
+            HashSet::from([String::from("machine-learning"), String::from("ai")])
+        );
+    }
+
+    #[test]
+    fn test_id_scheme() {
+        let tmp = testdir::testdir!();
+        std::fs::write(tmp.join("202401311200-lie-group.md"), "[[202402011000]]").unwrap();
+        std::fs::write(tmp.join("202402011000 Manifold.md"), "").unwrap();
+        std::fs::write(tmp.join("Index.md"), "[[202401311200-lie-group]]").unwrap();
+
+        let config = crate::Config {
+            id_scheme: crate::data::IdScheme::Timestamp,
+            ..Default::default()
+        };
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let mut index = NoteIndex::new(tracker, builder).0;
+
+        let mut ids = index.ids().cloned().collect_vec();
+        ids.sort();
+        assert_eq!(ids, vec!["202401311200", "202402011000", "index"]);
+        assert_eq!(index.get("202401311200").unwrap().id, "202401311200");
+
+        // links by id and by file name both resolve
+        assert_eq!(index.resolve_id("202401311200-lie-group"), "202401311200");
+        assert_eq!(index.inlinks_count("202401311200"), 1);
+        assert_eq!(index.inlinks_count("202402011000"), 1);
+        assert_eq!(index.broken_links_count(index.get("index").unwrap()), 0);
+
+        // updated notes keep their scheme
+        assert_eq!(
+            index
+                .update_note(&tmp.join("202402011000 Manifold.md"))
+                .unwrap(),
+            "202402011000"
+        );
+    }
+
+    #[test]
+    fn test_relative_links() {
+        let tmp = testdir::testdir!();
+        std::fs::create_dir(tmp.join("a")).unwrap();
+        std::fs::create_dir(tmp.join("b")).unwrap();
+        std::fs::write(tmp.join("a/Foo.md"), "").unwrap();
+        std::fs::write(tmp.join("b/Foo.md"), "").unwrap();
+        std::fs::write(tmp.join("a/X.md"), "[[Foo]]").unwrap();
+        std::fs::write(tmp.join("b/Y.md"), "[[Foo]] and [[foo-2]]").unwrap();
+        std::fs::write(tmp.join("Z.md"), "[[Foo]]").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let mut index = NoteIndex::new(tracker, builder).0;
+
+        // the note in the first folder keeps the id
+        assert!(index.get("foo").unwrap().path.ends_with("a/Foo.md"));
+        assert!(index.get("foo-2").unwrap().path.ends_with("b/Foo.md"));
+
+        let ids = |links: Vec<(String, String)>| {
+            links
+                .into_iter()
+                .map(|(id, _title)| id)
+                .sorted()
+                .collect::<Vec<_>>()
+        };
+
+        // links prefer the note in the same folder, and fall back to the vault-wide one
+        assert_eq!(ids(index.links_vec("x")), vec!["foo"]);
+        assert_eq!(ids(index.links_vec("y")), vec!["foo-2", "foo-2"]);
+        assert_eq!(ids(index.links_vec("z")), vec!["foo"]);
+
+        assert_eq!(ids(index.blinks_vec("foo")), vec!["x", "z"]);
+        assert_eq!(ids(index.blinks_vec("foo-2")), vec!["y"]);
+        assert_eq!(index.inlinks_count("foo-2"), 2);
+
+        // without a namesake in its folder, links resolve vault-wide again
+        index.remove_note("foo-2");
+        assert_eq!(ids(index.links_vec("y")), vec!["foo"]);
+        assert_eq!(ids(index.blinks_vec("foo")), vec!["x", "y", "z"]);
+    }
+
+    #[test]
+    fn test_id_collisions() {
+        let tmp = testdir::testdir!();
+        std::fs::create_dir(tmp.join("sub")).unwrap();
+        std::fs::write(tmp.join("Note.md"), "one").unwrap();
+        std::fs::write(tmp.join("note.md"), "one two").unwrap();
+        std::fs::write(tmp.join("sub/Note.md"), "one two three").unwrap();
+        std::fs::write(tmp.join("Other.md"), "").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let mut index = NoteIndex::new(tracker, builder).0;
+
+        // no note is lost, they are disambiguated in order of their paths
+        assert_eq!(index.inner.len(), 4);
+        assert_eq!(index.get("note").unwrap().words, 1);
+        assert_eq!(index.get("note-2").unwrap().words, 2);
+        assert_eq!(index.get("note-3").unwrap().words, 3);
+
+        assert_eq!(
+            index.id_collisions(),
+            vec![(
+                String::from("note"),
+                vec![
+                    ("note", std::path::Path::new("Note.md")),
+                    ("note-2", std::path::Path::new("note.md")),
+                    ("note-3", std::path::Path::new("sub/Note.md")),
+                ]
+            )]
+        );
+
+        // updated notes keep their disambiguated id
+        assert_eq!(
+            index.update_note(&tmp.join("sub/Note.md")).unwrap(),
+            "note-3"
+        );
+        assert_eq!(index.inner.len(), 4);
+
+        // without a counterpart, there is no collision
+        index.remove_note("note");
+        index.remove_note("note-2");
+        assert!(index.id_collisions().is_empty());
+    }
+
+    #[test]
+    fn test_max_note_size() {
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("Log.md"),
+            format!(
+                "---\ntitle: Log\n---\n#log [[Small]]\n{}",
+                "word ".repeat(1000)
+            ),
+        )
+        .unwrap();
+        std::fs::write(tmp.join("Small.md"), "#small [[Log]]").unwrap();
+
+        let config = crate::Config {
+            max_note_size: 1,
+            ..Default::default()
+        };
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = NoteIndex::new(tracker, builder).0;
+
+        // only the beginning of large notes is read
+        let log = index.get("log").unwrap();
+        assert!(log.truncated);
+        assert_eq!(log.title(), "Log");
+        assert_eq!(log.tags, vec!["#log"]);
+        assert_eq!(log.links, vec!["small"]);
+        assert!(log.words < 1000);
+        assert!(!index.get("small").unwrap().truncated);
+
+        // until they are needed in full
+        let full = index.get_full("log").unwrap().unwrap();
+        assert!(!full.truncated);
+        assert_eq!(full.words, 1002);
+        assert_eq!(
+            index.get_full("small").unwrap().unwrap().words,
+            index.get("small").unwrap().words
+        );
+        assert!(index.get_full("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_backlink_contexts() {
+        let tmp = testdir::testdir!();
+        std::fs::write(tmp.join("Target.md"), "---\naliases: [Goal]\n---\n").unwrap();
+        std::fs::write(
+            tmp.join("A.md"),
+            "First about [[target]]. Then unrelated. Again [[goal]].",
+        )
+        .unwrap();
+        std::fs::write(tmp.join("B.md"), "Only [[a]] here.").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = NoteIndex::new(tracker, builder).0;
+
+        assert_eq!(
+            index.backlink_contexts("target"),
+            vec![
+                (String::from("a"), String::from("First about target.")),
+                (String::from("a"), String::from("Again goal.")),
+            ]
+        );
+        assert_eq!(
+            index.backlink_contexts("a"),
+            vec![(String::from("b"), String::from("Only a here."))]
+        );
+        assert!(index.backlink_contexts("b").is_empty());
+    }
+
+    #[test]
+    fn test_backlinks() {
+        let tmp = testdir::testdir!();
+        std::fs::write(tmp.join("Target.md"), "---\naliases: [Goal]\n---\n").unwrap();
+        std::fs::write(tmp.join("A.md"), "[[target]] and again [[target]]").unwrap();
+        std::fs::write(tmp.join("B.md"), "[[goal]]").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let mut index = NoteIndex::new(tracker, builder).0;
+
+        assert_eq!(index.inlinks_count("target"), 3);
+        assert_eq!(
+            index
+                .blinks_vec("target")
+                .into_iter()
+                .map(|(id, _name)| id)
+                .sorted()
+                .collect_vec(),
+            vec!["a", "b"]
+        );
+
+        // editing a note replaces its links
+        std::fs::write(tmp.join("A.md"), "[[b]]").unwrap();
+        index.update_note(&tmp.join("A.md")).unwrap();
+        assert_eq!(index.inlinks_count("target"), 1);
+        assert_eq!(index.inlinks_count("b"), 1);
+
+        // removing a note removes its links
+        index.remove_note("b");
+        assert_eq!(index.inlinks_count("target"), 0);
+        assert!(index.blinks_vec("target").is_empty());
+        assert_eq!(index.backlinks.len(), 1);
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = NoteIndex::new(tracker, builder).0;
+        assert_eq!(index.inlinks_count("manifold"), 4);
+        assert_eq!(index.inlinks_count("diffeomorphism"), 1);
+    }
+
+    #[test]
+    fn test_rescan() {
+        let tmp = testdir::testdir!();
+        std::fs::write(tmp.join("A.md"), "one two").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = NoteIndexContainer::new(NoteIndex::new(tracker, builder).0);
+
+        std::fs::write(tmp.join("B.md"), "[[a]]").unwrap();
+        std::fs::write(tmp.join("A.md"), "one two three").unwrap();
+
+        // the old index is unaffected until the new one is swapped in
+        let rescan = IndexRescan::start(&config, &index);
+        assert_eq!(index.borrow().inner.len(), 1);
+
+        let (rescanned, errors) = rescan.finish().unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(rescanned.inner.len(), 2);
+        assert_eq!(rescanned.get("a").unwrap().words, 3);
+        assert_eq!(rescanned.blinks_vec("a").len(), 1);
+    }
+
+    #[test]
+    fn test_embeds() {
+        let tmp = testdir::testdir!();
+        std::fs::write(tmp.join("Source.md"), "![[Target]] ![[Missing]] [[Linked]]").unwrap();
+        std::fs::write(tmp.join("Target.md"), "").unwrap();
+        std::fs::write(tmp.join("Linked.md"), "").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = NoteIndex::new(tracker, builder).0;
+
+        assert_eq!(
+            index.embeds_vec("source"),
+            vec![(String::from("target"), String::from("Target"))]
+        );
+        assert_eq!(
+            index.links_vec("source"),
+            vec![(String::from("linked"), String::from("Linked"))]
+        );
+        assert!(index.embeds_vec("target").is_empty());
+    }
+
+    #[test]
+    fn test_attachments() {
+        let tmp = testdir::testdir!();
+        std::fs::create_dir(tmp.join("images")).unwrap();
+        std::fs::write(tmp.join("images").join("My Diagram.png"), "").unwrap();
+        std::fs::write(tmp.join("images").join("unused.jpg"), "").unwrap();
+        std::fs::write(tmp.join("paper.pdf"), "").unwrap();
+        std::fs::write(
+            tmp.join("Note.md"),
+            "![](images/My%20Diagram.png) [[paper.pdf]] ![](missing.png) [](other.md) [](notes.txt) ![](https://example.com/web.png)",
+        )
+        .unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = NoteIndex::new(tracker, builder).0;
+
+        let note = index.get("note").unwrap();
+        assert_eq!(
+            note.file_references,
+            vec![
+                "images/My%20Diagram.png",
+                "paper.pdf",
+                "missing.png",
+                "notes.txt"
+            ]
+        );
+        assert_eq!(index.missing_attachments(note), vec!["missing.png"]);
+
+        // attachments can be found by their ids, and links to them are not broken
+        assert_eq!(note.links, vec!["other"]);
+        assert_eq!(index.broken_links_count(note), 1);
+        assert_eq!(
+            index.attachment_path("my-diagram.png"),
+            Some(tmp.join("images").join("My Diagram.png").as_path())
+        );
+        assert_eq!(index.attachment_path("missing.png"), None);
+        assert_eq!(
+            index.attachments_vec("note"),
+            vec![
+                (
+                    String::from("my-diagram.png"),
+                    String::from("My Diagram.png")
+                ),
+                (String::from("paper.pdf"), String::from("paper.pdf")),
+            ]
+        );
+        assert_eq!(
+            index.attachment_blinks_vec("paper.pdf"),
+            vec![(String::from("note"), String::from("Note"))]
+        );
+        assert!(index.attachment_blinks_vec("unused.jpg").is_empty());
+        assert_eq!(
+            index.unreferenced_attachments(),
+            vec![std::path::Path::new("images/unused.jpg")]
+        );
+    }
+
+    /// Applies the changes read by the watcher until the given condition holds for the index, or fails after a few seconds.
+    fn await_events(
+        index: &NoteIndexContainer,
+        watcher: &IndexWatcher,
+        condition: impl Fn(&NoteIndex) -> bool,
+    ) {
+        for _ in 0..100 {
+            let (_modifications, _id_changes, errors) = watcher.apply(index);
+            assert!(errors.is_empty());
+            if condition(&index.borrow()) {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        panic!("File events were not handled in time.");
+    }
+
+    #[test]
+    fn test_file_events() {
+        let tmp = testdir::testdir!();
+        std::fs::create_dir(tmp.join("sub")).unwrap();
+        std::fs::write(tmp.join("A.md"), "one two").unwrap();
+        std::fs::write(tmp.join("sub").join("B.md"), "[[a]]").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = NoteIndexContainer::new(NoteIndex::new(tracker, builder).0);
+        let watcher = IndexWatcher::start(&index);
+        assert_eq!(index.borrow().inner.len(), 2);
+
+        // creation
+        std::fs::write(tmp.join("C.md"), "new note").unwrap();
+        await_events(&index, &watcher, |index| index.get("c").is_some());
+        assert!(index.borrow_mut().take_changed().contains("c"));
+
+        // modification
+        std::fs::write(tmp.join("A.md"), "one two three").unwrap();
+        await_events(&index, &watcher, |index| {
+            index.get("a").is_some_and(|note| note.words == 3)
+        });
+
+        // atomic save by renaming a temporary file onto the note
+        std::fs::write(tmp.join(".A.md.tmp"), "one").unwrap();
+        std::fs::rename(tmp.join(".A.md.tmp"), tmp.join("A.md")).unwrap();
+        await_events(&index, &watcher, |index| {
+            index.get("a").is_some_and(|note| note.words == 1)
+        });
+
+        // touching a note without changing it only updates its modification time
+        index.borrow_mut().take_changed();
+        let modified = index.borrow().get("a").unwrap().modified;
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::write(tmp.join("A.md"), "one").unwrap();
+        await_events(&index, &watcher, |index| {
+            index.get("a").is_some_and(|note| note.modified != modified)
+        });
+        assert!(!index.borrow_mut().take_changed().contains("a"));
+
+        // moving a folder keeps its notes
+        std::fs::rename(tmp.join("sub"), tmp.join("moved")).unwrap();
+        await_events(&index, &watcher, |index| {
+            index
+                .get("b")
+                .is_some_and(|note| note.path.starts_with(tmp.join("moved")))
+        });
+
+        // removal
+        std::fs::remove_file(tmp.join("C.md")).unwrap();
+        await_events(&index, &watcher, |index| index.get("c").is_none());
+        assert_eq!(index.borrow().inner.len(), 2);
+    }
 }
@@ -0,0 +1,188 @@
+use std::{collections::HashMap, fs, io, path, time};
+
+use crate::{data, error, io};
+
+/// The parsed notes of a vault, persisted in a cache file in the config directory.
+/// On the next start, notes whose files were not modified since are taken from the cache instead of being parsed again.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct IndexCache {
+    /// The version of rucola that wrote the cache, as other versions might parse notes differently.
+    version: String,
+    /// The vault the cached notes belong to.
+    vault: path::PathBuf,
+    /// The settings the cached notes were read with, as notes read with other settings differ in their tags, counts or truncation.
+    settings: Option<io::ReadSettings>,
+    /// The cached notes by their path.
+    notes: HashMap<path::PathBuf, data::Note>,
+}
+
+impl IndexCache {
+    /// Creates a cache of all notes of the given index.
+    pub fn new(index: &data::NoteIndex) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            vault: index.vault_path().to_path_buf(),
+            settings: Some(index.tracker.read_settings()),
+            notes: index
+                .inner
+                .values()
+                .map(|note| (note.path.clone(), note.clone()))
+                .collect(),
+        }
+    }
+
+    /// Returns the path of the cache file of the vault at the given path.
+    /// Each vault gets its own file, see [data::vault_file_name].
+    fn file_path(vault: &path::Path) -> error::Result<path::PathBuf> {
+        let mut path = confy::get_configuration_file_path(
+            "rucola",
+            data::vault_file_name("index-cache", vault).as_str(),
+        )?;
+        // confy appends .toml, but the cache is stored as json
+        path.set_extension("json");
+        Ok(path)
+    }
+
+    /// Loads the cached notes of the vault at the given path.
+    /// Returns an empty cache if there is none or it was written by another version.
+    pub fn load(vault: &path::Path) -> error::Result<Self> {
+        let file = match fs::File::open(Self::file_path(vault)?) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+
+        // A cache that cannot be read is simply rebuilt.
+        Ok(serde_json::from_reader::<_, Self>(io::BufReader::new(file))
+            .ok()
+            .filter(|cache| cache.version == env!("CARGO_PKG_VERSION") && cache.vault == vault)
+            .unwrap_or_default())
+    }
+
+    /// Writes the cached notes to the cache file of their vault, replacing the previous ones.
+    pub fn store(&self) -> error::Result<()> {
+        let path = Self::file_path(&self.vault)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        serde_json::to_writer(io::BufWriter::new(fs::File::create(path)?), self)?;
+        Ok(())
+    }
+
+    /// Discards all cached notes unless they were read with the given settings.
+    pub fn discard_unless_read_with(&mut self, settings: io::ReadSettings) {
+        if self.settings != Some(settings) {
+            self.notes.clear();
+        }
+    }
+
+    /// Takes the cached version of the note at the given path out of the cache, if the note file was not modified since it was cached.
+    /// Files with a different modification time are still taken from the cache if their content did not change, as sync tools often touch files without changing them.
+    pub fn take(
+        &mut self,
+        path: &path::Path,
+        modified: Option<time::SystemTime>,
+    ) -> Option<data::Note> {
+        let mut note = self.notes.remove(path)?;
+        if modified.is_some() && note.modified == modified {
+            return Some(note);
+        }
+
+        let checksum = note.checksum?;
+        let content = fs::read_to_string(path).ok()?;
+        (super::note::content_checksum(&content) == checksum).then(|| {
+            note.modified = modified;
+            note
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{data, io};
+
+    #[test]
+    fn test_index_cache() {
+        let tmp = testdir::testdir!();
+        std::fs::write(tmp.join("A.md"), "one two").unwrap();
+        std::fs::write(tmp.join("B.md"), "[[a]]").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let mut cache = super::IndexCache::new(&index);
+        assert_eq!(cache.notes.len(), 2);
+
+        // modified notes are not taken from the cache
+        let a = index.get("a").unwrap();
+        std::fs::write(tmp.join("A.md"), "one two three").unwrap();
+        assert!(cache
+            .take(&a.path, Some(std::time::SystemTime::UNIX_EPOCH))
+            .is_none());
+        assert!(cache.take(&a.path, a.modified).is_none());
+        std::fs::write(tmp.join("A.md"), "one two").unwrap();
+
+        let b = index.get("b").unwrap();
+        assert_eq!(
+            cache.take(&b.path, b.modified).map(|note| note.links),
+            Some(vec![String::from("a")])
+        );
+
+        // touched notes with the same content are still taken from the cache, with the new modification time
+        let mut cache = super::IndexCache::new(&index);
+        let touched = cache
+            .take(&a.path, Some(std::time::SystemTime::UNIX_EPOCH))
+            .unwrap();
+        assert_eq!(touched.modified, Some(std::time::SystemTime::UNIX_EPOCH));
+        assert_eq!(touched.words, 2);
+
+        // cached notes are used when indexing again, but new notes are read
+        std::fs::write(tmp.join("C.md"), "").unwrap();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let mut cache = super::IndexCache::new(&index);
+        cache.notes.values_mut().for_each(|note| note.words = 42);
+        let cached = data::NoteIndex::new_with_cache(tracker, builder, cache).0;
+        assert_eq!(cached.get("a").unwrap().words, 42);
+        assert_eq!(cached.get("b").unwrap().words, 42);
+        assert_eq!(cached.get("c").unwrap().words, 0);
+    }
+
+    #[test]
+    fn test_index_cache_settings() {
+        let tmp = testdir::testdir!();
+        std::fs::write(tmp.join("A.md"), "Some #inline tag").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = data::NoteIndex::new(tracker, builder).0;
+        assert_eq!(index.get("a").unwrap().tags, vec!["#inline"]);
+
+        // the cache is used as long as the settings stay the same
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let mut cache = super::IndexCache::new(&index);
+        cache.notes.values_mut().for_each(|note| note.words = 42);
+        let cached = data::NoteIndex::new_with_cache(tracker, builder, cache).0;
+        assert_eq!(cached.get("a").unwrap().words, 42);
+
+        // notes cached with another tag syntax are read again
+        let config = crate::Config {
+            tag_syntax: data::TagSyntax {
+                inline: false,
+                code: false,
+            },
+            ..Default::default()
+        };
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let mut cache = super::IndexCache::new(&index);
+        cache.notes.values_mut().for_each(|note| note.words = 42);
+        let reread = data::NoteIndex::new_with_cache(tracker, builder, cache).0;
+        assert!(reread.get("a").unwrap().tags.is_empty());
+        assert_ne!(reread.get("a").unwrap().words, 42);
+    }
+}
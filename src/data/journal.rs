@@ -0,0 +1,173 @@
+use std::time;
+
+use itertools::Itertools;
+use ratatui::{prelude::*, widgets::*};
+
+use crate::ui;
+
+use super::filter::{civil_from_days, days_from_civil};
+
+/// Length of a day in seconds.
+const DAY: u64 = 24 * 60 * 60;
+
+/// Recognizes daily notes by a date in their file name.
+/// The pattern is the file name of a daily note without its extension, with `YYYY`, `MM` and `DD` in place of year, month and day, e.g. `Journal YYYY-MM-DD`.
+#[derive(Debug, Clone)]
+pub struct DailyNotePattern(regex::Regex);
+
+impl DailyNotePattern {
+    /// Creates a pattern from the given string, if it contains a year, a month and a day.
+    pub fn new(pattern: &str) -> Option<Self> {
+        if !["YYYY", "MM", "DD"]
+            .iter()
+            .all(|placeholder| pattern.matches(placeholder).count() == 1)
+        {
+            return None;
+        }
+        let expression = regex::escape(pattern)
+            .replace("YYYY", r"(?P<year>\d{4})")
+            .replace("MM", r"(?P<month>\d{2})")
+            .replace("DD", r"(?P<day>\d{2})");
+        regex::Regex::new(&format!("^{expression}$")).ok().map(Self)
+    }
+
+    /// Returns the date of the daily note with the given file name, without its extension, as days since the unix epoch.
+    /// Returns `None` if the file name does not match the pattern or contains an invalid date.
+    pub fn date(&self, file_stem: &str) -> Option<i64> {
+        let captures = self.0.captures(file_stem)?;
+        let part = |name: &str| captures.name(name)?.as_str().parse::<i64>().ok();
+        let (year, month, day) = (part("year")?, part("month")?, part("day")?);
+        let days = days_from_civil(year, month, day);
+        // Reject dates like the 31st of February
+        (civil_from_days(days) == (year, month, day)).then_some(days)
+    }
+}
+
+/// Statistics about how consistently daily notes were written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DailyStreak {
+    /// The amount of days with a daily note.
+    pub days: usize,
+    /// The amount of consecutive days with a daily note up to today.
+    /// A streak still counts if only today's note is missing, as the day is not over yet.
+    pub current: usize,
+    /// The largest amount of consecutive days with a daily note.
+    pub longest: usize,
+    /// The amount of days of the current month before today without a daily note.
+    pub missed_this_month: usize,
+}
+
+impl DailyStreak {
+    /// Computes the streak statistics from the dates of all daily notes, as days since the unix epoch, relative to the given day.
+    pub fn new(dates: impl IntoIterator<Item = i64>, today: i64) -> Self {
+        let dates = dates.into_iter().sorted().dedup().collect::<Vec<_>>();
+
+        // Split the dates into runs of consecutive days
+        let mut runs: Vec<(i64, usize)> = Vec::new();
+        for &date in &dates {
+            match runs.last_mut() {
+                Some((end, length)) if *end + 1 == date => {
+                    *end = date;
+                    *length += 1;
+                }
+                _ => runs.push((date, 1)),
+            }
+        }
+
+        let current = runs
+            .iter()
+            .find(|(end, _)| *end == today || *end == today - 1)
+            .map(|(_, length)| *length)
+            .unwrap_or_default();
+
+        let (year, month, _) = civil_from_days(today);
+        let month_start = days_from_civil(year, month, 1);
+
+        Self {
+            days: dates.len(),
+            current,
+            longest: runs
+                .iter()
+                .map(|(_, length)| *length)
+                .max()
+                .unwrap_or_default(),
+            missed_this_month: (month_start..today)
+                .filter(|day| dates.binary_search(day).is_err())
+                .count(),
+        }
+    }
+
+    /// Converts these statistics to a ratatui table.
+    pub fn to_table(&self, styles: &ui::UiStyles) -> Table {
+        let rows = [
+            ("Daily notes:", self.days),
+            ("Current streak:", self.current),
+            ("Longest streak:", self.longest),
+            ("Missed (month):", self.missed_this_month),
+        ]
+        .map(|(name, count)| {
+            Row::new(vec![name.to_string(), format!("{count:7}")]).style(styles.text_style)
+        });
+
+        Table::new(rows, [Constraint::Length(15), Constraint::Length(7)]).column_spacing(1)
+    }
+
+    /// Returns the current day as days since the unix epoch, in UTC.
+    pub fn today() -> i64 {
+        time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)
+            .map(|duration| (duration.as_secs() / DAY) as i64)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daily_note_pattern() {
+        let pattern = DailyNotePattern::new("Journal YYYY-MM-DD").unwrap();
+        assert_eq!(
+            pattern.date("Journal 2024-01-01"),
+            Some(days_from_civil(2024, 1, 1))
+        );
+        assert_eq!(pattern.date("2024-01-01"), None);
+        assert_eq!(pattern.date("Journal 2024-02-31"), None);
+        assert_eq!(pattern.date("Journal 2024-01-01 (copy)"), None);
+
+        // dots are not treated as wildcards
+        let pattern = DailyNotePattern::new("DD.MM.YYYY").unwrap();
+        assert!(pattern.date("01.02.2024").is_some());
+        assert!(pattern.date("01-02-2024").is_none());
+
+        assert!(DailyNotePattern::new("YYYY-MM").is_none());
+    }
+
+    #[test]
+    fn test_daily_streak() {
+        let today = days_from_civil(2024, 3, 10);
+        let day = |day| days_from_civil(2024, 3, day);
+
+        // 1st to 3rd, 5th to 9th, today not yet written
+        let streak = DailyStreak::new([1, 2, 3, 5, 6, 7, 8, 9, 9].map(day), today);
+        assert_eq!(streak.days, 8);
+        assert_eq!(streak.current, 5);
+        assert_eq!(streak.longest, 5);
+        assert_eq!(streak.missed_this_month, 1);
+
+        // a gap yesterday breaks the streak, even if today was written
+        let streak = DailyStreak::new([days_from_civil(2024, 2, 29), day(1), day(10)], today);
+        assert_eq!(streak.current, 1);
+        assert_eq!(streak.longest, 2);
+        assert_eq!(streak.missed_this_month, 8);
+
+        assert_eq!(
+            DailyStreak::new([], today),
+            DailyStreak {
+                missed_this_month: 9,
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -1,17 +1,82 @@
 mod note;
+pub use note::strip_frontmatter;
+pub use note::IdScheme;
 pub use note::Note;
+pub use note::ParseOptions;
+pub use note::TagSyntax;
+pub use note::TextCounting;
 
 mod note_statistics;
 pub use note_statistics::EnvironmentStats;
+pub use note_statistics::ExportFormat;
+pub use note_statistics::HealthCriteria;
 pub use note_statistics::SortingMode;
+pub use note_statistics::StaleCriteria;
+pub use note_statistics::TagRenameImpact;
+
+mod expression;
 
 mod filter;
 pub use filter::Filter;
+pub use filter::ScoreWeights;
+pub use filter::TitleMode;
+
+mod graph;
+pub use graph::Centrality;
+pub use graph::LinkGraph;
+
+mod snapshot;
+pub use snapshot::ChangeReport;
+pub use snapshot::IndexSnapshot;
+
+mod journal;
+pub use journal::DailyNotePattern;
+pub use journal::DailyStreak;
+
+mod stats_cache;
+pub use stats_cache::StatsCache;
+pub use stats_cache::VaultSummary;
+
+mod pinned;
+pub use pinned::PinnedNotes;
+
+mod columns;
+pub use columns::Column;
+pub use columns::ColumnLayout;
+
+mod index_cache;
+pub use index_cache::IndexCache;
 
 mod index;
+pub use index::IndexRescan;
+pub use index::IndexWatcher;
 pub use index::NoteIndex;
 pub use index::NoteIndexContainer;
 
+/// Hashes the given parts with 64 bit FNV-1a, separating them so it matters where one ends and the next begins.
+/// Unlike the hasher of the standard library, the result does not change between Rust versions, so it can be persisted.
+pub fn stable_hash<'a>(parts: impl IntoIterator<Item = &'a [u8]>) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    for part in parts {
+        // 0xff never occurs in UTF-8, so it separates text parts unambiguously
+        for byte in part.iter().chain([&0xff]) {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// Returns the name of the state file of the given kind that belongs to the vault at the given path, without extension.
+/// Each vault gets its own file, named after a stable hash of its canonical path.
+pub fn vault_file_name(kind: &str, vault: &std::path::Path) -> String {
+    let vault = vault.canonicalize().unwrap_or_else(|_| vault.to_path_buf());
+    format!(
+        "{kind}-{:016x}",
+        stable_hash([vault.to_string_lossy().as_bytes()])
+    )
+}
+
 /// Turns a file name or link into its id in the following steps:
 ///  - everything after the first # or ., including the # or ., is ignored
 ///  - All characters are turned to lowercase
@@ -32,6 +97,77 @@ pub fn name_to_id(name: &str) -> String {
         .replace(".md", "")
 }
 
+/// Extracts the file name from a file reference of a note, ignoring anchors and decoding encoded spaces.
+fn reference_file_name(reference: &str) -> String {
+    let reference = reference.split(['#', '?']).next().unwrap_or_default();
+    reference
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .replace("%20", " ")
+}
+
+/// Wether a reference such as `[[paper.pdf]]` or `images/diagram.png` points to a local file with an extension other than markdown instead of to a note.
+fn is_file_reference(reference: &str) -> bool {
+    std::path::Path::new(&reference_file_name(reference))
+        .extension()
+        .is_some_and(|extension| extension != "md" && extension != "markdown")
+}
+
+/// Turns a file reference or the path of an attachment into the id of the attachment:
+///  - Only the file name is considered, including its extension.
+///  - All characters are turned to lowercase
+///  - Spaces ` ` are replaced by dashes `-`.
+/// ```
+///  assert_eq!(attachment_id("images/My%20Diagram.png"), "my-diagram.png");
+///  assert_eq!(attachment_id("paper.pdf"), "paper.pdf");
+/// ```
+pub fn attachment_id(reference: &str) -> String {
+    reference_file_name(reference)
+        .to_lowercase()
+        .replace(' ', "-")
+}
+
+/// Returns the id of the note a markdown link such as `[text](../Lie%20Theory.md#Definition)` points to.
+/// Returns none for links to websites, anchors within the same note or files other than markdown notes.
+/// As ids are derived from file names alone, the directories of relative paths are ignored.
+pub fn link_url_to_id(url: &str) -> Option<String> {
+    if url.contains("://") || url.starts_with('#') || url.starts_with("mailto:") {
+        return None;
+    }
+    let name = reference_file_name(url);
+    std::path::Path::new(&name)
+        .extension()
+        .is_some_and(|extension| extension == "md" || extension == "markdown")
+        .then(|| name_to_id(&name))
+}
+
+/// Returns the anchor of a link such as `Lie Theory#Definition` or `Lie Theory#^block`, i.e. everything after the first #.
+/// Returns none if the link has no or an empty anchor.
+pub fn link_anchor(link: &str) -> Option<String> {
+    link.split_once('#')
+        .map(|(_target, anchor)| anchor.replace("%20", " ").trim().to_owned())
+        .filter(|anchor| !anchor.is_empty())
+}
+
+/// Turns the anchor of a link into the fragment of the HTML element it points to.
+/// Block anchors such as `^block` are kept, headings are converted like the HTML builder converts them to element ids:
+///  - All characters are turned to lowercase
+///  - Punctuation other than dashes `-` and underscores `_` is removed.
+///  - Spaces ` ` are replaced by dashes `-`.
+pub fn anchor_to_fragment(anchor: &str) -> String {
+    if anchor.starts_with('^') {
+        return anchor.to_owned();
+    }
+    anchor
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -42,4 +178,81 @@ mod tests {
         assert_eq!(name_to_id("Lie Theory"), "lie-theory");
         assert_eq!(name_to_id("lie-theory"), "lie-theory");
     }
+
+    #[test]
+    fn test_link_url_to_id() {
+        assert_eq!(
+            link_url_to_id("../math/Lie%20Theory.md#Definition"),
+            Some(String::from("lie-theory"))
+        );
+        assert_eq!(
+            link_url_to_id("other-note.md"),
+            Some(String::from("other-note"))
+        );
+        assert_eq!(
+            link_url_to_id("notes.markdown"),
+            Some(String::from("notes"))
+        );
+        assert_eq!(link_url_to_id("https://example.com/readme.md"), None);
+        assert_eq!(link_url_to_id("#heading"), None);
+        assert_eq!(link_url_to_id("diagram.png"), None);
+        assert_eq!(link_url_to_id("folder/"), None);
+    }
+
+    #[test]
+    fn test_attachment_id() {
+        assert_eq!(attachment_id("images/My%20Diagram.png"), "my-diagram.png");
+        assert_eq!(attachment_id("paper.pdf"), "paper.pdf");
+        assert_eq!(attachment_id("/vault/Scans/Page 1.JPG"), "page-1.jpg");
+
+        assert!(is_file_reference("paper.pdf"));
+        assert!(is_file_reference("images/My%20Diagram.png"));
+        assert!(!is_file_reference("Lie Group"));
+        assert!(!is_file_reference("Lie Group.md#Definition"));
+        assert!(!is_file_reference("Other#Part"));
+    }
+
+    #[test]
+    fn test_stable_hash() {
+        // the hash must not change between versions, as it names persisted files
+        assert_eq!(stable_hash([b"rucola".as_slice()]), 0x7f4a_1c27_4b52_990a);
+        assert_ne!(
+            stable_hash([b"ab".as_slice(), b"c".as_slice()]),
+            stable_hash([b"a".as_slice(), b"bc".as_slice()])
+        );
+
+        // different spellings of the same vault path share their files
+        let tmp = testdir::testdir!();
+        std::fs::create_dir(tmp.join("sub")).unwrap();
+        assert_eq!(
+            vault_file_name("journal", &tmp),
+            vault_file_name("journal", &tmp.join("sub").join(".."))
+        );
+        assert_ne!(
+            vault_file_name("journal", &tmp),
+            vault_file_name("journal", &tmp.join("sub"))
+        );
+    }
+
+    #[test]
+    fn test_anchors() {
+        assert_eq!(
+            link_anchor("Lie Theory#Definition"),
+            Some(String::from("Definition"))
+        );
+        assert_eq!(
+            link_anchor("Lie%20Theory.md#Main%20Result"),
+            Some(String::from("Main Result"))
+        );
+        assert_eq!(link_anchor("note#^block-1"), Some(String::from("^block-1")));
+        assert_eq!(link_anchor("Lie Theory"), None);
+        assert_eq!(link_anchor("Lie Theory#"), None);
+
+        assert_eq!(anchor_to_fragment("Main Result"), "main-result");
+        assert_eq!(
+            anchor_to_fragment("What's a (Lie) group?"),
+            "whats-a-lie-group"
+        );
+        assert_eq!(anchor_to_fragment("^block-1"), "^block-1");
+    }
 }
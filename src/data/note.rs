@@ -1,38 +1,119 @@
 use ratatui::{prelude::*, widgets::*};
-use std::{fmt::Debug, fs, path};
+use std::{collections::HashMap, fmt::Debug, fs, io::Read, path, time};
 
 use itertools::Itertools;
+use unicode_segmentation::UnicodeSegmentation;
 
 use crate::{error, ui};
 
 /// An abstract representation of a note that contains statistics about it but _not_ the full text.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct Note {
-    /// The title of the note.
+    /// The name of the note, i.e. its file name without extension.
     pub name: String,
-    /// All tags contained at any part of the note.
+    /// The id of the note, by which it is linked and stored in the index.
+    /// Derived from the name when the note is read, the index may assign another one according to the configured [IdScheme].
+    pub id: String,
+    /// The title of the note as declared by the `title` frontmatter field, overriding the name for display.
+    pub title: Option<String>,
+    /// The text of the first level 1 heading of the note, used for display if no title is declared.
+    pub heading: Option<String>,
+    /// All tags contained at any part of the note, including those declared by the `tags` frontmatter field.
     pub tags: Vec<String>,
     /// All links contained within the note - no external (e.g. web) links.
     pub links: Vec<String>,
+    /// The target of each link together with the sentence it appears in, in the same order as the links.
+    pub link_contexts: Vec<(String, String)>,
+    /// The targets and anchors of all links pointing to a heading or block of another note, such as `[[Lie Group#Definition]]` or `[[Lie Group#^block]]`.
+    pub anchors: Vec<(String, String)>,
+    /// The ids of all notes embedded with `![[note]]`, which are not counted as links.
+    pub embeds: Vec<String>,
+    /// All external web links (starting with `http://` or `https://`) contained within the note.
+    pub external_links: Vec<String>,
+    /// The targets of all images and of all links to local files other than notes, such as `![](diagram.png)` or `[[paper.pdf]]`, as written.
+    pub file_references: Vec<String>,
     /// The number of words.
     pub words: usize,
     /// The number of characters.
     pub characters: usize,
+    /// The number of unchecked task list items, such as `- [ ] Do this`.
+    pub open_tasks: usize,
+    /// The number of checked task list items, such as `- [x] Did this`.
+    pub done_tasks: usize,
     /// A copy of the path leading to this note.
     pub path: path::PathBuf,
+    /// The creation time of the note file, if the file system supports it.
+    pub created: Option<time::SystemTime>,
+    /// The last modification time of the note file, if the file system supports it.
+    pub modified: Option<time::SystemTime>,
+    /// The fields of the frontmatter block at the start of the note, with lowercase keys.
+    pub frontmatter: HashMap<String, String>,
+    /// Alternative names of the note, as declared by the `aliases` frontmatter field or an `aliases:` line at the start of the note.
+    pub aliases: Vec<String>,
+    /// The amount of headings of each level, starting with level 1.
+    pub headings: [usize; 6],
+    /// Wether any heading is more than one level deeper than the heading before it, such as a level 3 heading directly following a level 1 heading.
+    pub skips_heading_levels: bool,
+    /// The level, text and line of every heading in order, with lines counted from zero after the frontmatter.
+    pub outline: Vec<(usize, String, usize)>,
+    /// The sorted hashes of all runs of consecutive words of the note, used to compare the content of notes without keeping it.
+    pub shingles: Vec<u64>,
+    /// Wether the note file exceeded the maximum note size, so only its beginning was read and the statistics cover only that part.
+    pub truncated: bool,
+    /// A hash of the content of the note file, used to recognize files whose modification time changed without their content changing.
+    /// Not known for truncated notes.
+    pub checksum: Option<u64>,
+    /// The primary language of the note as an ISO 639-1 code such as `en` or `de`, taken from the `lang` or `language` frontmatter field or detected from its text.
+    pub language: Option<String>,
 }
 
 impl Note {
-    /// Opens the file from the given path (if possible) and extracts metadata.
-    pub fn from_path(path: &path::Path) -> error::Result<Self> {
-        // Open the file.
-        let content = fs::read_to_string(path)?;
+    /// Opens the file from the given path (if possible) and extracts metadata as described by the given options.
+    pub fn from_path(path: &path::Path, options: ParseOptions) -> error::Result<Self> {
+        Self::from_content(path, &fs::read_to_string(path)?, options)
+    }
+
+    /// Opens the file from the given path like [Self::from_path], but only reads up to the given amount of bytes from its start.
+    /// Used for very large files, whose title, frontmatter and first tags are usually found at their start.
+    pub fn from_path_limited(
+        path: &path::Path,
+        options: ParseOptions,
+        limit: u64,
+    ) -> error::Result<Self> {
+        let mut bytes = vec![];
+        fs::File::open(path)?.take(limit).read_to_end(&mut bytes)?;
+        // the limit may cut a character in half
+        let mut note = Self::from_content(path, &String::from_utf8_lossy(&bytes), options)?;
+        note.truncated = fs::metadata(path)?.len() > limit;
+        if note.truncated {
+            note.checksum = None;
+        }
+        Ok(note)
+    }
+
+    /// Extracts metadata from the given content of the note file at the given path.
+    fn from_content(
+        path: &path::Path,
+        content: &str,
+        options: ParseOptions,
+    ) -> error::Result<Self> {
+        let ParseOptions {
+            tag_syntax,
+            counting,
+        } = options;
+        // Read file metadata for time stamps.
+        let metadata = fs::metadata(path).ok();
+        let checksum = content_checksum(content);
+
+        // Read frontmatter fields, then only consider the rest of the note
+        let frontmatter = parse_frontmatter(content);
+        let content = strip_frontmatter(content);
 
         // Parse markdown into AST
         let arena = comrak::Arena::new();
         let root = comrak::parse_document(
             &arena,
-            &content,
+            content,
             &comrak::Options {
                 extension: comrak::ExtensionOptionsBuilder::default()
                     .wikilinks_title_after_pipe(true)
@@ -43,44 +124,285 @@ impl Note {
             },
         );
 
+        // Count open and done tasks
+        let (open_tasks, done_tasks) = count_tasks(content);
+        // Level, text and position of all headings in order
+        let outline = root
+            .descendants()
+            .filter_map(|node| match &node.data.borrow().value {
+                comrak::nodes::NodeValue::Heading(heading) => Some((
+                    heading.level as usize,
+                    plain_text(node).trim().to_owned(),
+                    node.data.borrow().sourcepos.start.line.saturating_sub(1),
+                )),
+                _ => None,
+            })
+            .collect_vec();
+        let heading_levels = outline
+            .iter()
+            .map(|(level, _text, _line)| *level)
+            .collect_vec();
+
+        // Embeds: Targets of all `![[target]]` embeds, both of notes and of attachments
+        let embed_targets = embed_targets(content);
+
+        // Links: Go though all wikilinks and markdown links to other notes in the syntax tree, map them to ids and remember where they appear
+        let link_contexts = root
+            .descendants()
+            .flat_map(|node| {
+                let target = match &node.data.borrow().value {
+                    // wikilinks to attachments are only file references, embeds are kept separately
+                    comrak::nodes::NodeValue::WikiLink(link)
+                        if !super::is_file_reference(&link.url) && !is_embedded(node) =>
+                    {
+                        Some(super::name_to_id(&link.url))
+                    }
+                    comrak::nodes::NodeValue::Link(link) => super::link_url_to_id(&link.url),
+                    _ => None,
+                }?;
+                Some((target, link_context(node)))
+            })
+            .collect_vec();
+
+        // Heading: The text of the first level 1 heading, if it is not empty
+        let heading = root
+            .descendants()
+            .find(|node| {
+                matches!(
+                    &node.data.borrow().value,
+                    comrak::nodes::NodeValue::Heading(heading) if heading.level == 1
+                )
+            })
+            .map(|node| plain_text(node).trim().to_owned())
+            .filter(|heading| !heading.is_empty());
+
+        // Name: Remove file extension
+        let name = path
+            .file_stem()
+            .map(|os| os.to_string_lossy().to_string())
+            .ok_or_else(|| error::RucolaError::NoteNameCannotBeRead(path.to_path_buf()))?;
+
         Ok(Self {
-            // Name: Remove file extension
-            name: path
-                .file_stem()
-                .map(|os| os.to_string_lossy().to_string())
-                .ok_or_else(|| error::RucolaError::NoteNameCannotBeRead(path.to_path_buf()))?,
+            // Id: Derived from the name by default
+            id: super::name_to_id(&name),
+            name,
+            // Title: Read from the frontmatter.
+            title: frontmatter
+                .get("title")
+                .filter(|title| !title.is_empty())
+                .cloned(),
+            heading,
             // Path: Already given - convert to owned version.
             path: path.to_path_buf(),
-            // Tags: Go though all text nodes in the AST, split them at whitespace and look for those starting with a hash.
+            // Tags: Go though all text nodes in the AST (and code, if configured), split them at whitespace and look for those starting with a hash.
+            // Then add the tags from the frontmatter, accepting both the plural and the singular key and adding the hash if it is missing.
+            // Each tag is kept once.
             tags: root
                 .descendants()
+                .filter(|_node| tag_syntax.inline)
                 .flat_map(|node| match &node.data.borrow().value {
-                    comrak::nodes::NodeValue::Text(content) => content
-                        .split_whitespace()
-                        .filter(|s| s.starts_with('#'))
-                        .map(|s| s.to_owned())
-                        .collect_vec(),
+                    comrak::nodes::NodeValue::Text(content) => inline_tags(content),
+                    comrak::nodes::NodeValue::Code(code) if tag_syntax.code => {
+                        inline_tags(&code.literal)
+                    }
+                    comrak::nodes::NodeValue::CodeBlock(code) if tag_syntax.code => {
+                        inline_tags(&code.literal)
+                    }
                     _ => vec![],
                 })
+                .chain(
+                    frontmatter
+                        .get("tags")
+                        .or_else(|| frontmatter.get("tag"))
+                        .map(|value| parse_list(value))
+                        .unwrap_or_default()
+                        .into_iter()
+                        .flat_map(|item| {
+                            item.split_whitespace()
+                                .map(|tag| format!("#{}", tag.trim_start_matches('#')))
+                                .collect_vec()
+                        }),
+                )
+                .unique()
+                .collect(),
+            // Links: Only the targets
+            links: link_contexts
+                .iter()
+                .map(|(target, _context)| target.clone())
+                .collect(),
+            link_contexts,
+            // Anchors: Go through the same links again and keep those with an anchor
+            anchors: root
+                .descendants()
+                .flat_map(|node| match &node.data.borrow().value {
+                    comrak::nodes::NodeValue::WikiLink(link)
+                        if !super::is_file_reference(&link.url) && !is_embedded(node) =>
+                    {
+                        super::link_anchor(&link.url)
+                            .map(|anchor| (super::name_to_id(&link.url), anchor))
+                    }
+                    comrak::nodes::NodeValue::Link(link) => {
+                        super::link_url_to_id(&link.url).zip(super::link_anchor(&link.url))
+                    }
+                    _ => None,
+                })
+                .collect(),
+            // External links: Go through all regular links in the syntax tree and keep those pointing to the web
+            external_links: root
+                .descendants()
+                .flat_map(|node| match &node.data.borrow().value {
+                    comrak::nodes::NodeValue::Link(link)
+                        if link.url.starts_with("http://") || link.url.starts_with("https://") =>
+                    {
+                        Some(link.url.clone())
+                    }
+                    _ => None,
+                })
                 .collect(),
-            // Links: Go though all wikilinks in the syntax tree and map them
-            links: root
+            // File references: Go through all images, regular links and wikilinks and keep those pointing to local files with an extension
+            file_references: root
                 .descendants()
                 .flat_map(|node| match &node.data.borrow().value {
-                    comrak::nodes::NodeValue::WikiLink(link) => Some(super::name_to_id(&link.url)),
+                    comrak::nodes::NodeValue::Image(link)
+                    | comrak::nodes::NodeValue::Link(link)
+                        if !link.url.contains("://") && !link.url.starts_with('#') =>
+                    {
+                        Some(link.url.clone())
+                    }
+                    comrak::nodes::NodeValue::WikiLink(link) if !is_embedded(node) => {
+                        Some(link.url.clone())
+                    }
                     _ => None,
                 })
+                .chain(embed_targets.iter().cloned())
+                .filter(|reference| super::is_file_reference(reference))
                 .collect(),
-            // Words: Split at whitespace, grouping multiple consecutive instances of whitespace together.
-            // See definition of `split_whitespace` for criteria.
-            words: content.split_whitespace().count(),
-            // Characters: Simply use the length of the string.
-            characters: content.len(),
+            // Embeds: Only those of notes, mapped to ids
+            embeds: embed_targets
+                .iter()
+                .filter(|target| !super::is_file_reference(target))
+                .map(|target| super::name_to_id(target))
+                .collect(),
+            // Words and characters: Counted as configured
+            words: counting.words(content),
+            characters: counting.characters(content),
+            // Headings: Count the headings of each level and compare each heading with the one before it.
+            headings: heading_levels.iter().fold([0; 6], |mut counts, level| {
+                counts[level.clamp(&1, &6) - 1] += 1;
+                counts
+            }),
+            skips_heading_levels: heading_levels
+                .iter()
+                .tuple_windows()
+                .any(|(previous, level)| *level > previous + 1),
+            outline,
+            // Tasks: Count the checkboxes of task lists.
+            open_tasks,
+            done_tasks,
+            // Time stamps: Taken from the metadata, if the file system supports them.
+            created: metadata.as_ref().and_then(|md| md.created().ok()),
+            modified: metadata.as_ref().and_then(|md| md.modified().ok()),
+            // Aliases: Read from the frontmatter and the first line, accepting both the plural and the singular key.
+            // The title also serves as an alias, so links may use it.
+            aliases: frontmatter
+                .get("aliases")
+                .or_else(|| frontmatter.get("alias"))
+                .map(|value| parse_list(value))
+                .unwrap_or_default()
+                .into_iter()
+                .chain(first_line_aliases(content))
+                .chain(
+                    frontmatter
+                        .get("title")
+                        .filter(|title| !title.is_empty())
+                        .cloned(),
+                )
+                .collect(),
+            // Language: Declared in the frontmatter or detected from the text.
+            language: frontmatter
+                .get("lang")
+                .or_else(|| frontmatter.get("language"))
+                .map(|language| language.trim().to_lowercase())
+                .filter(|language| !language.is_empty())
+                .or_else(|| detect_language(content).map(String::from)),
+            // Frontmatter: Read from the leading block delimited by `---`, if there is one.
+            frontmatter,
+            // Shingles: Hash all runs of consecutive words.
+            shingles: shingles(content),
+            truncated: false,
+            checksum: Some(checksum),
         })
     }
 
+    /// Returns the ids of all alternative names this note can be linked by: Its aliases and its name, in case its id is not derived from the name.
+    pub fn alias_ids(&self) -> impl Iterator<Item = String> + '_ {
+        self.aliases
+            .iter()
+            .chain(std::iter::once(&self.name))
+            .map(|alias| super::name_to_id(alias))
+            .filter(|alias| *alias != self.id)
+    }
+
+    /// Returns the title to display for this note: Its frontmatter title if it declares one, else its first level 1 heading and its name otherwise.
+    pub fn title(&self) -> &str {
+        self.title
+            .as_deref()
+            .or(self.heading.as_deref())
+            .unwrap_or(&self.name)
+    }
+
+    /// Checks wether the note file was changed since this note was read from it, by comparing the checksum of its current content.
+    /// Truncated notes, whose checksum is not known, are compared by their modification time instead.
+    pub fn changed_on_disk(&self) -> bool {
+        match self.checksum {
+            Some(checksum) => fs::read_to_string(&self.path)
+                .map(|content| content_checksum(&content) != checksum)
+                .unwrap_or(true),
+            None => {
+                fs::metadata(&self.path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+                    != self.modified
+            }
+        }
+    }
+
+    /// Estimates the time needed to read this note at the given reading speed, in whole minutes rounded up.
+    pub fn reading_minutes(&self, words_per_minute: usize) -> usize {
+        self.words.div_ceil(words_per_minute.max(1))
+    }
+
+    /// Returns the similarity of the titles of this and the other note between 0 and 1, ignoring case.
+    /// This is one minus the edit distance of the titles, normalized by the length of the longer one.
+    pub fn title_similarity(&self, other: &Note) -> f64 {
+        let a = self.name.to_lowercase().chars().collect_vec();
+        let b = other.name.to_lowercase().chars().collect_vec();
+        let longer = a.len().max(b.len());
+        if longer == 0 {
+            return 1.;
+        }
+        1. - edit_distance(&a, &b) as f64 / longer as f64
+    }
+
+    /// Returns the similarity of the contents of this and the other note between 0 and 1.
+    /// This is the Jaccard index of their shingles.
+    pub fn content_similarity(&self, other: &Note) -> f64 {
+        if self.shingles.is_empty() && other.shingles.is_empty() {
+            return 0.;
+        }
+        // both lists are sorted, so merge them to count the shared shingles
+        let shared = self
+            .shingles
+            .iter()
+            .merge_join_by(other.shingles.iter(), |a, b| a.cmp(b))
+            .filter(|either| matches!(either, itertools::EitherOrBoth::Both(_, _)))
+            .count();
+        shared as f64 / (self.shingles.len() + other.shingles.len() - shared) as f64
+    }
+
     /// Converts this note to a small ratatui table displaying its most vital stats.
-    pub fn to_stats_table(&self, styles: &ui::UiStyles) -> Table {
+    /// The reading time is estimated with the given reading speed.
+    pub fn to_stats_table(&self, styles: &ui::UiStyles, words_per_minute: usize) -> Table {
         let stats_widths = [
             Constraint::Length(8),
             Constraint::Length(12),
@@ -115,12 +437,473 @@ impl Note {
                 Cell::from("Path:").style(styles.text_style),
                 Cell::from(self.path.to_str().unwrap_or_default()).style(styles.text_style),
             ]),
+            Row::new(vec![
+                Cell::from("Reading:").style(styles.text_style),
+                Cell::from(format!("{:7} min", self.reading_minutes(words_per_minute)))
+                    .style(styles.text_style),
+                Cell::from("Embeds:").style(styles.text_style),
+                Cell::from(self.embeds.len().to_string()).style(styles.text_style),
+            ]),
         ];
 
         Table::new(stats_rows, stats_widths).column_spacing(1)
     }
 }
 
+/// Returns the targets of all `![[target]]` embeds in the given content outside of code blocks, without their aliases.
+/// Embeds are found in the raw content, as the markdown parser does not treat the `!` as part of the wikilink.
+fn embed_targets(content: &str) -> Vec<String> {
+    let mut targets = vec![];
+    let mut in_code = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        let mut rest = line;
+        while let Some(start) = rest.find("![[") {
+            rest = &rest[start + 3..];
+            let Some(end) = rest.find("]]") else {
+                break;
+            };
+            let target = rest[..end].split('|').next().unwrap_or_default().trim();
+            if !target.is_empty() {
+                targets.push(target.to_string());
+            }
+            rest = &rest[end + 2..];
+        }
+    }
+
+    targets
+}
+
+/// Wether the given wikilink node is preceded by a `!`, making it an embed rather than a link.
+fn is_embedded<'a>(node: &'a comrak::nodes::AstNode<'a>) -> bool {
+    node.previous_sibling().is_some_and(|previous| {
+        matches!(&previous.data.borrow().value, comrak::nodes::NodeValue::Text(text) if text.ends_with('!'))
+    })
+}
+
+/// Extracts the simple `key: value` pairs of a frontmatter block delimited by `---` at the very start of the given content.
+/// Lists given as indented `- item` lines below a key are converted to the inline form `[item, item]`.
+/// Other nested structures are not supported, only top level fields are read.
+fn parse_frontmatter(content: &str) -> HashMap<String, String> {
+    let mut lines = content.lines();
+
+    if lines.next().map(str::trim_end) != Some("---") {
+        return HashMap::new();
+    }
+
+    let mut fields = HashMap::new();
+    // the key of the last field and the items of a list that might follow it
+    let mut last_key: Option<String> = None;
+    let mut items = Vec::new();
+
+    for line in lines.take_while(|line| !matches!(line.trim_end(), "---" | "...")) {
+        if let Some(item) = line.trim_start().strip_prefix("- ") {
+            items.push(item.trim().trim_matches(['"', '\'']).to_string());
+            continue;
+        }
+        // any other indented line belongs to a nested value
+        if line.starts_with(char::is_whitespace) {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            if let Some(last_key) = last_key.take() {
+                if !items.is_empty() {
+                    fields.insert(last_key, format!("[{}]", items.join(", ")));
+                    items.clear();
+                }
+            }
+            let key = key.trim().to_lowercase();
+            if !key.is_empty() {
+                fields.insert(
+                    key.clone(),
+                    value.trim().trim_matches(['"', '\'']).to_string(),
+                );
+                last_key = Some(key);
+            }
+        }
+    }
+    if let Some(last_key) = last_key {
+        if !items.is_empty() {
+            fields.insert(last_key, format!("[{}]", items.join(", ")));
+        }
+    }
+
+    fields
+}
+
+/// Returns the given content without a frontmatter block delimited by `---` at its very start, if there is one.
+/// Content with an unterminated frontmatter block is returned unchanged.
+pub fn strip_frontmatter(content: &str) -> &str {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return content;
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        if matches!(line.trim_end(), "---" | "...") {
+            return &rest[offset..];
+        }
+    }
+    content
+}
+
+/// Counts the open and the done items of task lists in the given content, ignoring fenced code blocks.
+fn count_tasks(content: &str) -> (usize, usize) {
+    let mut open = 0;
+    let mut done = 0;
+    let mut in_code = false;
+
+    for line in content.lines() {
+        let line = line.trim_start();
+        if line.starts_with("```") || line.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        // strip the list marker, which is either a bullet or a number followed by a dot or parenthesis
+        let Some(item) = line.strip_prefix(['-', '*', '+']).or_else(|| {
+            line.trim_start_matches(|c: char| c.is_ascii_digit())
+                .strip_prefix(['.', ')'])
+                .filter(|_| line.starts_with(|c: char| c.is_ascii_digit()))
+        }) else {
+            continue;
+        };
+        match item.trim_start().get(..3) {
+            Some("[ ]") => open += 1,
+            Some("[x]" | "[X]") => done += 1,
+            _ => {}
+        }
+    }
+
+    (open, done)
+}
+
+/// Frequent short words of the languages that can be detected, by their ISO 639-1 code.
+const LANGUAGE_WORDS: [(&str, &[&str]); 6] = [
+    (
+        "en",
+        &[
+            "the", "and", "is", "of", "to", "in", "that", "it", "with", "for", "this", "are", "be",
+            "on", "not", "as",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "ein", "eine", "mit", "zu", "den", "von",
+            "sich", "auf", "für", "auch", "es", "ich",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "la", "les", "et", "est", "un", "une", "des", "du", "pas", "que", "pour", "dans",
+            "sur", "ce", "avec",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "el", "los", "las", "y", "es", "un", "una", "que", "del", "por", "para", "con", "no",
+            "se", "como",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "il", "lo", "gli", "e", "è", "di", "che", "un", "una", "per", "non", "con", "della",
+            "sono",
+        ],
+    ),
+    (
+        "nl",
+        &[
+            "de", "het", "een", "en", "is", "van", "niet", "dat", "op", "te", "met", "voor",
+            "zijn", "ook",
+        ],
+    ),
+];
+
+/// The amount of frequent words of a language a text needs to contain to be recognized as that language.
+const LANGUAGE_MIN_WORDS: usize = 5;
+
+/// Detects the primary language of the given text by counting the frequent words of every known language it contains.
+/// Returns `None` if the text contains too few of them or two languages are equally likely.
+fn detect_language(content: &str) -> Option<&'static str> {
+    let mut counts = [0; LANGUAGE_WORDS.len()];
+    for word in content.split_whitespace() {
+        let word = word
+            .trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase();
+        for (count, (_language, words)) in counts.iter_mut().zip(LANGUAGE_WORDS.iter()) {
+            if words.contains(&word.as_str()) {
+                *count += 1;
+            }
+        }
+    }
+
+    let best = counts.iter().copied().max().unwrap_or_default();
+    let mut candidates = counts
+        .iter()
+        .zip(LANGUAGE_WORDS.iter())
+        .filter(|(count, _)| **count == best);
+    match (candidates.next(), candidates.next()) {
+        (Some((_, (language, _))), None) if best >= LANGUAGE_MIN_WORDS => Some(language),
+        _ => None,
+    }
+}
+
+/// The amount of consecutive words hashed into one shingle.
+const SHINGLE_LENGTH: usize = 3;
+
+/// Hashes all runs of [SHINGLE_LENGTH] consecutive words of the given content, ignoring case and punctuation, and returns the sorted and deduplicated hashes.
+/// Content with fewer words is hashed as a whole.
+fn shingles(content: &str) -> Vec<u64> {
+    let words = content
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect_vec();
+
+    words
+        .windows(SHINGLE_LENGTH.min(words.len()).max(1))
+        .map(|window| super::stable_hash(window.iter().map(|word| word.as_bytes())))
+        .sorted_unstable()
+        .dedup()
+        .collect()
+}
+
+/// Hashes the entire content of a note file, see [Note::checksum].
+pub(super) fn content_checksum(content: &str) -> u64 {
+    super::stable_hash([content.as_bytes()])
+}
+
+/// Computes the Levenshtein distance between the two given sequences of characters.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    // distances from the prefix of a handled so far to every prefix of b
+    let mut row = (0..=b.len()).collect_vec();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Where tags are read from in the body of a note, in addition to the `tags` field of the frontmatter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct TagSyntax {
+    /// Wether to read inline tags such as `#tag` from the text of notes.
+    pub inline: bool,
+    /// Wether to also read inline tags from code blocks and inline code.
+    pub code: bool,
+}
+
+impl Default for TagSyntax {
+    fn default() -> Self {
+        Self {
+            inline: true,
+            code: false,
+        }
+    }
+}
+
+/// How words and characters of notes are counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum TextCounting {
+    /// Words are separated by whitespace, characters are the bytes of the UTF-8 encoded text.
+    /// Fast, but only meaningful for texts in latin script.
+    #[default]
+    Whitespace,
+    /// Words are found by unicode word segmentation, which also counts each CJK ideograph as a word, and characters are grapheme clusters.
+    Unicode,
+}
+
+impl TextCounting {
+    /// Returns the amount of words of the given text.
+    pub fn words(&self, text: &str) -> usize {
+        match self {
+            TextCounting::Whitespace => text.split_whitespace().count(),
+            TextCounting::Unicode => text.unicode_words().count(),
+        }
+    }
+
+    /// Returns the amount of characters of the given text.
+    pub fn characters(&self, text: &str) -> usize {
+        match self {
+            TextCounting::Whitespace => text.len(),
+            TextCounting::Unicode => text.graphemes(true).count(),
+        }
+    }
+}
+
+/// Describes how notes are parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub struct ParseOptions {
+    /// Where tags are read from.
+    pub tag_syntax: TagSyntax,
+    /// How words and characters are counted.
+    pub counting: TextCounting,
+}
+
+/// Returns all words of the given text that start with a hash and are more than just the hash.
+fn inline_tags(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .filter(|word| word.starts_with('#') && word.len() > 1)
+        .map(|word| word.to_owned())
+        .collect()
+}
+
+/// How the ids of notes, by which they are linked, are derived.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum IdScheme {
+    /// The file name, in lowercase and with spaces replaced by dashes, e.g. `lie-group` for `math/Lie Group.md`.
+    #[default]
+    Name,
+    /// The path relative to the vault, converted like the file name, e.g. `math/lie-group` for `math/Lie Group.md`.
+    Path,
+    /// The `id` field of the frontmatter, falling back to the file name for notes without one.
+    Frontmatter,
+    /// The timestamp prefix of Zettelkasten notes of at least 8 digits, e.g. `202401311200` for `202401311200-lie-group.md`, falling back to the file name for notes without one.
+    Timestamp,
+}
+
+impl IdScheme {
+    /// Returns the id of the given note in the vault at the given path according to this scheme.
+    /// In all schemes, the note can still be linked by its file name, see [Note::alias_ids].
+    pub fn id(&self, note: &Note, vault: &path::Path) -> String {
+        match self {
+            Self::Name => super::name_to_id(&note.name),
+            Self::Path => {
+                let relative = note
+                    .path
+                    .strip_prefix(vault)
+                    .unwrap_or(&note.path)
+                    .with_extension("");
+                super::name_to_id(
+                    &relative
+                        .components()
+                        .map(|component| component.as_os_str().to_string_lossy())
+                        .join("/"),
+                )
+            }
+            Self::Frontmatter => note
+                .frontmatter
+                .get("id")
+                .filter(|id| !id.is_empty())
+                .map(|id| super::name_to_id(id))
+                .unwrap_or_else(|| super::name_to_id(&note.name)),
+            Self::Timestamp => {
+                let prefix = note
+                    .name
+                    .chars()
+                    .take_while(|c| c.is_ascii_digit())
+                    .collect::<String>();
+                if prefix.len() >= 8 {
+                    prefix
+                } else {
+                    super::name_to_id(&note.name)
+                }
+            }
+        }
+    }
+}
+
+/// Returns the plain text of the given node and all its descendants, with line breaks turned into spaces.
+fn plain_text<'a>(node: &'a comrak::nodes::AstNode<'a>) -> String {
+    node.descendants()
+        .filter_map(|node| match &node.data.borrow().value {
+            comrak::nodes::NodeValue::Text(text) => Some(text.clone()),
+            comrak::nodes::NodeValue::Code(code) => Some(code.literal.clone()),
+            comrak::nodes::NodeValue::SoftBreak | comrak::nodes::NodeValue::LineBreak => {
+                Some(String::from(" "))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the sentence a link appears in, taken from the closest paragraph, heading or table cell containing it.
+/// Falls back to the whole block if no single sentence contains the text of the link.
+fn link_context<'a>(link: &'a comrak::nodes::AstNode<'a>) -> String {
+    let block = link
+        .ancestors()
+        .find(|node| {
+            matches!(
+                node.data.borrow().value,
+                comrak::nodes::NodeValue::Paragraph
+                    | comrak::nodes::NodeValue::Heading(_)
+                    | comrak::nodes::NodeValue::TableCell
+            )
+        })
+        .unwrap_or(link);
+    let text = plain_text(block);
+    let link_text = plain_text(link);
+
+    // Sentences end with a punctuation mark followed by whitespace
+    let mut start = 0;
+    let mut sentences = vec![];
+    for ((index, c), (_next_index, next)) in text.char_indices().tuple_windows() {
+        if matches!(c, '.' | '!' | '?') && next.is_whitespace() {
+            sentences.push(&text[start..=index]);
+            start = index + 1;
+        }
+    }
+    sentences.push(&text[start..]);
+
+    sentences
+        .into_iter()
+        .find(|sentence| sentence.contains(&link_text))
+        .unwrap_or(text.as_str())
+        .trim()
+        .to_owned()
+}
+
+/// Reads the aliases declared by a first line of the form `aliases: ML, Machine Learning`, ignoring the case of the key.
+fn first_line_aliases(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .next()
+        .and_then(|line| line.split_once(':'))
+        .filter(|(key, _value)| matches!(key.trim().to_lowercase().as_str(), "aliases" | "alias"))
+        .map(|(_key, value)| parse_list(value))
+        .unwrap_or_default()
+}
+
+/// Splits a frontmatter value that is either a single value, a comma separated enumeration or a list of the form `[a, b]` into its elements.
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(|item| item.trim().trim_matches(['"', '\'']).to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -128,14 +911,20 @@ mod tests {
 
     #[test]
     fn test_loading() {
-        let _note =
-            crate::data::Note::from_path(Path::new("./tests/common/notes/Books.md")).unwrap();
+        let _note = crate::data::Note::from_path(
+            Path::new("./tests/common/notes/Books.md"),
+            Default::default(),
+        )
+        .unwrap();
     }
 
     #[test]
     fn test_values() {
-        let note =
-            crate::data::Note::from_path(Path::new("./tests/common/notes/math/Chart.md")).unwrap();
+        let note = crate::data::Note::from_path(
+            Path::new("./tests/common/notes/math/Chart.md"),
+            Default::default(),
+        )
+        .unwrap();
 
         assert_eq!(note.name, String::from("Chart"));
         assert_eq!(
@@ -148,9 +937,477 @@ mod tests {
         );
         assert_eq!(note.words, 115);
         assert_eq!(note.characters, 678);
+        assert_eq!(note.reading_minutes(200), 1);
+        assert_eq!(note.reading_minutes(100), 2);
+        assert_eq!(note.reading_minutes(115), 1);
+        assert_eq!(note.headings, [0, 2, 0, 0, 0, 0]);
+        assert!(!note.skips_heading_levels);
         assert_eq!(
             note.path,
             PathBuf::from("./tests/common/notes/math/Chart.md")
         );
     }
+
+    #[test]
+    fn test_headings() {
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("Skipping.md"),
+            "## Start\n### Deeper\n# Top\n### Skipped\n```\n# Code\n```\n",
+        )
+        .unwrap();
+
+        let note =
+            crate::data::Note::from_path(&tmp.join("Skipping.md"), Default::default()).unwrap();
+        assert_eq!(note.headings, [1, 1, 2, 0, 0, 0]);
+        assert!(note.skips_heading_levels);
+
+        let osx = crate::data::Note::from_path(
+            Path::new("./tests/common/notes/OSX.md"),
+            Default::default(),
+        )
+        .unwrap();
+        assert_eq!(osx.headings, [0; 6]);
+        assert!(!osx.skips_heading_levels);
+    }
+
+    #[test]
+    fn test_outline() {
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("Outline.md"),
+            "---\ntitle: Outline\n---\n# Top\nSome text.\n\n## Sub *heading*\n```\n# Code\n```\n### Deeper\n",
+        )
+        .unwrap();
+
+        let note =
+            crate::data::Note::from_path(&tmp.join("Outline.md"), Default::default()).unwrap();
+        assert_eq!(
+            note.outline,
+            vec![
+                (1, String::from("Top"), 0),
+                (2, String::from("Sub heading"), 3),
+                (3, String::from("Deeper"), 7),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_frontmatter() {
+        let fields = super::parse_frontmatter(
+            "---\nStatus: draft\nauthor: \"me\"\ntags:\n  - a\n---\nstatus: not frontmatter\n",
+        );
+
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields.get("status"), Some(&String::from("draft")));
+        assert_eq!(fields.get("author"), Some(&String::from("me")));
+        assert_eq!(fields.get("tags"), Some(&String::from("[a]")));
+
+        assert!(super::parse_frontmatter("status: draft\n").is_empty());
+
+        let chart = crate::data::Note::from_path(
+            Path::new("./tests/common/notes/math/Chart.md"),
+            Default::default(),
+        )
+        .unwrap();
+        assert!(chart.frontmatter.is_empty());
+    }
+
+    #[test]
+    fn test_frontmatter_metadata() {
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("Meta.md"),
+            "---\ntitle: Nice Title\ntags: [a, b]\nstatus: draft\n---\nBody words here #c\n",
+        )
+        .unwrap();
+
+        let note = crate::data::Note::from_path(&tmp.join("Meta.md"), Default::default()).unwrap();
+        assert_eq!(note.name, "Meta");
+        assert_eq!(note.title(), "Nice Title");
+        assert_eq!(note.words, 4);
+        assert_eq!(note.headings, [0; 6]);
+        assert!(note.tags.contains(&String::from("#a")));
+        assert!(note.tags.contains(&String::from("#b")));
+        assert!(note.tags.contains(&String::from("#c")));
+        assert!(note.aliases.contains(&String::from("Nice Title")));
+        assert_eq!(note.frontmatter.get("status"), Some(&String::from("draft")));
+
+        assert_eq!(super::strip_frontmatter("---\na: b\n---\nbody"), "body");
+        assert_eq!(
+            super::strip_frontmatter("---\na: b\nbody"),
+            "---\na: b\nbody"
+        );
+        assert_eq!(super::strip_frontmatter("body"), "body");
+
+        let chart = crate::data::Note::from_path(
+            Path::new("./tests/common/notes/math/Chart.md"),
+            Default::default(),
+        )
+        .unwrap();
+        assert_eq!(chart.title(), "Chart");
+    }
+
+    #[test]
+    fn test_heading_title() {
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("2024-01-31.md"),
+            "Intro\n\n## Sub\n\n# The *Real* Title\n\n# Second\n",
+        )
+        .unwrap();
+        std::fs::write(tmp.join("Plain.md"), "## Only a subheading\n").unwrap();
+        std::fs::write(
+            tmp.join("Both.md"),
+            "---\ntitle: Declared\n---\n# Heading\n",
+        )
+        .unwrap();
+
+        let note =
+            crate::data::Note::from_path(&tmp.join("2024-01-31.md"), Default::default()).unwrap();
+        assert_eq!(note.heading.as_deref(), Some("The Real Title"));
+        assert_eq!(note.title(), "The Real Title");
+        // the id is still derived from the file name
+        assert_eq!(note.name, "2024-01-31");
+        assert_eq!(note.id, "2024-01-31");
+
+        let plain =
+            crate::data::Note::from_path(&tmp.join("Plain.md"), Default::default()).unwrap();
+        assert_eq!(plain.heading, None);
+        assert_eq!(plain.title(), "Plain");
+
+        // the frontmatter title takes precedence
+        let both = crate::data::Note::from_path(&tmp.join("Both.md"), Default::default()).unwrap();
+        assert_eq!(both.heading.as_deref(), Some("Heading"));
+        assert_eq!(both.title(), "Declared");
+    }
+
+    #[test]
+    fn test_markdown_links() {
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("Links.md"),
+            "[[Wiki]] [text](../math/Lie%20Group.md) [web](https://example.com/a.md) [anchor](#top) ![](image.png) [[Other#Part]]",
+        )
+        .unwrap();
+
+        let note = crate::data::Note::from_path(&tmp.join("Links.md"), Default::default()).unwrap();
+        assert_eq!(note.links, vec!["wiki", "lie-group", "other"]);
+        assert_eq!(note.file_references, vec!["image.png"]);
+        assert_eq!(note.external_links, vec!["https://example.com/a.md"]);
+    }
+
+    #[test]
+    fn test_changed_on_disk() {
+        let tmp = testdir::testdir!();
+        let path = tmp.join("Synced.md");
+        std::fs::write(&path, "Some content").unwrap();
+
+        let note = crate::data::Note::from_path(&path, Default::default()).unwrap();
+        assert!(!note.changed_on_disk());
+
+        // rewriting the same content is no change
+        std::fs::write(&path, "Some content").unwrap();
+        assert!(!note.changed_on_disk());
+
+        std::fs::write(&path, "Some other content").unwrap();
+        assert!(note.changed_on_disk());
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(note.changed_on_disk());
+    }
+
+    #[test]
+    fn test_language() {
+        assert_eq!(
+            super::detect_language(
+                "The manifold is a space that is locally similar to the plane and it is smooth."
+            ),
+            Some("en")
+        );
+        assert_eq!(
+            super::detect_language("Die Mannigfaltigkeit ist ein Raum, der lokal wie die Ebene aussieht und auch glatt ist."),
+            Some("de")
+        );
+        assert_eq!(super::detect_language("Manifold, chart, atlas."), None);
+
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("Declared.md"),
+            "---\nlang: DE\n---\nThe note is written in English, but that is not what it says.",
+        )
+        .unwrap();
+        let note = super::Note::from_path(&tmp.join("Declared.md"), Default::default()).unwrap();
+        assert_eq!(note.language.as_deref(), Some("de"));
+
+        let chart = super::Note::from_path(
+            std::path::Path::new("./tests/common/notes/math/Chart.md"),
+            Default::default(),
+        )
+        .unwrap();
+        assert_eq!(chart.language.as_deref(), Some("en"));
+    }
+
+    #[test]
+    fn test_embeds() {
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("Embeds.md"),
+            "![[Lie Group]] and [[Wiki]]! ![[diagram.png]]\n\n![[Other#Part|alias]]\n\n```\n![[Code]]\n```\n",
+        )
+        .unwrap();
+
+        let note = super::Note::from_path(&tmp.join("Embeds.md"), Default::default()).unwrap();
+        assert_eq!(note.embeds, vec!["lie-group", "other"]);
+        assert_eq!(note.links, vec!["wiki"]);
+        assert!(note.anchors.is_empty());
+        assert_eq!(note.file_references, vec!["diagram.png"]);
+
+        assert_eq!(
+            super::embed_targets("![[a|b]] ![[ c ]] ![[]] ![[open"),
+            vec!["a", "c"]
+        );
+    }
+
+    #[test]
+    fn test_tag_syntax() {
+        use crate::data::{ParseOptions, TagSyntax};
+
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("Tags.md"),
+            "---\ntags: [a, b]\n---\nText #a #c and # alone.\n\n`#d`\n\n```\n#e\n```\n",
+        )
+        .unwrap();
+
+        let note = super::Note::from_path(&tmp.join("Tags.md"), ParseOptions::default()).unwrap();
+        assert_eq!(note.tags, vec!["#a", "#c", "#b"]);
+
+        let note = super::Note::from_path(
+            &tmp.join("Tags.md"),
+            ParseOptions {
+                tag_syntax: TagSyntax {
+                    inline: true,
+                    code: true,
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(note.tags, vec!["#a", "#c", "#d", "#e", "#b"]);
+
+        let note = super::Note::from_path(
+            &tmp.join("Tags.md"),
+            ParseOptions {
+                tag_syntax: TagSyntax {
+                    inline: false,
+                    code: true,
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(note.tags, vec!["#a", "#b"]);
+    }
+
+    #[test]
+    fn test_text_counting() {
+        use crate::data::TextCounting;
+
+        let text = "Hello, wörld! e\u{301}  -- 日本語の文章";
+        assert_eq!(TextCounting::Whitespace.words(text), 5);
+        assert_eq!(TextCounting::Whitespace.characters(text), text.len());
+        // the dashes are no word, but every ideograph is
+        assert_eq!(TextCounting::Unicode.words(text), 9);
+        // the accent is combined with its letter
+        assert_eq!(TextCounting::Unicode.characters(text), 26);
+    }
+
+    #[test]
+    fn test_link_contexts() {
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("Contexts.md"),
+            "# About [[Topology]]\n\nA chart is a map. Charts form an [[Atlas|atlas]], see\n[Manifold](Manifold.md)! Done.\n\n- list item with [[Chart]]\n",
+        )
+        .unwrap();
+
+        let note =
+            crate::data::Note::from_path(&tmp.join("Contexts.md"), Default::default()).unwrap();
+        assert_eq!(note.links, vec!["topology", "atlas", "manifold", "chart"]);
+        assert_eq!(
+            note.link_contexts,
+            vec![
+                (String::from("topology"), String::from("About Topology")),
+                (
+                    String::from("atlas"),
+                    String::from("Charts form an atlas, see Manifold!")
+                ),
+                (
+                    String::from("manifold"),
+                    String::from("Charts form an atlas, see Manifold!")
+                ),
+                (String::from("chart"), String::from("list item with Chart")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_anchors() {
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("Anchors.md"),
+            "[[Lie Group#Definition]] [[Chart#^atlas-def|the definition]] [[Manifold]] [text](Smooth%20Map.md#Main%20Result) [anchor](#top)",
+        )
+        .unwrap();
+
+        let note =
+            crate::data::Note::from_path(&tmp.join("Anchors.md"), Default::default()).unwrap();
+        assert_eq!(
+            note.links,
+            vec!["lie-group", "chart", "manifold", "smooth-map"]
+        );
+        assert_eq!(
+            note.anchors,
+            vec![
+                (String::from("lie-group"), String::from("Definition")),
+                (String::from("chart"), String::from("^atlas-def")),
+                (String::from("smooth-map"), String::from("Main Result")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_id_schemes() {
+        use crate::data::IdScheme;
+
+        let vault = Path::new("/vault");
+        let note = crate::data::Note {
+            name: String::from("202401311200 Lie Group"),
+            path: PathBuf::from("/vault/math/202401311200 Lie Group.md"),
+            frontmatter: [(String::from("id"), String::from("LG"))].into(),
+            ..Default::default()
+        };
+
+        assert_eq!(IdScheme::Name.id(&note, vault), "202401311200-lie-group");
+        assert_eq!(
+            IdScheme::Path.id(&note, vault),
+            "math/202401311200-lie-group"
+        );
+        assert_eq!(IdScheme::Frontmatter.id(&note, vault), "lg");
+        assert_eq!(IdScheme::Timestamp.id(&note, vault), "202401311200");
+
+        // fall back to the name
+        let plain = crate::data::Note {
+            name: String::from("2024 Plans"),
+            id: String::from("2024-plans"),
+            ..Default::default()
+        };
+        assert_eq!(IdScheme::Frontmatter.id(&plain, vault), "2024-plans");
+        assert_eq!(IdScheme::Timestamp.id(&plain, vault), "2024-plans");
+        assert_eq!(plain.alias_ids().count(), 0);
+        assert_eq!(
+            crate::data::Note {
+                id: IdScheme::Timestamp.id(&note, vault),
+                ..note
+            }
+            .alias_ids()
+            .collect::<Vec<_>>(),
+            vec!["202401311200-lie-group"]
+        );
+    }
+
+    #[test]
+    fn test_first_line_aliases() {
+        assert_eq!(
+            super::first_line_aliases("Aliases: ML, \"Machine Learning\"\n# Heading"),
+            vec!["ML", "Machine Learning"]
+        );
+        assert_eq!(super::first_line_aliases("alias: [AI]"), vec!["AI"]);
+        assert!(super::first_line_aliases("# Heading\naliases: ML").is_empty());
+        assert!(super::first_line_aliases("Note: this is no alias").is_empty());
+        assert!(super::first_line_aliases("").is_empty());
+
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("Machine Learning.md"),
+            "---\naliases: [ML]\n---\naliases: AI\nText",
+        )
+        .unwrap();
+        let note =
+            crate::data::Note::from_path(&tmp.join("Machine Learning.md"), Default::default())
+                .unwrap();
+        assert_eq!(note.aliases, vec!["ML", "AI"]);
+    }
+
+    #[test]
+    fn test_frontmatter_lists() {
+        let fields = super::parse_frontmatter(
+            "---\naliases:\n  - ML\n  - \"Machine Learning\"\nstatus: done\n---\n",
+        );
+
+        assert_eq!(
+            fields.get("aliases"),
+            Some(&String::from("[ML, Machine Learning]"))
+        );
+        assert_eq!(fields.get("status"), Some(&String::from("done")));
+
+        assert_eq!(
+            super::parse_list("[ML, 'Machine Learning']"),
+            vec![String::from("ML"), String::from("Machine Learning")]
+        );
+        assert_eq!(super::parse_list("ML"), vec![String::from("ML")]);
+        assert!(super::parse_list("[]").is_empty());
+    }
+
+    #[test]
+    fn test_tasks() {
+        assert_eq!(
+            super::count_tasks(
+                "# Tasks\n - [ ] open\n - [x] done\n* [X] done\n1. [ ] open\n2) [ ] open\n- [] no task\n[ ] no task\n```\n- [ ] code\n```\n"
+            ),
+            (3, 2)
+        );
+
+        let linux = crate::data::Note::from_path(
+            Path::new("./tests/common/notes/Linux.md"),
+            Default::default(),
+        )
+        .unwrap();
+        assert_eq!(linux.open_tasks, 0);
+        assert_eq!(linux.done_tasks, 0);
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(super::edit_distance(&['a', 'b', 'c'], &['a', 'c']), 1);
+        assert_eq!(
+            super::edit_distance(&['k', 'i', 't'], &['s', 'i', 't', 's']),
+            2
+        );
+        assert_eq!(super::edit_distance(&[], &['a']), 1);
+
+        let note = |name: &str, content: &str| crate::data::Note {
+            name: name.to_string(),
+            shingles: super::shingles(content),
+            ..Default::default()
+        };
+
+        let a = note("Graph Theory", "A graph consists of vertices and edges.");
+        let b = note("graph theroy", "A Graph consists of vertices, and edges!");
+        let c = note("Topology", "A graph consists of vertices and nothing else.");
+        let d = note("Short", "graph");
+
+        assert_eq!(a.title_similarity(&a), 1.);
+        assert!((a.title_similarity(&b) - 10. / 12.).abs() < 1e-9);
+        assert!(a.title_similarity(&c) < 0.5);
+
+        // case and punctuation are ignored
+        assert_eq!(a.content_similarity(&b), 1.);
+        // 4 shared shingles out of 5 + 6
+        assert_eq!(a.content_similarity(&c), 4. / 7.);
+        assert_eq!(d.shingles.len(), 1);
+        assert_eq!(a.content_similarity(&d), 0.);
+        assert_eq!(note("", "").content_similarity(&note("", "")), 0.);
+    }
 }
@@ -1,13 +1,73 @@
-use crate::{data, ui};
+use crate::{data, error, ui};
+use itertools::Itertools;
 use ratatui::{prelude::*, widgets::*};
-use std::collections::HashMap;
+use rayon::prelude::*;
+use std::{
+    cmp,
+    collections::{HashMap, HashSet},
+    time,
+};
+
+use super::filter::civil_from_days;
+
+/// The title similarity from which on two notes are considered possible duplicates.
+const DUPLICATE_TITLE_SIMILARITY: f64 = 0.8;
+/// The content similarity from which on two notes are considered possible duplicates.
+const DUPLICATE_CONTENT_SIMILARITY: f64 = 0.5;
+
+/// Describes when a note is considered important but stale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct StaleCriteria {
+    /// The minimum amount of days since the last modification of a stale note.
+    pub days: u64,
+    /// The minimum amount of links from anywhere pointing to a stale note.
+    pub inlinks: usize,
+}
+
+impl Default for StaleCriteria {
+    fn default() -> Self {
+        Self {
+            days: 180,
+            inlinks: 3,
+        }
+    }
+}
+
+/// Describes how to weigh the problems of an environment when computing its health score, see [EnvironmentStats::health_score].
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct HealthCriteria {
+    /// Weight of the share of notes with broken links.
+    pub broken: f64,
+    /// Weight of the share of notes no other note of the environment links to.
+    pub orphans: f64,
+    /// Weight of the share of notes without tags.
+    pub untagged: f64,
+    /// Weight of the share of stub notes.
+    pub stubs: f64,
+    /// Notes with less words than this are considered stubs.
+    pub stub_words: usize,
+}
+
+impl Default for HealthCriteria {
+    fn default() -> Self {
+        Self {
+            broken: 1.,
+            orphans: 1.,
+            untagged: 1.,
+            stubs: 1.,
+            stub_words: 50,
+        }
+    }
+}
 
 /// A struct describing statistics to a note in relation to a containing environment.
 #[derive(Debug, Clone)]
 pub struct NoteEnvStatistics {
     /// The notes id
     pub id: String,
-    /// The fuzzy match score of this note with the filter used to create the environment
+    /// The composite match score of this note with the filter used to create the environment, see [data::ScoreWeights]
     match_score: i64,
     /// The amount of links pointing to this note from anywhere.
     inlinks_global: usize,
@@ -20,42 +80,325 @@ pub struct NoteEnvStatistics {
     outlinks_global: usize,
     /// The amount of links originating from this note that do not have a valid target anywhere.
     broken_links: usize,
+    /// Wether the note was pinned by the user and thus contained in the environment regardless of the filter.
+    pinned: bool,
+    /// Centrality metrics of this note within the link graph of the environment. Only computed on demand, see [EnvironmentStats::compute_centrality].
+    centrality: Option<data::Centrality>,
+    /// The connected component of the environment's link graph this note belongs to. Components are numbered by descending size, starting at 0.
+    component: usize,
+    /// The amount of notes of the environment this note links to that also link back to it.
+    reciprocal_links: usize,
+    /// Notes of the environment similar to this note but not yet linked with it, with their similarity. Only computed on demand, see [EnvironmentStats::compute_related].
+    related: Option<Vec<(String, f64)>>,
 }
 
 impl NoteEnvStatistics {
-    /// Creates a new instance of NoteEnvStatistics with only the two passed fields filled out.
-    fn new_empty(id: String, match_score: i64) -> Self {
+    /// Creates a new instance of NoteEnvStatistics with only the three passed fields filled out.
+    fn new_empty(id: String, match_score: i64, pinned: bool) -> Self {
         Self {
             id,
             match_score,
+            pinned,
             inlinks_global: 0,
             inlinks_local: 0,
             outlinks_local: 0,
             outlinks_global: 0,
             broken_links: 0,
+            centrality: None,
+            component: 0,
+            reciprocal_links: 0,
+            related: None,
         }
     }
 
-    /// Converts this note to a ratatui table row with its stats
-    fn to_row(&self, index: data::NoteIndexContainer, styles: &ui::UiStyles) -> Option<Row> {
+    /// The composite match score of this note with the filter used to create the environment.
+    pub fn score(&self) -> i64 {
+        self.match_score
+    }
+
+    /// Wether this note was pinned by the user.
+    pub fn pinned(&self) -> bool {
+        self.pinned
+    }
+
+    /// The connected component of the environment's link graph this note belongs to, 0 being the largest one.
+    pub fn component(&self) -> usize {
+        self.component
+    }
+
+    /// Notes of the environment similar to this note but not yet linked with it, most similar first, if they have been computed.
+    pub fn related(&self) -> Option<&[(String, f64)]> {
+        self.related.as_deref()
+    }
+
+    /// Wether this note fulfills the given criteria for stale notes at the given point in time, i.e. has not been modified for long but is linked often.
+    /// Notes without a modification time are never stale.
+    pub fn is_stale(
+        &self,
+        note: &data::Note,
+        criteria: StaleCriteria,
+        now: time::SystemTime,
+    ) -> bool {
+        self.inlinks_global >= criteria.inlinks
+            && note
+                .modified
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age.as_secs() >= criteria.days * 24 * 60 * 60)
+    }
+
+    /// Centrality metrics of this note within the environment, if they have been computed.
+    pub fn centrality(&self) -> Option<data::Centrality> {
+        self.centrality
+    }
+
+    /// Returns the value of the given note by which to sort when sorting with the given mode.
+    /// Sorting by name is not covered by this and always returns 0.
+    /// Centrality metrics are scaled to preserve their order as integers and are 0 if they have not been computed.
+    /// Keys are signed, as match scores can be negative with negative score weights.
+    fn sort_key(&self, note: &data::Note, mode: SortingMode) -> i64 {
+        match mode {
+            // This should not appear
+            SortingMode::Name => 0,
+            // These should appear
+            SortingMode::Words => note.words as i64,
+            SortingMode::Chars => note.characters as i64,
+            SortingMode::GlobalOutLinks => self.outlinks_global as i64,
+            SortingMode::LocalOutLinks => self.outlinks_local as i64,
+            SortingMode::GlobalInLinks => self.inlinks_global as i64,
+            SortingMode::LocalInLinks => self.inlinks_local as i64,
+            SortingMode::Score => self.score(),
+            SortingMode::Broken => self.broken_links as i64,
+            SortingMode::PageRank => self.centrality_key(|c| c.pagerank),
+            SortingMode::Betweenness => self.centrality_key(|c| c.betweenness),
+            SortingMode::Degree => self.centrality_key(|c| c.degree),
+            SortingMode::Component => self.component() as i64,
+            SortingMode::Tags => note.tags.len() as i64,
+            SortingMode::Reciprocal => self.reciprocal_links as i64,
+            SortingMode::Staleness => note
+                .modified
+                .and_then(|modified| modified.elapsed().ok())
+                .map_or(0, |age| age.as_secs() as i64),
+            SortingMode::LinkDensity => (self.link_density(note, 1) * 1e12) as i64,
+            SortingMode::Modified => note
+                .modified
+                .and_then(|modified| modified.duration_since(time::UNIX_EPOCH).ok())
+                .map_or(0, |since| since.as_secs() as i64),
+        }
+    }
+
+    /// Returns the amount of valid outgoing links of the given note per the given amount of words, so long notes do not dominate by length alone.
+    /// Notes without words are treated as having a single word.
+    pub fn link_density(&self, note: &data::Note, per_words: usize) -> f64 {
+        self.outlinks_global as f64 * per_words as f64 / note.words.max(1) as f64
+    }
+
+    /// Converts a centrality metric in [0, 1] to a sort key.
+    fn centrality_key(&self, metric: impl Fn(data::Centrality) -> f64) -> i64 {
+        self.centrality()
+            .map(|centrality| (metric(centrality) * 1e12) as i64)
+            .unwrap_or(0)
+    }
+
+    /// Converts this note to a ratatui table row with its name and the given columns of its stats, estimating reading times with the given reading speed and marking the note if it fulfills the given criteria for stale notes.
+    fn to_row(
+        &self,
+        index: data::NoteIndexContainer,
+        columns: &[data::Column],
+        styles: &ui::UiStyles,
+        words_per_minute: usize,
+        stale: StaleCriteria,
+    ) -> Option<Row> {
+        let now = time::SystemTime::now();
         // generate the stats row for each element
         index.borrow().get(&self.id).map(|note| {
-            Row::new(vec![
-                note.name.clone(),
-                format!("{:7}", note.words),
-                format!("{:7}", note.characters),
-                format!("{:7}", self.outlinks_global),
-                format!("{:7}", self.outlinks_local),
-                format!("{:7}", self.inlinks_global),
-                format!("{:7}", self.inlinks_local),
-            ])
-            .style(styles.text_style)
+            Row::new(
+                std::iter::once(if self.is_stale(note, stale, now) {
+                    format!("{} (stale)", note.title())
+                } else {
+                    note.title().to_string()
+                })
+                .chain(columns.iter().map(|column| {
+                    match column {
+                        data::Column::Words => format!("{:7}", note.words),
+                        data::Column::Chars => format!("{:7}", note.characters),
+                        data::Column::GlobalOutLinks => format!("{:7}", self.outlinks_global),
+                        data::Column::LocalOutLinks => format!("{:7}", self.outlinks_local),
+                        data::Column::GlobalInLinks => format!("{:7}", self.inlinks_global),
+                        data::Column::LocalInLinks => format!("{:7}", self.inlinks_local),
+                        data::Column::Broken => format!("{:7}", self.broken_links),
+                        data::Column::Modified => note
+                            .modified
+                            .and_then(|modified| modified.duration_since(time::UNIX_EPOCH).ok())
+                            .map(|since| {
+                                let (year, month, day) =
+                                    civil_from_days((since.as_secs() / (24 * 60 * 60)) as i64);
+                                format!("{year:04}-{month:02}-{day:02}")
+                            })
+                            .unwrap_or_default(),
+                        data::Column::Reading => {
+                            format!("{:4} min", note.reading_minutes(words_per_minute))
+                        }
+                    }
+                })),
+            )
+            .style(if self.pinned {
+                styles.pinned_style
+            } else {
+                styles.text_style
+            })
         })
     }
 }
 
+/// The statistics of a single note as written when exporting an environment.
+#[derive(Debug, Clone, serde::Serialize)]
+struct NoteExport<'a> {
+    id: &'a str,
+    name: &'a str,
+    title: &'a str,
+    path: &'a std::path::Path,
+    words: usize,
+    characters: usize,
+    tags: &'a [String],
+    outlinks_global: usize,
+    outlinks_local: usize,
+    inlinks_global: usize,
+    inlinks_local: usize,
+    broken_links: usize,
+    open_tasks: usize,
+    done_tasks: usize,
+    /// Seconds since the unix epoch.
+    created: Option<u64>,
+    /// Seconds since the unix epoch.
+    modified: Option<u64>,
+    /// Only exported to JSON, as the fields differ between notes.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    frontmatter: &'a HashMap<String, String>,
+}
+
+impl<'a> NoteExport<'a> {
+    /// The names of the columns when exporting to CSV, in the order of the fields.
+    const CSV_HEADER: &'static str = "id,name,title,path,words,characters,tags,outlinks_global,outlinks_local,inlinks_global,inlinks_local,broken_links,open_tasks,done_tasks,created,modified";
+
+    /// Collects the statistics of the given note and its statistics within an environment.
+    fn new(env_stats: &'a NoteEnvStatistics, note: &'a data::Note) -> Self {
+        let unix = |time: Option<time::SystemTime>| {
+            time.and_then(|time| time.duration_since(time::UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs())
+        };
+        Self {
+            id: &env_stats.id,
+            name: &note.name,
+            title: note.title(),
+            path: &note.path,
+            words: note.words,
+            characters: note.characters,
+            tags: &note.tags,
+            outlinks_global: env_stats.outlinks_global,
+            outlinks_local: env_stats.outlinks_local,
+            inlinks_global: env_stats.inlinks_global,
+            inlinks_local: env_stats.inlinks_local,
+            broken_links: env_stats.broken_links,
+            open_tasks: note.open_tasks,
+            done_tasks: note.done_tasks,
+            created: unix(note.created),
+            modified: unix(note.modified),
+            frontmatter: &note.frontmatter,
+        }
+    }
+
+    /// Converts these statistics to a line of CSV, quoting text fields as necessary.
+    fn to_csv(&self) -> String {
+        let optional = |value: Option<u64>| value.map(|v| v.to_string()).unwrap_or_default();
+        [
+            csv_field(self.id),
+            csv_field(self.name),
+            csv_field(self.title),
+            csv_field(&self.path.to_string_lossy()),
+            self.words.to_string(),
+            self.characters.to_string(),
+            csv_field(&self.tags.join(" ")),
+            self.outlinks_global.to_string(),
+            self.outlinks_local.to_string(),
+            self.inlinks_global.to_string(),
+            self.inlinks_local.to_string(),
+            self.broken_links.to_string(),
+            self.open_tasks.to_string(),
+            self.done_tasks.to_string(),
+            optional(self.created),
+            optional(self.modified),
+        ]
+        .join(",")
+    }
+}
+
+/// The statistics of a whole environment as written when exporting it.
+#[derive(Debug, Clone, serde::Serialize)]
+struct EnvironmentExport<'a> {
+    notes_total: usize,
+    words_total: usize,
+    characters_total: usize,
+    tags_total: usize,
+    local_local_links: usize,
+    local_global_links: usize,
+    global_local_links: usize,
+    broken_links: usize,
+    notes: Vec<NoteExport<'a>>,
+}
+
+/// The file formats statistics can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A JSON object containing the totals of the environment and a list of all notes.
+    Json,
+    /// A table of all notes with one line per note.
+    Csv,
+    /// The link graph between all notes in the DOT language.
+    Dot,
+    /// The link graph between all notes in GraphML.
+    GraphMl,
+}
+
+impl ExportFormat {
+    /// The file extension of files in this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Dot => "dot",
+            Self::GraphMl => "graphml",
+        }
+    }
+}
+
+/// The effects renaming a tag in every note of the vault would have, see [EnvironmentStats::tag_rename_impact].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TagRenameImpact {
+    /// Every tag that would be renamed, i.e. the tag itself and its subtags, with its new name and the amount of notes containing it, sorted.
+    pub renamed: Vec<(String, String, usize)>,
+    /// The ids of all notes containing any of the renamed tags, sorted.
+    pub notes: Vec<String>,
+    /// The new names already in use as tags, with the amount of notes already containing them, sorted.
+    pub collisions: Vec<(String, usize)>,
+    /// The ids of all notes already containing the new name of one of their renamed tags, which would then contain it twice, sorted.
+    pub merged_notes: Vec<String>,
+    /// The top-level tags whose environments would gain or lose notes, with the amount of notes gained and lost, sorted.
+    pub environments: Vec<(String, usize, usize)>,
+}
+
+/// The activity within an environment during a single period of time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GrowthPeriod {
+    /// The amount of notes created during the period.
+    pub created: usize,
+    /// The amount of words of the notes created during the period.
+    pub words: usize,
+    /// The amount of notes last modified during the period.
+    pub modified: usize,
+}
+
 /// Describes the current sorting mode of the displayed list.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
 pub enum SortingMode {
     #[default]
     Name,
@@ -67,6 +410,47 @@ pub enum SortingMode {
     LocalInLinks,
     Score,
     Broken,
+    PageRank,
+    Betweenness,
+    Degree,
+    Component,
+    Tags,
+    Reciprocal,
+    Staleness,
+    LinkDensity,
+    Modified,
+}
+
+impl SortingMode {
+    /// Parses a sorting mode from its name as used in `sort:` directives of a filter string.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "name" => Some(Self::Name),
+            "words" => Some(Self::Words),
+            "chars" => Some(Self::Chars),
+            "outlinks" => Some(Self::GlobalOutLinks),
+            "local-outlinks" => Some(Self::LocalOutLinks),
+            "inlinks" => Some(Self::GlobalInLinks),
+            "local-inlinks" => Some(Self::LocalInLinks),
+            "score" => Some(Self::Score),
+            "broken" => Some(Self::Broken),
+            "pagerank" => Some(Self::PageRank),
+            "betweenness" => Some(Self::Betweenness),
+            "degree" => Some(Self::Degree),
+            "component" => Some(Self::Component),
+            "tags" => Some(Self::Tags),
+            "reciprocal" => Some(Self::Reciprocal),
+            "stale" => Some(Self::Staleness),
+            "density" => Some(Self::LinkDensity),
+            "modified" => Some(Self::Modified),
+            _ => None,
+        }
+    }
+
+    /// Wether sorting by this mode requires the centrality metrics of the environment to be computed.
+    pub fn needs_centrality(&self) -> bool {
+        matches!(self, Self::PageRank | Self::Betweenness | Self::Degree)
+    }
 }
 
 /// A data struct containing statistical information about a (subset of a) user's notes.
@@ -76,12 +460,23 @@ pub struct EnvironmentStats {
     /// The total amount of words in the notes in this environment.
     /// What is a word and what not mirrors the definition from Note.words.
     word_count_total: usize,
+    /// The word counts of all notes in this environment, sorted ascendingly.
+    word_counts: Vec<usize>,
+    /// Creation time, modification time and word count of all notes in this environment.
+    /// Notes without a creation time are treated as created at their modification time.
+    note_times: Vec<(Option<time::SystemTime>, Option<time::SystemTime>, usize)>,
     /// The total amount of characters, including whitespace, in the notes of this environment.
     char_count_total: usize,
     /// The total amount of notes in this environment.
     note_count_total: usize,
     /// The total amount of _unique_ tags in this environment.
     tag_count_total: usize,
+    /// How many notes of this environment each tag occurs in.
+    tag_counts: HashMap<String, usize>,
+    /// The amount of notes of the environment containing both tags of each pair of tags that occur together, the lexicographically smaller tag first.
+    tag_pairs: HashMap<(String, String), usize>,
+    /// The amount of notes of the environment that link to each external URL.
+    external_link_counts: HashMap<String, usize>,
     /// Total amount of links from a note within the environment to another note within the environment.
     local_local_links: usize,
     /// Total amount of links from a note within the environment to any note.
@@ -92,68 +487,284 @@ pub struct EnvironmentStats {
     filtered_stats: Vec<NoteEnvStatistics>,
     /// Counts how many links among notes within the environment do not have a valid target anywhere.
     broken_links: usize,
+    /// The source id and missing target of every link among notes within the environment that does not have a valid target anywhere, sorted.
+    broken_link_targets: Vec<(String, String)>,
+    /// The source id and reference of every reference from a note of the environment to an attachment that does not exist, sorted.
+    missing_attachments: Vec<(String, String)>,
+    /// The paths, relative to the vault, of all attachments in the vault that no note anywhere references, sorted.
+    unreferenced_attachments: Vec<String>,
+    /// The filter used to create this environment, kept to update the environment when notes change.
+    filter: data::Filter,
+    /// The sorting modes and directions requested by the filter used to create this environment, most important first.
+    sort_directives: Vec<(SortingMode, bool)>,
+    /// The amount of notes in the environment without any tags.
+    untagged: usize,
+    /// The total amount of headings of each level in the notes of the environment, starting with level 1.
+    heading_counts: [usize; 6],
+    /// The ids of all notes in the environment without any headings, sorted.
+    without_headings: Vec<String>,
+    /// The ids of all notes in the environment that skip heading levels, sorted.
+    skipping_headings: Vec<String>,
+    /// The ids of all notes in the environment that no other note of the environment links to, sorted.
+    orphans: Vec<String>,
+    /// The ids of all notes in the environment that do not link to any other note of the environment, sorted.
+    sinks: Vec<String>,
+    /// The sizes of the connected components of the environment's link graph, largest first.
+    component_sizes: Vec<usize>,
+    /// The amount of pairs of notes of the environment linking to each other.
+    reciprocal_pairs: usize,
+    /// The amount of embeds of existing notes in notes of the environment.
+    embeds: usize,
+    /// The amount of notes of the environment embedded in any note of the vault.
+    embedded_notes: usize,
+    /// The amount of links between notes of the environment whose target does not link back, counting every pair of notes once.
+    one_way_links: usize,
+    /// The diameter and average shortest path length of the largest component. Only computed on demand, see [Self::compute_distances].
+    distances: Option<(usize, f64)>,
+    /// The strongly connected components of the environment's link graph with more than one note, i.e. notes linked in cycles. Only computed on demand, see [Self::compute_cycles].
+    cycles: Option<Vec<Vec<String>>>,
+    /// Pairs of notes with very similar titles or contents, with their title and content similarity. Only computed on demand, see [Self::compute_duplicates].
+    duplicates: Option<Vec<(String, String, f64, f64)>>,
 }
 
 impl EnvironmentStats {
     /// Creates a new set of statistics from the subset of the passed index that matches the given filter.
     pub fn new_with_filter(index: &super::NoteIndexContainer, filter: data::Filter) -> Self {
+        Self::new_with_filter_and_pinned(index, filter, &data::PinnedNotes::default(), None)
+    }
+
+    /// Creates a new set of statistics from the subset of the passed index that matches the given filter or is pinned.
+    /// If candidates are given, only notes with these ids are checked against the filter, e.g. because they are known to be a superset of the matching notes.
+    pub fn new_with_filter_and_pinned(
+        index: &super::NoteIndexContainer,
+        filter: data::Filter,
+        pinned: &data::PinnedNotes,
+        candidates: Option<&HashSet<String>>,
+    ) -> Self {
         let index = index.borrow();
+        let index = &*index;
 
-        // Filter the index -> Create an iterator
-        let mut filtered_index = index
-            .inner
-            .iter()
-            .filter_map(|(id, note)| {
-                filter.apply(note, &index).map(|score| {
+        // Filter the index in parallel
+        let matches = Self::candidate_notes(index, candidates)
+            .filter_map(|(id, note)| Self::match_note(index, &filter, pinned, id, note))
+            .collect();
+
+        Self::from_matches(index, filter, matches)
+    }
+
+    /// Updates the statistics after the notes with the given ids were created, modified or deleted in the index.
+    /// This only saves applying the filter: Only the changed notes are checked against it again, all other notes keep their match score, unless the filter depends on other notes.
+    /// All per-note link counts and aggregates are then recalculated from the matching notes like when creating new statistics, as a change to one note, e.g. to its aliases, can change how the links of any other note resolve.
+    /// Updating thus costs about as much as creating new statistics with an already applied filter.
+    /// Metrics only computed on demand are reset and the notes are sorted by match score again.
+    /// The unchanged notes also keep their pinned state, while changed notes are checked against the given pinned notes.
+    /// Statistics created from a restricted set of candidates are not restricted to them after an update.
+    pub fn update_notes(
+        &mut self,
+        index: &super::NoteIndexContainer,
+        pinned: &data::PinnedNotes,
+        changed: &HashSet<String>,
+    ) {
+        let mut filter = std::mem::take(&mut self.filter);
+        // links may have changed since the filter was last applied
+        filter.forget_index();
+        let index = index.borrow();
+        let index = &*index;
+
+        let matches = if filter.depends_on_other_notes() {
+            // Changes in any note may change which notes match, so check all of them.
+            Self::candidate_notes(index, None)
+                .filter_map(|(id, note)| Self::match_note(index, &filter, pinned, id, note))
+                .collect()
+        } else {
+            // Keep the unchanged notes that still exist, then check the changed ones.
+            let mut matches = self
+                .filtered_stats
+                .iter()
+                .filter(|env_stats| {
+                    !changed.contains(&env_stats.id) && index.inner.contains_key(&env_stats.id)
+                })
+                .map(|env_stats| {
+                    (
+                        env_stats.id.clone(),
+                        (env_stats.score(), env_stats.pinned()),
+                    )
+                })
+                .collect::<HashMap<_, _>>();
+            matches.par_extend(
+                Self::candidate_notes(index, Some(changed))
+                    .filter_map(|(id, note)| Self::match_note(index, &filter, pinned, id, note)),
+            );
+            matches
+        };
+
+        *self = Self::from_matches(index, filter, matches);
+    }
+
+    /// Checks a single note against the filter, returning its id, match score and wether it is pinned if it belongs to the environment.
+    fn match_note(
+        index: &data::NoteIndex,
+        filter: &data::Filter,
+        pinned: &data::PinnedNotes,
+        id: &str,
+        note: &data::Note,
+    ) -> Option<(String, (i64, bool))> {
+        let is_pinned = pinned.contains(id);
+        filter
+            .apply(note, index)
+            // pinned notes are always contained
+            .or_else(|| is_pinned.then_some(0))
+            .map(|score| (id.to_string(), (score, is_pinned)))
+    }
+
+    /// Calculates the statistics of the environment made up of the given notes, given by their id, match score and wether they are pinned.
+    fn from_matches(
+        index: &data::NoteIndex,
+        filter: data::Filter,
+        matches: HashMap<String, (i64, bool)>,
+    ) -> Self {
+        let sort_directives = filter.sorting.clone();
+
+        let mut filtered_index = matches
+            .into_iter()
+            .filter_map(|(id, (score, is_pinned))| {
+                index.inner.get(&id).map(|note| {
                     (
                         id.clone(),
-                        (NoteEnvStatistics::new_empty(id.clone(), score), note),
+                        (NoteEnvStatistics::new_empty(id, score, is_pinned), note),
                     )
                 })
             })
             .collect::<HashMap<_, _>>();
 
-        // Count links by iterating over unfiltered index
-        for (id, note) in index.inner.iter() {
-            // Remember if source is from withing the environment.
-            let local_source = filtered_index.contains_key(id);
-            // Keep track of found local targets.
-            let mut local_targets = 0;
-            // Keep track of found targets.
-            let mut global_targets = 0;
-
-            // Then go over its links.
-            for link in &note.links {
-                // Check if target exists
-                if index.inner.contains_key(link) {
-                    // and increase count of valid targets if so.
-                    global_targets += 1;
-
-                    // Now check if target is local.
-                    if let Some((target, _)) = filtered_index.get_mut(link) {
-                        // Always count up global inlink count of target.
-                        target.inlinks_global += 1;
-                        // If id of source is also in filtered index, also count up local inlink count of target.
-                        if local_source {
-                            target.inlinks_local += 1;
+        // Check the links of every note of the unfiltered index in parallel, remembering the targets within the environment and broken links from local sources.
+        let link_checks = index
+            .inner
+            .par_iter()
+            .map(|(id, note)| {
+                // Remember if source is from withing the environment.
+                let local_source = filtered_index.contains_key(id);
+                // Keep track of found targets.
+                let mut global_targets = 0;
+                // Keep track of found local targets.
+                let mut local_targets = Vec::new();
+                // Keep track of broken links.
+                let mut broken = Vec::new();
+
+                // Links to aliases count as links to the notes declaring them.
+                for link in note.links.iter().map(|link| index.resolve_link(note, link)) {
+                    // Check if target exists
+                    if index.inner.contains_key(link) {
+                        // and increase count of valid targets if so.
+                        global_targets += 1;
+                        // Now check if target is local.
+                        if filtered_index.contains_key(link) {
+                            local_targets.push(link);
                         }
-                        // Since this target was in the environment, increment the counter.
-                        local_targets += 1;
+                    } else if local_source {
+                        // Remember broken links of local sources.
+                        broken.push((id.clone(), link.to_string()));
+                    }
+                }
+                (
+                    id,
+                    note,
+                    local_source,
+                    global_targets,
+                    local_targets,
+                    broken,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // Remember broken links from local sources
+        let mut broken_link_targets = Vec::new();
+
+        // Add up the link counts found above.
+        for (id, note, local_source, global_targets, local_targets, broken) in link_checks {
+            for link in &local_targets {
+                if let Some((target, _)) = filtered_index.get_mut(*link) {
+                    // Always count up global inlink count of target.
+                    target.inlinks_global += 1;
+                    // If id of source is also in filtered index, also count up local inlink count of target.
+                    if local_source {
+                        target.inlinks_local += 1;
                     }
                 }
             }
+            broken_link_targets.extend(broken);
             // If source was local, we are interested in its stats.
             // Add the found local/global targets to the statistics (this could be an assignment).
             if let Some((source, _)) = filtered_index.get_mut(id) {
-                source.outlinks_local += local_targets;
+                source.outlinks_local += local_targets.len();
                 source.outlinks_global += global_targets;
-                source.broken_links = note.links.len() - global_targets;
+                source.broken_links = index.broken_links_count(note);
+            }
+        }
+
+        // Connected components of the local link graph
+        let graph = super::graph::LinkGraph::new(&index, filtered_index.keys());
+        let (component_sizes, components) = graph.components();
+        for (id, component) in components {
+            if let Some((env_stats, _)) = filtered_index.get_mut(&id) {
+                env_stats.component = component;
             }
         }
 
+        // Reciprocity of the local link graph
+        let mut reciprocal_links_total = 0;
+        for (id, reciprocal_links) in graph.reciprocal_links() {
+            if let Some((env_stats, _)) = filtered_index.get_mut(&id) {
+                env_stats.reciprocal_links = reciprocal_links;
+                reciprocal_links_total += reciprocal_links;
+            }
+        }
+        let links_total = graph.outgoing.iter().map(Vec::len).sum::<usize>();
+
+        broken_link_targets.sort();
+
+        // Embeds: Kept separately from links
+        let embedded = index
+            .inner
+            .values()
+            .flat_map(|note| {
+                note.embeds
+                    .iter()
+                    .map(move |embed| index.resolve_link(note, embed))
+            })
+            .collect::<HashSet<_>>();
+
+        // Attachments: References from local notes to missing attachments, and unreferenced attachments of the whole vault.
+        let missing_attachments = filtered_index
+            .iter()
+            .flat_map(|(id, (_, note))| {
+                index
+                    .missing_attachments(note)
+                    .into_iter()
+                    .map(|reference| (id.clone(), reference.to_string()))
+            })
+            .sorted()
+            .collect();
+        let unreferenced_attachments = index
+            .unreferenced_attachments()
+            .into_iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+
         Self {
             // Word count: Just map over the stats.
             word_count_total: filtered_index.values().map(|(_, stats)| stats.words).sum(),
+            // Word counts: Collect and sort for the distribution.
+            word_counts: filtered_index
+                .values()
+                .map(|(_, stats)| stats.words)
+                .sorted()
+                .collect(),
+            // Times: Just remember them.
+            note_times: filtered_index
+                .values()
+                .map(|(_, note)| (note.created.or(note.modified), note.modified, note.words))
+                .collect(),
             // Char count: Just map over the stats
             char_count_total: filtered_index
                 .values()
@@ -167,6 +778,31 @@ impl EnvironmentStats {
                 .flat_map(|(_, stats)| &stats.tags)
                 .collect::<std::collections::HashSet<_>>()
                 .len(),
+            // Tag counts: Count every (deduplicated) tag of every note.
+            tag_counts: filtered_index
+                .values()
+                .flat_map(|(_, stats)| stats.tags.iter().unique())
+                .cloned()
+                .counts(),
+            // Tag pairs: Count every pair of (deduplicated) tags of every note.
+            tag_pairs: filtered_index
+                .values()
+                .flat_map(|(_, stats)| {
+                    stats
+                        .tags
+                        .iter()
+                        .unique()
+                        .sorted()
+                        .tuple_combinations()
+                        .map(|(a, b)| (a.clone(), b.clone()))
+                })
+                .counts(),
+            // External link counts: Count every (deduplicated) external link of every note.
+            external_link_counts: filtered_index
+                .values()
+                .flat_map(|(_, note)| note.external_links.iter().unique())
+                .cloned()
+                .counts(),
             // Local-Local links: Check outgoing local links of all notes. Could also check incoming local links of all notes.
             local_local_links: filtered_index
                 .values()
@@ -187,6 +823,66 @@ impl EnvironmentStats {
                 .values()
                 .map(|(env_stats, _)| env_stats.broken_links)
                 .sum(),
+            broken_link_targets,
+            missing_attachments,
+            unreferenced_attachments,
+            sort_directives,
+            filter,
+            // Untagged: Notes without tags.
+            untagged: filtered_index
+                .values()
+                .filter(|(_, note)| note.tags.is_empty())
+                .count(),
+            // Heading counts: Sum up the counts of every level.
+            heading_counts: filtered_index
+                .values()
+                .fold([0; 6], |mut counts, (_, note)| {
+                    for (count, note_count) in counts.iter_mut().zip(note.headings) {
+                        *count += note_count;
+                    }
+                    counts
+                }),
+            // Heading structure: Notes without headings or with skipped levels.
+            without_headings: filtered_index
+                .iter()
+                .filter(|(_, (_, note))| note.headings.iter().all(|&count| count == 0))
+                .map(|(id, _)| id.clone())
+                .sorted()
+                .collect(),
+            skipping_headings: filtered_index
+                .iter()
+                .filter(|(_, (_, note))| note.skips_heading_levels)
+                .map(|(id, _)| id.clone())
+                .sorted()
+                .collect(),
+            // Orphans and sinks: Notes without local inlinks or outlinks.
+            orphans: filtered_index
+                .iter()
+                .filter(|(_, (env_stats, _))| env_stats.inlinks_local == 0)
+                .map(|(id, _)| id.clone())
+                .sorted()
+                .collect(),
+            sinks: filtered_index
+                .iter()
+                .filter(|(_, (env_stats, _))| env_stats.outlinks_local == 0)
+                .map(|(id, _)| id.clone())
+                .sorted()
+                .collect(),
+            component_sizes,
+            // Every reciprocal pair was counted from both sides.
+            reciprocal_pairs: reciprocal_links_total / 2,
+            embeds: filtered_index
+                .keys()
+                .map(|id| index.embeds_vec(id).len())
+                .sum(),
+            embedded_notes: filtered_index
+                .keys()
+                .filter(|id| embedded.contains(id.as_str()))
+                .count(),
+            one_way_links: links_total - reciprocal_links_total,
+            distances: None,
+            cycles: None,
+            duplicates: None,
             // Finally, reduce the vector to just the env stats
             filtered_stats: {
                 let mut fs = filtered_index
@@ -194,22 +890,383 @@ impl EnvironmentStats {
                     .map(|(env_stats, _)| env_stats)
                     .collect::<Vec<_>>();
 
-                // Default sort: By match score, descending.
-                fs.sort_by_cached_key(|env_stats| env_stats.match_score);
-                fs.reverse();
+                // Default sort: By match score, descending, and by id for equal scores to not depend on the order of the parallel passes.
+                fs.sort_by(|a, b| b.score().cmp(&a.score()).then_with(|| a.id.cmp(&b.id)));
+                // Pinned notes always come first.
+                fs.sort_by_key(|env_stats| !env_stats.pinned);
 
                 fs
             },
         }
     }
 
+    /// Creates a set of statistics for every top-level tag of the index, i.e. the part of a tag before the first `/`, sorted by tag.
+    /// The notes of every environment are gathered in a single pass over the index, each environment containing all notes tagged with the top-level tag or any of its subtags.
+    pub fn per_top_level_tag(index: &super::NoteIndexContainer) -> Vec<(String, Self)> {
+        let mut groups: HashMap<String, HashSet<String>> = HashMap::new();
+        for (id, note) in index.borrow().inner.iter() {
+            for tag in &note.tags {
+                let top_level = tag.split('/').next().unwrap_or(tag);
+                groups
+                    .entry(top_level.to_string())
+                    .or_default()
+                    .insert(id.clone());
+            }
+        }
+
+        groups
+            .into_iter()
+            .sorted_by(|(tag_a, _), (tag_b, _)| tag_a.cmp(tag_b))
+            .map(|(tag, ids)| {
+                let stats = Self::new_with_filter_and_pinned(
+                    index,
+                    data::Filter::default(),
+                    &data::PinnedNotes::default(),
+                    Some(&ids),
+                );
+                (tag, stats)
+            })
+            .collect()
+    }
+
+    /// Creates a set of statistics for every detected note language, sorted by language.
+    /// Notes whose language could not be detected are grouped under `unknown`.
+    pub fn per_language(index: &super::NoteIndexContainer) -> Vec<(String, Self)> {
+        let mut groups: HashMap<String, HashSet<String>> = HashMap::new();
+        for (id, note) in index.borrow().inner.iter() {
+            groups
+                .entry(
+                    note.language
+                        .clone()
+                        .unwrap_or_else(|| String::from("unknown")),
+                )
+                .or_default()
+                .insert(id.clone());
+        }
+
+        groups
+            .into_iter()
+            .sorted_by(|(lang_a, _), (lang_b, _)| lang_a.cmp(lang_b))
+            .map(|(language, ids)| {
+                let stats = Self::new_with_filter_and_pinned(
+                    index,
+                    data::Filter::default(),
+                    &data::PinnedNotes::default(),
+                    Some(&ids),
+                );
+                (language, stats)
+            })
+            .collect()
+    }
+
+    /// Previews renaming the tag `from` and all its subtags to `to` in every note of the index, without changing any notes.
+    /// Both tags may be given with or without their leading hash.
+    pub fn tag_rename_impact(index: &data::NoteIndex, from: &str, to: &str) -> TagRenameImpact {
+        let from = format!("#{}", from.trim_start_matches('#'));
+        let to = format!("#{}", to.trim_start_matches('#'));
+
+        // The new name of a tag, if it is renamed.
+        let rename = |tag: &str| {
+            tag.strip_prefix(&from)
+                .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+                .map(|rest| format!("{to}{rest}"))
+        };
+        // The top-level tags of a set of tags, as used by [Self::per_top_level_tag].
+        let top_levels = |tags: &[String]| {
+            tags.iter()
+                .map(|tag| tag.split('/').next().unwrap_or(tag).to_string())
+                .collect::<HashSet<_>>()
+        };
+
+        let mut impact = TagRenameImpact::default();
+        let mut renamed = HashMap::<(String, String), usize>::new();
+        let mut gained = HashMap::<String, usize>::new();
+        let mut lost = HashMap::<String, usize>::new();
+
+        for (id, note) in index.inner.iter() {
+            let tags = note.tags.iter().unique().cloned().collect::<Vec<_>>();
+            let new_tags = tags
+                .iter()
+                .map(|tag| rename(tag).unwrap_or_else(|| tag.clone()))
+                .collect::<Vec<_>>();
+            if tags == new_tags {
+                continue;
+            }
+            impact.notes.push(id.clone());
+
+            for tag in &tags {
+                if let Some(new_tag) = rename(tag) {
+                    if tags.contains(&new_tag) && !impact.merged_notes.contains(id) {
+                        impact.merged_notes.push(id.clone());
+                    }
+                    *renamed.entry((tag.clone(), new_tag)).or_default() += 1;
+                }
+            }
+
+            let (old_top, new_top) = (top_levels(&tags), top_levels(&new_tags));
+            for top in new_top.difference(&old_top) {
+                *gained.entry(top.clone()).or_default() += 1;
+            }
+            for top in old_top.difference(&new_top) {
+                *lost.entry(top.clone()).or_default() += 1;
+            }
+        }
+
+        // Collisions: New names already used by notes.
+        let new_names = renamed
+            .keys()
+            .map(|(_, new_tag)| new_tag.as_str())
+            .collect::<HashSet<_>>();
+        impact.collisions = index
+            .inner
+            .values()
+            .flat_map(|note| note.tags.iter().unique())
+            .filter(|tag| new_names.contains(tag.as_str()))
+            .cloned()
+            .counts()
+            .into_iter()
+            .sorted()
+            .collect();
+
+        impact.renamed = renamed
+            .into_iter()
+            .map(|((tag, new_tag), count)| (tag, new_tag, count))
+            .sorted()
+            .collect();
+        impact.environments = gained
+            .keys()
+            .chain(lost.keys())
+            .unique()
+            .map(|top| {
+                (
+                    top.clone(),
+                    gained.get(top).copied().unwrap_or_default(),
+                    lost.get(top).copied().unwrap_or_default(),
+                )
+            })
+            .sorted()
+            .collect();
+        impact.notes.sort();
+        impact.merged_notes.sort();
+
+        impact
+    }
+
+    /// Creates a set of statistics for every folder of the vault containing notes, sorted by folder.
+    /// Notes are grouped by the first `depth` folders of their path relative to the vault, so notes in deeper subfolders count towards their ancestor.
+    /// Notes directly in the vault, or all notes if `depth` is zero, are grouped under `/`.
+    pub fn per_directory(index: &super::NoteIndexContainer, depth: usize) -> Vec<(String, Self)> {
+        let mut groups: HashMap<String, HashSet<String>> = HashMap::new();
+        let index_ref = index.borrow();
+        for (id, note) in index_ref.inner.iter() {
+            let folder = index_ref
+                .relative_path(note)
+                .parent()
+                .map(|parent| {
+                    parent
+                        .components()
+                        .take(depth)
+                        .map(|component| component.as_os_str().to_string_lossy())
+                        .join("/")
+                })
+                .filter(|folder| !folder.is_empty())
+                .unwrap_or_else(|| String::from("/"));
+            groups.entry(folder).or_default().insert(id.clone());
+        }
+        drop(index_ref);
+
+        groups
+            .into_iter()
+            .sorted_by(|(folder_a, _), (folder_b, _)| folder_a.cmp(folder_b))
+            .map(|(folder, ids)| {
+                let stats = Self::new_with_filter_and_pinned(
+                    index,
+                    data::Filter::default(),
+                    &data::PinnedNotes::default(),
+                    Some(&ids),
+                );
+                (folder, stats)
+            })
+            .collect()
+    }
+
+    /// Counts the notes of the index matching the given filter or being pinned, without calculating any statistics.
+    /// If candidates are given, only notes with these ids are checked.
+    pub fn count_matches(
+        index: &super::NoteIndexContainer,
+        filter: &data::Filter,
+        pinned: &data::PinnedNotes,
+        candidates: Option<&HashSet<String>>,
+    ) -> usize {
+        let index = index.borrow();
+        let index = &*index;
+        Self::candidate_notes(index, candidates)
+            .filter(|(id, note)| pinned.contains(id) || filter.apply(note, index).is_some())
+            .count()
+    }
+
+    /// Returns a parallel iterator over the notes of the index with the given ids, or all notes if no ids are given.
+    fn candidate_notes<'a>(
+        index: &'a data::NoteIndex,
+        candidates: Option<&'a HashSet<String>>,
+    ) -> impl ParallelIterator<Item = (&'a String, &'a data::Note)> + 'a {
+        match candidates {
+            Some(candidates) => rayon::iter::Either::Left(
+                candidates
+                    .par_iter()
+                    .filter_map(|id| index.inner.get_key_value(id)),
+            ),
+            None => rayon::iter::Either::Right(index.inner.par_iter()),
+        }
+    }
+
+    /// Computes the streak statistics of the daily notes of this environment, recognized by the given pattern.
+    pub fn daily_streak(
+        &self,
+        index: &data::NoteIndex,
+        pattern: &data::DailyNotePattern,
+    ) -> data::DailyStreak {
+        data::DailyStreak::new(
+            self.filtered_stats
+                .iter()
+                .filter_map(|env_stats| index.get(&env_stats.id))
+                .filter_map(|note| pattern.date(&note.path.file_stem()?.to_string_lossy())),
+            data::DailyStreak::today(),
+        )
+    }
+
     /// Returns the nth element of the underlying sorted vector
     pub fn get_selected(&self, index: usize) -> Option<&NoteEnvStatistics> {
         self.filtered_stats.get(index)
     }
 
+    /// Computes PageRank, betweenness and degree centrality of all notes within the link graph of this environment, unless already done.
+    /// As betweenness takes quadratic time in the size of the environment, this only happens when sorting by one of these metrics.
+    pub fn compute_centrality(&mut self, index: &data::NoteIndex) {
+        if self
+            .filtered_stats
+            .iter()
+            .all(|env_stats| env_stats.centrality.is_some())
+        {
+            return;
+        }
+
+        let graph = super::graph::LinkGraph::new(
+            index,
+            self.filtered_stats.iter().map(|env_stats| &env_stats.id),
+        );
+        let mut centrality = graph.centrality();
+        for env_stats in self.filtered_stats.iter_mut() {
+            env_stats.centrality = Some(centrality.remove(&env_stats.id).unwrap_or_default());
+        }
+    }
+
+    /// Computes up to the given amount of related notes within this environment for every note of this environment, unless already done.
+    /// As this compares every pair of notes, it only happens on demand.
+    pub fn compute_related(&mut self, index: &data::NoteIndex, amount: usize) {
+        let ids = self.ids();
+        for env_stats in self.filtered_stats.iter_mut() {
+            if env_stats.related.is_none() {
+                env_stats.related = Some(index.related_notes(&env_stats.id, &ids, amount));
+            }
+        }
+    }
+
+    /// Computes the diameter and average shortest path length of the largest connected component of this environment, unless already done.
+    /// As this takes quadratic time in the size of the component, it only happens on demand.
+    pub fn compute_distances(&mut self, index: &data::NoteIndex) {
+        if self.distances.is_none() {
+            self.distances = super::graph::LinkGraph::new(
+                index,
+                self.filtered_stats.iter().map(|env_stats| &env_stats.id),
+            )
+            .distances();
+        }
+    }
+
+    /// Computes the groups of notes of this environment that are linked in cycles, unless already done.
+    pub fn compute_cycles(&mut self, index: &data::NoteIndex) {
+        if self.cycles.is_none() {
+            self.cycles = Some(
+                super::graph::LinkGraph::new(
+                    index,
+                    self.filtered_stats.iter().map(|env_stats| &env_stats.id),
+                )
+                .cycles(),
+            );
+        }
+    }
+
+    /// Returns the groups of notes of this environment that are linked in cycles, largest first, if they have been computed.
+    /// Within every group, each note can be reached from every other note by following links.
+    pub fn cycles(&self) -> Option<&[Vec<String>]> {
+        self.cycles.as_deref()
+    }
+
+    /// Computes the pairs of notes of this environment that are possibly duplicates of each other, unless already done.
+    /// As this compares every pair of notes, it only happens on demand.
+    pub fn compute_duplicates(&mut self, index: &data::NoteIndex) {
+        if self.duplicates.is_some() {
+            return;
+        }
+
+        let notes = self
+            .filtered_stats
+            .iter()
+            .filter_map(|env_stats| index.get(&env_stats.id).map(|note| (&env_stats.id, note)))
+            .sorted_by_key(|(id, _)| *id)
+            .collect_vec();
+
+        let mut duplicates = notes
+            .iter()
+            .tuple_combinations()
+            .filter_map(|((id_a, a), (id_b, b))| {
+                let title = a.title_similarity(b);
+                let content = a.content_similarity(b);
+                (title >= DUPLICATE_TITLE_SIMILARITY || content >= DUPLICATE_CONTENT_SIMILARITY)
+                    .then(|| (id_a.to_string(), id_b.to_string(), title, content))
+            })
+            .collect_vec();
+
+        duplicates.sort_by(|(_, _, title_a, content_a), (_, _, title_b, content_b)| {
+            title_b.max(*content_b).total_cmp(&title_a.max(*content_a))
+        });
+
+        self.duplicates = Some(duplicates);
+    }
+
+    /// Returns the pairs of notes of this environment that are possibly duplicates of each other, with their title and content similarity, if they have been computed.
+    /// Every pair is only listed once, with the lexicographically smaller id first, and the most similar pairs come first.
+    pub fn duplicates(&self) -> Option<&[(String, String, f64, f64)]> {
+        self.duplicates.as_deref()
+    }
+
+    /// Returns up to the given amount of the most similar pairs of notes of this environment that are not yet linked, if related notes have been computed.
+    /// Every pair is only listed once, with the lexicographically smaller id first.
+    pub fn link_suggestions(&self, amount: usize) -> Vec<(&str, &str, f64)> {
+        self.filtered_stats
+            .iter()
+            .flat_map(|env_stats| {
+                env_stats
+                    .related()
+                    .unwrap_or_default()
+                    .iter()
+                    .filter(|(other, _)| env_stats.id < *other)
+                    .map(|(other, similarity)| (env_stats.id.as_str(), other.as_str(), *similarity))
+            })
+            .sorted_by(|(a1, b1, s1), (a2, b2, s2)| {
+                s2.total_cmp(s1).then_with(|| (a1, b1).cmp(&(a2, b2)))
+            })
+            .take(amount)
+            .collect()
+    }
+
     /// Sorts the underlying vec
     pub fn sort(&mut self, index: data::NoteIndexContainer, mode: SortingMode, ascending: bool) {
+        if mode.needs_centrality() {
+            self.compute_centrality(&index.borrow());
+        }
+
         // Always sort by name first
         self.filtered_stats
             .sort_by_cached_key(|env_stats| env_stats.id.clone());
@@ -222,23 +1279,11 @@ impl EnvironmentStats {
             }
             // all others are usize and can be done in one thing
             self.filtered_stats.sort_by_cached_key(|env_stats| {
-                if let Some(note) = index.borrow().get(&env_stats.id) {
-                    match mode {
-                        // This should not appear
-                        SortingMode::Name => 0,
-                        // These should appear
-                        SortingMode::Words => note.words,
-                        SortingMode::Chars => note.characters,
-                        SortingMode::GlobalOutLinks => env_stats.outlinks_global,
-                        SortingMode::LocalOutLinks => env_stats.outlinks_local,
-                        SortingMode::GlobalInLinks => env_stats.inlinks_global,
-                        SortingMode::LocalInLinks => env_stats.inlinks_local,
-                        SortingMode::Score => env_stats.match_score as usize,
-                        SortingMode::Broken => env_stats.broken_links,
-                    }
-                } else {
-                    0
-                }
+                index
+                    .borrow()
+                    .get(&env_stats.id)
+                    .map(|note| env_stats.sort_key(note, mode))
+                    .unwrap_or(0)
             })
         }
 
@@ -246,45 +1291,469 @@ impl EnvironmentStats {
         if !ascending {
             self.filtered_stats.reverse();
         }
-    }
 
-    /// Returns the amount of notes in this environment.
-    pub fn len(&self) -> usize {
-        self.filtered_stats.len()
+        // Finally, move pinned notes to the top while keeping the order within both groups.
+        self.filtered_stats
+            .sort_by_key(|env_stats| !env_stats.pinned);
     }
 
-    /// Converts this environemnt to a table of rows with the (sorted) notes contained in it.
-    pub fn to_note_table(&self, index: data::NoteIndexContainer, styles: &ui::UiStyles) -> Table {
-        // Calculate widths
-        let notes_table_widths = [
-            Constraint::Min(25),
-            Constraint::Length(8),
-            Constraint::Length(8),
-            Constraint::Length(10),
-            Constraint::Length(10),
-            Constraint::Length(10),
-            Constraint::Length(10),
-        ];
+    /// Sorts the underlying vec by the `sort:` directives of the filter used to create this environment, if there were any.
+    /// Notes equal in all directives are sorted by name.
+    /// Returns wether the filter contained any directives.
+    pub fn sort_by_directives(&mut self, index: data::NoteIndexContainer) -> bool {
+        if self.sort_directives.is_empty() {
+            return false;
+        }
 
-        // Construct rows
-        let notes_rows = self
-            .filtered_stats
+        let index = index.borrow();
+        if self
+            .sort_directives
             .iter()
-            .flat_map(|note_env| note_env.to_row(index.clone(), styles))
-            .collect::<Vec<Row>>();
+            .any(|(mode, _)| mode.needs_centrality())
+        {
+            self.compute_centrality(&index);
+        }
+        let directives = &self.sort_directives;
+        self.filtered_stats.sort_by(|a, b| {
+            directives
+                .iter()
+                .fold(cmp::Ordering::Equal, |ordering, (mode, ascending)| {
+                    ordering.then_with(|| {
+                        let ordering = if *mode == SortingMode::Name {
+                            a.id.cmp(&b.id)
+                        } else {
+                            let key = |env_stats: &NoteEnvStatistics| {
+                                index
+                                    .get(&env_stats.id)
+                                    .map(|note| env_stats.sort_key(note, *mode))
+                                    .unwrap_or(0)
+                            };
+                            key(a).cmp(&key(b))
+                        };
+                        if *ascending {
+                            ordering
+                        } else {
+                            ordering.reverse()
+                        }
+                    })
+                })
+                .then_with(|| a.id.cmp(&b.id))
+        });
 
-        Table::new(notes_rows, notes_table_widths).column_spacing(1)
+        // Pinned notes still come first.
+        self.filtered_stats
+            .sort_by_key(|env_stats| !env_stats.pinned);
+
+        true
     }
 
-    /// Converts this environment statistics struct to a ratatui table with the basic, global stats.
-    pub fn to_global_stats_table(&self, styles: &ui::UiStyles) -> Table {
-        // Horizontal layout
-        let stats_widths = [
-            Constraint::Length(20),
-            Constraint::Length(16),
-            Constraint::Length(20),
-            Constraint::Length(16),
-            Constraint::Min(0),
+    /// Returns the ids of all notes in this environment, in their sorted order.
+    pub fn ids(&self) -> Vec<String> {
+        self.filtered_stats
+            .iter()
+            .map(|env_stats| env_stats.id.clone())
+            .collect()
+    }
+
+    /// Serializes the statistics of this environment and all its notes, in their current order, to the given format.
+    pub fn export(&self, index: &data::NoteIndex, format: ExportFormat) -> error::Result<String> {
+        let notes = || {
+            self.filtered_stats
+                .iter()
+                .filter_map(|env_stats| {
+                    index
+                        .get(&env_stats.id)
+                        .map(|note| NoteExport::new(env_stats, note))
+                })
+                .collect_vec()
+        };
+
+        Ok(match format {
+            ExportFormat::Json => serde_json::to_string_pretty(&EnvironmentExport {
+                notes_total: self.note_count_total,
+                words_total: self.word_count_total,
+                characters_total: self.char_count_total,
+                tags_total: self.tag_count_total,
+                local_local_links: self.local_local_links,
+                local_global_links: self.local_global_links,
+                global_local_links: self.global_local_links,
+                broken_links: self.broken_links,
+                notes: notes(),
+            })?,
+            ExportFormat::Csv => std::iter::once(NoteExport::CSV_HEADER.to_string())
+                .chain(notes().iter().map(NoteExport::to_csv))
+                .map(|line| line + "\n")
+                .collect(),
+            // Links to notes outside of the environment are left out.
+            ExportFormat::Dot => index.to_graph().restrict(&self.ids()).to_dot(index),
+            ExportFormat::GraphMl => index.to_graph().restrict(&self.ids()).to_graphml(index),
+        })
+    }
+
+    /// Returns the problems weighed by the health score with the amount of notes of this environment having them and their weight.
+    pub fn health_breakdown(&self, criteria: HealthCriteria) -> [(&'static str, usize, f64); 4] {
+        [
+            (
+                "Broken links",
+                self.filtered_stats
+                    .iter()
+                    .filter(|env_stats| env_stats.broken_links > 0)
+                    .count(),
+                criteria.broken,
+            ),
+            ("Orphans", self.orphans.len(), criteria.orphans),
+            ("Untagged", self.untagged, criteria.untagged),
+            (
+                "Stubs",
+                self.word_counts
+                    .partition_point(|&words| words < criteria.stub_words),
+                criteria.stubs,
+            ),
+        ]
+    }
+
+    /// Combines the problems of this environment to a single score between 0 and 100, higher being better.
+    /// The share of notes having each problem is subtracted from 100 percent, weighted relative to the sum of all weights.
+    /// Empty environments and criteria without any weight result in a full score.
+    pub fn health_score(&self, criteria: HealthCriteria) -> f64 {
+        let breakdown = self.health_breakdown(criteria);
+        let total_weight = breakdown.iter().map(|(_, _, weight)| weight).sum::<f64>();
+        if self.note_count_total == 0 || total_weight <= 0. {
+            return 100.;
+        }
+
+        let penalty = breakdown
+            .iter()
+            .map(|(_, count, weight)| *count as f64 / self.note_count_total as f64 * weight)
+            .sum::<f64>();
+
+        (100. * (1. - penalty / total_weight)).clamp(0., 100.)
+    }
+
+    /// Converts the health breakdown of this environment to a ratatui table, listing the notes affected by every problem.
+    pub fn to_health_table(&self, criteria: HealthCriteria, styles: &ui::UiStyles) -> Table {
+        let widths = [
+            Constraint::Length(14),
+            Constraint::Length(7),
+            Constraint::Length(5),
+            Constraint::Length(7),
+        ];
+
+        let rows = self
+            .health_breakdown(criteria)
+            .into_iter()
+            .map(|(name, count, weight)| {
+                Row::new(vec![
+                    format!("{name}:"),
+                    format!("{count:7}"),
+                    format!(
+                        "{:4.0}%",
+                        count as f64 * 100. / self.note_count_total.max(1) as f64
+                    ),
+                    format!("{weight:7.2}"),
+                ])
+                .style(styles.text_style)
+            })
+            .collect_vec();
+
+        Table::new(rows, widths).column_spacing(1).header(
+            Row::new(vec!["Problem", "  Notes", "Share", " Weight"]).style(styles.subtitle_style),
+        )
+    }
+
+    /// Returns up to the given amount of names of the notes of this environment with the highest value of the given sorting mode, with that value, highest first.
+    /// Notes with a value of 0 are not listed. Centrality metrics are not supported as they are not plain amounts.
+    pub fn top_notes<'a>(
+        &self,
+        index: &'a data::NoteIndex,
+        mode: SortingMode,
+        amount: usize,
+    ) -> Vec<(&'a str, usize)> {
+        self.filtered_stats
+            .iter()
+            .filter_map(|env_stats| {
+                index
+                    .get(&env_stats.id)
+                    .map(|note| (note.title(), env_stats.sort_key(note, mode)))
+            })
+            .filter(|(_, value)| *value > 0)
+            .map(|(name, value)| (name, value as usize))
+            .sorted_by(|(name_a, value_a), (name_b, value_b)| {
+                value_b.cmp(value_a).then_with(|| name_a.cmp(name_b))
+            })
+            .take(amount)
+            .collect()
+    }
+
+    /// Returns the names of the notes with the most valid outgoing links per the given amount of words, with that density, densest first.
+    /// Notes without links are left out.
+    pub fn top_link_density<'a>(
+        &self,
+        index: &'a data::NoteIndex,
+        per_words: usize,
+        amount: usize,
+    ) -> Vec<(&'a str, f64)> {
+        self.filtered_stats
+            .iter()
+            .filter(|env_stats| env_stats.outlinks_global > 0)
+            .filter_map(|env_stats| {
+                index
+                    .get(&env_stats.id)
+                    .map(|note| (note.title(), env_stats.link_density(note, per_words)))
+            })
+            .sorted_by(|(name_a, density_a), (name_b, density_b)| {
+                density_b
+                    .total_cmp(density_a)
+                    .then_with(|| name_a.cmp(name_b))
+            })
+            .take(amount)
+            .collect()
+    }
+
+    /// Returns the amount of notes in this environment.
+    pub fn len(&self) -> usize {
+        self.filtered_stats.len()
+    }
+
+    /// Returns the source id and missing target of every broken link within this environment.
+    pub fn broken_link_targets(&self) -> &[(String, String)] {
+        &self.broken_link_targets
+    }
+
+    /// Returns the source id and reference of every reference from a note of this environment to a missing attachment.
+    pub fn missing_attachments(&self) -> &[(String, String)] {
+        &self.missing_attachments
+    }
+
+    /// Returns the paths, relative to the vault, of all attachments no note of the vault references.
+    pub fn unreferenced_attachments(&self) -> &[String] {
+        &self.unreferenced_attachments
+    }
+
+    /// Returns the ids of all notes in this environment no other note of the environment links to.
+    pub fn orphans(&self) -> &[String] {
+        &self.orphans
+    }
+
+    /// Returns the ids of all notes in this environment that do not link to any other note of the environment.
+    pub fn sinks(&self) -> &[String] {
+        &self.sinks
+    }
+
+    /// Returns the word count below or at which the given percentage of notes in this environment lies, using the nearest rank.
+    /// Percentile 0 is the minimum, 50 the median and 100 the maximum word count. Returns 0 for empty environments.
+    pub fn word_percentile(&self, percentile: usize) -> usize {
+        if self.word_counts.is_empty() {
+            return 0;
+        }
+        let rank = (percentile.min(100) * self.word_counts.len()).div_ceil(100);
+        self.word_counts[rank.saturating_sub(1)]
+    }
+
+    /// Returns the amount of links between notes of this environment relative to the amount of possible links between them.
+    pub fn link_density(&self) -> f64 {
+        let possible = self.note_count_total * self.note_count_total.saturating_sub(1);
+        self.local_local_links as f64 / possible.max(1) as f64
+    }
+
+    /// Returns the mean word count of the notes in this environment.
+    pub fn word_mean(&self) -> f64 {
+        self.word_count_total as f64 / self.note_count_total.max(1) as f64
+    }
+
+    /// Sorts the word counts of this environment into the given amount of equally wide buckets, ranging from 0 to the maximum word count.
+    /// Returns the upper bound (inclusive) and the amount of notes of every bucket.
+    pub fn word_histogram(&self, buckets: usize) -> Vec<(usize, usize)> {
+        let buckets = buckets.max(1);
+        let width = (self.word_percentile(100) / buckets + 1).max(1);
+        let mut histogram = (1..=buckets)
+            .map(|bucket| (bucket * width - 1, 0))
+            .collect::<Vec<_>>();
+        for words in &self.word_counts {
+            histogram[(words / width).min(buckets - 1)].1 += 1;
+        }
+        histogram
+    }
+
+    /// Summarizes the activity in this environment during the given amount of consecutive periods of the given length, ending at the given point in time.
+    /// The periods are returned in chronological order, the most recent one last. Activity before the first or after the last period is ignored.
+    pub fn growth(
+        &self,
+        period: time::Duration,
+        periods: usize,
+        now: time::SystemTime,
+    ) -> Vec<GrowthPeriod> {
+        let mut growth = vec![GrowthPeriod::default(); periods];
+        // Determines the position of the period the given time lies in, if any.
+        let position = |t: time::SystemTime| {
+            let ago = now.duration_since(t).ok()?.as_secs_f64() / period.as_secs_f64();
+            (ago.is_finite() && (ago as usize) < periods).then(|| periods - 1 - ago as usize)
+        };
+
+        for (created, modified, words) in &self.note_times {
+            if let Some(position) = created.and_then(position) {
+                growth[position].created += 1;
+                growth[position].words += words;
+            }
+            if let Some(position) = modified.and_then(position) {
+                growth[position].modified += 1;
+            }
+        }
+
+        growth
+    }
+
+    /// Returns the given amount of tags occurring in the most notes of this environment, with their counts.
+    /// Tags occurring equally often are sorted by name.
+    pub fn top_tags(&self, amount: usize) -> Vec<(&str, usize)> {
+        self.tag_counts
+            .iter()
+            .map(|(tag, count)| (tag.as_str(), *count))
+            .sorted_by(|(tag_a, count_a), (tag_b, count_b)| {
+                count_b.cmp(count_a).then_with(|| tag_a.cmp(tag_b))
+            })
+            .take(amount)
+            .collect()
+    }
+
+    /// Returns up to the given amount of the pairs of tags that occur together in the most notes of this environment.
+    /// Next to the amount of notes containing both tags, returns the share of the notes with the rarer tag that also contain the other one.
+    pub fn top_tag_pairs(&self, amount: usize) -> Vec<(&str, &str, usize, f64)> {
+        self.tag_pairs
+            .iter()
+            .map(|((a, b), count)| {
+                let rarer = self
+                    .tag_counts
+                    .get(a)
+                    .min(self.tag_counts.get(b))
+                    .copied()
+                    .unwrap_or(*count)
+                    .max(1);
+                (a.as_str(), b.as_str(), *count, *count as f64 / rarer as f64)
+            })
+            .sorted_by(|(a1, b1, count_1, share_1), (a2, b2, count_2, share_2)| {
+                count_2
+                    .cmp(count_1)
+                    .then_with(|| share_2.total_cmp(share_1))
+                    .then_with(|| (a1, b1).cmp(&(a2, b2)))
+            })
+            .take(amount)
+            .collect()
+    }
+
+    /// Returns up to the given amount of the external URLs linked by the most notes of this environment, with the amount of notes linking to them.
+    pub fn top_external_urls(&self, amount: usize) -> Vec<(&str, usize)> {
+        self.external_link_counts
+            .iter()
+            .map(|(url, count)| (url.as_str(), *count))
+            .sorted_by(|(url_a, count_a), (url_b, count_b)| {
+                count_b.cmp(count_a).then_with(|| url_a.cmp(url_b))
+            })
+            .take(amount)
+            .collect()
+    }
+
+    /// Returns up to the given amount of the web domains linked most often by notes of this environment, with the amount of links to them.
+    /// Every note counts at most once per URL, as in [Self::top_external_urls].
+    pub fn top_external_domains(&self, amount: usize) -> Vec<(&str, usize)> {
+        self.external_link_counts
+            .iter()
+            .map(|(url, count)| (url_domain(url), *count))
+            .into_grouping_map()
+            .sum()
+            .into_iter()
+            .sorted_by(|(domain_a, count_a), (domain_b, count_b)| {
+                count_b.cmp(count_a).then_with(|| domain_a.cmp(domain_b))
+            })
+            .take(amount)
+            .collect()
+    }
+
+    /// Returns the amount of distinct external URLs linked by notes of this environment.
+    pub fn external_url_count(&self) -> usize {
+        self.external_link_counts.len()
+    }
+
+    /// Converts the heading structure of this environment to a ratatui table, with the amount of headings per level and of badly structured notes.
+    pub fn to_heading_table(&self, styles: &ui::UiStyles) -> Table {
+        let widths = [Constraint::Length(14), Constraint::Length(7)];
+
+        let rows = self
+            .heading_counts
+            .iter()
+            .enumerate()
+            .map(|(level, count)| (format!("Level {}:", level + 1), *count))
+            .chain([
+                (String::from("No headings:"), self.without_headings.len()),
+                (
+                    String::from("Skipped levels:"),
+                    self.skipping_headings.len(),
+                ),
+            ])
+            .map(|(name, count)| {
+                Row::new(vec![name, format!("{count:7}")]).style(styles.text_style)
+            })
+            .collect_vec();
+
+        Table::new(rows, widths).column_spacing(1)
+    }
+
+    /// Returns the sizes of the connected components of this environment's link graph, largest first.
+    pub fn component_sizes(&self) -> &[usize] {
+        &self.component_sizes
+    }
+
+    /// Returns the position of the next note after the given position in the sorted order that is contained in the given ids, wrapping around at the end.
+    pub fn next_position_of(&self, position: usize, ids: &[String]) -> Option<usize> {
+        let len = self.filtered_stats.len();
+        (1..=len)
+            .map(|offset| (position + offset) % len)
+            .find(|&i| ids.contains(&self.filtered_stats[i].id))
+    }
+
+    /// Estimates the time needed to read all notes of this environment at the given reading speed, in whole minutes rounded up.
+    pub fn reading_minutes_total(&self, words_per_minute: usize) -> usize {
+        self.word_count_total.div_ceil(words_per_minute.max(1))
+    }
+
+    /// Converts this environemnt to a table of rows with the (sorted) notes contained in it, showing the given columns next to their names.
+    /// Reading times are estimated with the given reading speed and notes fulfilling the given criteria are marked as stale.
+    pub fn to_note_table(
+        &self,
+        index: data::NoteIndexContainer,
+        columns: &[data::Column],
+        styles: &ui::UiStyles,
+        words_per_minute: usize,
+        stale: StaleCriteria,
+    ) -> Table {
+        // Calculate widths
+        let notes_table_widths = std::iter::once(Constraint::Min(25)).chain(
+            columns
+                .iter()
+                .map(|column| Constraint::Length(column.width())),
+        );
+
+        // Construct rows
+        let notes_rows = self
+            .filtered_stats
+            .iter()
+            .flat_map(|note_env| {
+                note_env.to_row(index.clone(), columns, styles, words_per_minute, stale)
+            })
+            .collect::<Vec<Row>>();
+
+        Table::new(notes_rows, notes_table_widths).column_spacing(1)
+    }
+
+    /// Converts this environment statistics struct to a ratatui table with the basic, global stats.
+    pub fn to_global_stats_table(&self, styles: &ui::UiStyles) -> Table {
+        // Horizontal layout
+        let stats_widths = [
+            Constraint::Length(20),
+            Constraint::Length(16),
+            Constraint::Length(20),
+            Constraint::Length(16),
+            Constraint::Min(0),
         ];
 
         //  === Global stats ===
@@ -308,11 +1777,196 @@ impl EnvironmentStats {
                 Cell::from("Broken links:").style(styles.text_style),
                 Cell::from(format!("{:7}", self.broken_links)).style(styles.text_style),
             ]),
+            Row::new(vec![
+                Cell::from("Orphans:").style(styles.text_style),
+                Cell::from(format!("{:7}", self.orphans.len())).style(styles.text_style),
+                Cell::from("Sinks (dead ends):").style(styles.text_style),
+                Cell::from(format!("{:7}", self.sinks.len())).style(styles.text_style),
+            ]),
+            Row::new(vec![
+                Cell::from("Components:").style(styles.text_style),
+                Cell::from(format!("{:7}", self.component_sizes.len())).style(styles.text_style),
+                Cell::from("Largest component:").style(styles.text_style),
+                Cell::from(format!(
+                    "{:7}",
+                    self.component_sizes.first().copied().unwrap_or_default()
+                ))
+                .style(styles.text_style),
+            ]),
+            Row::new(vec![
+                Cell::from("Embeds:").style(styles.text_style),
+                Cell::from(format!("{:7}", self.embeds)).style(styles.text_style),
+                Cell::from("Embedded notes:").style(styles.text_style),
+                Cell::from(format!("{:7}", self.embedded_notes)).style(styles.text_style),
+            ]),
         ];
 
         Table::new(global_stats_rows, stats_widths).column_spacing(1)
     }
 
+    /// Converts the link graph metrics of this environment to a ratatui table. Metrics not yet computed are left empty.
+    pub fn to_graph_stats_table(&self, styles: &ui::UiStyles) -> Table {
+        let widths = [Constraint::Length(20), Constraint::Length(10)];
+
+        let (diameter, average) = self
+            .distances
+            .map(|(diameter, average)| (format!("{diameter:7}"), format!("{average:7.2}")))
+            .unwrap_or_default();
+
+        let rows = [
+            Row::new(vec![
+                Cell::from("Components:").style(styles.text_style),
+                Cell::from(format!("{:7}", self.component_sizes.len())).style(styles.text_style),
+            ]),
+            Row::new(vec![
+                Cell::from("Largest component:").style(styles.text_style),
+                Cell::from(format!(
+                    "{:7}",
+                    self.component_sizes.first().copied().unwrap_or_default()
+                ))
+                .style(styles.text_style),
+            ]),
+            Row::new(vec![
+                Cell::from("Diameter:").style(styles.text_style),
+                Cell::from(diameter).style(styles.text_style),
+            ]),
+            Row::new(vec![
+                Cell::from("Avg. path length:").style(styles.text_style),
+                Cell::from(average).style(styles.text_style),
+            ]),
+            Row::new(vec![
+                Cell::from("Reciprocal pairs:").style(styles.text_style),
+                Cell::from(format!("{:7}", self.reciprocal_pairs)).style(styles.text_style),
+            ]),
+            Row::new(vec![
+                Cell::from("One-way links:").style(styles.text_style),
+                Cell::from(format!("{:7}", self.one_way_links)).style(styles.text_style),
+            ]),
+        ];
+
+        Table::new(rows, widths).column_spacing(1)
+    }
+
+    /// Counts the links among the notes of the given global environment by whether their source and target lie within this environment.
+    /// The first index is the source, the second the target, with 0 meaning within this environment and 1 outside of it.
+    pub fn link_matrix(&self, global: &Self) -> [[usize; 2]; 2] {
+        let outgoing = self
+            .local_global_links
+            .saturating_sub(self.local_local_links);
+        let incoming = self
+            .global_local_links
+            .saturating_sub(self.local_local_links);
+        [
+            [self.local_local_links, outgoing],
+            [
+                incoming,
+                global
+                    .local_local_links
+                    .saturating_sub(self.local_local_links + outgoing + incoming),
+            ],
+        ]
+    }
+
+    /// Converts the link matrix of this environment within the given global environment to a ratatui table, with sources as rows and targets as columns.
+    pub fn to_link_matrix_table(&self, global: &Self, styles: &ui::UiStyles) -> Table {
+        let widths = [
+            Constraint::Length(14),
+            Constraint::Length(12),
+            Constraint::Length(12),
+        ];
+
+        let matrix = self.link_matrix(global);
+        let rows = [("Environment", matrix[0]), ("Rest", matrix[1])]
+            .into_iter()
+            .map(|(label, [inside, outside])| {
+                Row::new(vec![
+                    Cell::from(format!("{label} →")).style(styles.subtitle_style),
+                    Cell::from(format!("{inside:7}")).style(styles.text_style),
+                    Cell::from(format!("{outside:7}")).style(styles.text_style),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        Table::new(rows, widths).column_spacing(1).header(
+            Row::new(vec![
+                Cell::from(""),
+                Cell::from("Environment"),
+                Cell::from("Rest"),
+            ])
+            .style(styles.subtitle_style),
+        )
+    }
+
+    /// Compares the size of this environment to the given global environment.
+    /// Returns a label, the amount within this environment and the amount within the global environment for notes, words, unique tags and links.
+    pub fn coverage(&self, global: &Self) -> [(&'static str, usize, usize); 4] {
+        [
+            ("Notes", self.note_count_total, global.note_count_total),
+            ("Words", self.word_count_total, global.word_count_total),
+            ("Tags", self.tag_count_total, global.tag_count_total),
+            ("Links", self.local_local_links, global.local_local_links),
+        ]
+    }
+
+    /// Converts a list of labeled environments, as created by [Self::per_top_level_tag] or [Self::per_directory], to a ratatui table comparing them side by side.
+    /// The first column shows the labels under the given header.
+    pub fn to_comparison_table<'a>(
+        environments: &'a [(String, Self)],
+        label: &'a str,
+        styles: &ui::UiStyles,
+    ) -> Table<'a> {
+        let widths = [
+            Constraint::Min(20),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(8),
+        ];
+
+        let rows = environments.iter().map(|(tag, env)| {
+            Row::new(vec![
+                tag.clone(),
+                format!("{:7}", env.note_count_total),
+                format!("{:7}", env.word_count_total),
+                format!("{:7}", env.local_local_links),
+                format!(
+                    "{:9.2}",
+                    env.local_local_links as f64 / env.note_count_total.max(1) as f64
+                ),
+                format!("{:8.1}%", env.link_density() * 100.),
+                format!("{:7}", env.broken_links),
+            ])
+            .style(styles.text_style)
+        });
+
+        Table::new(rows, widths).column_spacing(1).header(
+            Row::new(vec![
+                label,
+                "  Notes",
+                "  Words",
+                "  Links",
+                "Links/Note",
+                "   Density",
+                " Broken",
+            ])
+            .style(styles.subtitle_style),
+        )
+    }
+
+    /// Returns the key figures of this environment, e.g. to cache them.
+    pub fn summary(&self) -> data::VaultSummary {
+        data::VaultSummary {
+            notes: self.note_count_total,
+            words: self.word_count_total,
+            characters: self.char_count_total,
+            tags: self.tag_count_total,
+            links: self.local_local_links,
+            broken_links: self.broken_links,
+        }
+    }
+
     /// Converts this environment statistics struct to a ratatui table with the full, local stats.
     pub fn to_local_stats_table(&self, global: &Self, styles: &ui::UiStyles) -> Table {
         // Horizontal layout
@@ -390,12 +2044,61 @@ impl EnvironmentStats {
                 ))
                 .style(styles.text_style),
             ]),
+            Row::new(vec![
+                Cell::from("Orphans:").style(styles.text_style),
+                Cell::from(format!(
+                    "{:7} ({:3}%)",
+                    self.orphans.len(),
+                    self.orphans.len() * 100 / self.note_count_total.max(1)
+                ))
+                .style(styles.text_style),
+                Cell::from("Sinks (dead ends):").style(styles.text_style),
+                Cell::from(format!(
+                    "{:7} ({:3}%)",
+                    self.sinks.len(),
+                    self.sinks.len() * 100 / self.note_count_total.max(1)
+                ))
+                .style(styles.text_style),
+            ]),
+            Row::new(vec![
+                Cell::from("Components:").style(styles.text_style),
+                Cell::from(format!("{:7}", self.component_sizes.len())).style(styles.text_style),
+                Cell::from("Largest component:").style(styles.text_style),
+                Cell::from(format!(
+                    "{:7} ({:3}%)",
+                    self.component_sizes.first().copied().unwrap_or_default(),
+                    self.component_sizes.first().copied().unwrap_or_default() * 100
+                        / self.note_count_total.max(1)
+                ))
+                .style(styles.text_style),
+            ]),
         ];
 
         Table::new(local_stats_rows, stats_widths).column_spacing(1)
     }
 }
 
+/// Quotes the given text for use as a CSV field if it contains separators, quotes or line breaks.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Extracts the domain of the given web URL, without scheme, port, path or a leading `www.`.
+fn url_domain(url: &str) -> &str {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let host = without_scheme
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+    let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+    let host = host.split(':').next().unwrap_or_default();
+    host.strip_prefix("www.").unwrap_or(host)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -410,7 +2113,7 @@ mod tests {
 
         assert_eq!(index.inner.len(), 11);
 
-        let index = std::rc::Rc::new(std::cell::RefCell::new(index));
+        let index = data::NoteIndexContainer::new(index);
 
         // === Filter 1 ===
 
@@ -424,6 +2127,7 @@ mod tests {
             blinks: vec![],
             title: String::new(),
             full_text: None,
+            ..Default::default()
         };
 
         let env1 = EnvironmentStats::new_with_filter(&index, filter1);
@@ -445,7 +2149,7 @@ mod tests {
 
         assert_eq!(index.inner.len(), 11);
 
-        let index = std::rc::Rc::new(std::cell::RefCell::new(index));
+        let index = data::NoteIndexContainer::new(index);
         // === Filter 2 ===
 
         let filter2 = data::Filter {
@@ -458,6 +2162,7 @@ mod tests {
             blinks: vec![],
             title: String::new(),
             full_text: None,
+            ..Default::default()
         };
         let env2 = EnvironmentStats::new_with_filter(&index, filter2);
 
@@ -489,7 +2194,7 @@ mod tests {
 
         assert_eq!(index.inner.len(), 11);
 
-        let index = std::rc::Rc::new(std::cell::RefCell::new(index));
+        let index = data::NoteIndexContainer::new(index);
 
         // === Filter 3 ===
 
@@ -500,6 +2205,7 @@ mod tests {
             blinks: vec![],
             title: "operating".to_string(),
             full_text: None,
+            ..Default::default()
         };
         let env3 = EnvironmentStats::new_with_filter(&index, filter3);
 
@@ -520,7 +2226,7 @@ mod tests {
 
         assert_eq!(index.inner.len(), 11);
 
-        let index = std::rc::Rc::new(std::cell::RefCell::new(index));
+        let index = data::NoteIndexContainer::new(index);
 
         // === Filter 4 ===
 
@@ -531,6 +2237,7 @@ mod tests {
             blinks: vec![("atlas".to_string(), true)],
             title: String::new(),
             full_text: None,
+            ..Default::default()
         };
         let env4 = EnvironmentStats::new_with_filter(&index, filter4);
 
@@ -551,7 +2258,7 @@ mod tests {
 
         assert_eq!(index.inner.len(), 11);
 
-        let index = std::rc::Rc::new(std::cell::RefCell::new(index));
+        let index = data::NoteIndexContainer::new(index);
 
         // === Filter 5 ===
 
@@ -562,6 +2269,7 @@ mod tests {
             blinks: vec![("atlas".to_string(), true)],
             title: String::new(),
             full_text: None,
+            ..Default::default()
         };
         let env5 = EnvironmentStats::new_with_filter(&index, filter5);
 
@@ -572,4 +2280,1093 @@ mod tests {
         assert_eq!(env5.global_local_links, 9);
         assert_eq!(env5.broken_links, 1);
     }
+
+    #[test]
+    fn test_env_stats_weighted_score() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        let filter =
+            data::Filter::new("#diffgeo #topology", true).with_weights(data::ScoreWeights {
+                title: 1,
+                tags: 10,
+                links: 0,
+            });
+        let env = EnvironmentStats::new_with_filter(&index, filter);
+
+        // notes matching both tags are sorted first
+        assert_eq!(env.get_selected(0).map(|stats| stats.score()), Some(20));
+        assert_eq!(env.get_selected(1).map(|stats| stats.score()), Some(20));
+        assert_eq!(env.get_selected(2).map(|stats| stats.score()), Some(10));
+
+        for stats in env.filtered_stats.iter() {
+            let expected = if stats.id == "chart" || stats.id == "manifold" {
+                20
+            } else {
+                10
+            };
+            assert_eq!(stats.score(), expected);
+        }
+    }
+
+    #[test]
+    fn test_count_matches() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        for (filter_string, any) in [("", false), ("#diffgeo #topology", false), ("#os", true)] {
+            let filter = data::Filter::new(filter_string, any);
+            assert_eq!(
+                EnvironmentStats::count_matches(
+                    &index,
+                    &filter,
+                    &data::PinnedNotes::default(),
+                    None
+                ),
+                EnvironmentStats::new_with_filter(&index, filter).len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_env_stats_pinned() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        let mut pinned = data::PinnedNotes::default();
+        pinned.toggle("linux");
+        pinned.toggle("manifold");
+
+        let filter = data::Filter::new("#diffgeo", false);
+
+        assert_eq!(EnvironmentStats::count_matches(&index, &filter, &pinned), 5);
+
+        let mut env = EnvironmentStats::new_with_filter_and_pinned(&index, filter, &pinned, None);
+
+        assert_eq!(env.len(), 5);
+
+        for mode in [SortingMode::Name, SortingMode::Words, SortingMode::Score] {
+            for ascending in [true, false] {
+                env.sort(index.clone(), mode, ascending);
+
+                assert!(env.get_selected(0).unwrap().pinned());
+                assert!(env.get_selected(1).unwrap().pinned());
+                assert!(!env.get_selected(2).unwrap().pinned());
+            }
+        }
+
+        env.sort(index.clone(), SortingMode::Name, true);
+        assert_eq!(
+            env.ids(),
+            vec!["linux", "manifold", "atlas", "chart", "smooth-map"]
+        );
+    }
+
+    #[test]
+    fn test_env_stats_sort_directives() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        // no directives
+        let mut env = EnvironmentStats::new_with_filter(&index, data::Filter::new("#os", false));
+        assert!(!env.sort_by_directives(index.clone()));
+
+        // single directive
+        let mut env = EnvironmentStats::new_with_filter(
+            &index,
+            data::Filter::new("#diffgeo sort:-inlinks", false),
+        );
+        assert!(env.sort_by_directives(index.clone()));
+        assert_eq!(env.get_selected(0).unwrap().id, "manifold");
+
+        // multiple directives, ties in the first one are resolved by the second one
+        let mut env = EnvironmentStats::new_with_filter(
+            &index,
+            data::Filter::new("#os sort:broken sort:-name", false),
+        );
+        assert!(env.sort_by_directives(index.clone()));
+        assert_eq!(
+            env.ids(),
+            vec!["windows", "osx", "operating-systems", "linux"]
+        );
+    }
+
+    #[test]
+    fn test_env_stats_candidates() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+        let pinned = data::PinnedNotes::default();
+
+        let candidates = ["chart", "linux", "manifold", "nonexistent"]
+            .into_iter()
+            .map(String::from)
+            .collect::<HashSet<_>>();
+
+        let env = EnvironmentStats::new_with_filter_and_pinned(
+            &index,
+            data::Filter::new("#diffgeo", false),
+            &pinned,
+            Some(&candidates),
+        );
+
+        assert_eq!(env.len(), 2);
+        assert_eq!(
+            EnvironmentStats::count_matches(
+                &index,
+                &data::Filter::new("#diffgeo", false),
+                &pinned,
+                Some(&candidates)
+            ),
+            2
+        );
+        // link statistics still take the whole index into account
+        env.filtered_stats
+            .iter()
+            .filter(|env_stats| env_stats.id == "manifold")
+            .for_each(|ma| {
+                assert_eq!(ma.inlinks_global, 4);
+                assert_eq!(ma.inlinks_local, 1);
+            });
+    }
+
+    #[test]
+    fn test_env_stats_centrality() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        let mut env =
+            EnvironmentStats::new_with_filter(&index, data::Filter::new("#diffgeo", false));
+
+        // not computed until needed
+        assert!(env
+            .filtered_stats
+            .iter()
+            .all(|env_stats| env_stats.centrality().is_none()));
+
+        env.sort(index.clone(), SortingMode::PageRank, false);
+
+        assert!(env
+            .filtered_stats
+            .iter()
+            .all(|env_stats| env_stats.centrality().is_some()));
+        assert_eq!(env.get_selected(0).unwrap().id, "manifold");
+
+        env.sort(index.clone(), SortingMode::Betweenness, false);
+        assert_eq!(env.get_selected(0).unwrap().id, "manifold");
+
+        // directives compute centrality as well
+        let mut env = EnvironmentStats::new_with_filter(
+            &index,
+            data::Filter::new("#diffgeo sort:-pagerank", false),
+        );
+        assert!(env.sort_by_directives(index.clone()));
+        assert_eq!(env.get_selected(0).unwrap().id, "manifold");
+    }
+
+    #[test]
+    fn test_env_stats_stale() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        let env = EnvironmentStats::new_with_filter(
+            &index,
+            data::Filter::new("#diffgeo #topology #lietheo", true),
+        );
+        let criteria = StaleCriteria::default();
+        let year = std::time::Duration::from_secs(365 * 24 * 60 * 60);
+
+        let index_b = index.borrow();
+        let stale_at = |now: &dyn Fn(&data::Note) -> time::SystemTime| {
+            env.filtered_stats
+                .iter()
+                .filter(|env_stats| {
+                    let note = index_b.get(&env_stats.id).unwrap();
+                    env_stats.is_stale(note, criteria, now(note))
+                })
+                .map(|env_stats| env_stats.id.as_str())
+                .sorted()
+                .collect_vec()
+        };
+
+        // a year after their last modification, only notes with at least 3 inlinks are stale
+        assert_eq!(
+            stale_at(&|note| note.modified.unwrap() + year),
+            vec!["manifold", "topology"]
+        );
+        // a day after, nothing is stale
+        assert!(stale_at(&|note| note.modified.unwrap() + year / 365).is_empty());
+
+        assert_eq!(
+            SortingMode::from_name("stale"),
+            Some(SortingMode::Staleness)
+        );
+    }
+
+    #[test]
+    fn test_sort_key_negative_score() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+        let note = index.get("manifold").unwrap();
+
+        let worse = NoteEnvStatistics::new_empty(String::from("worse"), -5, false);
+        let better = NoteEnvStatistics::new_empty(String::from("better"), 3, false);
+
+        // negative scores still sort below positive ones
+        assert!(
+            worse.sort_key(note, SortingMode::Score) < better.sort_key(note, SortingMode::Score)
+        );
+        assert_eq!(worse.sort_key(note, SortingMode::Score), -5);
+    }
+
+    #[test]
+    fn test_env_stats_sort_modified() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        let mut env = EnvironmentStats::new_with_filter(&index, data::Filter::default());
+        env.sort(index.clone(), SortingMode::Modified, true);
+
+        let index_b = index.borrow();
+        let modified = env
+            .ids()
+            .iter()
+            .map(|id| index_b.get(id).unwrap().modified)
+            .collect_vec();
+        assert!(modified.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        assert_eq!(
+            SortingMode::from_name("modified"),
+            Some(SortingMode::Modified)
+        );
+    }
+
+    #[test]
+    fn test_env_stats_orphans_sinks() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        let global = EnvironmentStats::new_with_filter(&index, data::Filter::default());
+
+        assert_eq!(global.orphans(), ["books", "operating-systems"]);
+        assert_eq!(
+            global.sinks(),
+            ["books", "linux", "osx", "topology", "windows"]
+        );
+
+        let mut env =
+            EnvironmentStats::new_with_filter(&index, data::Filter::new("#topology", false));
+        env.sort(index.clone(), SortingMode::Name, true);
+
+        assert_eq!(env.orphans(), ["chart"]);
+        assert_eq!(env.sinks(), ["topology"]);
+
+        // cycling wraps around
+        assert_eq!(env.ids(), vec!["chart", "manifold", "topology"]);
+        assert_eq!(env.next_position_of(0, env.orphans()), Some(0));
+        assert_eq!(env.next_position_of(0, env.sinks()), Some(2));
+        assert_eq!(env.next_position_of(2, env.sinks()), Some(2));
+        assert_eq!(env.next_position_of(0, &[]), None);
+    }
+
+    #[test]
+    fn test_env_stats_components() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        // without the hub note, the os notes fall apart
+        let env = EnvironmentStats::new_with_filter(
+            &index,
+            data::Filter::new("#os/linux #os/win #lietheo #topology", true),
+        );
+
+        assert_eq!(env.component_sizes(), [5, 1, 1]);
+
+        let component = |id: &str| {
+            env.filtered_stats
+                .iter()
+                .find(|env_stats| env_stats.id == id)
+                .unwrap()
+                .component()
+        };
+        assert_eq!(component("atlas"), 0);
+        assert_eq!(component("lie-group"), 0);
+        assert_ne!(component("linux"), component("windows"));
+    }
+
+    #[test]
+    fn test_env_stats_tag_counts() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        let env = EnvironmentStats::new_with_filter(&index, data::Filter::new("#diffgeo", false));
+
+        assert_eq!(env.tag_counts.len(), env.tag_count_total);
+        assert_eq!(env.tag_counts["#diffgeo"], 4);
+        assert_eq!(env.tag_counts["#topology"], 2);
+        assert_eq!(env.tag_counts["#lietheo"], 1);
+
+        assert_eq!(env.top_tags(2), vec![("#diffgeo", 4), ("#topology", 2)]);
+        assert_eq!(env.top_tags(10).len(), 3);
+
+        assert_eq!(
+            env.top_tag_pairs(5),
+            vec![
+                ("#diffgeo", "#topology", 2, 1.),
+                ("#diffgeo", "#lietheo", 1, 1.)
+            ]
+        );
+
+        // the rarer tag #lietheo occurs without #diffgeo in the lie group note
+        let env =
+            EnvironmentStats::new_with_filter(&index, data::Filter::new("#diffgeo #lietheo", true));
+        assert_eq!(env.top_tag_pairs(5)[1], ("#diffgeo", "#lietheo", 1, 0.5));
+    }
+
+    #[test]
+    fn test_env_stats_word_distribution() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        // words: 72, 115, 116, 129
+        let env = EnvironmentStats::new_with_filter(&index, data::Filter::new("#diffgeo", false));
+
+        assert_eq!(env.word_percentile(0), 72);
+        assert_eq!(env.word_percentile(25), 72);
+        assert_eq!(env.word_percentile(50), 115);
+        assert_eq!(env.word_percentile(75), 116);
+        assert_eq!(env.word_percentile(100), 129);
+        assert_eq!(env.word_mean(), 108.);
+        assert_eq!(env.reading_minutes_total(200), 3);
+        assert_eq!(env.reading_minutes_total(100), 5);
+
+        let histogram = env.word_histogram(4);
+        assert_eq!(histogram, vec![(32, 0), (65, 0), (98, 1), (131, 3)]);
+        assert_eq!(
+            histogram.iter().map(|(_, count)| count).sum::<usize>(),
+            env.note_count_total
+        );
+
+        // empty environments
+        let env = EnvironmentStats::new_with_filter(&index, data::Filter::new("#nothing", false));
+        assert_eq!(env.word_percentile(50), 0);
+        assert_eq!(env.word_mean(), 0.);
+        assert_eq!(env.reading_minutes_total(200), 0);
+        assert_eq!(env.word_histogram(3), vec![(0, 0), (1, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn test_env_stats_growth() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        let env = EnvironmentStats::new_with_filter(&index, data::Filter::new("#diffgeo", false));
+
+        let now = time::SystemTime::now();
+        let century = time::Duration::from_secs(100 * 365 * 24 * 60 * 60);
+
+        // everything happened within the last century
+        let growth = env.growth(century, 2, now);
+        assert_eq!(growth.len(), 2);
+        assert_eq!(growth[0], GrowthPeriod::default());
+        assert_eq!(
+            growth[1],
+            GrowthPeriod {
+                created: 4,
+                words: 432,
+                modified: 4,
+            }
+        );
+
+        // nothing happened before the epoch
+        let growth = env.growth(century, 3, time::SystemTime::UNIX_EPOCH);
+        assert!(growth
+            .iter()
+            .all(|period| *period == GrowthPeriod::default()));
+    }
+
+    #[test]
+    fn test_env_stats_broken_link_targets() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        let env = EnvironmentStats::new_with_filter(&index, data::Filter::default());
+        assert_eq!(
+            env.broken_link_targets(),
+            [("chart".to_string(), "diffeomorphism".to_string())]
+        );
+        assert_eq!(env.broken_link_targets().len(), env.broken_links);
+
+        let env = EnvironmentStats::new_with_filter(&index, data::Filter::new("#os", false));
+        assert!(env.broken_link_targets().is_empty());
+    }
+
+    #[test]
+    fn test_env_stats_related() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        let mut env =
+            EnvironmentStats::new_with_filter(&index, data::Filter::new("#topology", false));
+        assert!(env.link_suggestions(5).is_empty());
+
+        env.compute_related(&index.borrow(), 5);
+
+        // chart and topology share a tag, but neither links to the other
+        assert_eq!(
+            env.link_suggestions(5),
+            vec![("chart", "topology", 1. / 3.)]
+        );
+        assert!(env
+            .filtered_stats
+            .iter()
+            .all(|env_stats| env_stats.related().is_some()));
+    }
+
+    #[test]
+    fn test_env_stats_per_top_level_tag() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        let envs = EnvironmentStats::per_top_level_tag(&index);
+
+        assert_eq!(
+            envs.iter().map(|(tag, _)| tag.as_str()).collect::<Vec<_>>(),
+            vec!["#diffgeo", "#lietheo", "#os", "#topology"]
+        );
+
+        // subtags are grouped with their top-level tag
+        let (_, os) = &envs[2];
+        assert_eq!(os.note_count_total, 4);
+        assert_eq!(os.local_local_links, 6);
+
+        // the same as filtering by the tag
+        let (_, topology) = &envs[3];
+        let filtered =
+            EnvironmentStats::new_with_filter(&index, data::Filter::new("#topology", false));
+        assert_eq!(topology.note_count_total, filtered.note_count_total);
+        assert_eq!(topology.local_local_links, filtered.local_local_links);
+        assert_eq!(topology.link_density(), 2. / 6.);
+    }
+
+    #[test]
+    fn test_env_stats_per_language() {
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("English.md"),
+            "This is a note that is written in English and it links [[German]].",
+        )
+        .unwrap();
+        std::fs::write(
+            tmp.join("German.md"),
+            "Das ist eine Notiz, die auf Deutsch geschrieben ist und auch kurz ist.",
+        )
+        .unwrap();
+        std::fs::write(tmp.join("Empty.md"), "").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        let envs = EnvironmentStats::per_language(&index);
+
+        assert_eq!(
+            envs.iter()
+                .map(|(lang, _)| lang.as_str())
+                .collect::<Vec<_>>(),
+            vec!["de", "en", "unknown"]
+        );
+        assert!(envs.iter().all(|(_, env)| env.note_count_total == 1));
+
+        // the same as filtering by the language
+        let (_, english) = &envs[1];
+        let filtered =
+            EnvironmentStats::new_with_filter(&index, data::Filter::new("lang:en", false));
+        assert_eq!(english.note_count_total, filtered.note_count_total);
+        assert_eq!(english.word_count_total, filtered.word_count_total);
+    }
+
+    #[test]
+    fn test_env_stats_distances() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        // chart - manifold - topology
+        let mut env =
+            EnvironmentStats::new_with_filter(&index, data::Filter::new("#topology", false));
+        assert_eq!(env.distances, None);
+
+        env.compute_distances(&index.borrow());
+        assert_eq!(env.distances, Some((2, 8. / 6.)));
+    }
+
+    #[test]
+    fn test_env_stats_cycles() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        // without atlas, chart is no longer part of a cycle
+        let mut env = EnvironmentStats::new_with_filter(
+            &index,
+            data::Filter::new("#diffgeo !#lietheo", false),
+        );
+        assert_eq!(env.cycles(), None);
+
+        env.compute_cycles(&index.borrow());
+        assert_eq!(
+            env.cycles(),
+            Some(&[vec!["manifold".to_string(), "smooth-map".to_string()]][..])
+        );
+
+        // hierarchies are free of cycles
+        let mut env = EnvironmentStats::new_with_filter(&index, data::Filter::new("#os", false));
+        env.compute_cycles(&index.borrow());
+        assert_eq!(env.cycles(), Some(&[][..]));
+    }
+
+    #[test]
+    fn test_env_stats_duplicates() {
+        let tmp = testdir::testdir!();
+        std::fs::write(tmp.join("Graph Theory.md"), "Vertices and edges.").unwrap();
+        std::fs::write(tmp.join("Graph Theroy.md"), "Something entirely different.").unwrap();
+        std::fs::write(
+            tmp.join("Cats.md"),
+            "Cats are small carnivorous mammals that purr a lot.",
+        )
+        .unwrap();
+        std::fs::write(
+            tmp.join("Felines.md"),
+            "Cats are small carnivorous mammals that purr a lot. They sleep.",
+        )
+        .unwrap();
+        std::fs::write(tmp.join("Dogs.md"), "Dogs bark loudly.").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        let mut env = EnvironmentStats::new_with_filter(&index, data::Filter::default());
+        assert_eq!(env.duplicates(), None);
+
+        env.compute_duplicates(&index.borrow());
+        let duplicates = env
+            .duplicates()
+            .unwrap()
+            .iter()
+            .map(|(a, b, _, _)| (a.as_str(), b.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            duplicates,
+            vec![("graph-theory", "graph-theroy"), ("cats", "felines")]
+        );
+
+        // 7 shared shingles out of 7 + 9
+        let (_, _, title, content) = env.duplicates().unwrap()[1];
+        assert!(title < 0.5);
+        assert_eq!(content, 7. / 9.);
+    }
+
+    #[test]
+    fn test_env_stats_external_links() {
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("A.md"),
+            "[Docs](https://docs.rs/ratatui) and [again](https://docs.rs/ratatui) and <https://www.rust-lang.org/learn>",
+        )
+        .unwrap();
+        std::fs::write(
+            tmp.join("B.md"),
+            "[Docs](https://docs.rs/ratatui), [Crossterm](http://docs.rs/crossterm?x=1) and [[A]] and [local](a.md)",
+        )
+        .unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        assert_eq!(index.get("a").unwrap().external_links.len(), 3);
+        assert_eq!(index.get("b").unwrap().external_links.len(), 2);
+
+        let index = data::NoteIndexContainer::new(index);
+        let env = EnvironmentStats::new_with_filter(&index, data::Filter::default());
+
+        assert_eq!(env.external_url_count(), 3);
+        assert_eq!(
+            env.top_external_urls(2),
+            vec![
+                ("https://docs.rs/ratatui", 2),
+                ("http://docs.rs/crossterm?x=1", 1)
+            ]
+        );
+        assert_eq!(
+            env.top_external_domains(5),
+            vec![("docs.rs", 3), ("rust-lang.org", 1)]
+        );
+
+        assert_eq!(
+            super::url_domain("https://user@example.com:8080/a#b"),
+            "example.com"
+        );
+        assert_eq!(super::url_domain("http://www.example.com"), "example.com");
+    }
+
+    #[test]
+    fn test_env_stats_export() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        let mut env =
+            EnvironmentStats::new_with_filter(&index, data::Filter::new("#diffgeo", false));
+        env.sort(index.clone(), SortingMode::Name, true);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&env.export(&index.borrow(), ExportFormat::Json).unwrap())
+                .unwrap();
+        assert_eq!(json["notes_total"], 4);
+        assert_eq!(json["words_total"], 432);
+        assert_eq!(json["notes"].as_array().unwrap().len(), 4);
+        assert_eq!(json["notes"][0]["id"], "atlas");
+        assert_eq!(json["notes"][0]["words"], 116);
+
+        let csv = env.export(&index.borrow(), ExportFormat::Csv).unwrap();
+        let lines = csv.lines().collect_vec();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("id,name,title,path,words"));
+        assert!(lines[1].starts_with("atlas,Atlas,"));
+        assert!(lines[1].contains(",116,"));
+
+        let dot = env.export(&index.borrow(), ExportFormat::Dot).unwrap();
+        assert!(dot.starts_with("digraph notes {"));
+        assert!(dot.contains("\"atlas\" [label=\"Atlas\"];"));
+        assert!(dot.contains("\"atlas\" -> \"chart\";"));
+        assert!(!dot.contains("\"topology\""));
+
+        let graphml = env.export(&index.borrow(), ExportFormat::GraphMl).unwrap();
+        assert_eq!(graphml.matches("<node ").count(), 4);
+        assert!(graphml.contains("<edge source=\"atlas\" target=\"chart\"/>"));
+
+        assert_eq!(super::csv_field("plain"), "plain");
+        assert_eq!(super::csv_field("a, \"b\""), "\"a, \"\"b\"\"\"");
+    }
+
+    #[test]
+    fn test_env_stats_health() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        // only chart has a broken link
+        let env = EnvironmentStats::new_with_filter(&index, data::Filter::new("#diffgeo", false));
+        let criteria = HealthCriteria::default();
+        assert_eq!(
+            env.health_breakdown(criteria),
+            [
+                ("Broken links", 1, 1.),
+                ("Orphans", 0, 1.),
+                ("Untagged", 0, 1.),
+                ("Stubs", 0, 1.)
+            ]
+        );
+        assert_eq!(env.health_score(criteria), 93.75);
+
+        // smooth map has 72 words
+        let criteria = HealthCriteria {
+            stub_words: 100,
+            ..Default::default()
+        };
+        assert_eq!(env.health_breakdown(criteria)[3], ("Stubs", 1, 1.));
+        assert_eq!(env.health_score(criteria), 87.5);
+
+        let criteria = HealthCriteria {
+            broken: 1.,
+            orphans: 0.,
+            untagged: 0.,
+            stubs: 0.,
+            stub_words: 50,
+        };
+        assert_eq!(env.health_score(criteria), 75.);
+
+        // without weights or notes, there is nothing to complain about
+        let criteria = HealthCriteria {
+            broken: 0.,
+            ..criteria
+        };
+        assert_eq!(env.health_score(criteria), 100.);
+        let env = EnvironmentStats::new_with_filter(&index, data::Filter::new("#nothing", false));
+        assert_eq!(env.health_score(HealthCriteria::default()), 100.);
+    }
+
+    #[test]
+    fn test_env_stats_headings() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        let env = EnvironmentStats::new_with_filter(&index, data::Filter::new("#os", false));
+
+        // OS: 2, Linux: 4, Windows: 2 + 1, OSX: 0
+        assert_eq!(env.heading_counts, [0, 8, 1, 0, 0, 0]);
+        assert_eq!(env.without_headings, vec!["osx".to_string()]);
+        assert!(env.skipping_headings.is_empty());
+    }
+
+    #[test]
+    fn test_env_stats_top_notes() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        let env = EnvironmentStats::new_with_filter(&index, data::Filter::new("#diffgeo", false));
+        let index = index.borrow();
+
+        assert_eq!(
+            env.top_notes(&index, SortingMode::GlobalInLinks, 3),
+            vec![("Manifold", 4), ("Atlas", 2), ("Smooth Map", 2)]
+        );
+        assert_eq!(
+            env.top_notes(&index, SortingMode::Words, 2),
+            vec![("Manifold", 129), ("Atlas", 116)]
+        );
+        assert_eq!(
+            env.top_notes(&index, SortingMode::Tags, 10),
+            vec![
+                ("Atlas", 2),
+                ("Chart", 2),
+                ("Manifold", 2),
+                ("Smooth Map", 1)
+            ]
+        );
+        // notes without broken links are not listed
+        assert_eq!(
+            env.top_notes(&index, SortingMode::Broken, 10),
+            vec![("Chart", 1)]
+        );
+    }
+
+    #[test]
+    fn test_env_stats_reciprocity() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        // manifold links back and forth with atlas, lie group and smooth map, all other links of the math notes are one way
+        let mut env = EnvironmentStats::new_with_filter(
+            &index,
+            data::Filter::new("#diffgeo #topology #lietheo", true),
+        );
+        assert_eq!(env.reciprocal_pairs, 3);
+        assert_eq!(env.one_way_links, 7);
+
+        env.sort(index.clone(), SortingMode::Reciprocal, false);
+        assert_eq!(env.get_selected(0).unwrap().id, "manifold");
+        assert_eq!(env.get_selected(0).unwrap().reciprocal_links, 3);
+
+        // chart and manifold link to manifold and topology respectively, but not back
+        let env = EnvironmentStats::new_with_filter(&index, data::Filter::new("#topology", false));
+        assert_eq!(env.reciprocal_pairs, 0);
+        assert_eq!(env.one_way_links, 2);
+    }
+
+    #[test]
+    fn test_env_stats_update_notes() {
+        let tmp = testdir::testdir!();
+        std::fs::write(tmp.join("A.md"), "#x Links to [[B]].").unwrap();
+        std::fs::write(tmp.join("B.md"), "#x Short.").unwrap();
+        std::fs::write(tmp.join("C.md"), "#y Links to [[A]].").unwrap();
+
+        let config = crate::Config::default();
+        let build_index = || {
+            let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+            let builder = io::HtmlBuilder::new(&config, tmp.clone());
+            data::NoteIndexContainer::new(data::NoteIndex::new(tracker, builder).0)
+        };
+
+        let mut env =
+            EnvironmentStats::new_with_filter(&build_index(), data::Filter::new("#x", false));
+        assert_eq!(env.len(), 2);
+
+        std::fs::write(tmp.join("B.md"), "#y No longer matching.").unwrap();
+        std::fs::write(tmp.join("D.md"), "#x Also links to [[A]].").unwrap();
+
+        let index = build_index();
+        let changed = ["b", "d"].map(String::from).into_iter().collect();
+        env.update_notes(&index, &data::PinnedNotes::default(), &changed);
+        let fresh = EnvironmentStats::new_with_filter(&index, data::Filter::new("#x", false));
+
+        let mut ids = env.ids();
+        ids.sort();
+        assert_eq!(ids, vec!["a".to_string(), "d".to_string()]);
+        assert_eq!(env.word_count_total, fresh.word_count_total);
+        assert_eq!(env.tag_counts, fresh.tag_counts);
+        assert_eq!(env.local_local_links, fresh.local_local_links);
+        assert_eq!(env.global_local_links, fresh.global_local_links);
+        assert_eq!(env.orphans, fresh.orphans);
+        assert_eq!(env.sinks, fresh.sinks);
+        assert_eq!(env.sinks, vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn test_env_stats_deterministic_order() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        // all notes match the empty filter equally well, so they are ordered by id
+        let env = EnvironmentStats::new_with_filter(&index, data::Filter::default());
+        let ids = env.ids();
+        assert_eq!(ids.len(), 11);
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(
+            ids,
+            EnvironmentStats::new_with_filter(&index, data::Filter::default()).ids()
+        );
+    }
+
+    #[test]
+    fn test_tag_rename_impact() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        // windows leaves the #os environment and forms its own
+        let impact = EnvironmentStats::tag_rename_impact(&index, "#os/win", "windows");
+        assert_eq!(
+            impact.renamed,
+            vec![("#os/win".to_string(), "#windows".to_string(), 1)]
+        );
+        assert_eq!(impact.notes, vec!["windows".to_string()]);
+        assert!(impact.collisions.is_empty());
+        assert!(impact.merged_notes.is_empty());
+        assert_eq!(
+            impact.environments,
+            vec![("#os".to_string(), 0, 1), ("#windows".to_string(), 1, 0)]
+        );
+
+        // subtags are renamed as well, and #topology is already in use
+        let impact = EnvironmentStats::tag_rename_impact(&index, "#os", "#topology");
+        assert_eq!(impact.renamed.len(), 4);
+        assert_eq!(impact.notes.len(), 4);
+        assert_eq!(impact.collisions, vec![("#topology".to_string(), 3)]);
+        assert_eq!(
+            impact.environments,
+            vec![("#os".to_string(), 0, 4), ("#topology".to_string(), 4, 0)]
+        );
+
+        // prefixes of tags are not renamed
+        let impact = EnvironmentStats::tag_rename_impact(&index, "#o", "#p");
+        assert_eq!(impact, super::TagRenameImpact::default());
+    }
+
+    #[test]
+    fn test_env_stats_per_directory() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        // all notes lie within the common folder
+        let folders = EnvironmentStats::per_directory(&index, 1);
+        assert_eq!(folders.len(), 1);
+        assert_eq!(folders[0].0, "common");
+        assert_eq!(folders[0].1.note_count_total, 11);
+
+        // the math notes form their own folder
+        let folders = EnvironmentStats::per_directory(&index, 3);
+        assert_eq!(
+            folders
+                .iter()
+                .map(|(folder, env)| (folder.as_str(), env.note_count_total))
+                .collect::<Vec<_>>(),
+            vec![("common/notes", 5), ("common/notes/math", 6)]
+        );
+        assert_eq!(folders[1].1.word_count_total, 618);
+
+        // without any depth, all notes are grouped together
+        let folders = EnvironmentStats::per_directory(&index, 0);
+        assert_eq!(folders[0].0, "/");
+        assert_eq!(folders[0].1.note_count_total, 11);
+    }
+
+    #[test]
+    fn test_env_stats_link_density() {
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, std::path::PathBuf::from("./tests")).unwrap();
+        let builder = io::HtmlBuilder::new(&config, std::path::PathBuf::from("./tests"));
+        let index = data::NoteIndex::new(tracker, builder).0;
+
+        let index = data::NoteIndexContainer::new(index);
+
+        let mut env = EnvironmentStats::new_with_filter(
+            &index,
+            data::Filter::new("#diffgeo #topology #lietheo", true),
+        );
+
+        // smooth map is short, so its two links weigh more than the three of atlas and lie group
+        let top = env.top_link_density(&index.borrow(), 100, 3);
+        assert_eq!(
+            top.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+            vec!["Manifold", "Smooth Map", "Atlas"]
+        );
+        assert!((top[1].1 - 200. / 72.).abs() < 1e-9);
+
+        env.sort(index.clone(), SortingMode::LinkDensity, false);
+        assert_eq!(env.get_selected(0).unwrap().id, "manifold");
+        assert_eq!(
+            SortingMode::from_name("density"),
+            Some(SortingMode::LinkDensity)
+        );
+    }
+
+    #[test]
+    fn test_env_stats_embeds() {
+        let tmp = testdir::testdir!();
+        std::fs::write(tmp.join("Source.md"), "![[Target]] ![[Missing]] [[Linked]]").unwrap();
+        std::fs::write(tmp.join("Target.md"), "![[Source]]").unwrap();
+        std::fs::write(tmp.join("Linked.md"), "").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = data::NoteIndex::new(tracker, builder).0;
+        let index = data::NoteIndexContainer::new(index);
+
+        // embeds are counted separately from links
+        let env = EnvironmentStats::new_with_filter(&index, data::Filter::default());
+        assert_eq!(env.embeds, 2);
+        assert_eq!(env.embedded_notes, 2);
+        assert_eq!(env.local_local_links, 1);
+
+        // notes embedded from outside the environment still count as embedded
+        let env = EnvironmentStats::new_with_filter(&index, data::Filter::new("target", false));
+        assert_eq!(env.embeds, 1);
+        assert_eq!(env.embedded_notes, 1);
+    }
+
+    #[test]
+    fn test_env_stats_link_matrix() {
+        let tmp = testdir::testdir!();
+        std::fs::write(tmp.join("Alpha.md"), "#inner [[Beta]] [[Gamma]]").unwrap();
+        std::fs::write(tmp.join("Beta.md"), "#inner [[Alpha]]").unwrap();
+        std::fs::write(tmp.join("Gamma.md"), "[[Delta]] [[Alpha]]").unwrap();
+        std::fs::write(tmp.join("Delta.md"), "[[Gamma]]").unwrap();
+
+        let config = crate::Config::default();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = data::NoteIndex::new(tracker, builder).0;
+        let index = data::NoteIndexContainer::new(index);
+
+        let global = EnvironmentStats::new_with_filter(&index, data::Filter::default());
+        let env = EnvironmentStats::new_with_filter(&index, data::Filter::new("#inner", false));
+
+        assert_eq!(env.link_matrix(&global), [[2, 1], [1, 2]]);
+        assert_eq!(global.link_matrix(&global), [[6, 0], [0, 0]]);
+
+        let coverage = env.coverage(&global);
+        assert_eq!(coverage[0], ("Notes", 2, 4));
+        assert_eq!(coverage[2], ("Tags", 1, 1));
+        assert_eq!(coverage[3], ("Links", 2, 6));
+    }
 }
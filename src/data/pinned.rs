@@ -0,0 +1,63 @@
+use std::{collections::HashSet, path};
+
+use crate::{data, error};
+
+/// The set of notes the user pinned, which are shown at the top of every environment regardless of the active filter.
+/// The pinned notes are persisted between sessions in a state file of each vault in the config directory, see [data::vault_file_name].
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PinnedNotes {
+    /// The ids of all pinned notes.
+    ids: HashSet<String>,
+}
+
+impl PinnedNotes {
+    /// Loads the pinned notes of the vault at the given path from their state file, creating an empty set if none exists.
+    pub fn load(vault: &path::Path) -> error::Result<Self> {
+        Ok(confy::load(
+            "rucola",
+            data::vault_file_name("pinned", vault).as_str(),
+        )?)
+    }
+
+    /// Writes the pinned notes to the state file of the vault at the given path.
+    pub fn store(&self, vault: &path::Path) -> error::Result<()> {
+        Ok(confy::store(
+            "rucola",
+            data::vault_file_name("pinned", vault).as_str(),
+            self,
+        )?)
+    }
+
+    /// Checks if the note with the given id is pinned.
+    pub fn contains(&self, id: &str) -> bool {
+        self.ids.contains(id)
+    }
+
+    /// Pins the note with the given id if it was not pinned, and unpins it otherwise.
+    /// Returns wether the note is now pinned.
+    pub fn toggle(&mut self, id: &str) -> bool {
+        if self.ids.remove(id) {
+            false
+        } else {
+            self.ids.insert(id.to_owned());
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PinnedNotes;
+
+    #[test]
+    fn test_toggle() {
+        let mut pinned = PinnedNotes::default();
+
+        assert!(!pinned.contains("chart"));
+        assert!(pinned.toggle("chart"));
+        assert!(pinned.contains("chart"));
+        assert!(!pinned.contains("atlas"));
+        assert!(!pinned.toggle("chart"));
+        assert!(!pinned.contains("chart"));
+    }
+}
@@ -0,0 +1,185 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    path, time,
+};
+
+use itertools::Itertools;
+
+use crate::{data, error};
+
+/// The parts of a note remembered in a snapshot.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct NoteSnapshot {
+    /// The number of words of the note.
+    words: usize,
+    /// The ids of all notes the note linked to, resolving aliases.
+    links: BTreeSet<String>,
+}
+
+/// A copy of the parts of an index relevant to track how notes and their links change over time.
+/// The last snapshot is persisted between sessions in a state file of each vault in the config directory, see [data::vault_file_name].
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct IndexSnapshot {
+    /// The point in time the snapshot was taken, if one has been taken at all.
+    taken: Option<time::SystemTime>,
+    /// The remembered notes by their id.
+    notes: HashMap<String, NoteSnapshot>,
+}
+
+impl IndexSnapshot {
+    /// Takes a snapshot of the given index at the current point in time.
+    pub fn new(index: &data::NoteIndex) -> Self {
+        Self {
+            taken: Some(time::SystemTime::now()),
+            notes: index
+                .ids()
+                .filter_map(|id| index.get(id).map(|note| (id, note)))
+                .map(|(id, note)| {
+                    (
+                        id.clone(),
+                        NoteSnapshot {
+                            words: note.words,
+                            links: note
+                                .links
+                                .iter()
+                                .map(|link| index.resolve_link(note, link).to_string())
+                                .collect(),
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Loads the last snapshot of the vault at the given path from its state file, creating an empty one if none exists.
+    pub fn load(vault: &path::Path) -> error::Result<Self> {
+        Ok(confy::load(
+            "rucola",
+            data::vault_file_name("snapshot", vault).as_str(),
+        )?)
+    }
+
+    /// Writes this snapshot to the state file of the vault at the given path, replacing the last one.
+    pub fn store(&self, vault: &path::Path) -> error::Result<()> {
+        Ok(confy::store(
+            "rucola",
+            data::vault_file_name("snapshot", vault).as_str(),
+            self,
+        )?)
+    }
+
+    /// The point in time the snapshot was taken, if one has been taken at all.
+    pub fn taken(&self) -> Option<time::SystemTime> {
+        self.taken
+    }
+
+    /// Compares this snapshot to the given index, reporting all changes made since it was taken.
+    pub fn diff(&self, index: &data::NoteIndex) -> ChangeReport {
+        let current = Self::new(index);
+
+        let link_pairs = |snapshot: &Self| {
+            snapshot
+                .notes
+                .iter()
+                .flat_map(|(id, note)| note.links.iter().map(|link| (id.clone(), link.clone())))
+                .collect::<BTreeSet<_>>()
+        };
+        let old_links = link_pairs(self);
+        let new_links = link_pairs(&current);
+
+        ChangeReport {
+            added: current
+                .notes
+                .keys()
+                .filter(|id| !self.notes.contains_key(*id))
+                .cloned()
+                .sorted()
+                .collect(),
+            removed: self
+                .notes
+                .keys()
+                .filter(|id| !current.notes.contains_key(*id))
+                .cloned()
+                .sorted()
+                .collect(),
+            links_added: new_links.difference(&old_links).cloned().collect(),
+            links_removed: old_links.difference(&new_links).cloned().collect(),
+            word_deltas: current
+                .notes
+                .iter()
+                .filter_map(|(id, note)| {
+                    self.notes
+                        .get(id)
+                        .map(|old| (id.clone(), note.words as isize - old.words as isize))
+                })
+                .filter(|(_, delta)| *delta != 0)
+                .sorted_by(|(id_a, delta_a), (id_b, delta_b)| {
+                    delta_b
+                        .abs()
+                        .cmp(&delta_a.abs())
+                        .then_with(|| id_a.cmp(id_b))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The changes made to the notes of an index since a snapshot was taken, see [IndexSnapshot::diff].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ChangeReport {
+    /// The ids of all notes created since the snapshot, sorted.
+    pub added: Vec<String>,
+    /// The ids of all notes deleted since the snapshot, sorted.
+    pub removed: Vec<String>,
+    /// The source and target ids of all links created since the snapshot, sorted.
+    pub links_added: Vec<(String, String)>,
+    /// The source and target ids of all links deleted since the snapshot, sorted.
+    pub links_removed: Vec<(String, String)>,
+    /// The change in word count of all notes present both in the snapshot and now whose word count changed, largest changes first.
+    pub word_deltas: Vec<(String, isize)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{data, io};
+
+    #[test]
+    fn test_diff() {
+        let tmp = testdir::testdir!();
+        std::fs::write(tmp.join("A.md"), "Links to [[B]] and [[C]].").unwrap();
+        std::fs::write(tmp.join("B.md"), "Short.").unwrap();
+        std::fs::write(tmp.join("C.md"), "Gone soon.").unwrap();
+
+        let config = crate::Config::default();
+        let build_index = || {
+            let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+            let builder = io::HtmlBuilder::new(&config, tmp.clone());
+            data::NoteIndex::new(tracker, builder).0
+        };
+
+        let snapshot = super::IndexSnapshot::new(&build_index());
+        assert!(snapshot.taken().is_some());
+        assert_eq!(
+            snapshot.diff(&build_index()),
+            super::ChangeReport::default()
+        );
+
+        std::fs::write(tmp.join("A.md"), "Links to [[B]] and [[D]] only.").unwrap();
+        std::fs::write(tmp.join("B.md"), "Not so short anymore.").unwrap();
+        std::fs::remove_file(tmp.join("C.md")).unwrap();
+        std::fs::write(tmp.join("D.md"), "New.").unwrap();
+
+        let report = snapshot.diff(&build_index());
+        assert_eq!(report.added, vec!["d".to_string()]);
+        assert_eq!(report.removed, vec!["c".to_string()]);
+        assert_eq!(report.links_added, vec![("a".to_string(), "d".to_string())]);
+        assert_eq!(
+            report.links_removed,
+            vec![("a".to_string(), "c".to_string())]
+        );
+        assert_eq!(
+            report.word_deltas,
+            vec![("b".to_string(), 3), ("a".to_string(), 1)]
+        );
+    }
+}
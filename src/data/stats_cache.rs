@@ -0,0 +1,101 @@
+use std::{path, time};
+
+use crate::error;
+
+/// How long cached statistics are considered current, unless the vault changed in the meantime.
+const MAX_AGE: time::Duration = time::Duration::from_secs(24 * 60 * 60);
+
+/// The key figures of a vault, as shown in the global statistics.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct VaultSummary {
+    /// The amount of notes.
+    pub notes: usize,
+    /// The total amount of words.
+    pub words: usize,
+    /// The total amount of characters.
+    pub characters: usize,
+    /// The amount of unique tags.
+    pub tags: usize,
+    /// The amount of links between notes.
+    pub links: usize,
+    /// The amount of links without a valid target.
+    pub broken_links: usize,
+}
+
+/// The statistics of the vault of the last session, persisted in a state file in the config directory.
+/// They are shown while the vault is indexed at startup, until the current statistics are available.
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct StatsCache {
+    /// The vault the statistics were computed for.
+    vault: Option<path::PathBuf>,
+    /// The point in time the statistics were cached.
+    taken: Option<time::SystemTime>,
+    /// The cached statistics.
+    summary: VaultSummary,
+}
+
+impl StatsCache {
+    /// Caches the given statistics of the vault at the given path at the current point in time.
+    pub fn new(vault: &path::Path, summary: VaultSummary) -> Self {
+        Self {
+            vault: Some(vault.to_path_buf()),
+            taken: Some(time::SystemTime::now()),
+            summary,
+        }
+    }
+
+    /// Loads the cached statistics from their state file, creating an empty cache if none exists.
+    pub fn load() -> error::Result<Self> {
+        Ok(confy::load("rucola", "stats-cache")?)
+    }
+
+    /// Writes the cached statistics to their state file, replacing the previous ones.
+    pub fn store(&self) -> error::Result<()> {
+        Ok(confy::store("rucola", "stats-cache", self)?)
+    }
+
+    /// Returns the cached statistics with the vault they belong to and wether they are stale, if any were cached.
+    /// Statistics are stale once they are older than a day, or if the vault folder was modified after they were cached.
+    /// As only the vault folder itself is checked, changes in subfolders go unnoticed until the statistics expire.
+    pub fn summary(&self) -> Option<(&path::Path, VaultSummary, bool)> {
+        let vault = self.vault.as_deref()?;
+        let taken = self.taken?;
+
+        let expired = taken.elapsed().map_or(true, |age| age > MAX_AGE);
+        let modified = std::fs::metadata(vault)
+            .and_then(|metadata| metadata.modified())
+            .map_or(true, |modified| modified > taken);
+
+        Some((vault, self.summary, expired || modified))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_cache_staleness() {
+        assert!(StatsCache::default().summary().is_none());
+
+        let tmp = testdir::testdir!();
+        let summary = VaultSummary {
+            notes: 3,
+            ..Default::default()
+        };
+
+        let cache = StatsCache::new(&tmp, summary);
+        assert_eq!(cache.summary(), Some((tmp.as_path(), summary, false)));
+
+        // expired statistics
+        let old = StatsCache {
+            taken: time::SystemTime::now().checked_sub(MAX_AGE * 2),
+            ..cache.clone()
+        };
+        assert!(old.summary().unwrap().2);
+
+        // vault removed in the meantime
+        let gone = StatsCache::new(&tmp.join("missing"), summary);
+        assert!(gone.summary().unwrap().2);
+    }
+}
@@ -29,6 +29,12 @@ pub enum RucolaError {
     NotifyEventError(notify::Event),
     #[error("Failed to create parse options.")]
     ComrakError,
+    #[error("Failed to serialize statistics or cached notes: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("{0} notes share their id with another note and were given a different one, see the statistics screen.")]
+    IdCollisions(usize),
+    #[error("{} notes were changed outside of rucola since they were read: {0:?}", .0.len())]
+    ExternalChange(Vec<std::path::PathBuf>),
 }
 
 impl RucolaError {
@@ -1,4 +1,5 @@
 use crate::{data, error};
+use itertools::Itertools;
 use std::{fs, io::Write, path, process};
 
 /// Saves configurations to manipulate the file system the notes are stored in.
@@ -10,6 +11,12 @@ pub struct FileManager {
     default_extension: String,
     /// The editor to use for notes
     editor: Option<Vec<String>>,
+    /// The folder of note templates, if one is configured.
+    templates: Option<path::PathBuf>,
+    /// The folder deleted notes are moved to, if one is configured.
+    trash: Option<path::PathBuf>,
+    /// The folder the undo journal is stored in, the config directory if none is configured.
+    journal_folder: Option<path::PathBuf>,
 }
 impl Default for FileManager {
     fn default() -> Self {
@@ -23,12 +30,22 @@ impl Default for FileManager {
 impl FileManager {
     pub fn new(config: &crate::Config, vault_path: path::PathBuf) -> Self {
         Self {
+            templates: (!config.templates.is_empty())
+                .then(|| vault_path.join(config.templates.trim_matches('/'))),
+            trash: (!config.trash.is_empty())
+                .then(|| vault_path.join(config.trash.trim_matches('/'))),
+            journal_folder: config.journal_folder.clone(),
             vault_path,
             default_extension: config.default_extension.clone(),
             editor: config.editor.clone(),
         }
     }
 
+    /// Returns the path of the undo journal of this vault.
+    fn journal_path(&self) -> error::Result<path::PathBuf> {
+        super::UndoJournal::file_path(self.journal_folder.as_deref(), &self.vault_path)
+    }
+
     /// Takes in a PathBuf and, if the current file extension is not set, append the default one.
     pub fn ensure_file_extension(&self, path: &mut path::PathBuf) {
         if path.extension().is_none() {
@@ -40,12 +57,14 @@ impl FileManager {
     /// Then retrieves the note of the given id from the index.
     /// Creates a new path from the old path with the new file name.
     /// The new extension is the one from the new path if given, if none is given (and no extension is not valid in the config), then the old extension is reapplied.
-    /// Then moves the old file to the new location and updates the index.
+    /// Then moves the old file to the new location and updates the links in all notes linking to it.
+    /// If any of these notes was changed on disk since it was indexed, nothing is changed and an [error::RucolaError::ExternalChange] listing them is returned instead, unless `overwrite_changes` is set.
     pub fn rename_note_file(
         &self,
         index: data::NoteIndexContainer,
         id: &str,
         new_name: String,
+        overwrite_changes: bool,
     ) -> error::Result<()> {
         // Check that the new name isn't empty
         if new_name.is_empty() {
@@ -66,7 +85,7 @@ impl FileManager {
 
         // Retrieve the old version from the table
         // This will not be changed - all changes to the index are handled by the watcher.
-        let index_b = index.borrow_mut();
+        let index_b = index.borrow();
         let note = index_b
             .get(id)
             .ok_or_else(|| error::RucolaError::NoteNotFound(id.to_owned()))?;
@@ -88,6 +107,20 @@ impl FileManager {
             }
         }
 
+        // Check that no note whose links are updated was changed by e.g. a sync tool since it was read, so these changes are not lost
+        if !overwrite_changes {
+            let changed = index_b
+                .blinks_vec(id)
+                .iter()
+                .filter_map(|(id, _)| index_b.get(id))
+                .filter(|other_note| other_note.changed_on_disk())
+                .map(|other_note| other_note.path.clone())
+                .collect_vec();
+            if !changed.is_empty() {
+                return Err(error::RucolaError::ExternalChange(changed));
+            }
+        }
+
         // ensure parent directory exists
         if let Some(parent) = new_path.parent() {
             if !parent.exists() {
@@ -97,6 +130,10 @@ impl FileManager {
 
         // actual fs copy (early returns if unsuccessfull)
         fs::rename(&note.path, &new_path)?;
+        let mut operation = super::Operation {
+            moves: vec![(note.path.clone(), new_path)],
+            rewrites: vec![],
+        };
 
         // === RENAMING ===
         // Create a regex that find links to the old name or id
@@ -134,9 +171,17 @@ impl FileManager {
                 .open(&other_note.path)?;
             // write new new (mostly old) string into the file
             file.write_all(res.as_bytes())?;
+
+            // remember the old content to be able to undo the rename
+            if res != old_content {
+                let new_content = res.into_owned();
+                operation
+                    .rewrites
+                    .push((other_note.path.clone(), old_content, new_content));
+            }
         }
 
-        Ok(())
+        super::UndoJournal::record(&self.journal_path()?, operation)
     }
 
     pub fn move_note_file(
@@ -145,7 +190,7 @@ impl FileManager {
         id: &str,
         new_path_buf: String,
     ) -> error::Result<()> {
-        let index_b = index.borrow_mut();
+        let index_b = index.borrow();
         // Retrieve the note in question from the table
         // It will not be changed - all changes to the index are handled by the watcher.
         let note = index_b
@@ -169,21 +214,171 @@ impl FileManager {
         // actual fs copy (early returns if unsuccessfull)
         fs::rename(&note.path, &new_path)?;
 
-        Ok(())
+        super::UndoJournal::record(
+            &self.journal_path()?,
+            super::Operation {
+                moves: vec![(note.path.clone(), new_path)],
+                rewrites: vec![],
+            },
+        )
     }
 
-    /// Follows a notes path and deletes it in the file system.
+    /// Follows a notes path and moves it to the trash, keeping its location relative to the vault.
+    /// If a file of the same path is already in the trash, a number is appended to the name of the newly trashed one.
+    /// Without a trash folder, the note is deleted permanently.
     pub fn delete_note_file(&self, index: data::NoteIndexContainer, id: &str) -> error::Result<()> {
         if let Some(note) = index.borrow().get(id) {
-            // Follow its path and delete it
-            fs::remove_file(&note.path)?;
+            let Some(trash) = &self.trash else {
+                // Follow its path and delete it
+                fs::remove_file(&note.path)?;
+                return Ok(());
+            };
+
+            let relative = note
+                .path
+                .strip_prefix(&self.vault_path)
+                .map(|relative| relative.to_path_buf())
+                .unwrap_or_else(|_| note.path.file_name().unwrap_or_default().into());
+
+            let mut target = trash.join(&relative);
+            let mut number = 2;
+            while target.exists() {
+                target.set_file_name(format!(
+                    "{} ({number}){}",
+                    relative
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    relative
+                        .extension()
+                        .map(|extension| format!(".{}", extension.to_string_lossy()))
+                        .unwrap_or_default(),
+                ));
+                number += 1;
+            }
+
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(&note.path, &target)?;
+
+            super::UndoJournal::record(
+                &self.journal_path()?,
+                super::Operation {
+                    moves: vec![(note.path.clone(), target)],
+                    rewrites: vec![],
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Reverts the most recent rename, move or deletion to the trash in this vault, including the links rewritten by it.
+    /// Fails if there is no operation to undo.
+    pub fn undo_last_operation(&self) -> error::Result<()> {
+        if super::UndoJournal::undo_last(&self.journal_path()?)? {
+            Ok(())
+        } else {
+            Err(error::RucolaError::Input(String::from(
+                "There is no operation to undo.",
+            )))
+        }
+    }
+
+    /// Returns the paths of all files in the trash relative to the trash folder, sorted.
+    pub fn trashed_files(&self) -> Vec<path::PathBuf> {
+        self.trash
+            .as_ref()
+            .map(|trash| {
+                ignore::WalkBuilder::new(trash)
+                    .standard_filters(false)
+                    .build()
+                    .flatten()
+                    .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_file()))
+                    .filter_map(|entry| {
+                        entry
+                            .path()
+                            .strip_prefix(trash)
+                            .ok()
+                            .map(|relative| relative.to_path_buf())
+                    })
+                    .sorted()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Moves the file at the given path relative to the trash folder back to the same location within the vault.
+    /// Fails if another file already exists at that location.
+    pub fn restore_trashed_file(&self, relative: &path::Path) -> error::Result<()> {
+        let Some(trash) = &self.trash else {
+            return Ok(());
+        };
+
+        let target = self.vault_path.join(relative);
+        if target.exists() {
+            return Err(error::RucolaError::Input(format!(
+                "Cannot restore {}, as a file of this name already exists.",
+                relative.display()
+            )));
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(trash.join(relative), target)?;
+        Ok(())
+    }
+
+    /// Permanently deletes the file at the given path relative to the trash folder.
+    pub fn delete_trashed_file(&self, relative: &path::Path) -> error::Result<()> {
+        if let Some(trash) = &self.trash {
+            fs::remove_file(trash.join(relative))?;
         }
         Ok(())
     }
 
     /// Creates a note of the given name in the file system (relative to the vault).
-    /// Registration in the index is handled centrally by the file watcher of the index itself.
-    pub fn create_note_file(&self, input_path: &str) -> error::Result<()> {
+    /// Registration in the index is handled centrally by the file watcher of the index itself, unless the caller reads the note right away.
+    /// Returns the path of the new note.
+    pub fn create_note_file(&self, input_path: &str) -> error::Result<path::PathBuf> {
+        // Write an preliminary input, so the file isn't empty (messed with XDG for some reason).
+        self.write_new_note(input_path, |name| format!("#{name}"))
+    }
+
+    /// Creates a note of the given name like [Self::create_note_file], but with the content of the given template.
+    /// Occurrences of `{{title}}` in the template are replaced by the name of the new note.
+    pub fn create_note_from_template(
+        &self,
+        input_path: &str,
+        template: &path::Path,
+    ) -> error::Result<path::PathBuf> {
+        let template = fs::read_to_string(template)?;
+        self.write_new_note(input_path, |name| template.replace("{{title}}", name))
+    }
+
+    /// Returns the paths of all files in the templates folder, sorted.
+    pub fn templates(&self) -> Vec<path::PathBuf> {
+        self.templates
+            .as_ref()
+            .map(|folder| {
+                ignore::WalkBuilder::new(folder)
+                    .build()
+                    .flatten()
+                    .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_file()))
+                    .map(|entry| entry.into_path())
+                    .sorted()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Creates the file of a new note of the given name (relative to the vault) with the content created from its file name.
+    fn write_new_note(
+        &self,
+        input_path: &str,
+        content: impl FnOnce(&str) -> String,
+    ) -> error::Result<path::PathBuf> {
         // Piece together the file path
         let mut path = self.vault_path.clone();
         path.push(input_path);
@@ -201,16 +396,35 @@ impl FileManager {
         // Create the file
         let mut file = fs::File::create(path.clone())?;
 
-        // Write an preliminary input, so the file isn't empty (messed with XDG for some reason).
         write!(
             file,
-            "#{}",
-            path.file_stem()
-                .map(|fs| fs.to_string_lossy().to_string())
-                .unwrap_or_else(|| "note".to_owned())
+            "{}",
+            content(
+                &path
+                    .file_stem()
+                    .map(|fs| fs.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "note".to_owned())
+            )
         )?;
 
-        Ok(())
+        Ok(path)
+    }
+
+    /// Writes the given exported statistics to a new file in the root of the vault, named after the current time and given extension.
+    /// Returns the path of the written file.
+    pub fn write_export(&self, content: &str, extension: &str) -> error::Result<path::PathBuf> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        let mut path = self.vault_path.clone();
+        path.push(format!("rucola-stats-{timestamp}"));
+        path.set_extension(extension);
+
+        fs::write(&path, content)?;
+
+        Ok(path)
     }
 
     /// Attempts to create a command to open the file at the given path to edit it.
@@ -257,6 +471,14 @@ impl FileManager {
             // if it was also not there, throw an error
             .ok_or_else(|| error::RucolaError::ApplicationMissing)
     }
+
+    /// Attempts to create a command to open the file at the given path with the system's default program for its type.
+    /// Used for attachments, which the configured editor is not meant for.
+    pub fn create_open_command(&self, path: &path::Path) -> error::Result<std::process::Command> {
+        open::commands(path)
+            .pop()
+            .ok_or_else(|| error::RucolaError::ApplicationMissing)
+    }
 }
 #[cfg(test)]
 mod tests {
@@ -295,8 +517,58 @@ mod tests {
         assert!(at_path.exists());
 
         // check we can create notes
-        let _lg = crate::data::Note::from_path(&lg_path).unwrap();
-        let _at = crate::data::Note::from_path(&at_path).unwrap();
+        let _lg = crate::data::Note::from_path(&lg_path, Default::default()).unwrap();
+        let _at = crate::data::Note::from_path(&at_path, Default::default()).unwrap();
+    }
+
+    #[test]
+    fn test_templates() {
+        let tmp = testdir::testdir!();
+        std::fs::create_dir(tmp.join("templates")).unwrap();
+        std::fs::write(
+            tmp.join("templates").join("Meeting.md"),
+            "# {{title}}\n#meeting",
+        )
+        .unwrap();
+        std::fs::write(tmp.join("templates").join("Daily.md"), "#daily").unwrap();
+        std::fs::write(tmp.join("Note.md"), "").unwrap();
+
+        let config = crate::Config::default();
+        let fm = super::FileManager::new(&config, tmp.clone());
+
+        let templates = fm.templates();
+        assert_eq!(
+            templates,
+            vec![
+                tmp.join("templates").join("Daily.md"),
+                tmp.join("templates").join("Meeting.md")
+            ]
+        );
+
+        fm.create_note_from_template("Standup", &templates[1])
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(tmp.join("Standup.md")).unwrap(),
+            "# Standup\n#meeting"
+        );
+
+        // templates are not indexed
+        let tracker = crate::io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = crate::io::HtmlBuilder::new(&config, tmp.clone());
+        let index = crate::data::NoteIndex::new(tracker, builder).0;
+        assert!(index.get("meeting").is_none());
+        assert!(index.get("note").is_some());
+        assert!(index.get("standup").is_some());
+
+        // without a templates folder, there are no templates
+        let fm = super::FileManager::new(
+            &crate::Config {
+                templates: String::new(),
+                ..Default::default()
+            },
+            tmp.clone(),
+        );
+        assert!(fm.templates().is_empty());
     }
 
     #[test]
@@ -324,15 +596,18 @@ mod tests {
         assert!(at_path.exists());
 
         // check we can create notes
-        let _lg = crate::data::Note::from_path(&lg_path).unwrap();
-        let _at = crate::data::Note::from_path(&at_path).unwrap();
+        let _lg = crate::data::Note::from_path(&lg_path, Default::default()).unwrap();
+        let _at = crate::data::Note::from_path(&at_path, Default::default()).unwrap();
     }
 
     #[test]
     fn test_delete() {
         let tmp = testdir::testdir!();
 
-        let config = crate::Config::default();
+        let config = crate::Config {
+            journal_folder: Some(tmp.join("journals")),
+            ..Default::default()
+        };
         let fm = super::FileManager::new(&config, tmp.clone());
 
         fm.create_note_file("Lie Group").unwrap();
@@ -349,7 +624,7 @@ mod tests {
         let tracker = crate::io::FileTracker::new(&config, tmp.clone()).unwrap();
         let builder = crate::io::HtmlBuilder::new(&config, tmp.clone());
         let index = crate::data::NoteIndex::new(tracker, builder).0;
-        let index_con = std::rc::Rc::new(std::cell::RefCell::new(index));
+        let index_con = crate::data::NoteIndexContainer::new(index);
 
         fm.delete_note_file(index_con.clone(), "lie-group").unwrap();
         assert!(!lg_path.exists());
@@ -360,11 +635,79 @@ mod tests {
         assert!(!at_path.exists());
     }
 
+    #[test]
+    fn test_trash() {
+        let tmp = testdir::testdir!();
+
+        let config = crate::Config {
+            journal_folder: Some(tmp.join("journals")),
+            ..Default::default()
+        };
+        let fm = super::FileManager::new(&config, tmp.clone());
+
+        fm.create_note_file("Math/Atlas").unwrap();
+        let at_path = tmp.join("Math").join("Atlas.md");
+
+        let tracker = crate::io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = crate::io::HtmlBuilder::new(&config, tmp.clone());
+        let index = crate::data::NoteIndex::new(tracker, builder).0;
+        let index_con = crate::data::NoteIndexContainer::new(index);
+
+        // deleted notes are moved to the trash
+        fm.delete_note_file(index_con.clone(), "atlas").unwrap();
+        assert!(!at_path.exists());
+        assert!(tmp.join(".trash").join("Math").join("Atlas.md").exists());
+
+        // trashing a file of the same path again keeps both
+        fm.create_note_file("Math/Atlas").unwrap();
+        fm.delete_note_file(index_con.clone(), "atlas").unwrap();
+        assert_eq!(
+            fm.trashed_files(),
+            vec![
+                path::PathBuf::from("Math").join("Atlas (2).md"),
+                path::PathBuf::from("Math").join("Atlas.md"),
+            ]
+        );
+
+        // trashed notes are not indexed
+        let tracker = crate::io::FileTracker::new(&config, tmp.clone()).unwrap();
+        assert!(tracker.note_paths().is_empty());
+
+        // restoring moves them back, but does not overwrite
+        let relative = path::PathBuf::from("Math").join("Atlas.md");
+        fm.restore_trashed_file(&relative).unwrap();
+        assert!(at_path.exists());
+        std::fs::write(tmp.join(".trash").join(&relative), "").unwrap();
+        assert!(fm.restore_trashed_file(&relative).is_err());
+
+        // trashed files can be deleted permanently
+        fm.delete_trashed_file(&relative).unwrap();
+        assert_eq!(
+            fm.trashed_files(),
+            vec![path::PathBuf::from("Math").join("Atlas (2).md")]
+        );
+
+        // without a trash folder, notes are deleted permanently
+        let fm = super::FileManager::new(
+            &crate::Config {
+                trash: String::new(),
+                ..Default::default()
+            },
+            tmp.clone(),
+        );
+        fm.delete_note_file(index_con.clone(), "atlas").unwrap();
+        assert!(!at_path.exists());
+        assert_eq!(fm.trashed_files(), Vec::<path::PathBuf>::new());
+    }
+
     #[test]
     fn test_rename() {
         let tmp = testdir::testdir!();
 
-        let config = crate::Config::default();
+        let config = crate::Config {
+            journal_folder: Some(tmp.join("journals")),
+            ..Default::default()
+        };
         let fm = super::FileManager::new(&config, tmp.clone());
 
         let lg_path = tmp.join(String::from("Lie Group.md"));
@@ -388,14 +731,19 @@ mod tests {
         assert!(index.get("atlas").is_some());
         assert!(index.get("lie-group").is_some());
 
-        let index_con = std::rc::Rc::new(std::cell::RefCell::new(index));
+        let index_con = crate::data::NoteIndexContainer::new(index);
 
         assert!(lg_path.exists());
         assert!(at_path.exists());
 
-        fm.rename_note_file(index_con.clone(), "lie-group", String::from("Lie Soup"))
-            .unwrap();
-        fm.rename_note_file(index_con.clone(), "atlas", String::from("Atlantis"))
+        fm.rename_note_file(
+            index_con.clone(),
+            "lie-group",
+            String::from("Lie Soup"),
+            false,
+        )
+        .unwrap();
+        fm.rename_note_file(index_con.clone(), "atlas", String::from("Atlantis"), false)
             .unwrap();
 
         assert!(lg_path_after.exists());
@@ -406,7 +754,10 @@ mod tests {
     fn test_rename_updates_links() {
         let tmp = testdir::testdir!();
 
-        let config = crate::Config::default();
+        let config = crate::Config {
+            journal_folder: Some(tmp.join("journals")),
+            ..Default::default()
+        };
         let fm = super::FileManager::new(&config, tmp.clone());
 
         let at_path = tmp.join(String::from("Atlas.md"));
@@ -437,7 +788,7 @@ mod tests {
         let builder = crate::io::HtmlBuilder::new(&config, tmp.clone());
         let index = crate::data::NoteIndex::new(tracker, builder).0;
 
-        let index_con = std::rc::Rc::new(std::cell::RefCell::new(index));
+        let index_con = crate::data::NoteIndexContainer::new(index);
 
         assert!(at_path.exists());
         assert!(ma_path.exists());
@@ -450,11 +801,22 @@ mod tests {
         assert!(ma_content.contains("[[Topology|topological space]]"));
         assert!(!ma_content.contains("[[Anthology|topological space]]"));
 
-        fm.rename_note_file(index_con.clone(), "topology", String::from("Anthology"))
-            .unwrap();
+        fm.rename_note_file(
+            index_con.clone(),
+            "topology",
+            String::from("Anthology"),
+            false,
+        )
+        .unwrap();
 
         // since we are not updating the index in between, topology must be done before atlas
-        fm.rename_note_file(index_con.clone(), "atlas", String::from("Atlantis"))
+        // and the changes to manifold by the first rename must be overwritten knowingly
+        assert!(matches!(
+            fm.rename_note_file(index_con.clone(), "atlas", String::from("Atlantis"), false),
+            Err(crate::error::RucolaError::ExternalChange(paths)) if paths.len() == 1 && paths[0].ends_with("Manifold.md")
+        ));
+        assert!(at_path.exists());
+        fm.rename_note_file(index_con.clone(), "atlas", String::from("Atlantis"), true)
             .unwrap();
 
         let ma_content = std::fs::read_to_string(&ma_path).unwrap();
@@ -468,7 +830,10 @@ mod tests {
     fn test_move() {
         let tmp = testdir::testdir!();
 
-        let config = crate::Config::default();
+        let config = crate::Config {
+            journal_folder: Some(tmp.join("journals")),
+            ..Default::default()
+        };
         let fm = super::FileManager::new(&config, tmp.clone());
 
         let lg_path = tmp.join(String::from("Lie Group.md"));
@@ -492,7 +857,7 @@ mod tests {
         let builder = crate::io::HtmlBuilder::new(&config, tmp.clone());
         let index = crate::data::NoteIndex::new(tracker, builder).0;
 
-        let index_con = std::rc::Rc::new(std::cell::RefCell::new(index));
+        let index_con = crate::data::NoteIndexContainer::new(index);
 
         assert!(lg_path.exists());
         assert!(at_path.exists());
@@ -532,4 +897,17 @@ mod tests {
         assert_eq!(md_ending, md_ending_tar);
         assert_eq!(txt_ending, txt_ending_tar);
     }
+
+    #[test]
+    fn test_write_export() {
+        let tmp = testdir::testdir!();
+
+        let fm = super::FileManager::new(&crate::Config::default(), tmp.clone());
+
+        let path = fm.write_export("id,name", "csv").unwrap();
+
+        assert_eq!(path.parent(), Some(tmp.as_path()));
+        assert_eq!(path.extension().unwrap(), "csv");
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "id,name");
+    }
 }
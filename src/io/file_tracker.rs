@@ -1,10 +1,11 @@
+use std::collections::HashSet;
 use std::path;
-use std::sync::mpsc::{self, TryIter};
+use std::sync::{mpsc, Mutex};
 
 use itertools::Itertools;
 use notify::Watcher;
 
-use crate::error;
+use crate::{data, error};
 
 /// Stores configuration to track the file system the notes are stored in.
 #[derive(Debug)]
@@ -13,13 +14,35 @@ pub struct FileTracker {
     vault_path: path::PathBuf,
     /// File types to consider notes
     file_types: ignore::types::Types,
+    /// Files and folders to exclude from the vault.
+    excludes: ignore::overrides::Override,
+    /// How to derive the ids of the notes in the vault.
+    id_scheme: data::IdScheme,
+    /// How to parse notes.
+    parse_options: data::ParseOptions,
+    /// The size in bytes above which only the beginning of a note is read, if any.
+    max_note_size: Option<u64>,
+    /// Lowercase file extensions of attachments.
+    attachment_extensions: Vec<String>,
     /// Watcher that checks for file changes in the vault directory and needs to be kept alive with this index.
     /// Can be unused because it is just here for RAII.
     #[allow(unused)]
     watcher: notify::INotifyWatcher,
     /// Channel from which file change events in the vault directory are deposited by the watcher and can be requested.
-    file_change_channel: mpsc::Receiver<Result<notify::Event, notify::Error>>,
+    /// Kept behind a mutex so the index can be shared between the threads computing statistics.
+    file_change_channel: Mutex<mpsc::Receiver<Result<notify::Event, notify::Error>>>,
 }
+/// Everything besides the note files themselves that determines the notes read by a file tracker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ReadSettings {
+    /// How the ids of notes are derived.
+    pub id_scheme: data::IdScheme,
+    /// How notes are parsed.
+    pub parse_options: data::ParseOptions,
+    /// The size in bytes above which only the beginning of a note is read, if any.
+    pub max_note_size: Option<u64>,
+}
+
 impl Default for FileTracker {
     fn default() -> Self {
         Self::new(
@@ -39,6 +62,20 @@ impl FileTracker {
             types_builder.select(name);
         }
 
+        // Pre-calculate excluded files, negating the patterns as overrides are whitelists by default
+        let mut excludes_builder = ignore::overrides::OverrideBuilder::new(&vault_path);
+        for pattern in config.exclude.iter() {
+            excludes_builder.add(&format!("!{pattern}"))?;
+        }
+        // Templates are no notes of their own
+        if !config.templates.is_empty() {
+            excludes_builder.add(&format!("!/{}/", config.templates.trim_matches('/')))?;
+        }
+        // Neither are deleted notes in the trash
+        if !config.trash.is_empty() {
+            excludes_builder.add(&format!("!/{}/", config.trash.trim_matches('/')))?;
+        }
+
         // Create asynchronous channel for file events.
         let (sender, receiver) = mpsc::channel();
 
@@ -51,8 +88,20 @@ impl FileTracker {
         Ok(Self {
             vault_path,
             file_types: types_builder.build()?,
+            excludes: excludes_builder.build()?,
+            id_scheme: config.id_scheme,
+            parse_options: data::ParseOptions {
+                tag_syntax: config.tag_syntax,
+                counting: config.text_counting,
+            },
+            max_note_size: (config.max_note_size > 0).then_some(config.max_note_size * 1024),
+            attachment_extensions: config
+                .attachment_extensions
+                .iter()
+                .map(|extension| extension.to_lowercase())
+                .collect(),
             watcher,
-            file_change_channel: receiver,
+            file_change_channel: Mutex::new(receiver),
         })
     }
 
@@ -63,27 +112,125 @@ impl FileTracker {
             .watch(&self.vault_path, notify::RecursiveMode::Recursive)
     }
 
-    /// Returns a file walker that iterates over all notes to index.
+    /// Returns the path of the vault this tracker watches.
+    pub fn vault_path(&self) -> &path::Path {
+        &self.vault_path
+    }
+
+    /// Returns the id of the given note according to the configured id scheme.
+    pub fn note_id(&self, note: &data::Note) -> String {
+        self.id_scheme.id(note, &self.vault_path)
+    }
+
+    /// Returns the configured settings notes are read with.
+    pub fn read_settings(&self) -> ReadSettings {
+        ReadSettings {
+            id_scheme: self.id_scheme,
+            parse_options: self.parse_options,
+            max_note_size: self.max_note_size,
+        }
+    }
+
+    /// Reads the note at the given path with the configured parse options.
+    /// Of notes larger than the maximum note size, only the beginning is read.
+    pub fn read_note(&self, path: &path::Path) -> error::Result<data::Note> {
+        match self.max_note_size {
+            Some(limit) if std::fs::metadata(path)?.len() > limit => {
+                data::Note::from_path_limited(path, self.parse_options, limit)
+            }
+            _ => self.read_full_note(path),
+        }
+    }
+
+    /// Reads the whole note at the given path with the configured parse options, regardless of its size.
+    pub fn read_full_note(&self, path: &path::Path) -> error::Result<data::Note> {
+        data::Note::from_path(path, self.parse_options)
+    }
+
+    /// Returns a file walker that iterates over all notes to index, sorted by file name.
+    /// Symbolic links are followed, cycles of them are reported as errors by the walker instead.
     pub fn get_walker(&self) -> ignore::Walk {
         ignore::WalkBuilder::new(&self.vault_path)
             .types(self.file_types.clone())
+            .overrides(self.excludes.clone())
+            .follow_links(true)
+            .sort_by_file_name(|a, b| a.cmp(b))
             .build()
     }
 
+    /// Returns the paths of all note files to index.
+    /// Files reachable by multiple paths through symbolic or hard links are only returned by the first of them.
+    pub fn note_paths(&self) -> Vec<path::PathBuf> {
+        unique_files(self.get_walker())
+    }
+
     /// Wether the given path is supposed to be tracked by rucola or not.
-    /// Checks for file endings and gitignore
+    /// Checks for file endings and gitignore, and that the file was not already reached by another path.
     pub fn is_tracked(&self, path: &path::PathBuf) -> bool {
-        self.get_walker()
-            .flatten()
-            .map(|dir_entry| dir_entry.path().to_path_buf())
-            .contains(path)
+        self.note_paths().contains(path)
     }
 
-    /// Returns an iterator over all events found by this tracker since the last check.
-    pub fn try_events_iter(&self) -> TryIter<'_, Result<notify::Event, notify::Error>> {
-        self.file_change_channel.try_iter()
+    /// Wether the given path has the extension of an attachment, ignoring case.
+    pub fn is_attachment(&self, path: &path::Path) -> bool {
+        path.extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .is_some_and(|extension| self.attachment_extensions.contains(&extension))
+    }
+
+    /// Returns the sorted paths of all attachments in the vault, respecting gitignore and the excluded files.
+    pub fn attachments(&self) -> Vec<path::PathBuf> {
+        unique_files(
+            ignore::WalkBuilder::new(&self.vault_path)
+                .overrides(self.excludes.clone())
+                .follow_links(true)
+                .build(),
+        )
+        .into_iter()
+        .filter(|path| self.is_attachment(path))
+        .sorted()
+        .collect()
     }
+
+    /// Returns all events found by this tracker since the last check.
+    pub fn try_events(&self) -> Vec<Result<notify::Event, notify::Error>> {
+        self.file_change_channel
+            .lock()
+            .map(|receiver| receiver.try_iter().collect())
+            .unwrap_or_default()
+    }
+}
+/// Returns the paths of all files the given walker reaches, skipping errors and files already reached by another path.
+fn unique_files(walker: ignore::Walk) -> Vec<path::PathBuf> {
+    let mut seen = HashSet::new();
+    walker
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file())
+        })
+        .map(|entry| entry.into_path())
+        .filter(|path| file_identity(path).map_or(true, |identity| seen.insert(identity)))
+        .collect()
 }
+
+/// Identifies the file at the given path independently of the path it is reached by.
+/// On unix, hard links to the same file share their device and inode.
+#[cfg(unix)]
+fn file_identity(path: &path::Path) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path)
+        .ok()
+        .map(|metadata| (metadata.dev(), metadata.ino()))
+}
+
+/// Identifies the file at the given path independently of the path it is reached by.
+/// Elsewhere, only symbolic links are resolved.
+#[cfg(not(unix))]
+fn file_identity(path: &path::Path) -> Option<path::PathBuf> {
+    std::fs::canonicalize(path).ok()
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -175,6 +322,84 @@ mod tests {
         assert!(tracker.is_tracked(&rs));
     }
 
+    #[test]
+    fn test_tracker_exclude() {
+        let tmp = testdir::testdir!();
+        std::fs::create_dir_all(tmp.join("templates")).unwrap();
+        std::fs::create_dir_all(tmp.join("sub").join("deeper")).unwrap();
+        std::fs::write(tmp.join("templates").join("Daily.md"), "").unwrap();
+        std::fs::write(tmp.join("Drawing.excalidraw.md"), "").unwrap();
+        std::fs::write(tmp.join("Note.md"), "").unwrap();
+        std::fs::write(tmp.join("sub").join("deeper").join("Deep.md"), "").unwrap();
+
+        let tracker = super::FileTracker::new(
+            &crate::Config {
+                exclude: vec!["templates/".to_owned(), "*.excalidraw.md".to_owned()],
+                ..Default::default()
+            },
+            tmp.clone(),
+        )
+        .unwrap();
+
+        assert!(tracker.is_tracked(&tmp.join("Note.md")));
+        assert!(tracker.is_tracked(&tmp.join("sub").join("deeper").join("Deep.md")));
+        assert!(!tracker.is_tracked(&tmp.join("templates").join("Daily.md")));
+        assert!(!tracker.is_tracked(&tmp.join("Drawing.excalidraw.md")));
+
+        assert!(super::FileTracker::new(
+            &crate::Config {
+                exclude: vec!["[".to_owned()],
+                ..Default::default()
+            },
+            tmp.clone(),
+        )
+        .is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_tracker_links() {
+        let tmp = testdir::testdir!();
+        std::fs::create_dir(tmp.join("real")).unwrap();
+        std::fs::write(tmp.join("real").join("Note.md"), "").unwrap();
+        // a symlinked folder, a cycle and a hard link
+        std::os::unix::fs::symlink(tmp.join("real"), tmp.join("linked")).unwrap();
+        std::os::unix::fs::symlink(tmp.clone(), tmp.join("real").join("cycle")).unwrap();
+        std::fs::hard_link(tmp.join("real").join("Note.md"), tmp.join("Hard.md")).unwrap();
+        std::fs::write(tmp.join("Other.md"), "").unwrap();
+
+        let tracker = super::FileTracker::new(&crate::Config::default(), tmp.clone()).unwrap();
+
+        assert_eq!(
+            tracker.note_paths(),
+            vec![tmp.join("Hard.md"), tmp.join("Other.md")]
+        );
+        assert!(tracker.is_tracked(&tmp.join("Hard.md")));
+        assert!(!tracker.is_tracked(&tmp.join("linked").join("Note.md")));
+        assert!(!tracker.is_tracked(&tmp.join("real").join("Note.md")));
+    }
+
+    #[test]
+    fn test_tracker_attachments() {
+        let tmp = testdir::testdir!();
+        std::fs::create_dir(tmp.join("images")).unwrap();
+        std::fs::write(tmp.join("images").join("Diagram.PNG"), "").unwrap();
+        std::fs::write(tmp.join("paper.pdf"), "").unwrap();
+        std::fs::write(tmp.join("Note.md"), "").unwrap();
+
+        let tracker = super::FileTracker::new(&crate::Config::default(), tmp.clone()).unwrap();
+
+        assert!(tracker.is_attachment(path::Path::new("a.png")));
+        assert!(!tracker.is_attachment(path::Path::new("a.md")));
+        assert_eq!(
+            tracker.attachments(),
+            vec![
+                tmp.join("images").join("Diagram.PNG"),
+                tmp.join("paper.pdf")
+            ]
+        );
+    }
+
     // #[test]
     // fn test_watcher_create() {
     //     let tmp = testdir::testdir!();
@@ -213,7 +438,10 @@ mod tests {
     fn test_watcher_rename() {
         let tmp = testdir::testdir!();
 
-        let config = crate::Config::default();
+        let config = crate::Config {
+            journal_folder: Some(tmp.join("journals")),
+            ..Default::default()
+        };
         let fm = crate::io::FileManager::new(&config, tmp.clone());
         fm.create_note_file("Lie Group").unwrap();
         fm.create_note_file("Math/Atlas").unwrap();
@@ -221,19 +449,37 @@ mod tests {
         let tracker = crate::io::FileTracker::new(&config, tmp.clone()).unwrap();
         let builder = crate::io::HtmlBuilder::new(&config, tmp.clone());
         let index = crate::data::NoteIndex::new(tracker, builder).0;
-        let index_con = std::rc::Rc::new(std::cell::RefCell::new(index));
+        let index_con = crate::data::NoteIndexContainer::new(index);
+        let watcher = crate::data::IndexWatcher::start(&index_con);
 
         assert!(index_con.borrow().get("atlas").is_some());
         assert!(index_con.borrow().get("lie-group").is_some());
         assert!(index_con.borrow().get("atlantis").is_none());
         assert!(index_con.borrow().get("lie-soup").is_none());
 
-        fm.rename_note_file(index_con.clone(), "atlas", String::from("Atlantis"))
-            .unwrap();
-        fm.rename_note_file(index_con.clone(), "lie-group", String::from("Lie Soup"))
+        fm.rename_note_file(index_con.clone(), "atlas", String::from("Atlantis"), false)
             .unwrap();
+        fm.rename_note_file(
+            index_con.clone(),
+            "lie-group",
+            String::from("Lie Soup"),
+            false,
+        )
+        .unwrap();
 
-        let (modifications, mut id_changes) = index_con.borrow_mut().handle_file_events().unwrap();
+        // wait for the watcher to read both renames
+        let mut modifications = false;
+        let mut id_changes = vec![];
+        for _ in 0..100 {
+            let (batch_modifications, batch_id_changes, errors) = watcher.apply(&index_con);
+            assert!(errors.is_empty());
+            modifications |= batch_modifications;
+            id_changes.extend(batch_id_changes);
+            if id_changes.len() >= 2 {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
         id_changes.sort_unstable_by(|(a1, _b1), (a2, _b2)| a1.cmp(a2));
 
         assert!(modifications);
@@ -276,7 +522,7 @@ mod tests {
     //     let tracker = crate::io::FileTracker::new(&config, tmp.clone()).unwrap();
     //     let builder = crate::io::HtmlBuilder::new(&config, tmp.clone());
     //     let index = crate::data::NoteIndex::new(tracker, builder).0;
-    //     let mut index_con = std::rc::Rc::new(std::cell::RefCell::new(index));
+    //     let mut index_con = crate::data::NoteIndexContainer::new(index);
 
     //     assert!(index_con.borrow().get("atlas").is_some());
     //     assert!(index_con.borrow().get("lie-group").is_some());
@@ -328,7 +574,7 @@ mod tests {
     //     let tracker = crate::io::FileTracker::new(&config, tmp.clone()).unwrap();
     //     let builder = crate::io::HtmlBuilder::new(&config, tmp.clone());
     //     let index = crate::data::NoteIndex::new(tracker, builder).0;
-    //     let index_con = std::rc::Rc::new(std::cell::RefCell::new(index));
+    //     let index_con = crate::data::NoteIndexContainer::new(index);
 
     //     assert!(index_con.borrow().get("atlas").is_some());
     //     assert!(index_con.borrow().get("lie-group").is_some());
@@ -88,6 +88,8 @@ impl HtmlBuilder {
                 extension: comrak::ExtensionOptionsBuilder::default()
                     .wikilinks_title_after_pipe(true)
                     .math_dollars(true)
+                    // the frontmatter is metadata and not rendered
+                    .front_matter_delimiter(Some(String::from("---")))
                     .build()
                     .map_err(|_e| error::RucolaError::ComrakError)?,
                 ..Default::default()
@@ -101,7 +103,17 @@ impl HtmlBuilder {
             // correct id urls for wiki links
             match node.data.borrow_mut().value {
                 comrak::nodes::NodeValue::WikiLink(ref mut link) => {
-                    link.url = format!("{}.html", data::name_to_id(&link.url));
+                    link.url = format!(
+                        "{}.html{}",
+                        data::name_to_id(&link.url),
+                        anchor_suffix(&link.url)
+                    );
+                }
+                // and markdown links to other notes
+                comrak::nodes::NodeValue::Link(ref mut link) => {
+                    if let Some(id) = data::link_url_to_id(&link.url) {
+                        link.url = format!("{id}.html{}", anchor_suffix(&link.url));
+                    }
                 }
                 comrak::nodes::NodeValue::Math(ref mut math) => {
                     contains_math = true;
@@ -136,20 +148,34 @@ impl HtmlBuilder {
         let mut tar_file = fs::File::create(&tar_path)?;
 
         writeln!(tar_file, "<!DOCTYPE html>")?;
-        writeln!(tar_file, "<title>{}</title>", note.name)?;
+        writeln!(tar_file, "<title>{}</title>", note.title())?;
         self.add_preamble(&mut tar_file, contains_math, contains_code)?;
 
+        let mut html = vec![];
         comrak::format_html(
             root,
             &comrak::Options {
                 extension: comrak::ExtensionOptionsBuilder::default()
                     .wikilinks_title_after_pipe(true)
                     .math_dollars(true)
+                    // give headings ids, so links can point to them
+                    .header_ids(Some(String::new()))
                     .build()
                     .map_err(|_e| error::RucolaError::ComrakError)?,
                 ..Default::default()
             },
-            &mut tar_file,
+            &mut html,
+        )?;
+
+        // turn block ids such as `^block` at the end of paragraphs and list items into elements links can point to
+        let block_ids = regex::Regex::new(r"(?m) \^([A-Za-z0-9-]+)(</p>|</li>|$)")?;
+        tar_file.write_all(
+            block_ids
+                .replace_all(
+                    &String::from_utf8_lossy(&html),
+                    r#"<span id="^$1"></span>$2"#,
+                )
+                .as_bytes(),
         )?;
 
         Ok(())
@@ -228,14 +254,26 @@ impl HtmlBuilder {
         }
         Ok(())
     }
-    /// Attempts to create a command to open the file at the given path to view it.
-    /// Target should be an html file.
+    /// Attempts to create a command to open the HTML file of the given note to view it, scrolled to the given anchor if there is one.
     /// Checks:
     ///  - The config file
     ///  - the systems default programms
     /// for an applicable program.
-    pub fn create_view_command(&self, note: &data::Note) -> error::Result<std::process::Command> {
+    pub fn create_view_command(
+        &self,
+        note: &data::Note,
+        anchor: Option<&str>,
+    ) -> error::Result<std::process::Command> {
         let path = self.name_to_html_path(&note.name);
+        // fragments are only understood in urls, not in paths
+        let path = match anchor {
+            Some(anchor) => std::ffi::OsString::from(format!(
+                "file://{}#{}",
+                fs::canonicalize(&path).unwrap_or(path).display(),
+                data::anchor_to_fragment(anchor)
+            )),
+            None => path.into_os_string(),
+        };
         // take the editor from the config file
         self.viewer
             .as_ref()
@@ -265,6 +303,13 @@ impl HtmlBuilder {
     }
 }
 
+/// Returns the fragment to append to the HTML file a link points to, if the link has an anchor.
+fn anchor_suffix(link: &str) -> String {
+    data::link_anchor(link)
+        .map(|anchor| format!("#{}", data::anchor_to_fragment(&anchor)))
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -274,10 +319,14 @@ mod tests {
     fn test_viewing() {
         let config = crate::Config::default();
         let fm = super::HtmlBuilder::new(&config, PathBuf::from("./tests"));
-        let note =
-            crate::data::Note::from_path(Path::new("./tests/common/notes/Books.md")).unwrap();
+        let note = crate::data::Note::from_path(
+            Path::new("./tests/common/notes/Books.md"),
+            Default::default(),
+        )
+        .unwrap();
 
-        fm.create_view_command(&note).unwrap();
+        fm.create_view_command(&note, None).unwrap();
+        fm.create_view_command(&note, Some("Heading")).unwrap();
     }
 
     #[test]
@@ -285,9 +334,11 @@ mod tests {
         let config = crate::Config::default();
         let hb = super::HtmlBuilder::new(&config, PathBuf::from("./tests"));
 
-        let os =
-            crate::data::Note::from_path(Path::new("./tests/common/notes/Operating Systems.md"))
-                .unwrap();
+        let os = crate::data::Note::from_path(
+            Path::new("./tests/common/notes/Operating Systems.md"),
+            Default::default(),
+        )
+        .unwrap();
 
         hb.create_html(&os, true).unwrap();
     }
@@ -298,9 +349,11 @@ mod tests {
         let hb = super::HtmlBuilder::new(&config, PathBuf::from("./tests"));
 
         // with math
-        let smooth_map =
-            crate::data::Note::from_path(Path::new("./tests/common/notes/math/Smooth Map.md"))
-                .unwrap();
+        let smooth_map = crate::data::Note::from_path(
+            Path::new("./tests/common/notes/math/Smooth Map.md"),
+            Default::default(),
+        )
+        .unwrap();
 
         hb.create_html(&smooth_map, true).unwrap();
     }
@@ -329,8 +382,11 @@ mod tests {
         let config = crate::Config::default();
         let hb = super::HtmlBuilder::new(&config, PathBuf::from("./tests"));
 
-        let books =
-            crate::data::Note::from_path(Path::new("./tests/common/notes/Books.md")).unwrap();
+        let books = crate::data::Note::from_path(
+            Path::new("./tests/common/notes/Books.md"),
+            Default::default(),
+        )
+        .unwrap();
 
         let b_path = hb.name_to_html_path("Books");
 
@@ -351,9 +407,11 @@ mod tests {
         let hb = super::HtmlBuilder::new(&config, PathBuf::from("./tests"));
 
         // with math
-        let liegroup =
-            crate::data::Note::from_path(Path::new("./tests/common/notes/math/Lie Group.md"))
-                .unwrap();
+        let liegroup = crate::data::Note::from_path(
+            Path::new("./tests/common/notes/math/Lie Group.md"),
+            Default::default(),
+        )
+        .unwrap();
 
         let lg_path = hb.name_to_html_path("Lie Group");
 
@@ -368,6 +426,28 @@ mod tests {
         assert!(lg_path.exists());
     }
 
+    #[test]
+    fn test_create_html_anchors() {
+        let tmp = testdir::testdir!();
+        std::fs::write(
+            tmp.join("Anchors.md"),
+            "# Main Result\n\nSee [[Lie Group#Definition]] and [text](Chart.md#^atlas).\n\nA paragraph ^para-1\n",
+        )
+        .unwrap();
+
+        let config = crate::Config::default();
+        let hb = super::HtmlBuilder::new(&config, tmp.clone());
+        let note =
+            crate::data::Note::from_path(&tmp.join("Anchors.md"), Default::default()).unwrap();
+        hb.create_html(&note, true).unwrap();
+
+        let html = std::fs::read_to_string(hb.name_to_html_path("Anchors")).unwrap();
+        assert!(html.contains("id=\"main-result\""));
+        assert!(html.contains("href=\"lie-group.html#definition\""));
+        assert!(html.contains("href=\"chart.html#"));
+        assert!(html.contains("A paragraph<span id=\"^para-1\"></span></p>"));
+    }
+
     #[test]
     fn test_replacements() {
         let config = crate::Config::default();
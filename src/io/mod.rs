@@ -3,6 +3,11 @@ pub use file_manager::FileManager;
 
 mod file_tracker;
 pub use file_tracker::FileTracker;
+pub use file_tracker::ReadSettings;
 
 mod html_builder;
 pub use html_builder::HtmlBuilder;
+
+mod undo;
+pub use undo::Operation;
+pub use undo::UndoJournal;
@@ -0,0 +1,181 @@
+use std::{fs, io, path};
+
+use crate::{data, error};
+
+/// How many operations are remembered at most.
+const JOURNAL_CAPACITY: usize = 20;
+
+/// A structural change to the files of a vault made by rucola, with everything needed to revert it.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Operation {
+    /// The files moved by the operation, from their old to their new path.
+    pub moves: Vec<(path::PathBuf, path::PathBuf)>,
+    /// The files whose content was rewritten by the operation, with their content before and after.
+    pub rewrites: Vec<(path::PathBuf, String, String)>,
+}
+
+/// The most recent renames, moves and deletions of notes in a vault, persisted in a journal file in the config directory or a configured journal folder.
+/// The last operation can be undone, also in a later session, including the links rewritten by it.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct UndoJournal {
+    /// The remembered operations, the most recent one last.
+    operations: Vec<Operation>,
+}
+
+impl UndoJournal {
+    /// Returns the path of the journal file of the vault at the given path, within the given folder or the config directory if none is given.
+    /// Each vault gets its own file, see [data::vault_file_name].
+    pub fn file_path(
+        folder: Option<&path::Path>,
+        vault: &path::Path,
+    ) -> error::Result<path::PathBuf> {
+        let name = data::vault_file_name("journal", vault);
+
+        let mut path = match folder {
+            Some(folder) => folder.join(name),
+            None => confy::get_configuration_file_path("rucola", name.as_str())?,
+        };
+        // confy appends .toml, but the journal is stored as json
+        path.set_extension("json");
+        Ok(path)
+    }
+
+    /// Loads the journal from the given journal file, creating an empty one if there is none.
+    fn load(journal: &path::Path) -> error::Result<Self> {
+        let file = match fs::File::open(journal) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(serde_json::from_reader(io::BufReader::new(file))?)
+    }
+
+    /// Writes the journal to the given journal file.
+    fn store(&self, journal: &path::Path) -> error::Result<()> {
+        if let Some(parent) = journal.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        serde_json::to_writer(io::BufWriter::new(fs::File::create(journal)?), self)?;
+        Ok(())
+    }
+
+    /// Adds the given operation as the most recent one to the given journal file, forgetting the oldest ones beyond the capacity.
+    pub fn record(journal_path: &path::Path, operation: Operation) -> error::Result<()> {
+        let mut journal = Self::load(journal_path)?;
+        journal.operations.push(operation);
+        if journal.operations.len() > JOURNAL_CAPACITY {
+            journal
+                .operations
+                .drain(..journal.operations.len() - JOURNAL_CAPACITY);
+        }
+        journal.store(journal_path)
+    }
+
+    /// Reverts the most recent operation in the given journal file and removes it from the journal.
+    /// Returns `false` if there is no operation to undo.
+    /// Nothing is changed if a moved file is no longer where the operation put it, its old location is taken, or a rewritten file was changed since.
+    pub fn undo_last(journal_path: &path::Path) -> error::Result<bool> {
+        let mut journal = Self::load(journal_path)?;
+        let Some(operation) = journal.operations.last() else {
+            return Ok(false);
+        };
+
+        // Check that the operation can be reverted completely before changing anything
+        for (from, to) in operation.moves.iter() {
+            if !to.exists() || from.exists() {
+                return Err(error::RucolaError::Input(format!(
+                    "Cannot move {} back to {}.",
+                    to.display(),
+                    from.display()
+                )));
+            }
+        }
+        let changed = operation
+            .rewrites
+            .iter()
+            .filter(|(path, _before, after)| {
+                fs::read_to_string(path).map_or(true, |content| content != *after)
+            })
+            .map(|(path, _before, _after)| path.clone())
+            .collect::<Vec<_>>();
+        if !changed.is_empty() {
+            return Err(error::RucolaError::ExternalChange(changed));
+        }
+
+        // Restore rewritten links first, then move the files back in reverse order
+        for (path, before, _after) in operation.rewrites.iter() {
+            fs::write(path, before)?;
+        }
+        for (from, to) in operation.moves.iter().rev() {
+            if let Some(parent) = from.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(to, from)?;
+        }
+
+        journal.operations.pop();
+        journal.store(journal_path)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{data, io};
+
+    #[test]
+    fn test_undo() {
+        let tmp = testdir::testdir!();
+        std::fs::write(tmp.join("Atlas.md"), "An atlas.").unwrap();
+        std::fs::write(tmp.join("Chart.md"), "Part of an [[Atlas]].").unwrap();
+
+        let mut config = crate::Config::default();
+        config.journal_folder = Some(tmp.join("journals"));
+        let fm = io::FileManager::new(&config, tmp.clone());
+        let journal = super::UndoJournal::file_path(Some(&tmp.join("journals")), &tmp).unwrap();
+        let tracker = io::FileTracker::new(&config, tmp.clone()).unwrap();
+        let builder = io::HtmlBuilder::new(&config, tmp.clone());
+        let index = data::NoteIndex::new(tracker, builder).0;
+        let index = data::NoteIndexContainer::new(index);
+
+        // nothing to undo yet
+        assert!(!super::UndoJournal::undo_last(&journal).unwrap());
+
+        // renames are undone including their rewritten links
+        fm.rename_note_file(index.clone(), "atlas", String::from("Atlantis"), false)
+            .unwrap();
+        assert!(tmp.join("Atlantis.md").exists());
+        assert!(journal.exists());
+        assert_eq!(
+            std::fs::read_to_string(tmp.join("Chart.md")).unwrap(),
+            "Part of an [[Atlantis]]."
+        );
+
+        assert!(super::UndoJournal::undo_last(&journal).unwrap());
+        assert!(tmp.join("Atlas.md").exists());
+        assert!(!tmp.join("Atlantis.md").exists());
+        assert_eq!(
+            std::fs::read_to_string(tmp.join("Chart.md")).unwrap(),
+            "Part of an [[Atlas]]."
+        );
+
+        // deleted notes are taken out of the trash again
+        fm.delete_note_file(index.clone(), "chart").unwrap();
+        assert!(!tmp.join("Chart.md").exists());
+        assert!(super::UndoJournal::undo_last(&journal).unwrap());
+        assert!(tmp.join("Chart.md").exists());
+        assert!(fm.trashed_files().is_empty());
+
+        // files changed since are not overwritten
+        fm.rename_note_file(index.clone(), "atlas", String::from("Atlantis"), false)
+            .unwrap();
+        std::fs::write(tmp.join("Chart.md"), "Changed").unwrap();
+        assert!(super::UndoJournal::undo_last(&journal).is_err());
+        assert!(tmp.join("Atlantis.md").exists());
+
+        std::fs::write(tmp.join("Chart.md"), "Part of an [[Atlantis]].").unwrap();
+        assert!(super::UndoJournal::undo_last(&journal).unwrap());
+        assert!(tmp.join("Atlas.md").exists());
+        assert!(!super::UndoJournal::undo_last(&journal).unwrap());
+    }
+}
@@ -54,8 +54,8 @@ fn main() -> error::Result<()> {
     init_hooks()?;
     let mut terminal = init_terminal()?;
 
-    // draw loading screen
-    draw_loading_screen(&mut terminal)?;
+    // draw loading screen, showing the statistics of the last session while indexing
+    draw_loading_screen(&mut terminal, &data::StatsCache::load().unwrap_or_default())?;
 
     // Create the app state
     let (mut app, errors) = app::App::new(args);
@@ -100,7 +100,8 @@ fn main() -> error::Result<()> {
         })?;
 
         // Inform the app of events
-        let maybe_keypress = if event::poll(std::time::Duration::from_millis(500))? {
+        // The timeout is kept short so filters typed in the select screen are applied soon after typing stops.
+        let maybe_keypress = if event::poll(std::time::Duration::from_millis(100))? {
             // Some event => reset current error
             current_error = None;
             // Check if the event was a keypress
@@ -133,6 +134,14 @@ fn main() -> error::Result<()> {
     //Restore previous terminal state
     restore_terminal()?;
 
+    // Cache the statistics for the loading screen of the next session
+    app.stats_cache().store()?;
+
+    // Cache the parsed notes to skip unchanged ones when indexing in the next session
+    for cache in app.index_caches() {
+        cache.store()?;
+    }
+
     // Return the right OK
     Ok(())
 }
@@ -180,18 +189,40 @@ You should have received a copy of the GNU General Public License along with thi
     ");
 }
 
-/// Draws nothing but a loading screen with an indexing message.
+/// Draws nothing but a loading screen with an indexing message and the cached statistics of the last session, if any.
 /// Temporary screen while the programm is indexing.
 fn draw_loading_screen(
     terminal: &mut Terminal<impl ratatui::backend::Backend>,
+    cache: &data::StatsCache,
 ) -> Result<ratatui::CompletedFrame, std::io::Error> {
+    let mut lines = vec![Line::from("Indexing...")];
+    if let Some((vault, summary, stale)) = cache.summary() {
+        lines.extend([
+            Line::from(""),
+            Line::from(format!(
+                "Last session{} in {}:",
+                if stale { " (possibly outdated)" } else { "" },
+                vault.display()
+            )),
+            Line::from(format!(
+                "{} notes, {} words, {} characters, {} tags",
+                summary.notes, summary.words, summary.characters, summary.tags
+            )),
+            Line::from(format!(
+                "{} links, {} broken",
+                summary.links, summary.broken_links
+            )),
+        ]);
+    }
+    let height = lines.len() as u16;
+
     // Draw 'loading' screen
     terminal.draw(|frame| {
         frame.render_widget(
-            ratatui::widgets::Paragraph::new("Indexing...").alignment(Alignment::Center),
+            ratatui::widgets::Paragraph::new(lines).alignment(Alignment::Center),
             Layout::vertical([
                 Constraint::Fill(1),
-                Constraint::Length(3),
+                Constraint::Length(height),
                 Constraint::Fill(1),
             ])
             .split(frame.size())[1],
@@ -0,0 +1,79 @@
+use std::{collections::VecDeque, path};
+
+use crate::{data, error};
+
+/// How many filter strings are remembered at most.
+const HISTORY_CAPACITY: usize = 50;
+
+/// A bounded history of previously entered filter strings that can be cycled through like a shell history.
+/// The entries are persisted between sessions in a state file of each vault in the config directory, see [data::vault_file_name].
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FilterHistory {
+    /// The remembered filter strings, the most recent one last.
+    entries: VecDeque<String>,
+    /// The index of the currently recalled entry, if the user is cycling through the history.
+    #[serde(skip)]
+    cursor: Option<usize>,
+}
+
+impl FilterHistory {
+    /// Loads the history of the vault at the given path from its state file, creating an empty one if none exists.
+    pub fn load(vault: &path::Path) -> error::Result<Self> {
+        Ok(confy::load(
+            "rucola",
+            data::vault_file_name("history", vault).as_str(),
+        )?)
+    }
+
+    /// Writes the history to the state file of the vault at the given path.
+    pub fn store(&self, vault: &path::Path) -> error::Result<()> {
+        Ok(confy::store(
+            "rucola",
+            data::vault_file_name("history", vault).as_str(),
+            self,
+        )?)
+    }
+
+    /// Adds a new filter string as the most recent entry and stops cycling.
+    /// Empty strings are ignored and earlier duplicates are removed.
+    pub fn push(&mut self, entry: String) {
+        self.cursor = None;
+        if entry.trim().is_empty() {
+            return;
+        }
+        self.entries.retain(|old| *old != entry);
+        self.entries.push_back(entry);
+        while self.entries.len() > HISTORY_CAPACITY {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Stops cycling through the history, so the next recall starts at the most recent entry again.
+    pub fn reset(&mut self) {
+        self.cursor = None;
+    }
+
+    /// Steps one entry back in time and returns it.
+    /// Stays at the oldest entry once it is reached, returns `None` if the history is empty.
+    pub fn previous(&mut self) -> Option<&str> {
+        let cursor = match self.cursor {
+            None => self.entries.len().checked_sub(1)?,
+            Some(cursor) => cursor.saturating_sub(1),
+        };
+        self.cursor = Some(cursor);
+        self.entries.get(cursor).map(|entry| entry.as_str())
+    }
+
+    /// Steps one entry forward in time and returns it.
+    /// Stepping past the most recent entry stops cycling and returns an empty string, returns `None` if not cycling.
+    pub fn next(&mut self) -> Option<&str> {
+        let cursor = self.cursor?;
+        if cursor + 1 < self.entries.len() {
+            self.cursor = Some(cursor + 1);
+            self.entries.get(cursor + 1).map(|entry| entry.as_str())
+        } else {
+            self.cursor = None;
+            Some("")
+        }
+    }
+}
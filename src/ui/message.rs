@@ -11,8 +11,46 @@ pub enum Message {
     DisplayStackPop,
     /// Pushes the given id to the top of the display stack.
     DisplayStackPush(String),
+    /// Pushes the given id to the top of the display stack, showing the note at the given heading or block anchor.
+    DisplayAnchor(String, String),
+    /// Pushes a random id out of the given ids to the top of the display stack.
+    DisplayRandom(Vec<String>),
+    /// Opens the given id in a new tab next to the current one, showing the note at the given heading or block anchor, if any.
+    TabOpen(String, Option<String>),
+    /// Switches to the next tab, wrapping around at the end.
+    TabNext,
+    /// Switches to the previous tab, wrapping around at the start.
+    TabPrevious,
+    /// Closes the current tab, switching to its neighbour or returning to the select screen if it was the last one.
+    TabClose,
+    /// Moves the current tab by the given amount of positions to the right (or left, if negative).
+    TabMove(isize),
+    /// Shows the keybindings of the current screen above it.
+    DisplayHelp,
+    /// Opens the stats screen for the environment currently shown on the select screen.
+    DisplayStats,
+    /// Closes the stats screen, returning to the select screen.
+    CloseStats,
+    /// Opens the vault screen to choose a vault to switch to.
+    DisplayVaults,
+    /// Closes the vault screen, returning to the select screen.
+    CloseVaults,
+    /// Switches to the vault at the given path, indexing it if it has not been opened before.
+    SwitchVault(std::path::PathBuf),
+    /// Indexes the current vault again in the background, replacing the index once done.
+    Rescan,
+    /// Opens the trash screen to restore or permanently delete trashed notes.
+    DisplayTrash,
+    /// Closes the trash screen, returning to the select screen.
+    CloseTrash,
+    /// Opens the graph screen for the environment currently shown on the select screen.
+    DisplayGraph,
+    /// Closes the graph screen, returning to the select screen.
+    CloseGraph,
     /// Restore the terminal, execute the given command and re-enter
     OpenExternalCommand(std::process::Command),
+    /// Restore the terminal, open the note at the given path with the given editor command and re-enter, then read the note again.
+    EditNote(std::process::Command, std::path::PathBuf),
 }
 
 /// Messages sent from the application to the terminal.
@@ -32,9 +70,29 @@ impl From<Message> for TerminalMessage {
             Message::None
             | Message::DisplayStackClear
             | Message::DisplayStackPop
-            | Message::DisplayStackPush(_) => Self::None,
+            | Message::DisplayStackPush(_)
+            | Message::DisplayAnchor(_, _)
+            | Message::DisplayRandom(_)
+            | Message::TabOpen(_, _)
+            | Message::TabNext
+            | Message::TabPrevious
+            | Message::TabClose
+            | Message::TabMove(_)
+            | Message::DisplayHelp
+            | Message::DisplayStats
+            | Message::CloseStats
+            | Message::DisplayVaults
+            | Message::CloseVaults
+            | Message::SwitchVault(_)
+            | Message::Rescan
+            | Message::DisplayTrash
+            | Message::CloseTrash
+            | Message::DisplayGraph
+            | Message::CloseGraph => Self::None,
             Message::Quit => Self::Quit,
-            Message::OpenExternalCommand(cmd) => Self::OpenExternalCommand(cmd),
+            Message::OpenExternalCommand(cmd) | Message::EditNote(cmd, _) => {
+                Self::OpenExternalCommand(cmd)
+            }
         }
     }
 }
@@ -1,3 +1,6 @@
+mod history;
+pub use history::FilterHistory;
+
 mod message;
 pub use message::Message;
 pub use message::TerminalMessage;
@@ -6,5 +9,8 @@ pub mod screen;
 
 pub use screen::Screen;
 
+mod note_view;
+pub use note_view::NoteView;
+
 mod uistyles;
 pub use uistyles::UiStyles;
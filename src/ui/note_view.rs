@@ -0,0 +1,562 @@
+use std::cell::Cell;
+
+use crate::{data, error, ui};
+
+use ratatui::{prelude::*, widgets::*};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The content of a note as styled lines that can be scrolled through vertically.
+/// Lines longer than the available width are wrapped, and scrolling counts wrapped rows, so every row of the note can be reached.
+pub struct NoteView {
+    /// The lines of the note's content without its frontmatter, styled by their markdown role.
+    lines: Vec<Line<'static>>,
+    /// The links in the content in order of appearance, with the line and the span within the line they are shown in.
+    /// Each link is given by its reference, i.e. the target of a wikilink or the url of a markdown link, and wether it is a wikilink.
+    links: Vec<(usize, usize, String, bool)>,
+    /// The index of the selected link, if any.
+    selected_link: Option<usize>,
+    /// The style of the selected link and the current search match.
+    selected_style: Style,
+    /// The text searched for, if any.
+    query: Option<String>,
+    /// The matches of the search in order of appearance, each given by its line and its byte range within the text of the line.
+    matches: Vec<(usize, usize, usize)>,
+    /// The index of the current match, if there are any.
+    current_match: Option<usize>,
+    /// The first shown row of the wrapped content.
+    scroll: usize,
+    /// The width and height of the area the content was last drawn in, used to wrap lines and page through the content while handling key presses.
+    viewport: Cell<(u16, u16)>,
+}
+
+impl NoteView {
+    /// Creates a view of the given markdown content, styling headings, quotes, code blocks and links with the given styles.
+    pub fn new(content: &str, styles: &ui::UiStyles) -> error::Result<Self> {
+        let links = regex::Regex::new(r"!?\[\[[^\]]+\]\]|!?\[[^\]]*\]\([^)\s]+\)")?;
+
+        let mut in_code = false;
+        let mut found_links = Vec::new();
+        let lines = data::strip_frontmatter(content)
+            .lines()
+            .enumerate()
+            .map(|(number, line)| {
+                let trimmed = line.trim_start();
+                if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                    in_code = !in_code;
+                    Line::styled(line.to_owned(), styles.subtitle_style)
+                } else if in_code || trimmed.starts_with('>') {
+                    Line::styled(line.to_owned(), styles.subtitle_style)
+                } else if trimmed.starts_with('#')
+                    && trimmed.trim_start_matches('#').starts_with(' ')
+                {
+                    Line::styled(line.to_owned(), styles.title_style)
+                } else {
+                    // highlight links within normal text
+                    let mut spans = Vec::new();
+                    let mut last = 0;
+                    for link in links.find_iter(line) {
+                        spans.push(Span::styled(
+                            line[last..link.start()].to_owned(),
+                            styles.text_style,
+                        ));
+                        let (reference, wikilink) = link_reference(link.as_str());
+                        found_links.push((number, spans.len(), reference, wikilink));
+                        spans.push(Span::styled(link.as_str().to_owned(), styles.hotkey_style));
+                        last = link.end();
+                    }
+                    spans.push(Span::styled(line[last..].to_owned(), styles.text_style));
+                    Line::from(spans)
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            lines,
+            links: found_links,
+            selected_link: None,
+            selected_style: styles.selected_style,
+            query: None,
+            matches: Vec::new(),
+            current_match: None,
+            scroll: 0,
+            viewport: Cell::new((0, 0)),
+        })
+    }
+
+    /// Returns the rows of the content when wrapped to the given width, highlighting the selected link and the matches of the search.
+    fn rows(&self, width: u16) -> Vec<Line<'static>> {
+        let selected = self
+            .selected_link
+            .and_then(|selected| self.links.get(selected));
+        self.lines
+            .iter()
+            .enumerate()
+            .flat_map(|(number, line)| {
+                let link = selected.filter(|(link_line, _, _, _)| *link_line == number);
+                // the matches are sorted by line
+                let first = self.matches.partition_point(|(line, _, _)| *line < number);
+                let last = self.matches.partition_point(|(line, _, _)| *line <= number);
+                if link.is_none() && first == last {
+                    return wrap(line, width as usize);
+                }
+
+                let mut line = line.clone();
+                if let Some((_, span, _, _)) = link {
+                    if let Some(span) = line.spans.get_mut(*span) {
+                        span.style = self.selected_style;
+                    }
+                }
+                let ranges = self.matches[first..last]
+                    .iter()
+                    .map(|(_, start, end)| (*start, *end))
+                    .collect::<Vec<_>>();
+                let line = highlight(&line, &ranges, |range, style| {
+                    if self.current_match == Some(first + range) {
+                        self.selected_style
+                    } else {
+                        style.add_modifier(Modifier::REVERSED)
+                    }
+                });
+                wrap(&line, width as usize)
+            })
+            .collect()
+    }
+
+    /// Searches the content for the given text, ignoring case, and moves to the first match at or below the shown part of the content.
+    /// Searching for empty text ends the search.
+    pub fn search(&mut self, query: &str) -> error::Result<()> {
+        self.matches.clear();
+        self.current_match = None;
+        if query.is_empty() {
+            self.query = None;
+            return Ok(());
+        }
+        self.query = Some(query.to_owned());
+
+        let pattern = regex::RegexBuilder::new(&regex::escape(query))
+            .case_insensitive(true)
+            .build()?;
+        for (number, line) in self.lines.iter().enumerate() {
+            let text = line
+                .spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect::<String>();
+            self.matches.extend(
+                pattern
+                    .find_iter(&text)
+                    .map(|found| (number, found.start(), found.end())),
+            );
+        }
+
+        if !self.matches.is_empty() {
+            let (width, _height) = self.viewport.get();
+            let starts = self.line_starts(width);
+            let first = self
+                .matches
+                .iter()
+                .position(|(line, _, _)| starts[*line] >= self.scroll)
+                .unwrap_or(0);
+            self.select_match(first);
+        }
+        Ok(())
+    }
+
+    /// Moves to the match at the given offset from the current one, wrapping around.
+    pub fn next_match(&mut self, offset: isize) {
+        if let Some(current) = self.current_match {
+            self.select_match(
+                (current as isize + offset).rem_euclid(self.matches.len() as isize) as usize,
+            );
+        }
+    }
+
+    /// Makes the match at the given index the current one and scrolls to it.
+    fn select_match(&mut self, index: usize) {
+        self.current_match = Some(index);
+        let (width, _height) = self.viewport.get();
+        let row = self.line_starts(width)[self.matches[index].0];
+        self.reveal_row(row);
+    }
+
+    /// Returns the searched text, the position of the current match counted from one, or zero if nothing matches, and the amount of matches, if searching.
+    pub fn search_status(&self) -> Option<(&str, usize, usize)> {
+        self.query.as_deref().map(|query| {
+            (
+                query,
+                self.current_match.map_or(0, |current| current + 1),
+                self.matches.len(),
+            )
+        })
+    }
+
+    /// Scrolls as little as possible to show the given row.
+    fn reveal_row(&mut self, row: usize) {
+        let (_width, height) = self.viewport.get();
+        if row < self.scroll {
+            self.scroll = row;
+        } else if row >= self.scroll + height as usize {
+            self.scroll = (row + 1).saturating_sub(height as usize);
+        }
+    }
+
+    /// Returns the row each line starts in when wrapped to the given width.
+    fn line_starts(&self, width: u16) -> Vec<usize> {
+        self.lines
+            .iter()
+            .scan(0, |row, line| {
+                let start = *row;
+                *row += wrap(line, width as usize).len();
+                Some(start)
+            })
+            .collect()
+    }
+
+    /// Scrolls the content so the given line, counted from zero after the frontmatter, is shown at the top if possible.
+    pub fn scroll_to_line(&mut self, line: usize) {
+        let (width, _height) = self.viewport.get();
+        let row = self
+            .line_starts(width)
+            .get(line)
+            .copied()
+            .unwrap_or(usize::MAX);
+        self.scroll = row.min(self.max_scroll());
+    }
+
+    /// Selects the link at the given offset from the currently selected one, wrapping around, and scrolls to it.
+    /// Without a selected link, moving forward selects the first link at or below the shown part of the content and moving backward the last one above its end.
+    pub fn select_link(&mut self, offset: isize) {
+        if self.links.is_empty() {
+            return;
+        }
+        let (width, height) = self.viewport.get();
+        let starts = self.line_starts(width);
+        let row_of = |link: usize| starts[self.links[link].0];
+
+        let selected = match self.selected_link {
+            Some(selected) => {
+                (selected as isize + offset).rem_euclid(self.links.len() as isize) as usize
+            }
+            None if offset >= 0 => (0..self.links.len())
+                .find(|link| row_of(*link) >= self.scroll)
+                .unwrap_or(0),
+            None => (0..self.links.len())
+                .rev()
+                .find(|link| row_of(*link) < self.scroll + height as usize)
+                .unwrap_or(self.links.len() - 1),
+        };
+        self.selected_link = Some(selected);
+
+        // keep the selected link within the shown part of the content
+        self.reveal_row(row_of(selected));
+    }
+
+    /// Returns the reference of the selected link, i.e. the target of a wikilink or the url of a markdown link, and wether it is a wikilink.
+    pub fn selected_link(&self) -> Option<(&str, bool)> {
+        self.selected_link
+            .and_then(|selected| self.links.get(selected))
+            .map(|(_, _, reference, wikilink)| (reference.as_str(), *wikilink))
+    }
+
+    /// The largest sensible scroll position, showing the last row of the content at the bottom of the last drawn area.
+    fn max_scroll(&self) -> usize {
+        let (width, height) = self.viewport.get();
+        self.rows(width).len().saturating_sub(height as usize)
+    }
+
+    /// Moves the shown part of the content by the given amount of rows, staying within the content.
+    pub fn scroll_by(&mut self, rows: isize) {
+        self.scroll = self
+            .scroll
+            .saturating_add_signed(rows)
+            .min(self.max_scroll());
+    }
+
+    /// Moves the shown part of the content by the given amount of pages, i.e. heights of the last drawn area.
+    pub fn scroll_pages(&mut self, pages: isize) {
+        let (_width, height) = self.viewport.get();
+        self.scroll_by(pages * (height.max(2) as isize - 1));
+    }
+
+    /// Shows the start of the content.
+    pub fn scroll_to_top(&mut self) {
+        self.scroll = 0;
+    }
+
+    /// Shows the end of the content.
+    pub fn scroll_to_bottom(&mut self) {
+        self.scroll = self.max_scroll();
+    }
+
+    /// Draws the content in the given area, surrounded by the given block, with a scrollbar on its right border.
+    pub fn draw(&self, area: Rect, buf: &mut Buffer, block: Block) {
+        let inner = block.inner(area);
+        Widget::render(block, area, buf);
+
+        // remember the area for handling key presses
+        self.viewport.set((inner.width, inner.height));
+
+        let rows = self.rows(inner.width);
+        let scroll = self
+            .scroll
+            .min(rows.len().saturating_sub(inner.height as usize));
+
+        let shown = rows
+            .into_iter()
+            .skip(scroll)
+            .take(inner.height as usize)
+            .collect::<Vec<_>>();
+        Widget::render(Paragraph::new(shown), inner, buf);
+
+        let mut state = ScrollbarState::new(self.max_scroll()).position(scroll);
+        StatefulWidget::render(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            area.inner(Margin {
+                horizontal: 0,
+                vertical: 1,
+            }),
+            buf,
+            &mut state,
+        );
+    }
+}
+
+/// Extracts the reference from a wikilink such as `[[Lie Theory#Definition|Lie]]` or a markdown link such as `[Lie](Lie%20Theory.md)`, with wether it is a wikilink.
+/// Aliases of wikilinks are dropped, anchors are kept.
+fn link_reference(link: &str) -> (String, bool) {
+    let link = link.strip_prefix('!').unwrap_or(link);
+    match link
+        .strip_prefix("[[")
+        .and_then(|link| link.strip_suffix("]]"))
+    {
+        Some(wikilink) => (
+            wikilink.split('|').next().unwrap_or_default().to_owned(),
+            true,
+        ),
+        None => (
+            link.rsplit_once("](")
+                .map(|(_text, url)| url.trim_end_matches(')'))
+                .unwrap_or_default()
+                .to_owned(),
+            false,
+        ),
+    }
+}
+
+/// Splits the spans of the given line at the given sorted, non-overlapping byte ranges of its text.
+/// The parts within the ranges are styled by the given function from the index of their range and their previous style.
+fn highlight(
+    line: &Line<'static>,
+    ranges: &[(usize, usize)],
+    style: impl Fn(usize, Style) -> Style,
+) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut offset = 0;
+
+    for span in line.spans.iter() {
+        let text = span.content.as_ref();
+        let span_end = offset + text.len();
+        // the position up to which the span was taken over
+        let mut cut = offset;
+
+        for (index, (start, end)) in ranges.iter().enumerate() {
+            let (start, end) = ((*start).max(cut), (*end).min(span_end));
+            if start >= end {
+                continue;
+            }
+            if start > cut {
+                spans.push(Span::styled(
+                    text[cut - offset..start - offset].to_owned(),
+                    span.style,
+                ));
+            }
+            spans.push(Span::styled(
+                text[start - offset..end - offset].to_owned(),
+                style(index, span.style),
+            ));
+            cut = end;
+        }
+
+        if cut < span_end {
+            spans.push(Span::styled(text[cut - offset..].to_owned(), span.style));
+        }
+        offset = span_end;
+    }
+
+    Line::from(spans).style(line.style)
+}
+
+/// Wraps the given line into rows of at most the given width, breaking between words where possible.
+/// Whitespace at the start of wrapped rows is dropped. Every line results in at least one row.
+fn wrap(line: &Line<'static>, width: usize) -> Vec<Line<'static>> {
+    let width = width.max(1);
+    let mut rows = Vec::new();
+    let mut current: Vec<Span<'static>> = Vec::new();
+    let mut current_width = 0;
+
+    for span in line.spans.iter() {
+        for word in span.content.split_word_bounds() {
+            // words longer than a whole row are broken between characters instead
+            let pieces = if Span::raw(word).width() > width {
+                word.graphemes(true).collect::<Vec<_>>()
+            } else {
+                vec![word]
+            };
+
+            for piece in pieces {
+                let piece_width = Span::raw(piece).width();
+                if current_width + piece_width > width && current_width > 0 {
+                    rows.push(Line::from(std::mem::take(&mut current)).style(line.style));
+                    current_width = 0;
+                    if piece.trim().is_empty() {
+                        continue;
+                    }
+                }
+                current.push(Span::styled(piece.to_owned(), span.style));
+                current_width += piece_width;
+            }
+        }
+    }
+    rows.push(Line::from(current).style(line.style));
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::text::Line;
+
+    #[test]
+    fn test_wrap() {
+        let as_text = |rows: Vec<Line>| {
+            rows.iter()
+                .map(|row| {
+                    row.spans
+                        .iter()
+                        .map(|span| span.content.as_ref())
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(as_text(super::wrap(&Line::from(""), 10)), vec![""]);
+        assert_eq!(
+            as_text(super::wrap(&Line::from("A short line"), 20)),
+            vec!["A short line"]
+        );
+        assert_eq!(
+            as_text(super::wrap(
+                &Line::from("Smooth manifolds and lie groups"),
+                10
+            )),
+            vec!["Smooth ", "manifolds ", "and lie ", "groups"]
+        );
+        assert_eq!(
+            as_text(super::wrap(&Line::from("Diffeomorphism"), 6)),
+            vec!["Diffeo", "morphi", "sm"]
+        );
+    }
+
+    #[test]
+    fn test_scrolling() {
+        let content = "---\ntitle: Atlas\n---\n# Atlas\n\nA long line of text wrapped into several rows.\nLast line";
+        let mut view = super::NoteView::new(content, &crate::ui::UiStyles::default()).unwrap();
+
+        // the frontmatter is not shown
+        assert_eq!(view.lines.len(), 4);
+
+        // 1 + 1 + 4 + 1 rows at a width of 14, showing 3 at once
+        view.viewport.set((14, 3));
+        assert_eq!(view.rows(14).len(), 7);
+
+        view.scroll_by(2);
+        assert_eq!(view.scroll, 2);
+        view.scroll_pages(1);
+        assert_eq!(view.scroll, 4);
+        view.scroll_by(10);
+        assert_eq!(view.scroll, 4);
+        view.scroll_by(-10);
+        assert_eq!(view.scroll, 0);
+        view.scroll_to_bottom();
+        assert_eq!(view.scroll, 4);
+        view.scroll_to_top();
+        assert_eq!(view.scroll, 0);
+    }
+
+    #[test]
+    fn test_links() {
+        let content = "# Atlas\n\nSee [[Chart#Definition|charts]] and [maps](Map%20Projection.md).\n\n\n![[Transition Map]]";
+        let mut view = super::NoteView::new(content, &crate::ui::UiStyles::default()).unwrap();
+        view.viewport.set((80, 2));
+
+        assert_eq!(view.selected_link(), None);
+
+        // links are selected in order and wrap around
+        view.select_link(1);
+        assert_eq!(view.selected_link(), Some(("Chart#Definition", true)));
+        view.select_link(1);
+        assert_eq!(view.selected_link(), Some(("Map%20Projection.md", false)));
+        view.select_link(1);
+        assert_eq!(view.selected_link(), Some(("Transition Map", true)));
+        // and the view follows them
+        assert_eq!(view.scroll, 4);
+        view.select_link(1);
+        assert_eq!(view.selected_link(), Some(("Chart#Definition", true)));
+        assert_eq!(view.scroll, 2);
+        view.select_link(-1);
+        assert_eq!(view.selected_link(), Some(("Transition Map", true)));
+    }
+
+    #[test]
+    fn test_scroll_to_line() {
+        let content = "# Atlas\nFirst\n## Charts\nSecond\nThird\nFourth";
+        let mut view = super::NoteView::new(content, &crate::ui::UiStyles::default()).unwrap();
+        view.viewport.set((20, 2));
+
+        view.scroll_to_line(2);
+        assert_eq!(view.scroll, 2);
+        // the last lines cannot be shown at the top
+        view.scroll_to_line(5);
+        assert_eq!(view.scroll, 4);
+        view.scroll_to_line(0);
+        assert_eq!(view.scroll, 0);
+    }
+
+    #[test]
+    fn test_search() {
+        let content = "# Manifolds\nA manifold is a space.\n\nEvery MANIFOLD has charts.";
+        let mut view = super::NoteView::new(content, &crate::ui::UiStyles::default()).unwrap();
+        view.viewport.set((40, 2));
+
+        // matches ignore case
+        view.search("manifold").unwrap();
+        assert_eq!(view.search_status(), Some(("manifold", 1, 3)));
+        assert_eq!(view.matches, vec![(0, 2, 10), (1, 2, 10), (3, 6, 14)]);
+
+        // and are highlighted without changing the text
+        let rows = view.rows(40);
+        assert_eq!(rows.len(), 4);
+        assert_eq!(
+            rows[1]
+                .spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect::<Vec<_>>(),
+            vec!["A ", "manifold", " is a space."]
+        );
+
+        // moving between matches wraps around and scrolls
+        view.next_match(2);
+        assert_eq!(view.search_status(), Some(("manifold", 3, 3)));
+        assert_eq!(view.scroll, 2);
+        view.next_match(1);
+        assert_eq!(view.search_status(), Some(("manifold", 1, 3)));
+        assert_eq!(view.scroll, 0);
+
+        view.search("chart atlas").unwrap();
+        assert_eq!(view.search_status(), Some(("chart atlas", 0, 0)));
+
+        view.search("").unwrap();
+        assert_eq!(view.search_status(), None);
+    }
+}
@@ -7,7 +7,21 @@ pub use select_screen::StatsShow;
 mod display_screen;
 pub use display_screen::DisplayScreen;
 
+mod stats_screen;
+pub use stats_screen::StatsConfig;
+pub use stats_screen::StatsScreen;
+
+mod vault_screen;
+pub use vault_screen::VaultScreen;
+
+mod trash_screen;
+pub use trash_screen::TrashScreen;
+
+mod graph_screen;
+pub use graph_screen::GraphScreen;
+
 use crate::{error, ui};
+use crossterm::event::KeyCode;
 
 /// A trait that is implemented by different screens within the application.
 pub trait Screen {
@@ -16,6 +30,50 @@ pub trait Screen {
 
     /// Informs the screen of user messages and possibly modifies the content.
     fn update(&mut self, key: crossterm::event::KeyEvent) -> error::Result<ui::Message>;
+
+    /// Returns the keybindings currently active on the screen as pairs of keys and their effect, to be listed in the help overlay.
+    fn keybindings(&self) -> Vec<(String, &'static str)>;
+}
+
+/// Lists the given bindings of actions to keys for the help overlay, given which action each key currently triggers.
+/// Of the keys shown for a binding, like `"N / P"`, only those triggering its action are kept, and bindings none of whose keys trigger their action are left out.
+fn live_keybindings<'a, A: Copy + PartialEq + 'a>(
+    bindings: impl IntoIterator<Item = &'a (A, &'static [KeyCode], &'static str, &'static str)>,
+    action: impl Fn(KeyCode) -> Option<A>,
+) -> Vec<(String, &'static str)> {
+    bindings
+        .into_iter()
+        .filter_map(|(bound, codes, keys, description)| {
+            let live = keys
+                .split(" / ")
+                .filter(|part| {
+                    codes
+                        .iter()
+                        .any(|code| shows_key(part, *code) && action(*code) == Some(*bound))
+                })
+                .collect::<Vec<_>>();
+            (!live.is_empty()).then(|| (live.join(" / "), *description))
+        })
+        .collect()
+}
+
+/// Checks wether the given part of a binding's keys, like `"G"` or `"Shift+Tab"`, is shown for the given key.
+fn shows_key(part: &str, code: KeyCode) -> bool {
+    match code {
+        KeyCode::Char(' ') => part == "Space",
+        KeyCode::Char(c) if c.is_uppercase() => {
+            part == c.to_string() || part == format!("Shift+{c}")
+        }
+        KeyCode::Char(c) => part == c.to_uppercase().to_string(),
+        KeyCode::Tab => part == "Tab",
+        KeyCode::BackTab => part == "Shift+Tab",
+        KeyCode::Enter => part == "Enter",
+        KeyCode::Esc => part == "Esc",
+        KeyCode::Backspace => part == "Backspace",
+        KeyCode::PageUp => part == "PgUp",
+        KeyCode::PageDown => part == "PgDn",
+        _ => false,
+    }
 }
 
 // Clears a text area and returns the contained string, if any.
@@ -1,3 +1,5 @@
+use std::collections::{HashMap, HashSet};
+
 use crate::{data, error, io, ui};
 
 use crossterm::event::KeyCode;
@@ -16,8 +18,256 @@ enum DisplayMode {
     Move,
     /// Confirming delete
     Delete,
+    /// Confirming a rename that overwrites changes made outside of rucola to notes linking to this one.
+    ConfirmRename,
+    /// Typing into the create box to search the content of the note.
+    Search,
+}
+
+/// The amount of related notes suggested in the sidebar.
+const RELATED_AMOUNT: usize = 10;
+
+/// An action of the display screen while the note is displayed, triggered by the keys bound to it in [BINDINGS].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    Quit,
+    Help,
+    ReturnToSelect,
+    Back,
+    NextTable,
+    SwitchPanel,
+    SelectEntry,
+    FollowEntry,
+    ScrollContent,
+    ScrollPage,
+    Jump,
+    Search,
+    NextMatch,
+    EndSearch,
+    SelectLink,
+    FollowLink,
+    ShowOutline,
+    ToggleBacklinks,
+    OpenTab,
+    SwitchTab,
+    MoveTab,
+    CloseTab,
+    OpenAttachment,
+    Edit,
+    View,
+    Rename,
+    Move,
+    Delete,
 }
 
+/// The keybindings of the display screen, used both to dispatch key presses and to list them in the help overlay.
+/// Each binding consists of its action, the keys triggering it, how these keys are shown and what the action does.
+/// Keys bound to several actions trigger the first one that is currently available.
+const BINDINGS: [(Action, &[KeyCode], &str, &str); 28] = [
+    (
+        Action::Quit,
+        &[KeyCode::Char('q'), KeyCode::Char('Q')],
+        "Q",
+        "Quit",
+    ),
+    (
+        Action::ReturnToSelect,
+        &[KeyCode::Char('f'), KeyCode::Char('F')],
+        "F",
+        "Return to the select screen",
+    ),
+    (
+        Action::Back,
+        &[
+            KeyCode::Char('h'),
+            KeyCode::Char('H'),
+            KeyCode::Left,
+            KeyCode::Backspace,
+        ],
+        "H / Backspace",
+        "Return to the previous note",
+    ),
+    (
+        Action::NextTable,
+        &[KeyCode::Tab, KeyCode::BackTab],
+        "Tab / Shift+Tab",
+        "Focus the next / previous table",
+    ),
+    (
+        Action::SwitchPanel,
+        &[KeyCode::Tab, KeyCode::BackTab],
+        "Tab / Shift+Tab",
+        "Switch between the backlinks and the content",
+    ),
+    (
+        Action::SelectEntry,
+        &[
+            KeyCode::Char('j'),
+            KeyCode::Char('J'),
+            KeyCode::Down,
+            KeyCode::Char('k'),
+            KeyCode::Char('K'),
+            KeyCode::Up,
+        ],
+        "J / K",
+        "Select the next / previous entry",
+    ),
+    (
+        Action::FollowEntry,
+        &[
+            KeyCode::Enter,
+            KeyCode::Char('l'),
+            KeyCode::Char('L'),
+            KeyCode::Right,
+        ],
+        "Enter / L",
+        "Open the selected entry",
+    ),
+    (
+        Action::ScrollContent,
+        &[
+            KeyCode::Char('j'),
+            KeyCode::Char('J'),
+            KeyCode::Down,
+            KeyCode::Char('k'),
+            KeyCode::Char('K'),
+            KeyCode::Up,
+        ],
+        "J / K",
+        "Scroll the content",
+    ),
+    (
+        Action::ScrollPage,
+        &[KeyCode::PageUp, KeyCode::PageDown],
+        "PgUp / PgDn",
+        "Scroll the content by a page",
+    ),
+    (
+        Action::Jump,
+        &[KeyCode::Char('g'), KeyCode::Char('G')],
+        "G / Shift+G",
+        "Jump to the top / bottom of the content",
+    ),
+    (
+        Action::Search,
+        &[KeyCode::Char('/')],
+        "/",
+        "Search the content",
+    ),
+    (
+        Action::NextMatch,
+        &[KeyCode::Char('n'), KeyCode::Char('N')],
+        "N / Shift+N",
+        "Go to the next / previous match",
+    ),
+    (Action::EndSearch, &[KeyCode::Esc], "Esc", "End the search"),
+    (
+        Action::SelectLink,
+        &[
+            KeyCode::Char('n'),
+            KeyCode::Char('N'),
+            KeyCode::Char('p'),
+            KeyCode::Char('P'),
+        ],
+        "N / P",
+        "Select the next / previous link of the content",
+    ),
+    (
+        Action::FollowLink,
+        &[
+            KeyCode::Enter,
+            KeyCode::Char('l'),
+            KeyCode::Char('L'),
+            KeyCode::Right,
+        ],
+        "Enter / L",
+        "Open the selected link of the content",
+    ),
+    (
+        Action::ShowOutline,
+        &[KeyCode::Char('o'), KeyCode::Char('O')],
+        "O",
+        "Show the outline",
+    ),
+    (
+        Action::ToggleBacklinks,
+        &[KeyCode::Char('b'), KeyCode::Char('B')],
+        "B",
+        "Show or hide the backlinks",
+    ),
+    (
+        Action::OpenTab,
+        &[KeyCode::Char('t'), KeyCode::Char('T')],
+        "T",
+        "Open the selected link or entry in a new tab",
+    ),
+    (
+        Action::SwitchTab,
+        &[KeyCode::Char('['), KeyCode::Char(']')],
+        "[ / ]",
+        "Switch to the previous / next tab",
+    ),
+    (
+        Action::MoveTab,
+        &[KeyCode::Char('{'), KeyCode::Char('}')],
+        "{ / }",
+        "Move the tab to the left / right",
+    ),
+    (
+        Action::CloseTab,
+        &[KeyCode::Char('x'), KeyCode::Char('X')],
+        "X",
+        "Close the tab",
+    ),
+    (
+        Action::OpenAttachment,
+        &[
+            KeyCode::Char('e'),
+            KeyCode::Char('E'),
+            KeyCode::Char('v'),
+            KeyCode::Char('V'),
+        ],
+        "E / V",
+        "Open the attachment",
+    ),
+    (
+        Action::Edit,
+        &[KeyCode::Char('e'), KeyCode::Char('E')],
+        "E",
+        "Edit the note",
+    ),
+    (
+        Action::View,
+        &[KeyCode::Char('v'), KeyCode::Char('V')],
+        "V",
+        "View the note",
+    ),
+    (
+        Action::Rename,
+        &[KeyCode::Char('r'), KeyCode::Char('R')],
+        "R",
+        "Rename the note",
+    ),
+    (
+        Action::Move,
+        &[KeyCode::Char('m'), KeyCode::Char('M')],
+        "M",
+        "Move the note",
+    ),
+    (
+        Action::Delete,
+        &[KeyCode::Char('d'), KeyCode::Char('D')],
+        "D",
+        "Delete the note",
+    ),
+    (
+        Action::Help,
+        &[KeyCode::Char('?')],
+        "?",
+        "Show the keybindings",
+    ),
+];
+
 /// The display screen displays a single note to the user.
 pub struct DisplayScreen {
     // === CONFIG ===
@@ -27,10 +277,18 @@ pub struct DisplayScreen {
     builder: io::HtmlBuilder,
     /// The used styles.
     styles: ui::UiStyles,
+    /// The reading speed used to estimate the reading time of the note.
+    words_per_minute: usize,
 
     // === DATA ===
     /// The internal stats of the displayed note.
     note: data::Note,
+    /// The heading or block of the note the link followed to reach it pointed to, if any.
+    anchor: Option<String>,
+    /// Wether an attachment instead of a note is displayed, of which only a placeholder with its path is shown.
+    attachment: bool,
+    /// The scrollable content of the note, none for attachments.
+    content: Option<ui::NoteView>,
     /// A reference to the index of all notes
     index: data::NoteIndexContainer,
     /// Array of all the link tables, in the order
@@ -38,17 +296,32 @@ pub struct DisplayScreen {
     /// - links
     /// - l2 backlinks
     /// - l2 links
-    links: [Vec<(String, String)>; 4],
+    /// - related notes not yet linked
+    links: [Vec<(String, String)>; 5],
+    /// The sentence the first link of each backlinking note to the displayed note appears in, by the id of the linking note.
+    contexts: HashMap<String, String>,
 
     // === UI ===
     /// The text area used to create new notes.
     name_area: tui_textarea::TextArea<'static>,
     /// The index of the note selected in each table
-    selected: [usize; 4],
+    selected: [usize; 5],
     /// The index of the primary table currently focused
     foc_table: usize,
+    /// Wether the content of the note is focused instead of the link tables.
+    content_focused: bool,
+    /// Wether the backlinks panel is shown next to the content instead of the link tables below it.
+    show_backlinks: bool,
+    /// Wether the outline of the note's headings is shown next to the content. While shown, it takes the navigation keys.
+    show_outline: bool,
+    /// The position of the selected heading among the shown headings of the outline.
+    outline_selected: usize,
+    /// The positions of the headings in the note's outline whose subheadings are hidden.
+    outline_collapsed: HashSet<usize>,
     /// Current input mode
     mode: DisplayMode,
+    /// The new name of a rename waiting for confirmation, with the amount of linking notes changed on disk.
+    pending_rename: Option<(String, usize)>,
 }
 
 impl DisplayScreen {
@@ -59,16 +332,37 @@ impl DisplayScreen {
         manager: io::FileManager,
         builder: io::HtmlBuilder,
         styles: ui::UiStyles,
+        words_per_minute: usize,
     ) -> error::Result<Self> {
         let index_b = index.borrow();
-        // Cache the note
-        let note = index_b
-            .get(note_id)
-            .ok_or_else(|| error::RucolaError::NoteNotFound(note_id.to_owned()))
-            .cloned()?;
+        // Cache the note, or create a placeholder for an attachment
+        let (note, attachment) = match index_b.get_full(note_id)? {
+            Some(note) => (note, false),
+            None => {
+                let path = index_b
+                    .attachment_path(note_id)
+                    .ok_or_else(|| error::RucolaError::NoteNotFound(note_id.to_owned()))?;
+                (
+                    data::Note {
+                        name: path
+                            .file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_default(),
+                        id: note_id.to_owned(),
+                        path: path.to_path_buf(),
+                        ..Default::default()
+                    },
+                    true,
+                )
+            }
+        };
 
-        // Get level 1 links
-        let l1links = index_b.links_vec(note_id);
+        // Get level 1 links, including the referenced attachments
+        let l1links = index_b
+            .links_vec(note_id)
+            .into_iter()
+            .chain(index_b.attachments_vec(note_id))
+            .collect_vec();
 
         // Get level 2 links
         let l2links = l1links
@@ -77,13 +371,42 @@ impl DisplayScreen {
             .collect_vec();
 
         // Get level 1 backlinks
-        let l1blinks = index_b.blinks_vec(note_id);
+        let l1blinks = if attachment {
+            index_b.attachment_blinks_vec(note_id)
+        } else {
+            index_b.blinks_vec(note_id)
+        };
+        // and where they link, keeping the first link of each note
+        let contexts = index_b
+            .backlink_contexts(note_id)
+            .into_iter()
+            .rev()
+            .collect();
         // Get level 2 backlinks
         let l2blinks = l1blinks
             .iter()
             .flat_map(|(id, _name)| index_b.blinks_vec(id))
             .collect();
 
+        // Get related notes that are not linked yet
+        let related = index_b
+            .related_notes(note_id, index_b.ids(), RELATED_AMOUNT)
+            .into_iter()
+            .flat_map(|(id, _similarity)| {
+                index_b.get(&id).map(|note| (id, note.title().to_owned()))
+            })
+            .collect();
+
+        // Load the content of notes to show it
+        let content = if attachment {
+            None
+        } else {
+            Some(ui::NoteView::new(
+                &std::fs::read_to_string(&note.path)?,
+                &styles,
+            )?)
+        };
+
         // Create input area and style it
 
         let mut name_area = tui_textarea::TextArea::default();
@@ -100,19 +423,96 @@ impl DisplayScreen {
         drop(index_b);
 
         Ok(Self {
-            links: [l1blinks, l1links, l2blinks, l2links],
+            links: [l1blinks, l1links, l2blinks, l2links, related],
+            contexts,
             note,
+            anchor: None,
+            attachment,
+            content_focused: content.is_some(),
+            show_backlinks: false,
+            show_outline: false,
+            outline_selected: 0,
+            outline_collapsed: HashSet::new(),
+            content,
             index,
             manager,
             builder,
             styles,
+            words_per_minute,
             name_area,
-            selected: [0; 4],
+            selected: [0; 5],
             foc_table: 0,
             mode: DisplayMode::Display,
+            pending_rename: None,
         })
     }
 
+    /// Sets the heading or block of the note to show, so viewing the note scrolls there.
+    pub fn set_anchor(&mut self, anchor: String) {
+        self.anchor = Some(anchor);
+    }
+
+    /// Returns the message to display the note selected in the currently focused link table.
+    /// Links of the note itself may point to a specific part of their target.
+    fn follow_selected_entry(&self) -> ui::Message {
+        self.links
+            // get the correct table
+            .get(self.foc_table)
+            // unwrap the current index
+            .and_then(|table| table.get(self.selected[self.foc_table]))
+            // and extract the id
+            .map(|(id, _name)| {
+                let index = self.index.borrow();
+                match self.note.anchors.iter().find(|(target, _anchor)| {
+                    self.foc_table == 1 && index.resolve_link(&self.note, target) == *id
+                }) {
+                    Some((_target, anchor)) => {
+                        ui::Message::DisplayAnchor(id.to_owned(), anchor.clone())
+                    }
+                    None => ui::Message::DisplayStackPush(id.to_owned()),
+                }
+            })
+            .unwrap_or(ui::Message::None)
+    }
+
+    /// Returns the message to display the note or attachment the selected link of the content points to.
+    /// Links to headings or blocks of notes let the display of the target know where to look.
+    fn follow_selected_link(&self) -> error::Result<ui::Message> {
+        let Some((reference, wikilink)) = self
+            .content
+            .as_ref()
+            .and_then(|content| content.selected_link())
+        else {
+            return Ok(ui::Message::None);
+        };
+
+        let index = self.index.borrow();
+        let id = if wikilink {
+            Some(data::name_to_id(reference))
+        } else {
+            data::link_url_to_id(reference)
+        };
+
+        match id.map(|id| index.resolve_link(&self.note, &id).to_owned()) {
+            Some(id) if index.get(&id).is_some() => Ok(match data::link_anchor(reference) {
+                Some(anchor) => ui::Message::DisplayAnchor(id, anchor),
+                None => ui::Message::DisplayStackPush(id),
+            }),
+            // Links that do not point to a note may still point to an attachment
+            _ if index
+                .attachment_path(&data::attachment_id(reference))
+                .is_some() =>
+            {
+                Ok(ui::Message::DisplayStackPush(data::attachment_id(
+                    reference,
+                )))
+            }
+            _ => Err(error::RucolaError::Input(format!(
+                "The link to {reference} does not point to a note or attachment."
+            ))),
+        }
+    }
+
     /// Sets the title & content of the name_area block
     fn set_name_area(&mut self, title: &str, content: Option<String>) {
         let title_top = block::Title::from(Line::from(vec![Span::styled(
@@ -137,21 +537,47 @@ impl super::Screen for DisplayScreen {
         // Generate vertical layout
         let vertical = Layout::vertical([
             Constraint::Length(1),
-            Constraint::Length(4),
-            Constraint::Fill(1),
+            Constraint::Length(5),
             Constraint::Fill(1),
         ]);
 
-        let [title_area, stats_area, links1_area, links2_area] = vertical.areas(area);
+        let [title_area, stats_area, body_area] = vertical.areas(area);
+
+        // The content of notes is shown above the link tables, or next to the backlinks panel
+        let constraints = if self.content.is_some() {
+            [Constraint::Fill(1), Constraint::Fill(1)]
+        } else {
+            [Constraint::Length(0), Constraint::Fill(1)]
+        };
+        let [content_area, body_area] = if self.show_backlinks {
+            Layout::horizontal(constraints)
+        } else {
+            Layout::vertical(constraints)
+        }
+        .areas(body_area);
+
+        // Related notes are shown in a sidebar next to the link tables
+        let [tables_area, related_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(30)]).areas(body_area);
+
+        let [links1_area, links2_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)]).areas(tables_area);
 
         // Title
-        let title = Line::from(vec![Span::styled(
-            self.note.name.as_str(),
+        let mut title = Line::from(vec![Span::styled(
+            self.note.title(),
             self.styles.title_style,
         )])
         .alignment(Alignment::Center);
+        if let Some(anchor) = &self.anchor {
+            title.push_span(Span::styled(format!(" › {anchor}"), self.styles.text_style));
+        }
 
         let instructions_bot_right = block::Title::from(Line::from(vec![
+            Span::styled("O", self.styles.hotkey_style),
+            Span::styled("utline──", self.styles.text_style),
+            Span::styled("B", self.styles.hotkey_style),
+            Span::styled("acklinks──", self.styles.text_style),
             Span::styled("V", self.styles.hotkey_style),
             Span::styled("iew──", self.styles.text_style),
             Span::styled("E", self.styles.hotkey_style),
@@ -166,14 +592,30 @@ impl super::Screen for DisplayScreen {
         .alignment(Alignment::Right)
         .position(block::Position::Bottom);
 
-        let stats = self.note.to_stats_table(&self.styles).block(
-            Block::bordered()
-                .title(style::Styled::set_style(
-                    "Statistics",
-                    self.styles.title_style,
-                ))
-                .title(instructions_bot_right),
-        );
+        let instructions_tabs = block::Title::from(Line::from(vec![
+            Span::styled("T", self.styles.hotkey_style),
+            Span::styled(": New Tab──", self.styles.text_style),
+            Span::styled("[", self.styles.hotkey_style),
+            Span::styled("/", self.styles.text_style),
+            Span::styled("]", self.styles.hotkey_style),
+            Span::styled(": Switch Tab──", self.styles.text_style),
+            Span::styled("{", self.styles.hotkey_style),
+            Span::styled("/", self.styles.text_style),
+            Span::styled("}", self.styles.hotkey_style),
+            Span::styled(": Move Tab──", self.styles.text_style),
+            Span::styled("X", self.styles.hotkey_style),
+            Span::styled(": Close Tab", self.styles.text_style),
+        ]))
+        .alignment(Alignment::Left)
+        .position(block::Position::Bottom);
+
+        let stats_block = Block::bordered()
+            .title(style::Styled::set_style(
+                "Statistics",
+                self.styles.title_style,
+            ))
+            .title(instructions_tabs)
+            .title(instructions_bot_right);
 
         // === All the links ===
 
@@ -183,16 +625,114 @@ impl super::Screen for DisplayScreen {
         let [blinks2, links2] = horizontal.areas(links2_area);
 
         Widget::render(title, title_area, buf);
-        Widget::render(stats, stats_area, buf);
+        if self.attachment {
+            // attachments have no statistics, only show where they are
+            let placeholder = Paragraph::new(vec![
+                Line::styled("Attachment", self.styles.subtitle_style),
+                Line::styled(
+                    self.index
+                        .borrow()
+                        .relative_path(&self.note)
+                        .to_string_lossy()
+                        .to_string(),
+                    self.styles.text_style,
+                ),
+            ])
+            .block(stats_block);
+            Widget::render(placeholder, stats_area, buf);
+        } else {
+            let stats = self
+                .note
+                .to_stats_table(&self.styles, self.words_per_minute)
+                .block(stats_block);
+            Widget::render(stats, stats_area, buf);
+        }
 
-        self.draw_link_table(0, "Backlinks", blinks1, buf);
-        self.draw_link_table(1, "Links", links1, buf);
-        self.draw_link_table(2, "Level 2 Backlinks", blinks2, buf);
-        self.draw_link_table(3, "Level 2 Links", links2, buf);
+        if let Some(content) = &self.content {
+            let instructions_bot_left = block::Title::from(Line::from(vec![
+                Span::styled("PgUp", self.styles.hotkey_style),
+                Span::styled("/", self.styles.text_style),
+                Span::styled("PgDn", self.styles.hotkey_style),
+                Span::styled(": Scroll──", self.styles.text_style),
+                Span::styled("G", self.styles.hotkey_style),
+                Span::styled("/", self.styles.text_style),
+                Span::styled("Shift+G", self.styles.hotkey_style),
+                Span::styled(": Top/Bottom──", self.styles.text_style),
+                Span::styled("/", self.styles.hotkey_style),
+                Span::styled(": Search──", self.styles.text_style),
+                Span::styled("N", self.styles.hotkey_style),
+                Span::styled("/", self.styles.text_style),
+                Span::styled("P", self.styles.hotkey_style),
+                Span::styled(": Next/Previous Link──", self.styles.text_style),
+                Span::styled("󰌑", self.styles.hotkey_style),
+                Span::styled(": Follow──", self.styles.text_style),
+                Span::styled("󰁮", self.styles.hotkey_style),
+                Span::styled(": Back", self.styles.text_style),
+            ]))
+            .alignment(Alignment::Left)
+            .position(block::Position::Bottom);
+
+            let mut block = Block::bordered()
+                .title(style::Styled::set_style("Content", self.styles.title_style))
+                .title(instructions_bot_left);
+            // While searching, show which match is the current one
+            if let Some((query, current, total)) = content.search_status() {
+                block = block
+                    .title(
+                        block::Title::from(Line::styled(
+                            match total {
+                                0 => format!("No matches for \"{query}\""),
+                                _ => format!("\"{query}\": {current}/{total}"),
+                            },
+                            self.styles.text_style,
+                        ))
+                        .alignment(Alignment::Right),
+                    )
+                    .title(
+                        block::Title::from(Line::from(vec![
+                            Span::styled("N", self.styles.hotkey_style),
+                            Span::styled("/", self.styles.text_style),
+                            Span::styled("Shift+N", self.styles.hotkey_style),
+                            Span::styled(": Next/Previous Match──", self.styles.text_style),
+                            Span::styled("Esc", self.styles.hotkey_style),
+                            Span::styled(": End Search", self.styles.text_style),
+                        ]))
+                        .alignment(Alignment::Right)
+                        .position(block::Position::Bottom),
+                    );
+            }
+            let block = block.border_style(if self.content_focused {
+                self.styles.title_style
+            } else {
+                Style::default()
+            });
+            // The outline is shown to the left of the content
+            let [outline_area, content_area] = Layout::horizontal([
+                Constraint::Length(if self.show_outline { 30 } else { 0 }),
+                Constraint::Fill(1),
+            ])
+            .areas(content_area);
+            if self.show_outline {
+                self.draw_outline(outline_area, buf);
+            }
+            content.draw(content_area, buf, block);
+        }
+
+        if self.show_backlinks {
+            self.draw_backlinks_panel(body_area, buf);
+        } else {
+            self.draw_link_table(0, "Backlinks", blinks1, buf);
+            self.draw_link_table(1, "Links", links1, buf);
+            self.draw_link_table(2, "Level 2 Backlinks", blinks2, buf);
+            self.draw_link_table(3, "Level 2 Links", links2, buf);
+            self.draw_link_table(4, "Related", related_area, buf);
+        }
 
         if self.mode == DisplayMode::Rename
             || self.mode == DisplayMode::Move
             || self.mode == DisplayMode::Delete
+            || self.mode == DisplayMode::ConfirmRename
+            || self.mode == DisplayMode::Search
         {
             let popup_areas = Layout::vertical([
                 Constraint::Fill(1),
@@ -229,6 +769,24 @@ impl super::Screen for DisplayScreen {
                 .block(Block::bordered().title(keys));
 
                 Widget::render(del, center_area, buf);
+            } else if let Some((_name, changed)) = &self.pending_rename {
+                let keys = block::Title::from(Line::from(vec![
+                    Span::styled("󰌑", self.styles.hotkey_style),
+                    Span::styled(": Rename anyway─", self.styles.text_style),
+                    Span::styled("Other", self.styles.hotkey_style),
+                    Span::styled(": Abort", self.styles.text_style),
+                ]))
+                .alignment(Alignment::Center)
+                .position(block::Position::Bottom);
+
+                let conflict = Paragraph::new(Span::styled(
+                    format!("{changed} linking notes were changed outside of rucola since they were read."),
+                    self.styles.text_style,
+                ))
+                .alignment(Alignment::Center)
+                .block(Block::bordered().title(keys));
+
+                Widget::render(conflict, center_area, buf);
             } else {
                 let name_input = self.name_area.widget();
                 Widget::render(name_input, center_area, buf);
@@ -236,87 +794,220 @@ impl super::Screen for DisplayScreen {
         }
     }
 
+    fn keybindings(&self) -> Vec<(String, &'static str)> {
+        let mut keybindings = Vec::new();
+        if self.show_outline {
+            keybindings.extend(
+                [
+                    ("J / K", "Select the next / previous heading"),
+                    ("Space", "Collapse or expand the subheadings"),
+                    ("Enter / L", "Scroll the content to the selected heading"),
+                    ("O / Esc", "Close the outline"),
+                ]
+                .map(|(keys, description)| (keys.to_owned(), description)),
+            );
+        }
+        // only list the keys that currently trigger their binding
+        keybindings.extend(super::live_keybindings(&BINDINGS, |code| self.action(code)));
+        keybindings
+    }
+
     fn update(&mut self, key: crossterm::event::KeyEvent) -> error::Result<ui::Message> {
+        // While the outline is shown, it takes the navigation keys
+        if self.mode == DisplayMode::Display && self.show_outline && self.update_outline(key) {
+            return Ok(ui::Message::None);
+        }
+
         match self.mode {
-            DisplayMode::Display => match key.code {
-                // Quit with Q
-                KeyCode::Char('Q' | 'q') => {
+            DisplayMode::Display => match self.action(key.code) {
+                Some(Action::Quit) => {
                     return Ok(ui::Message::Quit);
                 }
-                // Go back to selection with f
-                KeyCode::Char('F' | 'f') => {
+                Some(Action::Help) => {
+                    return Ok(ui::Message::DisplayHelp);
+                }
+                Some(Action::ReturnToSelect) => {
                     return Ok(ui::Message::DisplayStackClear);
                 }
-                // Return to selection or previous note with left or H
-                KeyCode::Left | KeyCode::Char('H' | 'h') => {
+                Some(Action::Back) => {
                     return Ok(ui::Message::DisplayStackPop);
                 }
-                // Go up in the current list with k
-                KeyCode::Up | KeyCode::Char('K' | 'k') => {
-                    if let Some(selected) = self.selected.get_mut(self.foc_table) {
-                        *selected = selected.saturating_sub(1);
+                Some(Action::ScrollContent) => {
+                    if let Some(content) = &mut self.content {
+                        content.scroll_by(
+                            if matches!(key.code, KeyCode::Up | KeyCode::Char('K' | 'k')) {
+                                -1
+                            } else {
+                                1
+                            },
+                        );
+                    }
+                }
+                Some(Action::ScrollPage) => {
+                    if let Some(content) = &mut self.content {
+                        content.scroll_pages(if key.code == KeyCode::PageUp { -1 } else { 1 });
+                    }
+                }
+                Some(Action::Jump) => {
+                    if let Some(content) = &mut self.content {
+                        if key.code == KeyCode::Char('g') {
+                            content.scroll_to_top();
+                        } else {
+                            content.scroll_to_bottom();
+                        }
+                    }
+                }
+                Some(Action::Search) => {
+                    self.mode = DisplayMode::Search;
+                    let query = self
+                        .content
+                        .as_ref()
+                        .and_then(|content| content.search_status())
+                        .map(|(query, _, _)| query.to_owned());
+                    self.set_name_area("Search in note...", query);
+                }
+                Some(Action::NextMatch) => {
+                    if let Some(content) = &mut self.content {
+                        content.next_match(if key.code == KeyCode::Char('n') {
+                            1
+                        } else {
+                            -1
+                        });
+                    }
+                }
+                Some(Action::EndSearch) => {
+                    if let Some(content) = &mut self.content {
+                        content.search("")?;
+                    }
+                }
+                Some(Action::SelectLink) => {
+                    if let Some(content) = &mut self.content {
+                        content.select_link(if matches!(key.code, KeyCode::Char('n' | 'N')) {
+                            1
+                        } else {
+                            -1
+                        });
                     }
                 }
-                // Go down in the current list with j
-                KeyCode::Down | KeyCode::Char('J' | 'j') => {
+                Some(Action::FollowLink) => {
+                    return self.follow_selected_link();
+                }
+                Some(Action::ShowOutline) => {
+                    self.show_outline = true;
+                }
+                Some(Action::SelectEntry) => {
                     if let Some(selected) = self.selected.get_mut(self.foc_table) {
-                        *selected = selected.saturating_add(1).min(
-                            self.links
-                                .get(self.foc_table)
-                                .map(|list| list.len().saturating_sub(1))
-                                .unwrap_or_default(),
-                        );
+                        *selected = if matches!(key.code, KeyCode::Up | KeyCode::Char('K' | 'k')) {
+                            selected.saturating_sub(1)
+                        } else {
+                            selected.saturating_add(1).min(
+                                self.links
+                                    .get(self.foc_table)
+                                    .map(|list| list.len().saturating_sub(1))
+                                    .unwrap_or_default(),
+                            )
+                        };
+                    }
+                }
+                Some(Action::FollowEntry) => {
+                    return Ok(self.follow_selected_entry());
+                }
+                // Show or hide the backlinks panel, focusing it when shown
+                Some(Action::ToggleBacklinks) => {
+                    self.show_backlinks = !self.show_backlinks;
+                    if self.show_backlinks {
+                        self.content_focused = false;
+                        self.foc_table = 0;
+                    }
+                }
+                // While the backlinks panel is shown, Tab switches between it and the content
+                Some(Action::SwitchPanel) => {
+                    self.content_focused = !self.content_focused && self.content.is_some();
+                    self.foc_table = 0;
+                }
+                // Change list, passing through the content if there is any
+                Some(Action::NextTable) if key.code == KeyCode::Tab => {
+                    if self.content_focused {
+                        self.content_focused = false;
+                        self.foc_table = 0;
+                    } else if self.content.is_some() && self.foc_table + 1 == self.links.len() {
+                        self.content_focused = true;
+                    } else {
+                        self.foc_table = (self.foc_table + 1) % self.links.len();
                     }
                 }
-                // Change list with Tab
-                KeyCode::Tab => {
-                    self.foc_table = (self.foc_table.wrapping_add(1)) % 4;
-                }
-                // Change list back with Shift+Tab or H
-                KeyCode::BackTab => {
-                    self.foc_table = (self.foc_table.wrapping_sub(1)) % 4;
-                }
-                // If enter, switch to that note
-                KeyCode::Enter | KeyCode::Right | KeyCode::Char('L' | 'l') => {
-                    return Ok(self
-                        .links
-                        // get the correct table
-                        .get(self.foc_table)
-                        // unwrap the current index
-                        .and_then(|table| table.get(self.selected[self.foc_table]))
-                        // and extract the id
-                        .map(|(id, _name)| ui::Message::DisplayStackPush(id.to_owned()))
-                        .unwrap_or(ui::Message::None));
-                }
-                // Open selected item in editor
-                KeyCode::Char('e' | 'E') => {
+                Some(Action::NextTable) => {
+                    if self.content_focused {
+                        self.content_focused = false;
+                        self.foc_table = self.links.len() - 1;
+                    } else if self.content.is_some() && self.foc_table == 0 {
+                        self.content_focused = true;
+                    } else {
+                        self.foc_table = (self.foc_table + self.links.len() - 1) % self.links.len();
+                    }
+                }
+                Some(Action::OpenTab) => {
+                    let message = if self.content_focused {
+                        self.follow_selected_link()?
+                    } else {
+                        self.follow_selected_entry()
+                    };
+                    return Ok(match message {
+                        ui::Message::DisplayStackPush(id) => ui::Message::TabOpen(id, None),
+                        ui::Message::DisplayAnchor(id, anchor) => {
+                            ui::Message::TabOpen(id, Some(anchor))
+                        }
+                        message => message,
+                    });
+                }
+                Some(Action::SwitchTab) => {
+                    return Ok(if key.code == KeyCode::Char(']') {
+                        ui::Message::TabNext
+                    } else {
+                        ui::Message::TabPrevious
+                    });
+                }
+                Some(Action::MoveTab) => {
+                    return Ok(ui::Message::TabMove(if key.code == KeyCode::Char('}') {
+                        1
+                    } else {
+                        -1
+                    }));
+                }
+                Some(Action::CloseTab) => {
+                    return Ok(ui::Message::TabClose);
+                }
+                // Open attachments with the system's default program
+                Some(Action::OpenAttachment) => {
                     return Ok(ui::Message::OpenExternalCommand(
+                        self.manager.create_open_command(&self.note.path)?,
+                    ));
+                }
+                Some(Action::Edit) => {
+                    return Ok(ui::Message::EditNote(
                         self.manager.create_edit_command(&self.note.path)?,
+                        self.note.path.clone(),
                     ));
                 }
-                // Open selected item in viewer
-                KeyCode::Char('v' | 'V') => {
+                Some(Action::View) => {
                     self.builder.create_html(&self.note, true)?;
                     return Ok(ui::Message::OpenExternalCommand(
-                        self.builder.create_view_command(&self.note)?,
+                        self.builder
+                            .create_view_command(&self.note, self.anchor.as_deref())?,
                     ));
                 }
-                // R: Rename note
-                KeyCode::Char('r' | 'R') => {
+                Some(Action::Rename) => {
                     self.mode = DisplayMode::Rename;
                     self.set_name_area("Enter new name of note...", Some(self.note.name.clone()));
                 }
-                // M: Move note
-                KeyCode::Char('m' | 'M') => {
+                Some(Action::Move) => {
                     self.mode = DisplayMode::Move;
                     self.set_name_area("Enter new location relative to vault...", None);
                 }
-                // D: Move note
-                KeyCode::Char('d' | 'D') => {
+                Some(Action::Delete) => {
                     self.mode = DisplayMode::Delete;
                 }
-
-                _ => {}
+                None => {}
             },
             DisplayMode::Rename => match key.code {
                 KeyCode::Esc => {
@@ -325,18 +1016,49 @@ impl super::Screen for DisplayScreen {
                 }
                 KeyCode::Enter => {
                     self.mode = DisplayMode::Display;
-                    self.manager.rename_note_file(
-                        self.index.clone(),
-                        &data::name_to_id(&self.note.name),
-                        super::extract_string_and_clear(&mut self.name_area).ok_or_else(|| {
+                    let new_name = super::extract_string_and_clear(&mut self.name_area)
+                        .ok_or_else(|| {
                             error::RucolaError::Input("New name is empty.".to_string())
-                        })?,
-                    )?;
+                        })?;
+                    match self.manager.rename_note_file(
+                        self.index.clone(),
+                        &self.note.id,
+                        new_name.clone(),
+                        false,
+                    ) {
+                        // Linking notes were changed on disk, ask before overwriting them
+                        Err(error::RucolaError::ExternalChange(changed)) => {
+                            self.pending_rename = Some((new_name, changed.len()));
+                            self.mode = DisplayMode::ConfirmRename;
+                        }
+                        result => result?,
+                    }
                 }
                 _ => {
                     self.name_area.input(key);
                 }
             },
+            DisplayMode::Search => match key.code {
+                KeyCode::Esc => {
+                    super::extract_string_and_clear(&mut self.name_area);
+                    self.mode = DisplayMode::Display;
+                    if let Some(content) = &mut self.content {
+                        content.search("")?;
+                    }
+                }
+                KeyCode::Enter => {
+                    super::extract_string_and_clear(&mut self.name_area);
+                    self.mode = DisplayMode::Display;
+                }
+                // Search while typing
+                _ => {
+                    self.name_area.input(key);
+                    let query = self.name_area.lines().first().cloned().unwrap_or_default();
+                    if let Some(content) = &mut self.content {
+                        content.search(&query)?;
+                    }
+                }
+            },
             DisplayMode::Move => match key.code {
                 KeyCode::Esc => {
                     super::extract_string_and_clear(&mut self.name_area);
@@ -346,7 +1068,7 @@ impl super::Screen for DisplayScreen {
                     self.mode = DisplayMode::Display;
                     self.manager.move_note_file(
                         self.index.clone(),
-                        &data::name_to_id(&self.note.name),
+                        &self.note.id,
                         super::extract_string_and_clear(&mut self.name_area).ok_or_else(|| {
                             error::RucolaError::Input("Move location is empty.".to_string())
                         })?,
@@ -361,13 +1083,26 @@ impl super::Screen for DisplayScreen {
                 KeyCode::Enter => {
                     // delete it from index & filesystem
                     self.manager
-                        .delete_note_file(self.index.clone(), &data::name_to_id(&self.note.name))?;
+                        .delete_note_file(self.index.clone(), &self.note.id)?;
                     return Ok(ui::Message::DisplayStackPop);
                 }
                 _ => {
                     self.mode = DisplayMode::Display;
                 }
             },
+            DisplayMode::ConfirmRename => {
+                self.mode = DisplayMode::Display;
+                if let Some((new_name, _changed)) = self.pending_rename.take() {
+                    if key.code == KeyCode::Enter {
+                        self.manager.rename_note_file(
+                            self.index.clone(),
+                            &self.note.id,
+                            new_name,
+                            true,
+                        )?;
+                    }
+                }
+            }
         }
 
         Ok(ui::Message::None)
@@ -375,6 +1110,234 @@ impl super::Screen for DisplayScreen {
 }
 
 impl DisplayScreen {
+    /// Returns the action the given key triggers while the note is displayed, if any.
+    fn action(&self, code: KeyCode) -> Option<Action> {
+        BINDINGS
+            .iter()
+            .find(|(action, codes, _keys, _description)| {
+                codes.contains(&code) && self.is_available(*action)
+            })
+            .map(|(action, _codes, _keys, _description)| *action)
+    }
+
+    /// Checks wether the given action can currently be triggered.
+    fn is_available(&self, action: Action) -> bool {
+        match action {
+            Action::SelectEntry | Action::FollowEntry => !self.content_focused,
+            Action::ScrollContent | Action::SelectLink | Action::FollowLink => self.content_focused,
+            Action::ScrollPage | Action::Jump | Action::Search | Action::ShowOutline => {
+                self.content.is_some()
+            }
+            Action::NextMatch | Action::EndSearch => self
+                .content
+                .as_ref()
+                .is_some_and(|content| content.search_status().is_some()),
+            Action::NextTable => !self.show_backlinks,
+            Action::SwitchPanel => self.show_backlinks,
+            Action::OpenAttachment => self.attachment,
+            Action::Edit | Action::View | Action::Rename | Action::Move | Action::Delete => {
+                !self.attachment
+            }
+            Action::Quit
+            | Action::Help
+            | Action::ReturnToSelect
+            | Action::Back
+            | Action::ToggleBacklinks
+            | Action::OpenTab
+            | Action::SwitchTab
+            | Action::MoveTab
+            | Action::CloseTab => true,
+        }
+    }
+
+    /// Returns the positions of the headings of the note's outline that are not hidden below a collapsed heading.
+    fn visible_outline(&self) -> Vec<usize> {
+        let mut visible = Vec::new();
+        // the level of the collapsed heading whose subheadings are currently skipped
+        let mut hidden_below = None;
+        for (position, (level, _text, _line)) in self.note.outline.iter().enumerate() {
+            match hidden_below {
+                Some(collapsed) if *level > collapsed => continue,
+                _ => hidden_below = None,
+            }
+            visible.push(position);
+            if self.outline_collapsed.contains(&position) {
+                hidden_below = Some(*level);
+            }
+        }
+        visible
+    }
+
+    /// Handles a key press while the outline is shown. Returns wether the key was used by the outline.
+    fn update_outline(&mut self, key: crossterm::event::KeyEvent) -> bool {
+        let visible = self.visible_outline();
+        let selected = visible.get(self.outline_selected).copied();
+        match key.code {
+            // Close the outline with o or escape
+            KeyCode::Char('o' | 'O') | KeyCode::Esc => {
+                self.show_outline = false;
+            }
+            KeyCode::Char('j' | 'J') | KeyCode::Down => {
+                self.outline_selected = self
+                    .outline_selected
+                    .saturating_add(1)
+                    .min(visible.len().saturating_sub(1));
+            }
+            KeyCode::Char('k' | 'K') | KeyCode::Up => {
+                self.outline_selected = self.outline_selected.saturating_sub(1);
+            }
+            // Collapse or expand the subheadings of the selected heading with space
+            KeyCode::Char(' ') => {
+                if let Some(selected) = selected {
+                    if !self.outline_collapsed.remove(&selected) {
+                        self.outline_collapsed.insert(selected);
+                    }
+                }
+            }
+            // Scroll the content to the selected heading
+            KeyCode::Enter | KeyCode::Char('l' | 'L') | KeyCode::Right => {
+                if let (Some((_level, _text, line)), Some(content)) = (
+                    selected.and_then(|selected| self.note.outline.get(selected)),
+                    &mut self.content,
+                ) {
+                    content.scroll_to_line(*line);
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Draws the outline of the note's headings, indented by their level, with the selected heading highlighted.
+    fn draw_outline(&self, area: Rect, buf: &mut Buffer) {
+        let items = self
+            .visible_outline()
+            .into_iter()
+            .map(|position| {
+                let (level, text, _line) = &self.note.outline[position];
+                let has_subheadings = self
+                    .note
+                    .outline
+                    .get(position + 1)
+                    .is_some_and(|(next_level, _, _)| next_level > level);
+                let marker = match (has_subheadings, self.outline_collapsed.contains(&position)) {
+                    (false, _) => "  ",
+                    (true, false) => "▾ ",
+                    (true, true) => "▸ ",
+                };
+                ListItem::new(Line::styled(
+                    format!("{}{marker}{text}", "  ".repeat(level.saturating_sub(1))),
+                    if *level == 1 {
+                        self.styles.subtitle_style
+                    } else {
+                        self.styles.text_style
+                    },
+                ))
+            })
+            .collect_vec();
+
+        let instructions_bot_left = block::Title::from(Line::from(vec![
+            Span::styled("󰌑", self.styles.hotkey_style),
+            Span::styled(": Jump──", self.styles.text_style),
+            Span::styled("Space", self.styles.hotkey_style),
+            Span::styled(": Fold──", self.styles.text_style),
+            Span::styled("O", self.styles.hotkey_style),
+            Span::styled(": Hide", self.styles.text_style),
+        ]))
+        .alignment(Alignment::Left)
+        .position(block::Position::Bottom);
+
+        let list = List::new(items)
+            .highlight_style(self.styles.selected_style)
+            .block(
+                Block::bordered()
+                    .title(style::Styled::set_style("Outline", self.styles.title_style))
+                    .title(instructions_bot_left),
+            );
+
+        let mut state = ListState::default().with_selected(Some(self.outline_selected));
+        StatefulWidget::render(list, area, buf, &mut state);
+    }
+
+    /// Draws the panel listing all notes linking to the displayed note, each with the sentence it links from.
+    /// The panel shares its selection with the backlinks table.
+    fn draw_backlinks_panel(&self, area: Rect, buf: &mut Buffer) {
+        let backlinks = &self.links[0];
+
+        let rows = backlinks
+            .iter()
+            .map(|(id, title)| {
+                Row::new(vec![Cell::from(Text::from(vec![
+                    Line::styled(title.as_str(), self.styles.text_style),
+                    Line::styled(
+                        format!(
+                            "  {}",
+                            self.contexts
+                                .get(id)
+                                .map(|context| context.as_str())
+                                .unwrap_or_default()
+                        ),
+                        self.styles.subtitle_style,
+                    ),
+                ]))])
+                .height(2)
+            })
+            .collect_vec();
+
+        // keep the selected backlink in the upper third of the panel, each taking two rows
+        let visible = area.height.saturating_sub(2) as usize / 2;
+        let mut state = TableState::new()
+            .with_offset(
+                self.selected[0]
+                    .saturating_sub(visible / 3)
+                    .min(backlinks.len().saturating_sub(visible)),
+            )
+            .with_selected(Some(self.selected[0]));
+
+        let instructions_bot_left = block::Title::from(Line::from(vec![
+            Span::styled("J", self.styles.hotkey_style),
+            Span::styled("/", self.styles.text_style),
+            Span::styled("K", self.styles.hotkey_style),
+            Span::styled(": Select──", self.styles.text_style),
+            Span::styled("󰌑", self.styles.hotkey_style),
+            Span::styled(": Open──", self.styles.text_style),
+            Span::styled("Tab", self.styles.hotkey_style),
+            Span::styled(": Content──", self.styles.text_style),
+            Span::styled("B", self.styles.hotkey_style),
+            Span::styled(": Hide", self.styles.text_style),
+        ]))
+        .alignment(Alignment::Left)
+        .position(block::Position::Bottom);
+
+        let count = block::Title::from(Line::styled(
+            format!(
+                "{} Note{}",
+                backlinks.len(),
+                if backlinks.len() == 1 { "" } else { "s" }
+            ),
+            self.styles.text_style,
+        ))
+        .alignment(Alignment::Right);
+
+        let table = Table::new(rows, [Constraint::Fill(1)])
+            .highlight_style(if self.content_focused {
+                self.styles.text_style
+            } else {
+                self.styles.selected_style
+            })
+            .block(
+                Block::bordered()
+                    .title(style::Styled::set_style(
+                        "Backlinks",
+                        self.styles.title_style,
+                    ))
+                    .title(count)
+                    .title(instructions_bot_left),
+            );
+
+        StatefulWidget::render(table, area, buf, &mut state);
+    }
+
     fn draw_link_table(&self, index: usize, title: &str, area: Rect, buf: &mut Buffer) {
         // Title
         let title = block::Title::from(Line::from(vec![Span::styled(
@@ -441,8 +1404,20 @@ impl DisplayScreen {
             .get(index)
             .map(|list| {
                 list.iter()
-                    .map(|(_id, name)| {
-                        Row::new(vec![Span::from(name).style(self.styles.text_style)])
+                    .map(|(id, name)| {
+                        Row::new(vec![
+                            Span::from(name).style(self.styles.text_style),
+                            // backlinks show where they link from
+                            Span::from(match index {
+                                0 => self
+                                    .contexts
+                                    .get(id)
+                                    .map(|c| c.as_str())
+                                    .unwrap_or_default(),
+                                _ => "",
+                            })
+                            .style(self.styles.subtitle_style),
+                        ])
                     })
                     .collect_vec()
             })
@@ -493,8 +1468,12 @@ impl DisplayScreen {
         };
 
         // Table
-        let table = Table::new(rows, [Constraint::Min(20)])
-            .highlight_style(if index == self.foc_table {
+        let widths = match index {
+            0 => [Constraint::Fill(1), Constraint::Fill(2)],
+            _ => [Constraint::Min(20), Constraint::Length(0)],
+        };
+        let table = Table::new(rows, widths)
+            .highlight_style(if index == self.foc_table && !self.content_focused {
                 self.styles.selected_style
             } else {
                 self.styles.text_style
@@ -0,0 +1,366 @@
+use crate::{data, error, ui};
+use crossterm::event::KeyCode;
+use ratatui::{
+    prelude::*,
+    widgets::{canvas, *},
+};
+use std::collections::HashSet;
+
+/// How far the view moves with each key press, relative to the visible width or height.
+const PAN_STEP: f64 = 0.1;
+/// How much the view is scaled with each key press.
+const ZOOM_STEP: f64 = 1.5;
+/// The maximum amount of labels shown at the default zoom level. Zooming in shows proportionally more.
+const LABELS: f64 = 20.;
+/// The maximum amount of notes shown, as laying them out takes quadratic time. Larger environments only show their best linked notes.
+const MAX_NOTES: usize = 500;
+
+/// An action of the graph screen, triggered by the keys bound to it in [BINDINGS].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    Close,
+    Help,
+    Pan,
+    Zoom,
+    Reset,
+    Select,
+    Open,
+}
+
+/// The keybindings of the graph screen, used both to dispatch key presses and to list them in the help overlay.
+/// Each binding consists of its action, the keys triggering it, how these keys are shown and what the action does.
+const BINDINGS: [(Action, &[KeyCode], &str, &str); 7] = [
+    (
+        Action::Close,
+        &[KeyCode::Esc, KeyCode::Char('q'), KeyCode::Char('Q')],
+        "Esc / Q",
+        "Return to the select screen",
+    ),
+    (
+        Action::Pan,
+        &[
+            KeyCode::Char('h'),
+            KeyCode::Char('H'),
+            KeyCode::Left,
+            KeyCode::Char('j'),
+            KeyCode::Char('J'),
+            KeyCode::Down,
+            KeyCode::Char('k'),
+            KeyCode::Char('K'),
+            KeyCode::Up,
+            KeyCode::Char('l'),
+            KeyCode::Char('L'),
+            KeyCode::Right,
+        ],
+        "H / J / K / L",
+        "Move the view",
+    ),
+    (
+        Action::Zoom,
+        &[KeyCode::Char('+'), KeyCode::Char('='), KeyCode::Char('-')],
+        "+ / -",
+        "Zoom in / out",
+    ),
+    (
+        Action::Reset,
+        &[KeyCode::Char('0')],
+        "0",
+        "Show the whole graph",
+    ),
+    (
+        Action::Select,
+        &[KeyCode::Tab, KeyCode::BackTab],
+        "Tab / Shift+Tab",
+        "Select the next / previous note",
+    ),
+    (
+        Action::Open,
+        &[KeyCode::Enter],
+        "Enter",
+        "Open the selected note",
+    ),
+    (
+        Action::Help,
+        &[KeyCode::Char('?')],
+        "?",
+        "Show the keybindings",
+    ),
+];
+
+/// The graph screen draws the link graph of an environment as nodes and edges and allows the user to move around in it and open its notes.
+pub struct GraphScreen {
+    /// The link graph of the shown notes of the environment.
+    graph: data::LinkGraph,
+    /// The amount of notes in the environment, which might be more than are shown, see [MAX_NOTES].
+    total: usize,
+    /// The position of each note of the graph in the plane, within [-1, 1].
+    positions: Vec<(f64, f64)>,
+    /// The title of each note of the graph.
+    titles: Vec<String>,
+    /// The pairs of linked notes, each pair once.
+    edges: Vec<(usize, usize)>,
+    /// For each note of the graph, the positions of the notes it is linked with.
+    neighbours: Vec<HashSet<usize>>,
+    /// The position of the currently selected note in the graph.
+    selected: usize,
+    /// The point shown in the center of the screen.
+    center: (f64, f64),
+    /// How far the view is zoomed in, 1 showing the whole graph.
+    zoom: f64,
+    /// The used styles.
+    styles: ui::UiStyles,
+}
+
+impl GraphScreen {
+    /// Creates a new graph screen showing the links between the notes of the given environment.
+    pub fn new(
+        env_stats: &data::EnvironmentStats,
+        index: data::NoteIndexContainer,
+        styles: ui::UiStyles,
+    ) -> Self {
+        let index = index.borrow();
+        let graph = data::LinkGraph::new(&index, env_stats.ids().iter());
+        let total = graph.len();
+
+        // Of large environments, only keep the notes with the most links
+        let graph = if total > MAX_NOTES {
+            let mut degrees = vec![0; total];
+            for (a, b) in graph.undirected_edges() {
+                degrees[a] += 1;
+                degrees[b] += 1;
+            }
+            let mut order = (0..total).collect::<Vec<_>>();
+            order.sort_by_key(|&position| std::cmp::Reverse(degrees[position]));
+            data::LinkGraph::new(
+                &index,
+                order
+                    .into_iter()
+                    .take(MAX_NOTES)
+                    .map(|position| &graph.ids[position]),
+            )
+        } else {
+            graph
+        };
+
+        let edges = graph.undirected_edges();
+        let mut neighbours = vec![HashSet::new(); graph.len()];
+        for &(a, b) in edges.iter() {
+            neighbours[a].insert(b);
+            neighbours[b].insert(a);
+        }
+
+        let titles = graph
+            .ids
+            .iter()
+            .map(|id| {
+                index
+                    .get(id)
+                    .map(|note| note.title().to_owned())
+                    .unwrap_or_else(|| id.clone())
+            })
+            .collect();
+
+        Self {
+            positions: graph.layout(),
+            edges,
+            neighbours,
+            graph,
+            total,
+            titles,
+            selected: 0,
+            center: (0., 0.),
+            zoom: 1.,
+            styles,
+        }
+    }
+
+    /// The amount of coordinate units visible from the center to each edge of the screen.
+    fn half_extent(&self) -> f64 {
+        // leave some room around the graph at the default zoom level
+        1.1 / self.zoom
+    }
+
+    /// Selects the note at the given offset from the currently selected one and moves the view to it.
+    fn select_relative(&mut self, offset: isize) {
+        if self.positions.is_empty() {
+            return;
+        }
+        self.selected =
+            (self.selected as isize + offset).rem_euclid(self.graph.len() as isize) as usize;
+        self.center = self.positions[self.selected];
+    }
+}
+
+impl super::Screen for GraphScreen {
+    fn draw(&self, area: layout::Rect, buf: &mut buffer::Buffer) {
+        let instructions_bot_left = block::Title::from(Line::from(vec![
+            Span::styled("H", self.styles.hotkey_style),
+            Span::styled("/", self.styles.text_style),
+            Span::styled("J", self.styles.hotkey_style),
+            Span::styled("/", self.styles.text_style),
+            Span::styled("K", self.styles.hotkey_style),
+            Span::styled("/", self.styles.text_style),
+            Span::styled("L", self.styles.hotkey_style),
+            Span::styled(": Move──", self.styles.text_style),
+            Span::styled("+", self.styles.hotkey_style),
+            Span::styled("/", self.styles.text_style),
+            Span::styled("-", self.styles.hotkey_style),
+            Span::styled(": Zoom──", self.styles.text_style),
+            Span::styled("0", self.styles.hotkey_style),
+            Span::styled(": Reset──", self.styles.text_style),
+            Span::styled("Tab", self.styles.hotkey_style),
+            Span::styled(": Select──", self.styles.text_style),
+            Span::styled("󰌑", self.styles.hotkey_style),
+            Span::styled(": Open──", self.styles.text_style),
+            Span::styled("Q", self.styles.hotkey_style),
+            Span::styled(": Back", self.styles.text_style),
+        ]))
+        .alignment(Alignment::Left)
+        .position(block::Position::Bottom);
+
+        let notes = if self.total > self.graph.len() {
+            format!("{} of {} notes", self.graph.len(), self.total)
+        } else {
+            format!("{} notes", self.total)
+        };
+        let summary = block::Title::from(Line::styled(
+            format!("{notes}, {} links", self.edges.len()),
+            self.styles.text_style,
+        ))
+        .alignment(Alignment::Right);
+
+        let edge_color = self.styles.text_style.fg.unwrap_or(Color::Reset);
+        let highlight_color = self.styles.hotkey_style.fg.unwrap_or(Color::Reset);
+        let node_color = self.styles.subtitle_style.fg.unwrap_or(Color::Reset);
+
+        let extent = self.half_extent();
+        // terminal cells are about twice as high as wide, so show more horizontally to keep the graph's proportions
+        let aspect = (area.width as f64 / (2. * area.height.max(1) as f64)).max(1.);
+        let show_all_labels = (self.graph.len() as f64) <= LABELS * self.zoom * self.zoom;
+        let selected_neighbours = self.neighbours.get(self.selected);
+
+        let diagram = canvas::Canvas::default()
+            .block(
+                Block::bordered()
+                    .title(style::Styled::set_style("Graph", self.styles.title_style))
+                    .title(summary)
+                    .title(instructions_bot_left),
+            )
+            .marker(symbols::Marker::Braille)
+            .x_bounds([
+                self.center.0 - extent * aspect,
+                self.center.0 + extent * aspect,
+            ])
+            .y_bounds([self.center.1 - extent, self.center.1 + extent])
+            .paint(|ctx| {
+                // Edges, those of the selected note highlighted
+                for &(a, b) in self.edges.iter() {
+                    let ((x1, y1), (x2, y2)) = (self.positions[a], self.positions[b]);
+                    let color = if a == self.selected || b == self.selected {
+                        highlight_color
+                    } else {
+                        edge_color
+                    };
+                    ctx.draw(&canvas::Line::new(x1, y1, x2, y2, color));
+                }
+                ctx.layer();
+
+                // Nodes
+                ctx.draw(&canvas::Points {
+                    coords: &self.positions,
+                    color: node_color,
+                });
+                ctx.layer();
+
+                // Labels of all notes if there is enough room, otherwise only of the selected note and its neighbours
+                for (position, (&(x, y), title)) in
+                    self.positions.iter().zip(self.titles.iter()).enumerate()
+                {
+                    let style = if position == self.selected {
+                        self.styles.selected_style
+                    } else if show_all_labels
+                        || selected_neighbours
+                            .is_some_and(|neighbours| neighbours.contains(&position))
+                    {
+                        self.styles.text_style
+                    } else {
+                        continue;
+                    };
+                    ctx.print(x, y, Span::styled(title.clone(), style));
+                }
+            });
+
+        Widget::render(diagram, area, buf);
+    }
+
+    fn keybindings(&self) -> Vec<(String, &'static str)> {
+        super::live_keybindings(&BINDINGS, |code| self.action(code))
+    }
+
+    fn update(&mut self, key: crossterm::event::KeyEvent) -> error::Result<ui::Message> {
+        let step = PAN_STEP * 2. * self.half_extent();
+        Ok(match self.action(key.code) {
+            // Back to the select screen
+            Some(Action::Close) => ui::Message::CloseGraph,
+            Some(Action::Help) => ui::Message::DisplayHelp,
+            // Panning
+            Some(Action::Pan) => {
+                match key.code {
+                    KeyCode::Char('h' | 'H') | KeyCode::Left => self.center.0 -= step,
+                    KeyCode::Char('l' | 'L') | KeyCode::Right => self.center.0 += step,
+                    KeyCode::Char('j' | 'J') | KeyCode::Down => self.center.1 -= step,
+                    _ => self.center.1 += step,
+                }
+                ui::Message::None
+            }
+            // Zooming
+            Some(Action::Zoom) => {
+                self.zoom = if key.code == KeyCode::Char('-') {
+                    (self.zoom / ZOOM_STEP).max(1. / ZOOM_STEP)
+                } else {
+                    self.zoom * ZOOM_STEP
+                };
+                ui::Message::None
+            }
+            // Show the whole graph again
+            Some(Action::Reset) => {
+                self.zoom = 1.;
+                self.center = (0., 0.);
+                ui::Message::None
+            }
+            // Selection, moving the view to the selected note
+            Some(Action::Select) => {
+                self.select_relative(if key.code == KeyCode::Tab { 1 } else { -1 });
+                ui::Message::None
+            }
+            // Open the selected note
+            Some(Action::Open) => self
+                .graph
+                .ids
+                .get(self.selected)
+                .map(|id| ui::Message::DisplayStackPush(id.clone()))
+                .unwrap_or(ui::Message::None),
+            None => ui::Message::None,
+        })
+    }
+}
+
+impl GraphScreen {
+    /// Returns the action the given key triggers, if any.
+    fn action(&self, code: KeyCode) -> Option<Action> {
+        BINDINGS
+            .iter()
+            .find(|(action, codes, _keys, _description)| {
+                codes.contains(&code) && self.is_available(*action)
+            })
+            .map(|(action, _codes, _keys, _description)| *action)
+    }
+
+    /// Checks wether the given action can currently be triggered.
+    fn is_available(&self, action: Action) -> bool {
+        match action {
+            Action::Select | Action::Open => !self.graph.ids.is_empty(),
+            Action::Close | Action::Help | Action::Pan | Action::Zoom | Action::Reset => true,
+        }
+    }
+}
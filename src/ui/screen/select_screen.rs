@@ -1,6 +1,10 @@
 use crate::{data, error, io, ui};
 use crossterm::event::KeyCode;
 use ratatui::{prelude::*, widgets::*};
+use std::{
+    collections::{HashMap, HashSet},
+    path, time,
+};
 
 use tui_textarea::TextArea;
 
@@ -14,6 +18,8 @@ enum SelectMode {
     SubmenuFile,
     /// Sorting submenu
     SubmenuSorting,
+    /// Submenu to show or hide columns of the note list
+    SubmenuColumns,
     /// Typing into the filter box.
     Filter,
     /// Show the help screen for the filter box.
@@ -24,8 +30,501 @@ enum SelectMode {
     Rename,
     /// Typing into the create box to move a note.
     Move,
+    /// Confirming a rename that overwrites changes made outside of rucola to notes linking to the renamed one.
+    ConfirmRename,
 }
 
+/// An action of the select screen in its main mode or a submenu, triggered by the keys bound to it in [BINDINGS].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    Quit,
+    Help,
+    CloseMenu,
+    FileMenu,
+    SortMenu,
+    ColumnMenu,
+    Filter,
+    FilterHelp,
+    ClearFilter,
+    ToggleAny,
+    CycleTitleMode,
+    Select,
+    SelectFirst,
+    Open,
+    Edit,
+    Pin,
+    NextOrphan,
+    NextDeadEnd,
+    Random,
+    TogglePreview,
+    ScrollPreview,
+    Stats,
+    Graph,
+    Vaults,
+    Create,
+    View,
+    Rename,
+    Move,
+    Delete,
+    Undo,
+    Trash,
+    Rescan,
+    Sort(data::SortingMode),
+    Reverse,
+    ToggleColumn(data::Column),
+}
+
+/// The keybindings of the select screen, used both to dispatch key presses and to list them in the help overlay.
+/// Each binding consists of the mode it applies in, its action, the keys triggering it, how these keys are shown and what the action does.
+const BINDINGS: [(SelectMode, Action, &[KeyCode], &str, &str); 62] = [
+    (
+        SelectMode::Select,
+        Action::Quit,
+        &[KeyCode::Char('q'), KeyCode::Char('Q')],
+        "Q",
+        "Quit",
+    ),
+    (
+        SelectMode::Select,
+        Action::Select,
+        &[
+            KeyCode::Char('j'),
+            KeyCode::Char('J'),
+            KeyCode::Down,
+            KeyCode::Char('k'),
+            KeyCode::Char('K'),
+            KeyCode::Up,
+        ],
+        "J / K",
+        "Select the next / previous note",
+    ),
+    (
+        SelectMode::Select,
+        Action::SelectFirst,
+        &[KeyCode::Char('0')],
+        "0",
+        "Select the first note",
+    ),
+    (
+        SelectMode::Select,
+        Action::Open,
+        &[
+            KeyCode::Enter,
+            KeyCode::Char('l'),
+            KeyCode::Char('L'),
+            KeyCode::Right,
+        ],
+        "Enter / L",
+        "Open the selected note",
+    ),
+    (
+        SelectMode::Select,
+        Action::Filter,
+        &[KeyCode::Char('f'), KeyCode::Char('F'), KeyCode::Char('/')],
+        "F / /",
+        "Filter the notes",
+    ),
+    (
+        SelectMode::Select,
+        Action::ClearFilter,
+        &[KeyCode::Char('c'), KeyCode::Char('C')],
+        "C",
+        "Clear the filter",
+    ),
+    (
+        SelectMode::Select,
+        Action::ToggleAny,
+        &[KeyCode::Char('a'), KeyCode::Char('A')],
+        "A",
+        "Switch between matching all and any conditions",
+    ),
+    (
+        SelectMode::Select,
+        Action::CycleTitleMode,
+        &[KeyCode::Char('t'), KeyCode::Char('T')],
+        "T",
+        "Cycle through title matching modes",
+    ),
+    (
+        SelectMode::Select,
+        Action::FilterHelp,
+        &[KeyCode::Char('h'), KeyCode::Char('H')],
+        "H",
+        "Show the filter syntax",
+    ),
+    (
+        SelectMode::Select,
+        Action::SortMenu,
+        &[KeyCode::Char('s'), KeyCode::Char('S')],
+        "S",
+        "Sort the notes",
+    ),
+    (
+        SelectMode::Select,
+        Action::ColumnMenu,
+        &[KeyCode::Char('u'), KeyCode::Char('U')],
+        "U",
+        "Show or hide columns",
+    ),
+    (
+        SelectMode::Select,
+        Action::FileMenu,
+        &[KeyCode::Char('m'), KeyCode::Char('M')],
+        "M",
+        "Manage the selected note",
+    ),
+    (
+        SelectMode::Select,
+        Action::Edit,
+        &[KeyCode::Char('e'), KeyCode::Char('E')],
+        "E",
+        "Edit the selected note",
+    ),
+    (
+        SelectMode::Select,
+        Action::Pin,
+        &[KeyCode::Char('p'), KeyCode::Char('P')],
+        "P",
+        "Pin or unpin the selected note",
+    ),
+    (
+        SelectMode::Select,
+        Action::NextOrphan,
+        &[KeyCode::Char('o'), KeyCode::Char('O')],
+        "O",
+        "Select the next note without incoming links",
+    ),
+    (
+        SelectMode::Select,
+        Action::NextDeadEnd,
+        &[KeyCode::Char('d'), KeyCode::Char('D')],
+        "D",
+        "Select the next note without outgoing links",
+    ),
+    (
+        SelectMode::Select,
+        Action::Random,
+        &[KeyCode::Char('r'), KeyCode::Char('R')],
+        "R",
+        "Open a random note",
+    ),
+    (
+        SelectMode::Select,
+        Action::TogglePreview,
+        &[KeyCode::Char('w'), KeyCode::Char('W')],
+        "W",
+        "Show or hide the preview",
+    ),
+    (
+        SelectMode::Select,
+        Action::ScrollPreview,
+        &[KeyCode::PageUp, KeyCode::PageDown],
+        "PgUp / PgDn",
+        "Scroll the preview",
+    ),
+    (
+        SelectMode::Select,
+        Action::Stats,
+        &[KeyCode::Char('i'), KeyCode::Char('I')],
+        "I",
+        "Show the statistics of the filtered notes",
+    ),
+    (
+        SelectMode::Select,
+        Action::Graph,
+        &[KeyCode::Char('g'), KeyCode::Char('G')],
+        "G",
+        "Show the link graph of the filtered notes",
+    ),
+    (
+        SelectMode::Select,
+        Action::Vaults,
+        &[KeyCode::Char('v'), KeyCode::Char('V')],
+        "V",
+        "Switch to another vault",
+    ),
+    (
+        SelectMode::Select,
+        Action::Help,
+        &[KeyCode::Char('?')],
+        "?",
+        "Show the keybindings",
+    ),
+    (
+        SelectMode::SubmenuFile,
+        Action::Create,
+        &[KeyCode::Char('n'), KeyCode::Char('N')],
+        "N",
+        "Create a new note",
+    ),
+    (
+        SelectMode::SubmenuFile,
+        Action::Edit,
+        &[KeyCode::Char('e'), KeyCode::Char('E')],
+        "E",
+        "Edit the selected note",
+    ),
+    (
+        SelectMode::SubmenuFile,
+        Action::View,
+        &[KeyCode::Char('v'), KeyCode::Char('V')],
+        "V",
+        "View the selected note",
+    ),
+    (
+        SelectMode::SubmenuFile,
+        Action::Rename,
+        &[KeyCode::Char('r'), KeyCode::Char('R')],
+        "R",
+        "Rename the selected note",
+    ),
+    (
+        SelectMode::SubmenuFile,
+        Action::Move,
+        &[KeyCode::Char('m'), KeyCode::Char('M')],
+        "M",
+        "Move the selected note",
+    ),
+    (
+        SelectMode::SubmenuFile,
+        Action::Delete,
+        &[KeyCode::Char('d'), KeyCode::Char('D')],
+        "D",
+        "Delete the selected note",
+    ),
+    (
+        SelectMode::SubmenuFile,
+        Action::Undo,
+        &[KeyCode::Char('z'), KeyCode::Char('Z')],
+        "Z",
+        "Undo the last rename, move or deletion",
+    ),
+    (
+        SelectMode::SubmenuFile,
+        Action::Trash,
+        &[KeyCode::Char('t'), KeyCode::Char('T')],
+        "T",
+        "Browse the trash",
+    ),
+    (
+        SelectMode::SubmenuFile,
+        Action::Rescan,
+        &[KeyCode::Char('u'), KeyCode::Char('U')],
+        "U",
+        "Index the vault again",
+    ),
+    (
+        SelectMode::SubmenuFile,
+        Action::CloseMenu,
+        &[KeyCode::Esc],
+        "Esc",
+        "Close the menu",
+    ),
+    (
+        SelectMode::SubmenuFile,
+        Action::Help,
+        &[KeyCode::Char('?')],
+        "?",
+        "Show the keybindings",
+    ),
+    (
+        SelectMode::SubmenuSorting,
+        Action::Sort(data::SortingMode::Name),
+        &[KeyCode::Char('a'), KeyCode::Char('A')],
+        "A",
+        "Sort by name",
+    ),
+    (
+        SelectMode::SubmenuSorting,
+        Action::Sort(data::SortingMode::Words),
+        &[KeyCode::Char('w'), KeyCode::Char('W')],
+        "W",
+        "Sort by words",
+    ),
+    (
+        SelectMode::SubmenuSorting,
+        Action::Sort(data::SortingMode::Chars),
+        &[KeyCode::Char('c'), KeyCode::Char('C')],
+        "C",
+        "Sort by characters",
+    ),
+    (
+        SelectMode::SubmenuSorting,
+        Action::Sort(data::SortingMode::GlobalOutLinks),
+        &[KeyCode::Char('o'), KeyCode::Char('O')],
+        "O",
+        "Sort by outlinks",
+    ),
+    (
+        SelectMode::SubmenuSorting,
+        Action::Sort(data::SortingMode::LocalOutLinks),
+        &[KeyCode::Char('u'), KeyCode::Char('U')],
+        "U",
+        "Sort by outlinks within the filtered notes",
+    ),
+    (
+        SelectMode::SubmenuSorting,
+        Action::Sort(data::SortingMode::GlobalInLinks),
+        &[KeyCode::Char('i'), KeyCode::Char('I')],
+        "I",
+        "Sort by inlinks",
+    ),
+    (
+        SelectMode::SubmenuSorting,
+        Action::Sort(data::SortingMode::LocalInLinks),
+        &[KeyCode::Char('n'), KeyCode::Char('N')],
+        "N",
+        "Sort by inlinks within the filtered notes",
+    ),
+    (
+        SelectMode::SubmenuSorting,
+        Action::Sort(data::SortingMode::Broken),
+        &[KeyCode::Char('b'), KeyCode::Char('B')],
+        "B",
+        "Sort by broken links",
+    ),
+    (
+        SelectMode::SubmenuSorting,
+        Action::Sort(data::SortingMode::PageRank),
+        &[KeyCode::Char('p'), KeyCode::Char('P')],
+        "P",
+        "Sort by PageRank",
+    ),
+    (
+        SelectMode::SubmenuSorting,
+        Action::Sort(data::SortingMode::Betweenness),
+        &[KeyCode::Char('e'), KeyCode::Char('E')],
+        "E",
+        "Sort by betweenness",
+    ),
+    (
+        SelectMode::SubmenuSorting,
+        Action::Sort(data::SortingMode::Degree),
+        &[KeyCode::Char('d'), KeyCode::Char('D')],
+        "D",
+        "Sort by degree",
+    ),
+    (
+        SelectMode::SubmenuSorting,
+        Action::Sort(data::SortingMode::Staleness),
+        &[KeyCode::Char('t'), KeyCode::Char('T')],
+        "T",
+        "Sort by staleness",
+    ),
+    (
+        SelectMode::SubmenuSorting,
+        Action::Sort(data::SortingMode::LinkDensity),
+        &[KeyCode::Char('l'), KeyCode::Char('L')],
+        "L",
+        "Sort by link density",
+    ),
+    (
+        SelectMode::SubmenuSorting,
+        Action::Sort(data::SortingMode::Modified),
+        &[KeyCode::Char('m'), KeyCode::Char('M')],
+        "M",
+        "Sort by modification date",
+    ),
+    (
+        SelectMode::SubmenuSorting,
+        Action::Reverse,
+        &[KeyCode::Char('r'), KeyCode::Char('R')],
+        "R",
+        "Reverse the order",
+    ),
+    (
+        SelectMode::SubmenuSorting,
+        Action::CloseMenu,
+        &[KeyCode::Esc, KeyCode::Char('s'), KeyCode::Char('S')],
+        "Esc / S",
+        "Close the menu",
+    ),
+    (
+        SelectMode::SubmenuSorting,
+        Action::Help,
+        &[KeyCode::Char('?')],
+        "?",
+        "Show the keybindings",
+    ),
+    (
+        SelectMode::SubmenuColumns,
+        Action::ToggleColumn(data::Column::Words),
+        &[KeyCode::Char('w'), KeyCode::Char('W')],
+        "W",
+        "Show or hide words",
+    ),
+    (
+        SelectMode::SubmenuColumns,
+        Action::ToggleColumn(data::Column::Chars),
+        &[KeyCode::Char('c'), KeyCode::Char('C')],
+        "C",
+        "Show or hide characters",
+    ),
+    (
+        SelectMode::SubmenuColumns,
+        Action::ToggleColumn(data::Column::GlobalOutLinks),
+        &[KeyCode::Char('o'), KeyCode::Char('O')],
+        "O",
+        "Show or hide outlinks",
+    ),
+    (
+        SelectMode::SubmenuColumns,
+        Action::ToggleColumn(data::Column::LocalOutLinks),
+        &[KeyCode::Char('u'), KeyCode::Char('U')],
+        "U",
+        "Show or hide outlinks within the filtered notes",
+    ),
+    (
+        SelectMode::SubmenuColumns,
+        Action::ToggleColumn(data::Column::GlobalInLinks),
+        &[KeyCode::Char('i'), KeyCode::Char('I')],
+        "I",
+        "Show or hide inlinks",
+    ),
+    (
+        SelectMode::SubmenuColumns,
+        Action::ToggleColumn(data::Column::LocalInLinks),
+        &[KeyCode::Char('n'), KeyCode::Char('N')],
+        "N",
+        "Show or hide inlinks within the filtered notes",
+    ),
+    (
+        SelectMode::SubmenuColumns,
+        Action::ToggleColumn(data::Column::Broken),
+        &[KeyCode::Char('b'), KeyCode::Char('B')],
+        "B",
+        "Show or hide broken links",
+    ),
+    (
+        SelectMode::SubmenuColumns,
+        Action::ToggleColumn(data::Column::Modified),
+        &[KeyCode::Char('m'), KeyCode::Char('M')],
+        "M",
+        "Show or hide the modification date",
+    ),
+    (
+        SelectMode::SubmenuColumns,
+        Action::ToggleColumn(data::Column::Reading),
+        &[KeyCode::Char('r'), KeyCode::Char('R')],
+        "R",
+        "Show or hide the reading time",
+    ),
+    (
+        SelectMode::SubmenuColumns,
+        Action::CloseMenu,
+        &[KeyCode::Esc, KeyCode::Enter],
+        "Esc / Enter",
+        "Close the menu",
+    ),
+    (
+        SelectMode::SubmenuColumns,
+        Action::Help,
+        &[KeyCode::Char('?')],
+        "?",
+        "Show the keybindings",
+    ),
+];
+
 /// Describes when to show a which stats area.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 pub enum StatsShow {
@@ -38,6 +537,21 @@ pub enum StatsShow {
     Local,
 }
 
+/// How long to wait after the last keystroke in the filter area before applying the filter to the displayed statistics.
+const FILTER_DEBOUNCE: time::Duration = time::Duration::from_millis(150);
+
+/// Remembers the notes matched by the last applied filter, so filters narrowing it only need to check those notes again.
+struct FilterCache {
+    /// The content of the filter area the cached filter was created from.
+    input: String,
+    /// The any/all setting the cached filter was created with.
+    any: bool,
+    /// The title mode the cached filter was created with.
+    title_mode: data::TitleMode,
+    /// The ids of all notes in the environment created by the cached filter.
+    ids: HashSet<String>,
+}
+
 /// The select screen shows the user statistical information about their notes and allows them to select one for display.
 pub struct SelectScreen {
     // === DATA ===
@@ -59,25 +573,60 @@ pub struct SelectScreen {
     // === UI ===
     /// The text area to type in filters.
     filter_area: TextArea<'static>,
+    /// Previously entered filters, recalled with the arrow keys while typing a filter.
+    history: ui::FilterHistory,
+    /// The notes shown at the top of the list regardless of the filter.
+    pinned: data::PinnedNotes,
+    /// The amount of notes matching the filter currently typed in the filter area.
+    match_count: usize,
+    /// The time of the last change to the filter area that has not yet been applied to the displayed statistics.
+    filter_changed: Option<time::Instant>,
+    /// The notes matched by the last applied filter.
+    filter_cache: Option<FilterCache>,
     /// The text area used to create new notes.
     name_area: TextArea<'static>,
+    /// The templates to choose from when creating a note, loaded when starting to create one.
+    templates: Vec<path::PathBuf>,
+    /// The index of the template chosen for the note to create, if any.
+    template: Option<usize>,
     /// Current input mode
     mode: SelectMode,
+    /// The id and new name of a rename waiting for confirmation, with the amount of linking notes changed on disk.
+    pending_rename: Option<(String, String, usize)>,
     /// Current state of the list
     ///
     /// This is saved as a simple usize from which the ListState to use with ratatui is constructed in immediate mode.
     /// This allows us to convert only the neccessary notes to ListItems and save some time.
     selected: usize,
+    /// Wether to show a preview of the selected note next to the list.
+    show_preview: bool,
+    /// The id and content of the last previewed note, if it could be read.
+    /// Loaded while drawing, so the preview follows the selection however it changes.
+    preview: std::cell::RefCell<Option<(String, Option<ui::NoteView>)>>,
 
     // === Sorting options ===
     /// UI mode wether the user wants the filter conditions to all apply or if any (one of them) is enough.
     any_conditions: bool,
+    /// UI mode for how to match the title part of the filter.
+    title_mode: data::TitleMode,
     /// Ui mode for the chosen sorting variant
     sorting: data::SortingMode,
     /// Sort ascedingly.
     sorting_asc: bool,
+    /// The columns shown in the note list and the last sorting chosen by the user.
+    columns: data::ColumnLayout,
     /// How to display the two stats blocks.
     stats_show: StatsShow,
+    /// Named filters from the config file that can be inserted into the filter via `@name`.
+    named_filters: HashMap<String, String>,
+    /// Weights to score notes matching the filter with.
+    score_weights: data::ScoreWeights,
+    /// Wether to normalize titles before fuzzy matching them with the filter.
+    normalize_titles: bool,
+    /// The reading speed used to estimate reading times.
+    words_per_minute: usize,
+    /// When to mark notes as stale.
+    stale: data::StaleCriteria,
 }
 
 impl SelectScreen {
@@ -87,26 +636,52 @@ impl SelectScreen {
         manager: io::FileManager,
         builder: io::HtmlBuilder,
         styles: ui::UiStyles,
-        stats_show: StatsShow,
+        config: &crate::Config,
+        history: ui::FilterHistory,
+        pinned: data::PinnedNotes,
+        columns: data::ColumnLayout,
     ) -> Self {
         let mut res = Self {
-            local_stats: data::EnvironmentStats::new_with_filter(&index, data::Filter::default()),
+            local_stats: data::EnvironmentStats::new_with_filter_and_pinned(
+                &index,
+                data::Filter::default(),
+                &pinned,
+                None,
+            ),
             global_stats: data::EnvironmentStats::new_with_filter(&index, data::Filter::default()),
             index: index.clone(),
             styles,
             builder,
             manager,
             filter_area: TextArea::default(),
+            history,
+            pinned,
+            match_count: 0,
+            filter_changed: None,
+            filter_cache: None,
             name_area: TextArea::default(),
+            templates: Vec::new(),
+            template: None,
             mode: SelectMode::Select,
+            pending_rename: None,
             any_conditions: false,
-            sorting: data::SortingMode::Name,
-            sorting_asc: true,
+            title_mode: data::TitleMode::Fuzzy,
+            sorting: columns.sorting,
+            sorting_asc: columns.ascending,
+            columns,
             selected: 0,
-            stats_show,
+            show_preview: false,
+            preview: std::cell::RefCell::new(None),
+            stats_show: config.stats_show,
+            named_filters: config.filters.clone(),
+            score_weights: config.score_weights,
+            normalize_titles: config.normalize_titles,
+            words_per_minute: config.words_per_minute,
+            stale: config.stale,
         };
 
-        res.local_stats.sort(index, data::SortingMode::Name, true);
+        res.local_stats.sort(index, res.sorting, res.sorting_asc);
+        res.match_count = res.local_stats.len();
 
         res.style_text_area();
 
@@ -131,6 +706,14 @@ impl SelectScreen {
         .alignment(Alignment::Right)
         .position(block::Position::Top);
 
+        // The amount of notes matching the current filter.
+        let match_count = block::Title::from(Line::from(vec![Span::styled(
+            format!("{} matches", self.match_count),
+            self.styles.text_style,
+        )]))
+        .alignment(Alignment::Left)
+        .position(block::Position::Bottom);
+
         let instructions_bot = block::Title::from(Line::from(vec![
             Span::styled("A", self.styles.hotkey_style),
             Span::styled(
@@ -138,6 +721,11 @@ impl SelectScreen {
                 self.styles.text_style,
             ),
             Span::styled(" Conditions──", self.styles.text_style),
+            Span::styled("T", self.styles.hotkey_style),
+            Span::styled(
+                format!("itle: {}──", self.title_mode.label()),
+                self.styles.text_style,
+            ),
             Span::styled("H", self.styles.hotkey_style),
             Span::styled("elp", self.styles.text_style),
         ]))
@@ -154,6 +742,7 @@ impl SelectScreen {
             Block::bordered()
                 .title(title_top)
                 .title(instructions)
+                .title(match_count)
                 .title(instructions_bot),
         );
 
@@ -187,28 +776,182 @@ impl SelectScreen {
         }
     }
 
+    /// Sets the title of the name area to show the chosen template of the note to create.
+    fn set_create_area_title(&mut self) {
+        let title = match self.template.and_then(|index| self.templates.get(index)) {
+            Some(template) => format!(
+                "Enter name of new note from template {} (Tab: Next template)...",
+                template
+                    .file_stem()
+                    .map(|name| name.to_string_lossy())
+                    .unwrap_or_default()
+            ),
+            None if !self.templates.is_empty() => {
+                String::from("Enter name of new note (Tab: Choose template)...")
+            }
+            None => String::from("Enter name of new note..."),
+        };
+        self.set_name_area(&title, None);
+    }
+
     /// Returns the heights of the global and local stats area with this filter string
     pub fn stats_heights(&self, filter_string: Option<&String>) -> (u16, u16) {
         let filtered = filter_string.map(|s| !s.is_empty()).unwrap_or(false);
         match self.stats_show {
-            StatsShow::Both => (5, 6),
+            StatsShow::Both => (7, 8),
             StatsShow::Relevant => {
                 if filtered {
-                    (0, 6)
+                    (0, 8)
                 } else {
-                    (6, 0)
+                    (8, 0)
                 }
             }
-            StatsShow::Local => (0, 6),
+            StatsShow::Local => (0, 8),
+        }
+    }
+
+    /// Returns the message to open the selected note in the editor, if a note is selected.
+    fn edit_selected(&self) -> error::Result<ui::Message> {
+        let Some(path) = self
+            // get the selected item in the list for the id
+            .local_stats
+            .get_selected(self.selected)
+            // use this id in the index to get the note
+            .and_then(|env_stats| {
+                // use the id to get the path
+                self.index
+                    .borrow()
+                    .get(&env_stats.id)
+                    .map(|note| note.path.clone())
+            })
+        else {
+            return Ok(ui::Message::None);
+        };
+        // use the config to create a valid opening command
+        Ok(ui::Message::EditNote(
+            self.manager.create_edit_command(&path)?,
+            path,
+        ))
+    }
+
+    /// Draws the content of the selected note, loading it first if a different note was previewed before.
+    fn draw_preview(&self, area: Rect, buf: &mut Buffer) {
+        let selected = self
+            .local_stats
+            .get_selected(self.selected)
+            .map(|env_stats| env_stats.id.clone());
+
+        let mut preview = self.preview.borrow_mut();
+        if let Some(id) = selected {
+            if preview
+                .as_ref()
+                .map_or(true, |(previewed, _)| *previewed != id)
+            {
+                let view = self
+                    .index
+                    .borrow()
+                    .get(&id)
+                    .and_then(|note| std::fs::read_to_string(&note.path).ok())
+                    .and_then(|content| ui::NoteView::new(&content, &self.styles).ok());
+                *preview = Some((id, view));
+            }
+        } else {
+            *preview = None;
+        }
+
+        let title = preview
+            .as_ref()
+            .and_then(|(id, _)| {
+                self.index
+                    .borrow()
+                    .get(id)
+                    .map(|note| note.title().to_owned())
+            })
+            .unwrap_or_default();
+        let instructions_bot_left = block::Title::from(Line::from(vec![
+            Span::styled("PgUp", self.styles.hotkey_style),
+            Span::styled("/", self.styles.text_style),
+            Span::styled("PgDn", self.styles.hotkey_style),
+            Span::styled(": Scroll", self.styles.text_style),
+        ]))
+        .alignment(Alignment::Left)
+        .position(block::Position::Bottom);
+        let block = Block::bordered()
+            .title(style::Styled::set_style("Preview", self.styles.title_style))
+            .title(
+                block::Title::from(Line::styled(title, self.styles.text_style))
+                    .alignment(Alignment::Right),
+            )
+            .title(instructions_bot_left);
+
+        match preview.as_ref() {
+            Some((_, Some(view))) => view.draw(area, buf, block),
+            Some((_, None)) => Widget::render(
+                Paragraph::new(Line::styled(
+                    "The note could not be read.",
+                    self.styles.text_style,
+                ))
+                .block(block),
+                area,
+                buf,
+            ),
+            None => Widget::render(block, area, buf),
         }
     }
 
+    /// Returns the statistics of the notes currently matching the filter.
+    pub fn local_stats(&self) -> &data::EnvironmentStats {
+        &self.local_stats
+    }
+
+    /// Returns the statistics of the whole vault.
+    pub fn global_stats(&self) -> &data::EnvironmentStats {
+        &self.global_stats
+    }
+
+    /// Returns the current content of the filter area.
+    fn filter_input(&self) -> String {
+        self.filter_area
+            .lines()
+            .first()
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns the ids of the notes that can possibly match a filter created from the given input, if this is known to be a subset of all notes.
+    fn cached_candidates(&self, input: &str) -> Option<&HashSet<String>> {
+        self.filter_cache
+            .as_ref()
+            .filter(|cache| {
+                !cache.any
+                    && !self.any_conditions
+                    && cache.title_mode == self.title_mode
+                    && data::Filter::narrows(&cache.input, input)
+            })
+            .map(|cache| &cache.ids)
+    }
+
+    /// Remembers the notes of the current local environment as the result of a filter created from the given input.
+    fn remember_filter(&mut self, input: String) {
+        self.filter_cache = Some(FilterCache {
+            input,
+            any: self.any_conditions,
+            title_mode: self.title_mode,
+            ids: self.local_stats.ids().into_iter().collect(),
+        });
+    }
+
     /// Creates a filter from the current content of the filter area.
     fn filter_from_input(&self) -> data::Filter {
         self.filter_area
             .lines()
             .first()
-            .map(|l| data::Filter::new(l, self.any_conditions))
+            .map(|l| {
+                data::Filter::new_with_named(l, self.any_conditions, &self.named_filters)
+                    .with_title_mode(self.title_mode)
+                    .with_weights(self.score_weights)
+                    .with_normalization(self.normalize_titles)
+            })
             .unwrap_or_default()
     }
 
@@ -216,38 +959,129 @@ impl SelectScreen {
     /// Every filtering neccessarily triggers a non-stable resort.
     fn filter(&mut self, filter: data::Filter) {
         // actual filtering
-        self.local_stats = data::EnvironmentStats::new_with_filter(&self.index, filter);
-        // reset sorting
-        self.sorting_asc = false;
-        self.sorting = data::SortingMode::Score;
-        self.local_stats
-            .sort(self.index.clone(), self.sorting, self.sorting_asc);
+        let input = self.filter_input();
+        self.local_stats = data::EnvironmentStats::new_with_filter_and_pinned(
+            &self.index,
+            filter,
+            &self.pinned,
+            self.cached_candidates(&input),
+        );
+        self.remember_filter(input);
+        self.filter_changed = None;
+        self.set_match_count(self.local_stats.len());
+        // reset sorting, unless the filter specified its own
+        if !self.local_stats.sort_by_directives(self.index.clone()) {
+            self.sorting_asc = false;
+            self.sorting = data::SortingMode::Score;
+            self.local_stats
+                .sort(self.index.clone(), self.sorting, self.sorting_asc);
+        }
         // on a new filter, select the first element
         self.selected = 0;
     }
 
+    /// Counts the notes matching the filter in the filter area without re-creating the statistics, which is deferred until typing pauses.
+    /// Filters searching the full text or the link graph are too expensive to count on every keystroke, their count is updated once typing pauses.
+    fn filter_later(&mut self) {
+        let filter = self.filter_from_input();
+        if !filter.is_expensive() {
+            let count = data::EnvironmentStats::count_matches(
+                &self.index,
+                &filter,
+                &self.pinned,
+                self.cached_candidates(&self.filter_input()),
+            );
+            self.set_match_count(count);
+        }
+        self.filter_changed = Some(time::Instant::now());
+    }
+
+    /// Applies the filter in the filter area, if it was changed and no further changes happened for a short while.
+    pub fn apply_pending_filter(&mut self) {
+        if self
+            .filter_changed
+            .is_some_and(|changed| changed.elapsed() >= FILTER_DEBOUNCE)
+        {
+            self.filter(self.filter_from_input());
+        }
+    }
+
+    /// Sets the displayed amount of matching notes, restyling the filter area if it changed.
+    fn set_match_count(&mut self, count: usize) {
+        if count != self.match_count {
+            self.match_count = count;
+            self.style_text_area();
+        }
+    }
+
     /// Re-creates the global and local stats from the index.
     /// To be performed after file management operations.
     pub fn refresh_env_stats(&mut self) {
+        // The previewed note may have changed as well
+        *self.preview.get_mut() = None;
         // Refresh global stats
         self.global_stats =
             data::EnvironmentStats::new_with_filter(&self.index, data::Filter::default());
         // Refresh local stats
-        self.local_stats =
-            data::EnvironmentStats::new_with_filter(&self.index, self.filter_from_input());
+        self.local_stats = data::EnvironmentStats::new_with_filter_and_pinned(
+            &self.index,
+            self.filter_from_input(),
+            &self.pinned,
+            None,
+        );
+        self.remember_filter(self.filter_input());
+
+        self.filter_changed = None;
+        self.set_match_count(self.local_stats.len());
 
         // Refresh sorting
+        if !self.local_stats.sort_by_directives(self.index.clone()) {
+            self.local_stats
+                .sort(self.index.clone(), self.sorting, self.sorting_asc);
+        }
+    }
+
+    /// Updates the global and local stats after the notes with the given ids changed in the index, keeping the current filter.
+    /// To be performed after file events, where only few notes change at a time.
+    pub fn update_env_stats(&mut self, changed: &HashSet<String>) {
+        // Reload the preview if the previewed note changed
+        if self
+            .preview
+            .get_mut()
+            .as_ref()
+            .is_some_and(|(id, _)| changed.contains(id))
+        {
+            *self.preview.get_mut() = None;
+        }
+        self.global_stats
+            .update_notes(&self.index, &data::PinnedNotes::default(), changed);
         self.local_stats
-            .sort(self.index.clone(), self.sorting, self.sorting_asc);
+            .update_notes(&self.index, &self.pinned, changed);
+
+        // The remembered matches of the current filter changed as well
+        let ids = self.local_stats.ids().into_iter().collect();
+        if let Some(cache) = self.filter_cache.as_mut() {
+            cache.ids = ids;
+        }
+
+        if self.filter_changed.is_none() {
+            self.set_match_count(self.local_stats.len());
+        }
+
+        // Refresh sorting
+        if !self.local_stats.sort_by_directives(self.index.clone()) {
+            self.local_stats
+                .sort(self.index.clone(), self.sorting, self.sorting_asc);
+        }
     }
 
-    /// Sets a new sorting mode and direction.
+    /// Sets a new sorting mode and direction and remembers it for the next session.
     /// If it did not match the old one, triggers a resort.
     fn set_mode_and_maybe_sort(
         &mut self,
         new_mode: impl Into<Option<data::SortingMode>>,
         new_asc: bool,
-    ) {
+    ) -> error::Result<()> {
         // if the sorting mode or ascending option has changed, resort and select the first element
         let new_mode = new_mode.into().unwrap_or(self.sorting);
         if new_mode != self.sorting || new_asc != self.sorting_asc {
@@ -257,84 +1091,323 @@ impl SelectScreen {
                 .sort(self.index.clone(), self.sorting, self.sorting_asc);
             self.selected = 0;
         }
+        if new_mode != self.columns.sorting || new_asc != self.columns.ascending {
+            self.columns.sorting = new_mode;
+            self.columns.ascending = new_asc;
+            self.columns.store(self.index.borrow().vault_path())?;
+        }
+        Ok(())
+    }
+
+    /// Returns the action the given key triggers in the current mode, if any.
+    fn action(&self, code: KeyCode) -> Option<Action> {
+        BINDINGS
+            .iter()
+            .find(|(mode, _action, codes, _keys, _description)| {
+                *mode == self.mode && codes.contains(&code)
+            })
+            .map(|(_mode, action, _codes, _keys, _description)| *action)
+    }
+
+    /// Shows or hides the given column of the note list and remembers the layout for the next session.
+    fn toggle_column(&mut self, column: data::Column) -> error::Result<()> {
+        self.columns.toggle(column);
+        self.columns.store(self.index.borrow().vault_path())
     }
 }
 
 impl super::Screen for SelectScreen {
+    fn keybindings(&self) -> Vec<(String, &'static str)> {
+        let keybindings = match self.mode {
+            SelectMode::Select
+            | SelectMode::SubmenuFile
+            | SelectMode::SubmenuSorting
+            | SelectMode::SubmenuColumns => {
+                let bindings = BINDINGS
+                    .iter()
+                    .filter(|(mode, _action, _codes, _keys, _description)| *mode == self.mode)
+                    .map(|(_mode, action, codes, keys, description)| {
+                        (*action, *codes, *keys, *description)
+                    })
+                    .collect::<Vec<_>>();
+                // only list the keys that trigger their binding, and not an earlier binding of the same key
+                return super::live_keybindings(&bindings, |code| self.action(code));
+            }
+            SelectMode::Filter => vec![
+                ("Enter / Esc", "Apply the filter"),
+                ("Up / Down", "Recall previous filters"),
+            ],
+            SelectMode::FilterHelp => vec![("Esc / C", "Close the filter syntax")],
+            SelectMode::Create => vec![
+                ("Enter", "Create the note"),
+                ("Tab", "Choose the next template"),
+                ("Esc", "Cancel"),
+            ],
+            SelectMode::Rename | SelectMode::Move => {
+                vec![("Enter", "Confirm"), ("Esc", "Cancel")]
+            }
+            SelectMode::ConfirmRename => vec![
+                ("Enter", "Overwrite the changed notes"),
+                ("Any other key", "Cancel the rename"),
+            ],
+        };
+        keybindings
+            .into_iter()
+            .map(|(keys, description)| (keys.to_owned(), description))
+            .collect()
+    }
+
     fn update(&mut self, key: crossterm::event::KeyEvent) -> error::Result<ui::Message> {
         // Check for mode
         match self.mode {
-            // Main mode: Switch to modes, general command
-            SelectMode::Select => match key.code {
-                // Q: Quit application
-                KeyCode::Char('q' | 'Q') => return Ok(ui::Message::Quit),
-                // R: Got to file management submenu
-                KeyCode::Char('m' | 'M') => {
+            // Main mode and submenus: Switch to modes, general commands
+            SelectMode::Select
+            | SelectMode::SubmenuFile
+            | SelectMode::SubmenuSorting
+            | SelectMode::SubmenuColumns => match self.action(key.code) {
+                Some(Action::Quit) => return Ok(ui::Message::Quit),
+                Some(Action::Help) => return Ok(ui::Message::DisplayHelp),
+                Some(Action::CloseMenu) => {
+                    self.mode = SelectMode::Select;
+                }
+                Some(Action::FileMenu) => {
                     self.mode = SelectMode::SubmenuFile;
                 }
-                // S: Got to sorting submenu
-                KeyCode::Char('s' | 'S') => {
+                Some(Action::SortMenu) => {
                     self.mode = SelectMode::SubmenuSorting;
                 }
-                // F or /: Go to filter mode
-                KeyCode::Char('f' | 'F' | '/') => {
+                Some(Action::ColumnMenu) => {
+                    self.mode = SelectMode::SubmenuColumns;
+                }
+                Some(Action::Filter) => {
                     self.mode = SelectMode::Filter;
                 }
-                // ?: Go to filter help mode
-                KeyCode::Char('?' | 'h' | 'H') => {
+                Some(Action::FilterHelp) => {
                     self.mode = SelectMode::FilterHelp;
                 }
-                // C: Clear filter
-                KeyCode::Char('c' | 'C') => {
+                Some(Action::ClearFilter) => {
                     let _ = super::extract_string_and_clear(&mut self.filter_area);
                     self.filter(data::Filter::default());
                 }
-                // T: Change all/any words requirement
-                KeyCode::Char('a' | 'A') => {
+                // Change all/any words requirement
+                Some(Action::ToggleAny) => {
                     self.any_conditions = !self.any_conditions;
                     self.filter(self.filter_from_input());
                     self.style_text_area();
                 }
+                Some(Action::CycleTitleMode) => {
+                    self.title_mode = self.title_mode.next();
+                    self.filter(self.filter_from_input());
+                    self.style_text_area();
+                }
+                // Open the statistics of the filtered notes
+                Some(Action::Stats) => {
+                    return Ok(ui::Message::DisplayStats);
+                }
+                // Show the link graph of the filtered notes
+                Some(Action::Graph) => {
+                    return Ok(ui::Message::DisplayGraph);
+                }
+                // Open a random note out of the filtered ones
+                Some(Action::Random) => {
+                    return Ok(ui::Message::DisplayRandom(self.local_stats.ids()));
+                }
+                Some(Action::Vaults) => {
+                    return Ok(ui::Message::DisplayVaults);
+                }
+                Some(Action::Pin) => {
+                    if let Some(id) = self
+                        .local_stats
+                        .get_selected(self.selected)
+                        .map(|env_stats| env_stats.id.clone())
+                    {
+                        self.pinned.toggle(&id);
+                        self.pinned.store(self.index.borrow().vault_path())?;
+                        self.refresh_env_stats();
+                        // keep the note selected at its new position
+                        self.selected = (0..self.local_stats.len())
+                            .find(|&i| {
+                                self.local_stats
+                                    .get_selected(i)
+                                    .is_some_and(|env_stats| env_stats.id == id)
+                            })
+                            .unwrap_or(0);
+                    }
+                }
+                // Open the selected note in the editor, also from the file submenu
+                Some(Action::Edit) => {
+                    self.mode = SelectMode::Select;
+                    return self.edit_selected();
+                }
+                Some(Action::TogglePreview) => {
+                    self.show_preview = !self.show_preview;
+                }
+                Some(Action::ScrollPreview) => {
+                    if let Some((_, Some(preview))) = self.preview.get_mut() {
+                        preview.scroll_pages(if key.code == KeyCode::PageUp { -1 } else { 1 });
+                    }
+                }
                 // Selection
-                // Down
-                KeyCode::Char('j' | 'J') | KeyCode::Down => {
-                    self.selected = self
-                        .selected
-                        .saturating_add(1)
-                        .min(self.local_stats.len().saturating_sub(1));
-                }
-                // Up
-                KeyCode::Char('k' | 'K') | KeyCode::Up => {
-                    self.selected = self.selected.saturating_sub(1);
-                }
-                // To the start
-                KeyCode::Char('0') => {
+                Some(Action::Select) => {
+                    self.selected = if matches!(key.code, KeyCode::Char('k' | 'K') | KeyCode::Up) {
+                        self.selected.saturating_sub(1)
+                    } else {
+                        self.selected
+                            .saturating_add(1)
+                            .min(self.local_stats.len().saturating_sub(1))
+                    };
+                }
+                Some(Action::SelectFirst) => {
                     self.selected = 0;
                 }
+                // Cycle through notes without incoming links from within the environment
+                Some(Action::NextOrphan) => {
+                    if let Some(position) = self
+                        .local_stats
+                        .next_position_of(self.selected, self.local_stats.orphans())
+                    {
+                        self.selected = position;
+                    }
+                }
+                // Cycle through dead ends, i.e. notes without outgoing links into the environment
+                Some(Action::NextDeadEnd) => {
+                    if let Some(position) = self
+                        .local_stats
+                        .next_position_of(self.selected, self.local_stats.sinks())
+                    {
+                        self.selected = position;
+                    }
+                }
                 // Open selected item in display view
-                KeyCode::Enter | KeyCode::Char('l' | 'L') | KeyCode::Right => {
+                Some(Action::Open) => {
                     if let Some(env_stats) = self.local_stats.get_selected(self.selected) {
                         return Ok(ui::Message::DisplayStackPush(env_stats.id.clone()));
                     }
                 }
-                _ => {}
+                Some(Action::Create) => {
+                    self.mode = SelectMode::Create;
+                    self.templates = self.manager.templates();
+                    self.template = None;
+                    self.set_create_area_title();
+                }
+                Some(Action::View) => {
+                    self.mode = SelectMode::Select;
+                    if let Some(env_stats) = self.local_stats.get_selected(self.selected) {
+                        if let Some(note) = self.index.borrow().get(&env_stats.id) {
+                            self.builder.create_html(note, true)?;
+                            return Ok(ui::Message::OpenExternalCommand(
+                                self.builder.create_view_command(note, None)?,
+                            ));
+                        }
+                    }
+                }
+                Some(Action::Rename) => {
+                    self.mode = SelectMode::Rename;
+                    let name = self
+                        // get the selected item in the list for the id
+                        .local_stats
+                        .get_selected(self.selected)
+                        // use this id in the index to get the note
+                        .and_then(|env_stats| {
+                            // use the id to get the name
+                            self.index
+                                .borrow()
+                                .get(&env_stats.id)
+                                .map(|note| note.name.clone())
+                        });
+
+                    self.set_name_area("Enter new name of note...", name);
+                }
+                Some(Action::Move) => {
+                    self.mode = SelectMode::Move;
+                    self.set_name_area("Enter new location relative to vault...", None);
+                }
+                Some(Action::Delete) => {
+                    if let Some(env_stats) = self
+                        // get the selected item in the list for the id
+                        .local_stats
+                        .get_selected(self.selected)
+                    {
+                        // delete it from index & filesystem
+                        self.manager
+                            .delete_note_file(self.index.clone(), &env_stats.id)?;
+                        // if successfull, refresh the ui
+                        self.refresh_env_stats();
+                    }
+                    self.mode = SelectMode::Select;
+                }
+                Some(Action::Undo) => {
+                    self.mode = SelectMode::Select;
+                    self.manager.undo_last_operation()?;
+                    // if successfull, refresh the ui
+                    self.refresh_env_stats();
+                }
+                Some(Action::Trash) => {
+                    self.mode = SelectMode::Select;
+                    return Ok(ui::Message::DisplayTrash);
+                }
+                // Index the vault again in the background
+                Some(Action::Rescan) => {
+                    self.mode = SelectMode::Select;
+                    return Ok(ui::Message::Rescan);
+                }
+                Some(Action::Sort(mode)) => {
+                    self.set_mode_and_maybe_sort(mode, mode == data::SortingMode::Name)?;
+                    self.mode = SelectMode::Select;
+                }
+                Some(Action::Reverse) => {
+                    self.set_mode_and_maybe_sort(None, !self.sorting_asc)?;
+                    self.mode = SelectMode::Select;
+                }
+                // Show or hide a column, staying in the menu until it is closed
+                Some(Action::ToggleColumn(column)) => self.toggle_column(column)?,
+                None => {}
             },
             // Filter mode: Type in filter values
             SelectMode::Filter => {
                 match key.code {
-                    // Escape or Enter: Back to main mode
+                    // Escape or Enter: Back to main mode, remember the filter
                     KeyCode::Esc | KeyCode::Enter => {
                         self.mode = SelectMode::Select;
                         self.filter(self.filter_from_input());
+                        if let Some(line) = self.filter_area.lines().first() {
+                            self.history.push(line.clone());
+                        }
+                        self.history.store(self.index.borrow().vault_path())?;
                     }
-                    // All other key events are passed on to the text area, then the filter is immediately applied
+                    // Up and Down: Recall previous filters
+                    KeyCode::Up | KeyCode::Down => {
+                        let entry = if key.code == KeyCode::Up {
+                            self.history.previous()
+                        } else {
+                            self.history.next()
+                        };
+                        if let Some(entry) = entry {
+                            super::extract_string_and_clear(&mut self.filter_area);
+                            self.filter_area.insert_str(entry);
+                        }
+                        self.filter_later();
+                    }
+                    // All other key events are passed on to the text area, then the matches are counted and the filter applied once typing pauses
                     _ => {
                         // Else -> Pass on to the text area
+                        self.history.reset();
                         self.filter_area.input(key);
-                        self.filter(self.filter_from_input());
+                        self.filter_later();
                     }
                 };
             }
+            // Confirming a rename: Only overwrite the changed notes on Enter
+            SelectMode::ConfirmRename => {
+                self.mode = SelectMode::Select;
+                if let Some((id, new_name, _changed)) = self.pending_rename.take() {
+                    if key.code == KeyCode::Enter {
+                        self.manager
+                            .rename_note_file(self.index.clone(), &id, new_name, true)?;
+                        self.refresh_env_stats();
+                    }
+                }
+            }
             SelectMode::FilterHelp => {
                 match key.code {
                     // Escape or Enter: Back to main mode
@@ -345,93 +1418,6 @@ impl super::Screen for SelectScreen {
                     _ => {}
                 };
             }
-            // File mode: Wait for second input
-            SelectMode::SubmenuFile => {
-                match key.code {
-                    // D: Delete note
-                    KeyCode::Char('d' | 'D') => {
-                        if let Some(env_stats) = self
-                            // get the selected item in the list for the id
-                            .local_stats
-                            .get_selected(self.selected)
-                        {
-                            // delete it from index & filesystem
-                            self.manager
-                                .delete_note_file(self.index.clone(), &env_stats.id)?;
-                            // if successfull, refresh the ui
-                            self.refresh_env_stats();
-                        }
-                        self.mode = SelectMode::Select;
-                    }
-                    // Open selected item in editor
-                    KeyCode::Char('e' | 'E') => {
-                        self.mode = SelectMode::Select;
-                        if let Some(res) = self
-                            // get the selected item in the list for the id
-                            .local_stats
-                            .get_selected(self.selected)
-                            // use this id in the index to get the note
-                            .and_then(|env_stats| {
-                                // use the id to get the path
-                                self.index
-                                    .borrow()
-                                    .get(&env_stats.id)
-                                    .map(|note| note.path.clone())
-                            })
-                        {
-                            // use the config to create a valid opening command
-                            return Ok(ui::Message::OpenExternalCommand(
-                                self.manager.create_edit_command(&res)?,
-                            ));
-                        }
-                    }
-                    // N: Create note
-                    KeyCode::Char('n' | 'N') => {
-                        self.mode = SelectMode::Create;
-                        self.set_name_area("Enter name of new note...", None);
-                    }
-                    // R: Rename note
-                    KeyCode::Char('r' | 'R') => {
-                        self.mode = SelectMode::Rename;
-                        let name = self
-                            // get the selected item in the list for the id
-                            .local_stats
-                            .get_selected(self.selected)
-                            // use this id in the index to get the note
-                            .and_then(|env_stats| {
-                                // use the id to get the name
-                                self.index
-                                    .borrow()
-                                    .get(&env_stats.id)
-                                    .map(|note| note.name.clone())
-                            });
-
-                        self.set_name_area("Enter new name of note...", name);
-                    }
-                    // M: Move note
-                    KeyCode::Char('m' | 'M') => {
-                        self.mode = SelectMode::Move;
-                        self.set_name_area("Enter new location relative to vault...", None);
-                    }
-                    // Open view mode
-                    KeyCode::Char('v' | 'V') => {
-                        self.mode = SelectMode::Select;
-                        if let Some(env_stats) = self.local_stats.get_selected(self.selected) {
-                            if let Some(note) = self.index.borrow().get(&env_stats.id) {
-                                self.builder.create_html(note, true)?;
-                                return Ok(ui::Message::OpenExternalCommand(
-                                    self.builder.create_view_command(note)?,
-                                ));
-                            }
-                        }
-                    }
-                    // Back to select mode
-                    KeyCode::Esc => {
-                        self.mode = SelectMode::Select;
-                    }
-                    _ => {}
-                }
-            }
             // Modes that require input in the text box.
             SelectMode::Create | SelectMode::Rename | SelectMode::Move => {
                 match key.code {
@@ -448,14 +1434,22 @@ impl super::Screen for SelectScreen {
                         match mode {
                             SelectMode::Create => {
                                 // Create & register the note
-                                self.manager.create_note_file(
-                                    &super::extract_string_and_clear(&mut self.name_area)
-                                        .ok_or_else(|| {
-                                            error::RucolaError::Input(String::from(
-                                                "New note may not be empty.",
-                                            ))
-                                        })?,
-                                )?;
+                                let name = super::extract_string_and_clear(&mut self.name_area)
+                                    .ok_or_else(|| {
+                                        error::RucolaError::Input(String::from(
+                                            "New note may not be empty.",
+                                        ))
+                                    })?;
+                                let path =
+                                    match self.template.and_then(|index| self.templates.get(index))
+                                    {
+                                        Some(template) => self
+                                            .manager
+                                            .create_note_from_template(&name, template)?,
+                                        None => self.manager.create_note_file(&name)?,
+                                    };
+                                // the new note is small, so it is read right away instead of waiting for the watcher
+                                self.index.borrow_mut().update_note(&path)?;
                                 // if successfull, refresh the ui
                                 self.refresh_env_stats();
                             }
@@ -464,18 +1458,32 @@ impl super::Screen for SelectScreen {
                                 if let Some(env_stats) =
                                     self.local_stats.get_selected(self.selected)
                                 {
-                                    self.manager.rename_note_file(
-                                        self.index.clone(),
-                                        &env_stats.id,
+                                    let id = env_stats.id.clone();
+                                    let new_name =
                                         super::extract_string_and_clear(&mut self.name_area)
                                             .ok_or_else(|| {
                                                 error::RucolaError::Input(
                                                     "New name is empty.".to_string(),
                                                 )
-                                            })?,
-                                    )?;
-                                    // if successfull, refresh the ui
-                                    self.refresh_env_stats();
+                                            })?;
+                                    match self.manager.rename_note_file(
+                                        self.index.clone(),
+                                        &id,
+                                        new_name.clone(),
+                                        false,
+                                    ) {
+                                        // Linking notes were changed on disk, ask before overwriting them
+                                        Err(error::RucolaError::ExternalChange(changed)) => {
+                                            self.pending_rename =
+                                                Some((id, new_name, changed.len()));
+                                            self.mode = SelectMode::ConfirmRename;
+                                        }
+                                        result => {
+                                            result?;
+                                            // if successfull, refresh the ui
+                                            self.refresh_env_stats();
+                                        }
+                                    }
                                 }
                             }
                             SelectMode::Move => {
@@ -502,6 +1510,15 @@ impl super::Screen for SelectScreen {
                             }
                         }
                     }
+                    // Tab: Choose the next template for the note to create, or none after the last one
+                    KeyCode::Tab if self.mode == SelectMode::Create => {
+                        self.template = match self.template {
+                            None if !self.templates.is_empty() => Some(0),
+                            Some(index) if index + 1 < self.templates.len() => Some(index + 1),
+                            _ => None,
+                        };
+                        self.set_create_area_title();
+                    }
                     // All other key events are passed on to the text area
                     _ => {
                         // Else -> Pass on to the text area
@@ -509,49 +1526,6 @@ impl super::Screen for SelectScreen {
                     }
                 };
             }
-            // Sorting submenu: Wait for second input
-            SelectMode::SubmenuSorting => match key.code {
-                KeyCode::Char('a' | 'A') => {
-                    self.set_mode_and_maybe_sort(data::SortingMode::Name, true);
-                    self.mode = SelectMode::Select;
-                }
-                KeyCode::Char('w' | 'W') => {
-                    self.set_mode_and_maybe_sort(data::SortingMode::Words, false);
-                    self.mode = SelectMode::Select;
-                }
-                KeyCode::Char('c' | 'C') => {
-                    self.set_mode_and_maybe_sort(data::SortingMode::Chars, false);
-                    self.mode = SelectMode::Select;
-                }
-                KeyCode::Char('o' | 'O') => {
-                    self.set_mode_and_maybe_sort(data::SortingMode::GlobalOutLinks, false);
-                    self.mode = SelectMode::Select;
-                }
-                KeyCode::Char('u' | 'U') => {
-                    self.set_mode_and_maybe_sort(data::SortingMode::LocalOutLinks, false);
-                    self.mode = SelectMode::Select;
-                }
-                KeyCode::Char('i' | 'I') => {
-                    self.set_mode_and_maybe_sort(data::SortingMode::GlobalInLinks, false);
-                    self.mode = SelectMode::Select;
-                }
-                KeyCode::Char('n' | 'N') => {
-                    self.set_mode_and_maybe_sort(data::SortingMode::LocalInLinks, false);
-                    self.mode = SelectMode::Select;
-                }
-                KeyCode::Char('b' | 'B') => {
-                    self.set_mode_and_maybe_sort(data::SortingMode::Broken, false);
-                    self.mode = SelectMode::Select;
-                }
-                KeyCode::Char('r' | 'R') => {
-                    self.set_mode_and_maybe_sort(None, !self.sorting_asc);
-                    self.mode = SelectMode::Select;
-                }
-                KeyCode::Esc | KeyCode::Char('s' | 'S') => {
-                    self.mode = SelectMode::Select;
-                }
-                _ => {}
-            },
         };
 
         Ok(ui::Message::None)
@@ -571,6 +1545,14 @@ impl super::Screen for SelectScreen {
         // Generate areas
         let [global_stats_area, local_stats_area, filter_area, table_area] = vertical.areas(area);
 
+        // The preview takes the right half of the list's area
+        let [table_area, preview_area] = Layout::horizontal(if self.show_preview {
+            [Constraint::Fill(1), Constraint::Fill(1)]
+        } else {
+            [Constraint::Fill(1), Constraint::Length(0)]
+        })
+        .areas(table_area);
+
         // Generate stats areas
         let global_stats =
             self.global_stats
@@ -615,8 +1597,10 @@ impl super::Screen for SelectScreen {
                 SelectMode::Select
                 | SelectMode::Rename
                 | SelectMode::Move
+                | SelectMode::ConfirmRename
                 | SelectMode::SubmenuFile
-                | SelectMode::SubmenuSorting => Some(self.selected),
+                | SelectMode::SubmenuSorting
+                | SelectMode::SubmenuColumns => Some(self.selected),
                 SelectMode::Filter | SelectMode::FilterHelp | SelectMode::Create => None,
             });
 
@@ -636,73 +1620,113 @@ impl super::Screen for SelectScreen {
             Span::styled("/", self.styles.text_style),
             Span::styled("󰌑", self.styles.hotkey_style),
             Span::styled(": Open──", self.styles.text_style),
+            Span::styled("E", self.styles.hotkey_style),
+            Span::styled("dit──", self.styles.text_style),
+            Span::styled("O", self.styles.hotkey_style),
+            Span::styled("rphans──", self.styles.text_style),
+            Span::styled("D", self.styles.hotkey_style),
+            Span::styled("ead Ends──", self.styles.text_style),
         ]))
         .alignment(Alignment::Left)
         .position(block::Position::Bottom);
 
         let instructions_bot_right = block::Title::from(Line::from(vec![
+            Span::styled("Stat", self.styles.text_style),
+            Span::styled("i", self.styles.hotkey_style),
+            Span::styled("stics──", self.styles.text_style),
+            Span::styled("G", self.styles.hotkey_style),
+            Span::styled("raph──", self.styles.text_style),
+            Span::styled("Previe", self.styles.text_style),
+            Span::styled("w", self.styles.hotkey_style),
+            Span::styled("──", self.styles.text_style),
+            Span::styled("P", self.styles.hotkey_style),
+            Span::styled("in──", self.styles.text_style),
+            Span::styled("R", self.styles.hotkey_style),
+            Span::styled("andom──", self.styles.text_style),
+            Span::styled("V", self.styles.hotkey_style),
+            Span::styled("aults──", self.styles.text_style),
             Span::styled("M", self.styles.hotkey_style),
             Span::styled("anage Files──", self.styles.text_style),
             Span::styled("S", self.styles.hotkey_style),
             Span::styled("orting──", self.styles.text_style),
+            Span::styled("Col", self.styles.text_style),
+            Span::styled("u", self.styles.hotkey_style),
+            Span::styled("mns──", self.styles.text_style),
             Span::styled("Q", self.styles.hotkey_style),
             Span::styled("uit", self.styles.text_style),
         ]))
         .alignment(Alignment::Right)
         .position(block::Position::Bottom);
 
-        let table_heading_key_style = if self.mode == SelectMode::SubmenuSorting {
-            self.styles.hotkey_style
-        } else {
-            self.styles.subtitle_style
+        let table_heading_key_style = |column: Option<data::Column>| {
+            // r reverses the sorting instead of sorting by reading time
+            if self.mode == SelectMode::SubmenuSorting && column != Some(data::Column::Reading)
+                || self.mode == SelectMode::SubmenuColumns && column.is_some()
+            {
+                self.styles.hotkey_style
+            } else {
+                self.styles.subtitle_style
+            }
+        };
+        // The header of the column the notes are sorted by is underlined
+        let table_heading_style = |mode: data::SortingMode| {
+            if self.sorting == mode {
+                self.styles
+                    .subtitle_style
+                    .add_modifier(Modifier::UNDERLINED)
+            } else {
+                self.styles.subtitle_style
+            }
         };
 
         // Finally generate the table from the generated row and width data
         let table = self
             .local_stats
-            .to_note_table(self.index.clone(), &self.styles)
+            .to_note_table(
+                self.index.clone(),
+                self.columns.columns(),
+                &self.styles,
+                self.words_per_minute,
+                self.stale,
+            )
             // Add Headers
-            .header(Row::new(vec![
-                Line::from(vec![
-                    Span::styled("N", self.styles.subtitle_style),
-                    Span::styled("a", table_heading_key_style),
-                    Span::styled("me", self.styles.subtitle_style),
-                ]),
-                Line::from(vec![
-                    Span::styled("  ", self.styles.subtitle_style),
-                    Span::styled("W", table_heading_key_style),
-                    Span::styled("ords", self.styles.subtitle_style),
-                ]),
-                Line::from(vec![
-                    Span::styled("  ", self.styles.subtitle_style),
-                    Span::styled("C", table_heading_key_style),
-                    Span::styled("hars", self.styles.subtitle_style),
-                ]),
-                Line::from(vec![
-                    Span::styled("Global", self.styles.subtitle_style),
-                    Span::styled("O", table_heading_key_style),
-                    Span::styled("ut", self.styles.subtitle_style),
-                ]),
-                Line::from(vec![
-                    Span::styled("LocalO", self.styles.subtitle_style),
-                    Span::styled("u", table_heading_key_style),
-                    Span::styled("t", self.styles.subtitle_style),
-                ]),
-                Line::from(vec![
-                    Span::styled("Global", self.styles.subtitle_style),
-                    Span::styled("I", table_heading_key_style),
-                    Span::styled("n", self.styles.subtitle_style),
-                ]),
-                Line::from(vec![
-                    Span::styled("LocalI", self.styles.subtitle_style),
-                    Span::styled("n", table_heading_key_style),
-                ]),
-            ]))
+            .header(Row::new(
+                std::iter::once(Line::from(vec![
+                    Span::styled("N", table_heading_style(data::SortingMode::Name)),
+                    Span::styled("a", table_heading_key_style(None)),
+                    Span::styled("me", table_heading_style(data::SortingMode::Name)),
+                ]))
+                .chain(self.columns.columns().iter().map(|&column| {
+                    let [before, key, after] = column.header();
+                    // reading times are sorted by words, but only the words column is marked
+                    let style = if column == data::Column::Reading {
+                        self.styles.subtitle_style
+                    } else {
+                        table_heading_style(column.sorting_mode())
+                    };
+                    Line::from(vec![
+                        Span::styled(before, style),
+                        Span::styled(key, table_heading_key_style(Some(column))),
+                        Span::styled(after, style),
+                    ])
+                })),
+            ))
             .highlight_style(self.styles.selected_style)
             // Add Instructions and a title
             .block(
                 Block::bordered()
                     .title(style::Styled::set_style("Notes", self.styles.title_style))
+                    .title(
+                        block::Title::from(Line::styled(
+                            format!(
+                                "{} min reading",
+                                self.local_stats
+                                    .reading_minutes_total(self.words_per_minute)
+                            ),
+                            self.styles.text_style,
+                        ))
+                        .alignment(Alignment::Right),
+                    )
                     .title(instructions_bot_left)
                     .title(instructions_bot_right),
             );
@@ -716,6 +1740,10 @@ impl super::Screen for SelectScreen {
 
         StatefulWidget::render(table, table_area, buf, &mut state);
 
+        if self.show_preview {
+            self.draw_preview(preview_area, buf);
+        }
+
         // Render possible pop-ups
         match self.mode {
             SelectMode::SubmenuFile | SelectMode::SubmenuSorting => {
@@ -727,6 +1755,9 @@ impl super::Screen for SelectScreen {
                         ("M", "Move selected note"),
                         ("D", "Delete selected note"),
                         ("V", "Open HTML in external viewer"),
+                        ("T", "Browse trash"),
+                        ("U", "Update index from disk"),
+                        ("Z", "Undo last rename, move or delete"),
                     ]
                 } else {
                     vec![
@@ -738,6 +1769,11 @@ impl super::Screen for SelectScreen {
                         ("I", "Sort by global inlinks"),
                         ("N", "Sort by local inlinks"),
                         ("B", "Sort by broken links"),
+                        ("P", "Sort by PageRank"),
+                        ("E", "Sort by betweenness"),
+                        ("D", "Sort by degree centrality"),
+                        ("T", "Sort by time since modification"),
+                        ("L", "Sort by links per word"),
                         ("R", "Reverse sorting"),
                     ]
                 };
@@ -805,13 +1841,65 @@ impl super::Screen for SelectScreen {
                 Widget::render(Clear, center_area, buf);
                 Widget::render(create_input, center_area, buf);
             }
+            SelectMode::ConfirmRename => {
+                let changed = self
+                    .pending_rename
+                    .as_ref()
+                    .map(|(_id, _name, changed)| *changed)
+                    .unwrap_or_default();
+
+                let keys = block::Title::from(Line::from(vec![
+                    Span::styled("󰌑", self.styles.hotkey_style),
+                    Span::styled(": Rename anyway─", self.styles.text_style),
+                    Span::styled("Other", self.styles.hotkey_style),
+                    Span::styled(": Abort", self.styles.text_style),
+                ]))
+                .alignment(Alignment::Center)
+                .position(block::Position::Bottom);
+
+                let conflict = Paragraph::new(Span::styled(
+                    format!("{changed} linking notes were changed outside of rucola since they were read."),
+                    self.styles.text_style,
+                ))
+                .alignment(Alignment::Center)
+                .block(Block::bordered().title(keys));
+
+                let popup_areas = Layout::vertical([
+                    Constraint::Fill(1),
+                    Constraint::Length(3),
+                    Constraint::Fill(1),
+                ])
+                .split(area);
+
+                let center_area = Layout::horizontal([
+                    Constraint::Fill(1),
+                    Constraint::Percentage(60),
+                    Constraint::Fill(1),
+                ])
+                .split(popup_areas[1])[1];
+
+                // Clear the area and then render the widget on top.
+                Widget::render(Clear, center_area, buf);
+                Widget::render(conflict, center_area, buf);
+            }
             SelectMode::FilterHelp => {
                 let help_widths = [Constraint::Length(9), Constraint::Min(0)];
 
                 let help_rows = [
                     Row::new(vec![
                         Cell::from("#[tag]").style(self.styles.subtitle_style),
-                        Cell::from("Show notes with tag [tag].").style(self.styles.text_style),
+                        Cell::from("Show notes with tag [tag], * matches anything.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("#=[tag]").style(self.styles.subtitle_style),
+                        Cell::from("Show notes with exactly [tag], not its subtags.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("~#[tag]").style(self.styles.subtitle_style),
+                        Cell::from("Show notes with a tag similar to [tag].")
+                            .style(self.styles.text_style),
                     ]),
                     Row::new(vec![
                         Cell::from("!#[tag]").style(self.styles.subtitle_style),
@@ -836,6 +1924,110 @@ impl super::Screen for SelectScreen {
                         Cell::from("Show notes not linked to from [note].")
                             .style(self.styles.text_style),
                     ]),
+                    Row::new(vec![
+                        Cell::from("![word]").style(self.styles.subtitle_style),
+                        Cell::from("Show notes whose title does not match [word].")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("=[words]").style(self.styles.subtitle_style),
+                        Cell::from("Match the title exactly, == to respect case.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("re:[expr]").style(self.styles.subtitle_style),
+                        Cell::from("Match the title with a regular expression.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("path:").style(self.styles.subtitle_style),
+                        Cell::from("Show notes within a folder of the vault.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("lang:").style(self.styles.subtitle_style),
+                        Cell::from("Show notes in a language, e.g. lang:de.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("near:").style(self.styles.subtitle_style),
+                        Cell::from("Notes within [n] links of a note, e.g. near:atlas:2.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("[key]:").style(self.styles.subtitle_style),
+                        Cell::from("Notes with a frontmatter field, e.g. status:draft.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("has: is:").style(self.styles.subtitle_style),
+                        Cell::from("Notes with a property, e.g. has:brokenlinks.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("created:").style(self.styles.subtitle_style),
+                        Cell::from("Filter by creation date, e.g. >2024-01-01 or <7d.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("modified:").style(self.styles.subtitle_style),
+                        Cell::from("Filter by modification date, e.g. <2w.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("words:").style(self.styles.subtitle_style),
+                        Cell::from("Filter by word count, e.g. >500 or <100.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("chars:").style(self.styles.subtitle_style),
+                        Cell::from("Filter by character count, e.g. <1000.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("todo:").style(self.styles.subtitle_style),
+                        Cell::from("Filter by open tasks, e.g. >3.").style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("inlinks:").style(self.styles.subtitle_style),
+                        Cell::from("Filter by incoming links, e.g. >5.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("outlinks:").style(self.styles.subtitle_style),
+                        Cell::from("Filter by outgoing links, e.g. 0.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("sort:").style(self.styles.subtitle_style),
+                        Cell::from("Sort results, e.g. sort:words or sort:-inlinks.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("expr:").style(self.styles.subtitle_style),
+                        Cell::from("Rest is an expression, e.g. expr: words > 300.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("@[name]").style(self.styles.subtitle_style),
+                        Cell::from("Insert the named filter [name] from the config.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("AND OR").style(self.styles.subtitle_style),
+                        Cell::from("Combine conditions, group them with ( ).")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("& + -").style(self.styles.subtitle_style),
+                        Cell::from("Intersect, unite or subtract, e.g. @math - @done.")
+                            .style(self.styles.text_style),
+                    ]),
+                    Row::new(vec![
+                        Cell::from("NOT").style(self.styles.subtitle_style),
+                        Cell::from("Show notes not matching the following condition.")
+                            .style(self.styles.text_style),
+                    ]),
                     Row::new(vec![
                         Cell::from("|").style(self.styles.subtitle_style),
                         Cell::from("All text after | will be searched in the full text.")
@@ -848,6 +2040,8 @@ impl super::Screen for SelectScreen {
                     ]),
                 ];
 
+                let help_height = help_rows.len() as u16 + 2;
+
                 let help_table = Table::new(help_rows, help_widths).column_spacing(1).block(
                     Block::bordered()
                         .title(style::Styled::set_style(
@@ -866,7 +2060,7 @@ impl super::Screen for SelectScreen {
 
                 let popup_areas = Layout::vertical([
                     Constraint::Fill(1),
-                    Constraint::Length(10),
+                    Constraint::Length(help_height),
                     Constraint::Fill(1),
                 ])
                 .split(area);
@@ -0,0 +1,1581 @@
+use crate::{data, error, io, ui};
+
+use crossterm::event::KeyCode;
+use itertools::Itertools;
+use ratatui::{prelude::*, widgets::*};
+use std::{path, time};
+use tui_textarea::TextArea;
+
+/// The amount of tags to show in the tag chart.
+const TOP_TAGS: usize = 12;
+/// The amount of tags to show in the tag chart of the overview.
+const OVERVIEW_TAGS: usize = 30;
+/// The amount of buckets of the word count histogram.
+const WORD_BUCKETS: usize = 10;
+/// The amount of related notes to consider per note when suggesting links.
+const RELATED_PER_NOTE: usize = 5;
+/// Length of a day in seconds.
+const DAY: u64 = 24 * 60 * 60;
+/// Length of a week in seconds.
+const WEEK: u64 = 7 * 24 * 60 * 60;
+/// Length of a month in seconds, approximated as 30 days.
+const MONTH: u64 = 30 * 24 * 60 * 60;
+
+/// The amount of notes in each top list.
+const TOP_NOTES: usize = 10;
+
+/// What the stats screen shows below the general statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StatsView {
+    /// Charts and tables of the displayed environment.
+    Environment,
+    /// How much of the vault the displayed environment covers, how it is linked to the rest and its top tags.
+    Overview,
+    /// The comparison of all top-level tags.
+    Tags,
+    /// The comparison of all folders.
+    Folders,
+    /// The comparison of all note languages.
+    Languages,
+    /// The changes since the last snapshot.
+    Changes,
+    /// Lists of the notes topping different metrics.
+    TopNotes,
+    /// The preview of renaming a tag.
+    TagRename,
+    /// The notes that share their id with other notes.
+    IdCollisions,
+}
+
+/// An action of the stats screen, triggered by the keys bound to it in [BINDINGS].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    Close,
+    Help,
+    SelectBrokenLink,
+    OpenBrokenLink,
+    Overview,
+    CompareTags,
+    CompareFolders,
+    CompareLanguages,
+    TopNotes,
+    Changes,
+    Snapshot,
+    IdCollisions,
+    RenameTag,
+    SwitchGrowth,
+    SwitchUrls,
+    Export,
+}
+
+/// The keybindings of the stats screen, used both to dispatch key presses and to list them in the help overlay.
+/// Each binding consists of its action, the keys triggering it, how these keys are shown and what the action does.
+const BINDINGS: [(Action, &[KeyCode], &str, &str); 16] = [
+    (
+        Action::Close,
+        &[KeyCode::Esc, KeyCode::Char('q'), KeyCode::Char('Q')],
+        "Esc / Q",
+        "Return to the select screen",
+    ),
+    (
+        Action::SelectBrokenLink,
+        &[
+            KeyCode::Char('j'),
+            KeyCode::Char('J'),
+            KeyCode::Down,
+            KeyCode::Char('k'),
+            KeyCode::Char('K'),
+            KeyCode::Up,
+        ],
+        "J / K",
+        "Select the next / previous broken link",
+    ),
+    (
+        Action::OpenBrokenLink,
+        &[
+            KeyCode::Enter,
+            KeyCode::Char('l'),
+            KeyCode::Char('L'),
+            KeyCode::Right,
+        ],
+        "Enter / L",
+        "Open the source of the selected broken link",
+    ),
+    (
+        Action::Overview,
+        &[KeyCode::Char('o'), KeyCode::Char('O')],
+        "O",
+        "Show the overview compared to the whole vault",
+    ),
+    (
+        Action::CompareTags,
+        &[KeyCode::Char('t'), KeyCode::Char('T')],
+        "T",
+        "Compare all top-level tags",
+    ),
+    (
+        Action::CompareFolders,
+        &[KeyCode::Char('f'), KeyCode::Char('F')],
+        "F",
+        "Compare all folders",
+    ),
+    (
+        Action::CompareLanguages,
+        &[KeyCode::Char('a'), KeyCode::Char('A')],
+        "A",
+        "Compare all note languages",
+    ),
+    (
+        Action::TopNotes,
+        &[KeyCode::Char('n'), KeyCode::Char('N')],
+        "N",
+        "Show the top notes",
+    ),
+    (
+        Action::Changes,
+        &[KeyCode::Char('d'), KeyCode::Char('D')],
+        "D",
+        "Show the changes since the last snapshot",
+    ),
+    (
+        Action::Snapshot,
+        &[KeyCode::Char('s'), KeyCode::Char('S')],
+        "S",
+        "Take a new snapshot",
+    ),
+    (
+        Action::IdCollisions,
+        &[KeyCode::Char('i'), KeyCode::Char('I')],
+        "I",
+        "Show id collisions",
+    ),
+    (
+        Action::RenameTag,
+        &[KeyCode::Char('r'), KeyCode::Char('R')],
+        "R",
+        "Preview renaming a tag",
+    ),
+    (
+        Action::SwitchGrowth,
+        &[KeyCode::Char('p'), KeyCode::Char('P')],
+        "P",
+        "Switch between weekly and monthly growth",
+    ),
+    (
+        Action::SwitchUrls,
+        &[KeyCode::Char('u'), KeyCode::Char('U')],
+        "U",
+        "Switch between external domains and full URLs",
+    ),
+    (
+        Action::Export,
+        &[
+            KeyCode::Char('e'),
+            KeyCode::Char('E'),
+            KeyCode::Char('c'),
+            KeyCode::Char('C'),
+            KeyCode::Char('g'),
+            KeyCode::Char('G'),
+            KeyCode::Char('m'),
+            KeyCode::Char('M'),
+        ],
+        "E / C / G / M",
+        "Export to JSON / CSV / DOT / GraphML",
+    ),
+    (
+        Action::Help,
+        &[KeyCode::Char('?')],
+        "?",
+        "Show the keybindings",
+    ),
+];
+
+/// The settings of the stats screen taken from the config file.
+#[derive(Debug, Clone)]
+pub struct StatsConfig {
+    /// How to compute the health score.
+    health: data::HealthCriteria,
+    /// How many folders deep to group notes by.
+    directory_depth: usize,
+    /// How to recognize daily notes, if the configured pattern is valid.
+    daily_notes: Option<data::DailyNotePattern>,
+    /// The amount of words to relate the amount of links of a note to.
+    link_density_words: usize,
+}
+
+impl StatsConfig {
+    /// Extracts the settings of the stats screen from the given config.
+    /// Returns an error alongside if the daily note pattern is invalid.
+    pub fn new(config: &crate::Config) -> (Self, Option<error::RucolaError>) {
+        let daily_notes = data::DailyNotePattern::new(&config.daily_note_pattern);
+        let error = daily_notes.is_none().then(|| {
+            error::RucolaError::Input(String::from(
+                "The daily note pattern must contain YYYY, MM and DD once each.",
+            ))
+        });
+        (
+            Self {
+                health: config.health,
+                directory_depth: config.directory_depth,
+                daily_notes,
+                link_density_words: config.link_density_words,
+            },
+            error,
+        )
+    }
+}
+
+/// The stats screen shows detailed statistics of the environment the select screen was showing when it was opened.
+pub struct StatsScreen {
+    // === CONFIG ===
+    /// The used styles.
+    styles: ui::UiStyles,
+    /// The file manager used to write exported statistics.
+    manager: io::FileManager,
+    /// The settings from the config file.
+    config: StatsConfig,
+
+    // === DATA ===
+    /// The statistics of the displayed environment.
+    stats: data::EnvironmentStats,
+    /// The statistics of the whole vault, to compare the displayed environment to.
+    global: data::EnvironmentStats,
+    /// A reference to the index of all notes.
+    index: data::NoteIndexContainer,
+    /// The statistics of every top-level tag, computed when first shown.
+    tag_stats: Option<Vec<(String, data::EnvironmentStats)>>,
+    /// The journaling streaks of the daily notes of the displayed environment, if daily notes can be recognized.
+    daily_streak: Option<data::DailyStreak>,
+    /// The statistics of every folder, computed when first shown.
+    folder_stats: Option<Vec<(String, data::EnvironmentStats)>>,
+    /// The statistics of every note language, computed when first shown.
+    language_stats: Option<Vec<(String, data::EnvironmentStats)>>,
+    /// The last previewed tag rename with its old and new tag.
+    tag_rename: Option<(String, String, data::TagRenameImpact)>,
+
+    // === UI ===
+    /// Wether to show growth per month instead of per week.
+    monthly: bool,
+    /// The index of the selected broken link.
+    selected: usize,
+    /// What to show below the general statistics.
+    view: StatsView,
+    /// Wether to list full external URLs instead of their domains.
+    show_urls: bool,
+    /// The changes since the last snapshot and when it was taken, loaded when first shown.
+    changes: Option<(Option<time::SystemTime>, data::ChangeReport)>,
+    /// The path of the file the statistics were last exported to.
+    exported: Option<path::PathBuf>,
+    /// The text area to enter a tag rename to preview, if currently shown.
+    rename_area: Option<TextArea<'static>>,
+}
+
+impl StatsScreen {
+    /// Creates a new stats screen displaying the given environment, computing further on-demand statistics with the given index.
+    pub fn new(
+        mut stats: data::EnvironmentStats,
+        global: data::EnvironmentStats,
+        index: data::NoteIndexContainer,
+        manager: io::FileManager,
+        styles: ui::UiStyles,
+        config: StatsConfig,
+    ) -> Self {
+        stats.compute_related(&index.borrow(), RELATED_PER_NOTE);
+        stats.compute_distances(&index.borrow());
+        stats.compute_cycles(&index.borrow());
+        stats.compute_duplicates(&index.borrow());
+        let daily_streak = config
+            .daily_notes
+            .as_ref()
+            .map(|pattern| stats.daily_streak(&index.borrow(), pattern));
+
+        Self {
+            styles,
+            manager,
+            config,
+            stats,
+            global,
+            index,
+            tag_stats: None,
+            folder_stats: None,
+            language_stats: None,
+            daily_streak,
+            tag_rename: None,
+            monthly: false,
+            selected: 0,
+            view: StatsView::Environment,
+            show_urls: false,
+            changes: None,
+            exported: None,
+            rename_area: None,
+        }
+    }
+
+    /// Draws a horizontal bar chart of the given amount of tags occurring in the most notes.
+    fn draw_tag_chart(&self, area: Rect, buf: &mut Buffer, amount: usize) {
+        let tags = self.stats.top_tags(amount);
+
+        let bars = tags
+            .iter()
+            .map(|(tag, count)| {
+                Bar::default()
+                    .value(*count as u64)
+                    .label(Line::from(*tag))
+                    .style(self.styles.text_style)
+                    .value_style(self.styles.selected_style)
+            })
+            .collect::<Vec<_>>();
+
+        let chart = BarChart::default()
+            .direction(Direction::Horizontal)
+            .bar_width(1)
+            .bar_gap(0)
+            .data(BarGroup::default().bars(&bars))
+            .label_style(self.styles.subtitle_style)
+            .block(Block::bordered().title(style::Styled::set_style(
+                "Top Tags",
+                self.styles.title_style,
+            )));
+
+        Widget::render(chart, area, buf);
+    }
+
+    /// Draws gauges of how much of the vault the environment covers and the link matrix next to a large chart of the top tags.
+    fn draw_overview(&self, area: Rect, buf: &mut Buffer) {
+        let [left_area, tags_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(area);
+
+        let coverage = self.stats.coverage(&self.global);
+        let [gauges_area, matrix_area] = Layout::vertical([
+            Constraint::Length(3 * coverage.len() as u16),
+            Constraint::Fill(1),
+        ])
+        .areas(left_area);
+
+        for ((label, local, global), gauge_area) in coverage.iter().zip(
+            Layout::vertical([Constraint::Length(3); 4])
+                .split(gauges_area)
+                .iter(),
+        ) {
+            let gauge = Gauge::default()
+                .block(
+                    Block::bordered()
+                        .title(style::Styled::set_style(*label, self.styles.title_style)),
+                )
+                .gauge_style(self.styles.selected_style)
+                .ratio((*local as f64 / (*global).max(1) as f64).clamp(0., 1.))
+                .label(Span::styled(
+                    format!(
+                        "{local} of {global} ({:.0}%)",
+                        *local as f64 * 100. / (*global).max(1) as f64
+                    ),
+                    self.styles.text_style,
+                ));
+            Widget::render(gauge, *gauge_area, buf);
+        }
+
+        let matrix = self
+            .stats
+            .to_link_matrix_table(&self.global, &self.styles)
+            .block(Block::bordered().title(style::Styled::set_style(
+                "Links (Source → Target)",
+                self.styles.title_style,
+            )));
+        Widget::render(matrix, matrix_area, buf);
+
+        self.draw_tag_chart(tags_area, buf, OVERVIEW_TAGS);
+    }
+
+    /// Draws a table of all broken links with their source, highlighting the selected one.
+    fn draw_broken_links(&self, area: Rect, buf: &mut Buffer) {
+        let broken = self.stats.broken_link_targets();
+
+        let rows = broken
+            .iter()
+            .map(|(source, target)| Row::new(vec![source.as_str(), target.as_str()]))
+            .collect::<Vec<_>>();
+
+        let instructions_bot_left = block::Title::from(Line::from(vec![
+            Span::styled("J", self.styles.hotkey_style),
+            Span::styled("/", self.styles.text_style),
+            Span::styled("K", self.styles.hotkey_style),
+            Span::styled(": Select──", self.styles.text_style),
+            Span::styled("󰌑", self.styles.hotkey_style),
+            Span::styled(": Open", self.styles.text_style),
+        ]))
+        .alignment(Alignment::Left)
+        .position(block::Position::Bottom);
+
+        let table = Table::new(rows, [Constraint::Fill(1), Constraint::Fill(1)])
+            .style(self.styles.text_style)
+            .header(Row::new(vec![
+                Cell::from("Source").style(self.styles.subtitle_style),
+                Cell::from("Target").style(self.styles.subtitle_style),
+            ]))
+            .highlight_style(self.styles.selected_style)
+            .block(
+                Block::bordered()
+                    .title(style::Styled::set_style(
+                        "Broken Links",
+                        self.styles.title_style,
+                    ))
+                    .title(
+                        block::Title::from(Line::styled(
+                            format!("{}", broken.len()),
+                            self.styles.text_style,
+                        ))
+                        .alignment(Alignment::Right),
+                    )
+                    .title(instructions_bot_left),
+            );
+
+        let mut state = TableState::new().with_selected(Some(self.selected));
+
+        StatefulWidget::render(table, area, buf, &mut state);
+    }
+
+    /// Exports the displayed statistics to a new file in the given format and remembers its path.
+    fn export(&mut self, format: data::ExportFormat) -> error::Result<()> {
+        let content = self.stats.export(&self.index.borrow(), format)?;
+        self.exported = Some(self.manager.write_export(&content, format.extension())?);
+        Ok(())
+    }
+
+    /// Returns the action the given key triggers, if any.
+    fn action(&self, code: KeyCode) -> Option<Action> {
+        BINDINGS
+            .iter()
+            .find(|(action, codes, _keys, _description)| {
+                codes.contains(&code) && self.is_available(*action)
+            })
+            .map(|(action, _codes, _keys, _description)| *action)
+    }
+
+    /// Checks wether the given action can currently be triggered.
+    /// The broken links, growth and external links are only shown with the environment's statistics.
+    fn is_available(&self, action: Action) -> bool {
+        match action {
+            Action::SelectBrokenLink
+            | Action::OpenBrokenLink
+            | Action::SwitchGrowth
+            | Action::SwitchUrls => self.view == StatsView::Environment,
+            Action::Close
+            | Action::Help
+            | Action::Overview
+            | Action::CompareTags
+            | Action::CompareFolders
+            | Action::CompareLanguages
+            | Action::TopNotes
+            | Action::Changes
+            | Action::Snapshot
+            | Action::IdCollisions
+            | Action::RenameTag
+            | Action::Export => true,
+        }
+    }
+
+    /// Shows the given view, or the environment's statistics if it is already shown.
+    fn toggle_view(&mut self, view: StatsView) {
+        self.view = if self.view == view {
+            StatsView::Environment
+        } else {
+            view
+        };
+    }
+
+    /// Draws compact tables of the notes topping different metrics, side by side.
+    fn draw_top_notes(&self, area: Rect, buf: &mut Buffer) {
+        let lists = [
+            ("Most Inlinks", data::SortingMode::GlobalInLinks),
+            ("Most Outlinks", data::SortingMode::GlobalOutLinks),
+            ("Longest", data::SortingMode::Words),
+            ("Most Tags", data::SortingMode::Tags),
+            ("Most Broken Links", data::SortingMode::Broken),
+        ];
+
+        let index = self.index.borrow();
+        let areas = Layout::horizontal([Constraint::Fill(1); 6]).split(area);
+        for ((title, mode), list_area) in lists.into_iter().zip(areas.iter()) {
+            let rows = self
+                .stats
+                .top_notes(&index, mode, TOP_NOTES)
+                .into_iter()
+                .map(|(name, value)| Row::new(vec![name.to_string(), format!("{value:7}")]))
+                .collect::<Vec<_>>();
+
+            let table = Table::new(rows, [Constraint::Fill(1), Constraint::Length(7)])
+                .style(self.styles.text_style)
+                .block(
+                    Block::bordered()
+                        .title(style::Styled::set_style(title, self.styles.title_style)),
+                );
+
+            Widget::render(table, *list_area, buf);
+        }
+
+        // Link density is a ratio, so it is listed with decimals
+        let rows = self
+            .stats
+            .top_link_density(&index, self.config.link_density_words, TOP_NOTES)
+            .into_iter()
+            .map(|(name, density)| Row::new(vec![name.to_string(), format!("{density:7.2}")]))
+            .collect::<Vec<_>>();
+        let table = Table::new(rows, [Constraint::Fill(1), Constraint::Length(7)])
+            .style(self.styles.text_style)
+            .block(Block::bordered().title(style::Styled::set_style(
+                format!("Links per {} Words", self.config.link_density_words),
+                self.styles.title_style,
+            )));
+        Widget::render(table, areas[5], buf);
+    }
+
+    /// Opens the text area to enter a tag rename to preview.
+    fn open_rename_area(&mut self) {
+        let mut area = TextArea::default();
+        area.set_style(self.styles.input_style);
+        area.set_cursor_line_style(self.styles.input_style);
+        area.set_block(Block::bordered().title(style::Styled::set_style(
+            "Enter tag and new name to preview renaming, e.g. #old #new...",
+            self.styles.title_style,
+        )));
+        self.rename_area = Some(area);
+    }
+
+    /// Previews the tag rename entered in the text area as old and new tag separated by whitespace.
+    fn preview_tag_rename(&mut self, input: &str) -> error::Result<()> {
+        let Some((from, to)) = input.split_whitespace().collect_tuple() else {
+            return Err(error::RucolaError::Input(String::from(
+                "Enter exactly the old and the new tag.",
+            )));
+        };
+        let impact = data::EnvironmentStats::tag_rename_impact(&self.index.borrow(), from, to);
+        self.tag_rename = Some((from.to_string(), to.to_string(), impact));
+        self.view = StatsView::TagRename;
+        Ok(())
+    }
+
+    /// Draws the effects of the last previewed tag rename.
+    fn draw_tag_rename(&self, area: Rect, buf: &mut Buffer) {
+        let Some((from, to, impact)) = &self.tag_rename else {
+            return;
+        };
+
+        let block = Block::bordered()
+            .title(style::Styled::set_style(
+                format!("Renaming {from} to {to}"),
+                self.styles.title_style,
+            ))
+            .title(
+                block::Title::from(Line::styled(
+                    format!("{} notes affected", impact.notes.len()),
+                    self.styles.text_style,
+                ))
+                .alignment(Alignment::Right),
+            );
+        let inner = block.inner(area);
+        Widget::render(block, area, buf);
+
+        let [renamed_area, environments_area, collisions_area, notes_area] =
+            Layout::horizontal([Constraint::Fill(1); 4]).areas(inner);
+
+        let renamed = Table::new(
+            impact.renamed.iter().map(|(tag, new_tag, count)| {
+                Row::new(vec![
+                    tag.to_string(),
+                    new_tag.to_string(),
+                    format!("{count:5}"),
+                ])
+            }),
+            [
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Length(5),
+            ],
+        )
+        .style(self.styles.text_style)
+        .header(Row::new(vec![
+            Cell::from("Tag").style(self.styles.subtitle_style),
+            Cell::from("New Name").style(self.styles.subtitle_style),
+            Cell::from("Notes").style(self.styles.subtitle_style),
+        ]));
+
+        let environments = Table::new(
+            impact.environments.iter().map(|(tag, gained, lost)| {
+                Row::new(vec![
+                    tag.to_string(),
+                    format!("{gained:+5}"),
+                    format!("{:+5}", -(*lost as isize)),
+                ])
+            }),
+            [
+                Constraint::Fill(1),
+                Constraint::Length(5),
+                Constraint::Length(5),
+            ],
+        )
+        .style(self.styles.text_style)
+        .header(Row::new(vec![
+            Cell::from("Environment").style(self.styles.subtitle_style),
+            Cell::from("Gains").style(self.styles.subtitle_style),
+            Cell::from("Loses").style(self.styles.subtitle_style),
+        ]));
+
+        let collisions = Table::new(
+            impact
+                .collisions
+                .iter()
+                .map(|(tag, count)| Row::new(vec![tag.to_string(), format!("{count:5}")])),
+            [Constraint::Fill(1), Constraint::Length(5)],
+        )
+        .style(self.styles.text_style)
+        .header(Row::new(vec![
+            Cell::from("Existing Tag").style(self.styles.subtitle_style),
+            Cell::from("Notes").style(self.styles.subtitle_style),
+        ]));
+
+        // Notes already containing a new name are marked, as they would contain it twice
+        let notes = Table::new(
+            impact.notes.iter().map(|id| {
+                Row::new(vec![
+                    id.to_string(),
+                    if impact.merged_notes.contains(id) {
+                        String::from("merged")
+                    } else {
+                        String::new()
+                    },
+                ])
+            }),
+            [Constraint::Fill(1), Constraint::Length(6)],
+        )
+        .style(self.styles.text_style)
+        .header(Row::new(vec![
+            Cell::from("Note").style(self.styles.subtitle_style),
+            Cell::from(""),
+        ]));
+
+        Widget::render(renamed, renamed_area, buf);
+        Widget::render(environments, environments_area, buf);
+        Widget::render(collisions, collisions_area, buf);
+        Widget::render(notes, notes_area, buf);
+    }
+
+    /// Compares the last snapshot to the current index, unless already done.
+    fn load_changes(&mut self) -> error::Result<()> {
+        if self.changes.is_none() {
+            let snapshot = data::IndexSnapshot::load(self.index.borrow().vault_path())?;
+            self.changes = Some((snapshot.taken(), snapshot.diff(&self.index.borrow())));
+        }
+        Ok(())
+    }
+
+    /// Draws the changes to notes, links and word counts since the last snapshot.
+    fn draw_changes(&self, area: Rect, buf: &mut Buffer) {
+        let Some((taken, report)) = &self.changes else {
+            return;
+        };
+
+        let title = match taken.and_then(|taken| taken.elapsed().ok()) {
+            Some(age) => format!("Changes since Snapshot ({} days ago)", age.as_secs() / DAY),
+            None => String::from("Changes since Snapshot (none taken yet)"),
+        };
+
+        let block =
+            Block::bordered().title(style::Styled::set_style(title, self.styles.title_style));
+        let inner = block.inner(area);
+        Widget::render(block, area, buf);
+
+        let [notes_area, links_area, words_area] =
+            Layout::horizontal([Constraint::Fill(1); 3]).areas(inner);
+
+        // Notes and links, additions first
+        let notes = report
+            .added
+            .iter()
+            .map(|id| Row::new(vec!["+", id.as_str()]))
+            .chain(
+                report
+                    .removed
+                    .iter()
+                    .map(|id| Row::new(vec!["-", id.as_str()])),
+            )
+            .collect::<Vec<_>>();
+        let links = report
+            .links_added
+            .iter()
+            .map(|(source, target)| Row::new(vec!["+", source.as_str(), target.as_str()]))
+            .chain(
+                report
+                    .links_removed
+                    .iter()
+                    .map(|(source, target)| Row::new(vec!["-", source.as_str(), target.as_str()])),
+            )
+            .collect::<Vec<_>>();
+        let words = report
+            .word_deltas
+            .iter()
+            .map(|(id, delta)| Row::new(vec![id.to_string(), format!("{delta:+7}")]))
+            .collect::<Vec<_>>();
+
+        let notes = Table::new(notes, [Constraint::Length(1), Constraint::Fill(1)])
+            .style(self.styles.text_style)
+            .header(Row::new(vec![
+                Cell::from(""),
+                Cell::from(format!(
+                    "Notes (+{}/-{})",
+                    report.added.len(),
+                    report.removed.len()
+                ))
+                .style(self.styles.subtitle_style),
+            ]));
+        let links = Table::new(
+            links,
+            [
+                Constraint::Length(1),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+            ],
+        )
+        .style(self.styles.text_style)
+        .header(Row::new(vec![
+            Cell::from(""),
+            Cell::from(format!(
+                "Links (+{}/-{})",
+                report.links_added.len(),
+                report.links_removed.len()
+            ))
+            .style(self.styles.subtitle_style),
+            Cell::from("Target").style(self.styles.subtitle_style),
+        ]));
+        let words = Table::new(words, [Constraint::Fill(1), Constraint::Length(7)])
+            .style(self.styles.text_style)
+            .header(Row::new(vec![
+                Cell::from("Note").style(self.styles.subtitle_style),
+                Cell::from("  Words").style(self.styles.subtitle_style),
+            ]));
+
+        Widget::render(notes, notes_area, buf);
+        Widget::render(links, links_area, buf);
+        Widget::render(words, words_area, buf);
+    }
+
+    /// Draws a list of references to missing attachments and of attachments no note references.
+    fn draw_attachments(&self, area: Rect, buf: &mut Buffer) {
+        let missing = self.stats.missing_attachments();
+        let unreferenced = self.stats.unreferenced_attachments();
+
+        let rows = missing
+            .iter()
+            .map(|(source, reference)| Row::new(vec![reference.as_str(), source.as_str()]))
+            .chain(
+                unreferenced
+                    .iter()
+                    .map(|attachment| Row::new(vec![attachment.as_str(), "-"])),
+            )
+            .collect::<Vec<_>>();
+
+        let table = Table::new(rows, [Constraint::Fill(2), Constraint::Fill(1)])
+            .style(self.styles.text_style)
+            .header(Row::new(vec![
+                Cell::from("Attachment").style(self.styles.subtitle_style),
+                Cell::from("Referenced by").style(self.styles.subtitle_style),
+            ]))
+            .block(
+                Block::bordered()
+                    .title(style::Styled::set_style(
+                        "Attachments",
+                        self.styles.title_style,
+                    ))
+                    .title(
+                        block::Title::from(Line::styled(
+                            format!("{} missing, {} unused", missing.len(), unreferenced.len()),
+                            self.styles.text_style,
+                        ))
+                        .alignment(Alignment::Right),
+                    ),
+            );
+
+        Widget::render(table, area, buf);
+    }
+
+    /// Draws a table of the most frequently linked external domains or URLs.
+    fn draw_external_links(&self, area: Rect, buf: &mut Buffer) {
+        let amount = area.height.saturating_sub(3) as usize;
+        let (title, links) = if self.show_urls {
+            ("External URLs", self.stats.top_external_urls(amount))
+        } else {
+            ("External Domains", self.stats.top_external_domains(amount))
+        };
+
+        let rows = links
+            .into_iter()
+            .map(|(link, count)| Row::new(vec![link.to_string(), format!("{count:5}")]))
+            .collect::<Vec<_>>();
+
+        let table = Table::new(rows, [Constraint::Fill(1), Constraint::Length(5)])
+            .style(self.styles.text_style)
+            .header(Row::new(vec![
+                Cell::from(if self.show_urls { "URL" } else { "Domain" })
+                    .style(self.styles.subtitle_style),
+                Cell::from("Links").style(self.styles.subtitle_style),
+            ]))
+            .block(
+                Block::bordered()
+                    .title(style::Styled::set_style(title, self.styles.title_style))
+                    .title(
+                        block::Title::from(Line::styled(
+                            format!("{}", self.stats.external_url_count()),
+                            self.styles.text_style,
+                        ))
+                        .alignment(Alignment::Right),
+                    ),
+            );
+
+        Widget::render(table, area, buf);
+    }
+
+    /// Draws a table of all notes that would share their id with another note, with the ids they were given instead.
+    fn draw_id_collisions(&self, area: Rect, buf: &mut Buffer) {
+        let index = self.index.borrow();
+        let collisions = index.id_collisions();
+
+        let rows = collisions
+            .iter()
+            .flat_map(|(base, notes)| {
+                notes.iter().map(move |(id, path)| {
+                    Row::new(vec![
+                        base.clone(),
+                        id.to_string(),
+                        path.to_string_lossy().to_string(),
+                    ])
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Fill(2),
+            ],
+        )
+        .style(self.styles.text_style)
+        .header(Row::new(vec![
+            Cell::from("Id").style(self.styles.subtitle_style),
+            Cell::from("Given Id").style(self.styles.subtitle_style),
+            Cell::from("Path").style(self.styles.subtitle_style),
+        ]))
+        .block(
+            Block::bordered()
+                .title(style::Styled::set_style(
+                    "Id Collisions",
+                    self.styles.title_style,
+                ))
+                .title(
+                    block::Title::from(Line::styled(
+                        format!("{}", collisions.len()),
+                        self.styles.text_style,
+                    ))
+                    .alignment(Alignment::Right),
+                ),
+        );
+
+        Widget::render(table, area, buf);
+    }
+
+    /// Draws a table comparing the statistics of all top-level tags.
+    fn draw_tag_comparison(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered().title(style::Styled::set_style(
+            "Top-Level Tags",
+            self.styles.title_style,
+        ));
+
+        match &self.tag_stats {
+            Some(tag_stats) => Widget::render(
+                data::EnvironmentStats::to_comparison_table(tag_stats, "Tag", &self.styles)
+                    .block(block),
+                area,
+                buf,
+            ),
+            None => Widget::render(block, area, buf),
+        }
+    }
+
+    /// Draws a table comparing the statistics of all folders.
+    fn draw_folder_comparison(&self, area: Rect, buf: &mut Buffer) {
+        let block =
+            Block::bordered().title(style::Styled::set_style("Folders", self.styles.title_style));
+
+        match &self.folder_stats {
+            Some(folder_stats) => Widget::render(
+                data::EnvironmentStats::to_comparison_table(folder_stats, "Folder", &self.styles)
+                    .block(block),
+                area,
+                buf,
+            ),
+            None => Widget::render(block, area, buf),
+        }
+    }
+
+    /// Draws a table comparing the statistics of all note languages.
+    fn draw_language_comparison(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered().title(style::Styled::set_style(
+            "Languages",
+            self.styles.title_style,
+        ));
+
+        match &self.language_stats {
+            Some(language_stats) => Widget::render(
+                data::EnvironmentStats::to_comparison_table(
+                    language_stats,
+                    "Language",
+                    &self.styles,
+                )
+                .block(block),
+                area,
+                buf,
+            ),
+            None => Widget::render(block, area, buf),
+        }
+    }
+
+    /// Draws a list of the pairs of tags that occur together most often.
+    fn draw_tag_pairs(&self, area: Rect, buf: &mut Buffer) {
+        let rows = self
+            .stats
+            .top_tag_pairs(area.height.saturating_sub(3) as usize)
+            .into_iter()
+            .map(|(a, b, count, share)| {
+                Row::new(vec![
+                    a.to_string(),
+                    b.to_string(),
+                    format!("{count:5}"),
+                    format!("{:3.0}%", share * 100.),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Length(5),
+                Constraint::Length(5),
+            ],
+        )
+        .style(self.styles.text_style)
+        .header(Row::new(vec![
+            Cell::from("Tag").style(self.styles.subtitle_style),
+            Cell::from("Tag").style(self.styles.subtitle_style),
+            Cell::from("Notes").style(self.styles.subtitle_style),
+            Cell::from("Share").style(self.styles.subtitle_style),
+        ]))
+        .block(Block::bordered().title(style::Styled::set_style(
+            "Tag Pairs",
+            self.styles.title_style,
+        )));
+
+        Widget::render(table, area, buf);
+    }
+
+    /// Draws a list of all groups of notes linked in cycles.
+    fn draw_cycles(&self, area: Rect, buf: &mut Buffer) {
+        let cycles = self.stats.cycles().unwrap_or_default();
+
+        let rows = cycles
+            .iter()
+            .map(|cycle| Row::new(vec![format!("{:3}", cycle.len()), cycle.join(", ")]))
+            .collect::<Vec<_>>();
+
+        let table = Table::new(rows, [Constraint::Length(5), Constraint::Fill(1)])
+            .style(self.styles.text_style)
+            .header(Row::new(vec![
+                Cell::from("Size").style(self.styles.subtitle_style),
+                Cell::from("Notes").style(self.styles.subtitle_style),
+            ]))
+            .block(
+                Block::bordered()
+                    .title(style::Styled::set_style(
+                        "Link Cycles",
+                        self.styles.title_style,
+                    ))
+                    .title(
+                        block::Title::from(Line::styled(
+                            format!("{}", cycles.len()),
+                            self.styles.text_style,
+                        ))
+                        .alignment(Alignment::Right),
+                    ),
+            );
+
+        Widget::render(table, area, buf);
+    }
+
+    /// Draws a list of all pairs of notes that are possibly duplicates of each other.
+    fn draw_duplicates(&self, area: Rect, buf: &mut Buffer) {
+        let duplicates = self.stats.duplicates().unwrap_or_default();
+
+        let rows = duplicates
+            .iter()
+            .map(|(a, b, title, content)| {
+                Row::new(vec![
+                    a.to_string(),
+                    b.to_string(),
+                    format!("{:3.0}%", title * 100.),
+                    format!("{:3.0}%", content * 100.),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Length(5),
+                Constraint::Length(5),
+            ],
+        )
+        .style(self.styles.text_style)
+        .header(Row::new(vec![
+            Cell::from("Note").style(self.styles.subtitle_style),
+            Cell::from("Duplicate").style(self.styles.subtitle_style),
+            Cell::from("Title").style(self.styles.subtitle_style),
+            Cell::from("Text").style(self.styles.subtitle_style),
+        ]))
+        .block(
+            Block::bordered()
+                .title(style::Styled::set_style(
+                    "Possible Duplicates",
+                    self.styles.title_style,
+                ))
+                .title(
+                    block::Title::from(Line::styled(
+                        format!("{}", duplicates.len()),
+                        self.styles.text_style,
+                    ))
+                    .alignment(Alignment::Right),
+                ),
+        );
+
+        Widget::render(table, area, buf);
+    }
+
+    /// Draws a list of the most similar pairs of notes that do not link to each other yet.
+    fn draw_link_suggestions(&self, area: Rect, buf: &mut Buffer) {
+        let rows = self
+            .stats
+            .link_suggestions(area.height.saturating_sub(3) as usize)
+            .into_iter()
+            .map(|(a, b, similarity)| {
+                Row::new(vec![
+                    a.to_string(),
+                    b.to_string(),
+                    format!("{:3.0}%", similarity * 100.),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Length(4),
+            ],
+        )
+        .style(self.styles.text_style)
+        .header(Row::new(vec![
+            Cell::from("Note").style(self.styles.subtitle_style),
+            Cell::from("Related").style(self.styles.subtitle_style),
+            Cell::from("Sim.").style(self.styles.subtitle_style),
+        ]))
+        .block(Block::bordered().title(style::Styled::set_style(
+            "Link Suggestions",
+            self.styles.title_style,
+        )));
+
+        Widget::render(table, area, buf);
+    }
+
+    /// Draws sparklines of the notes created, words added and notes modified per period, one column per period.
+    fn draw_growth(&self, area: Rect, buf: &mut Buffer) {
+        let title = if self.monthly {
+            "Growth per Month"
+        } else {
+            "Growth per Week"
+        };
+        let block =
+            Block::bordered().title(style::Styled::set_style(title, self.styles.title_style));
+        let inner = block.inner(area);
+        Widget::render(block, area, buf);
+
+        let [labels_area, lines_area] =
+            Layout::horizontal([Constraint::Length(22), Constraint::Fill(1)]).areas(inner);
+
+        let period = time::Duration::from_secs(if self.monthly { MONTH } else { WEEK });
+        let growth = self
+            .stats
+            .growth(period, lines_area.width as usize, time::SystemTime::now());
+
+        let series: [(&str, Vec<u64>); 3] = [
+            (
+                "Notes created",
+                growth.iter().map(|p| p.created as u64).collect(),
+            ),
+            (
+                "Words added",
+                growth.iter().map(|p| p.words as u64).collect(),
+            ),
+            (
+                "Notes modified",
+                growth.iter().map(|p| p.modified as u64).collect(),
+            ),
+        ];
+
+        let rows = Layout::vertical([Constraint::Fill(1); 3]);
+        for ((label, data), (label_area, line_area)) in series.iter().zip(
+            rows.split(labels_area)
+                .iter()
+                .zip(rows.split(lines_area).iter()),
+        ) {
+            Widget::render(
+                Line::styled(
+                    format!("{label} ({})", data.iter().sum::<u64>()),
+                    self.styles.subtitle_style,
+                ),
+                *label_area,
+                buf,
+            );
+            Widget::render(
+                Sparkline::default()
+                    .data(data)
+                    .style(self.styles.text_style),
+                *line_area,
+                buf,
+            );
+        }
+    }
+
+    /// Draws the distribution of word counts as key figures and a histogram.
+    fn draw_word_distribution(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered().title(style::Styled::set_style(
+            "Note Lengths",
+            self.styles.title_style,
+        ));
+        let inner = block.inner(area);
+        Widget::render(block, area, buf);
+
+        let [figures_area, histogram_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(inner);
+
+        // Key figures
+        let figures = Table::new(
+            [
+                Row::new(vec![
+                    Cell::from("Min").style(self.styles.subtitle_style),
+                    Cell::from("10%").style(self.styles.subtitle_style),
+                    Cell::from("Median").style(self.styles.subtitle_style),
+                    Cell::from("Mean").style(self.styles.subtitle_style),
+                    Cell::from("90%").style(self.styles.subtitle_style),
+                    Cell::from("Max").style(self.styles.subtitle_style),
+                ]),
+                Row::new(vec![
+                    format!("{:6}", self.stats.word_percentile(0)),
+                    format!("{:6}", self.stats.word_percentile(10)),
+                    format!("{:6}", self.stats.word_percentile(50)),
+                    format!("{:6.0}", self.stats.word_mean()),
+                    format!("{:6}", self.stats.word_percentile(90)),
+                    format!("{:6}", self.stats.word_percentile(100)),
+                ])
+                .style(self.styles.text_style),
+            ],
+            [Constraint::Length(7); 6],
+        )
+        .column_spacing(1);
+
+        // Histogram, labeled with the upper bound of each bucket
+        let histogram = self.stats.word_histogram(WORD_BUCKETS);
+        let bar_width = (histogram_area.width / WORD_BUCKETS as u16)
+            .saturating_sub(1)
+            .max(1);
+        let bars = histogram
+            .iter()
+            .map(|(bound, count)| {
+                Bar::default()
+                    .value(*count as u64)
+                    .label(Line::from(format!("≤{bound}")))
+                    .style(self.styles.text_style)
+                    .value_style(self.styles.selected_style)
+            })
+            .collect::<Vec<_>>();
+
+        let chart = BarChart::default()
+            .bar_width(bar_width)
+            .bar_gap(1)
+            .data(BarGroup::default().bars(&bars))
+            .label_style(self.styles.subtitle_style);
+
+        Widget::render(figures, figures_area, buf);
+        Widget::render(chart, histogram_area, buf);
+    }
+
+    /// Draws the statistics and the current view.
+    fn draw_view(&self, area: Rect, buf: &mut Buffer) {
+        // Generate vertical layout
+        let vertical = Layout::vertical([
+            Constraint::Length(8),
+            Constraint::Fill(1),
+            Constraint::Length(11),
+        ]);
+
+        let [stats_area, charts_area, bottom_area] = vertical.areas(area);
+
+        let [growth_area, suggestions_area, duplicates_area, cycles_area, streak_area, headings_area] =
+            Layout::horizontal([
+                Constraint::Fill(2),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                // Only make room for the streaks if daily notes are recognized
+                Constraint::Length(if self.daily_streak.is_some() { 25 } else { 0 }),
+                Constraint::Length(24),
+            ])
+            .areas(bottom_area);
+
+        let [tags_area, words_area, broken_area, external_area] =
+            Layout::horizontal([Constraint::Fill(1); 4]).areas(charts_area);
+
+        let instructions_bot_right = block::Title::from(Line::from(vec![
+            Span::styled("O", self.styles.hotkey_style),
+            Span::styled("verview──", self.styles.text_style),
+            Span::styled("T", self.styles.hotkey_style),
+            Span::styled("ags──", self.styles.text_style),
+            Span::styled("F", self.styles.hotkey_style),
+            Span::styled("olders──", self.styles.text_style),
+            Span::styled("L", self.styles.text_style),
+            Span::styled("a", self.styles.hotkey_style),
+            Span::styled("nguages──", self.styles.text_style),
+            Span::styled("P", self.styles.hotkey_style),
+            Span::styled("eriod──", self.styles.text_style),
+            Span::styled("U", self.styles.hotkey_style),
+            Span::styled("RLs──", self.styles.text_style),
+            Span::styled("E", self.styles.hotkey_style),
+            Span::styled("xport JSON──", self.styles.text_style),
+            Span::styled("C", self.styles.hotkey_style),
+            Span::styled("SV──", self.styles.text_style),
+            Span::styled("G", self.styles.hotkey_style),
+            Span::styled("raph DOT──", self.styles.text_style),
+            Span::styled("Graph", self.styles.text_style),
+            Span::styled("M", self.styles.hotkey_style),
+            Span::styled("L──", self.styles.text_style),
+            Span::styled("S", self.styles.hotkey_style),
+            Span::styled("napshot──", self.styles.text_style),
+            Span::styled("D", self.styles.hotkey_style),
+            Span::styled("iff──", self.styles.text_style),
+            Span::styled("Top ", self.styles.text_style),
+            Span::styled("N", self.styles.hotkey_style),
+            Span::styled("otes──", self.styles.text_style),
+            Span::styled("R", self.styles.hotkey_style),
+            Span::styled("ename Tag──", self.styles.text_style),
+            Span::styled("I", self.styles.hotkey_style),
+            Span::styled("d Collisions──", self.styles.text_style),
+            Span::styled("Q", self.styles.hotkey_style),
+            Span::styled("/", self.styles.text_style),
+            Span::styled("Esc", self.styles.hotkey_style),
+            Span::styled(": Back", self.styles.text_style),
+        ]))
+        .alignment(Alignment::Right)
+        .position(block::Position::Bottom);
+
+        let mut stats_block = Block::bordered()
+            .title(style::Styled::set_style(
+                "Statistics",
+                self.styles.title_style,
+            ))
+            .title(instructions_bot_right);
+        if let Some(exported) = &self.exported {
+            stats_block = stats_block.title(
+                block::Title::from(Line::styled(
+                    format!("Exported to {}", exported.display()),
+                    self.styles.text_style,
+                ))
+                .alignment(Alignment::Right),
+            );
+        }
+        let stats = self
+            .stats
+            .to_global_stats_table(&self.styles)
+            .block(stats_block);
+
+        // Link graph metrics and health next to the general statistics
+        let [stats_area, graph_area, health_area] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(34),
+            Constraint::Length(40),
+        ])
+        .areas(stats_area);
+        let graph = self
+            .stats
+            .to_graph_stats_table(&self.styles)
+            .block(Block::bordered().title(style::Styled::set_style(
+                "Link Graph",
+                self.styles.title_style,
+            )));
+
+        Widget::render(stats, stats_area, buf);
+        let health = self
+            .stats
+            .to_health_table(self.config.health, &self.styles)
+            .block(
+                Block::bordered()
+                    .title(style::Styled::set_style("Health", self.styles.title_style))
+                    .title(
+                        block::Title::from(Line::styled(
+                            format!("{:.0}/100", self.stats.health_score(self.config.health)),
+                            self.styles.selected_style,
+                        ))
+                        .alignment(Alignment::Right),
+                    ),
+            );
+
+        Widget::render(graph, graph_area, buf);
+        Widget::render(health, health_area, buf);
+
+        match self.view {
+            StatsView::Environment => {}
+            StatsView::Overview => {
+                self.draw_overview(charts_area.union(bottom_area), buf);
+                return;
+            }
+            StatsView::Tags => {
+                let [comparison_area, pairs_area] =
+                    Layout::horizontal([Constraint::Fill(2), Constraint::Fill(1)])
+                        .areas(charts_area.union(bottom_area));
+                self.draw_tag_comparison(comparison_area, buf);
+                self.draw_tag_pairs(pairs_area, buf);
+                return;
+            }
+            StatsView::Folders => {
+                self.draw_folder_comparison(charts_area.union(bottom_area), buf);
+                return;
+            }
+            StatsView::Languages => {
+                self.draw_language_comparison(charts_area.union(bottom_area), buf);
+                return;
+            }
+            StatsView::Changes => {
+                self.draw_changes(charts_area.union(bottom_area), buf);
+                return;
+            }
+            StatsView::TopNotes => {
+                self.draw_top_notes(charts_area.union(bottom_area), buf);
+                return;
+            }
+            StatsView::TagRename => {
+                self.draw_tag_rename(charts_area.union(bottom_area), buf);
+                return;
+            }
+            StatsView::IdCollisions => {
+                self.draw_id_collisions(charts_area.union(bottom_area), buf);
+                return;
+            }
+        }
+
+        self.draw_tag_chart(tags_area, buf, TOP_TAGS);
+        self.draw_word_distribution(words_area, buf);
+        self.draw_growth(growth_area, buf);
+        self.draw_broken_links(broken_area, buf);
+        let [external_area, attachments_area] =
+            Layout::vertical([Constraint::Fill(1); 2]).areas(external_area);
+        self.draw_external_links(external_area, buf);
+        self.draw_attachments(attachments_area, buf);
+        self.draw_link_suggestions(suggestions_area, buf);
+        self.draw_duplicates(duplicates_area, buf);
+        self.draw_cycles(cycles_area, buf);
+
+        let headings = self
+            .stats
+            .to_heading_table(&self.styles)
+            .block(Block::bordered().title(style::Styled::set_style(
+                "Headings",
+                self.styles.title_style,
+            )));
+        Widget::render(headings, headings_area, buf);
+
+        if let Some(daily_streak) = &self.daily_streak {
+            let streak = daily_streak
+                .to_table(&self.styles)
+                .block(Block::bordered().title(style::Styled::set_style(
+                    "Daily Notes",
+                    self.styles.title_style,
+                )));
+            Widget::render(streak, streak_area, buf);
+        }
+    }
+}
+
+impl super::Screen for StatsScreen {
+    fn draw(&self, area: layout::Rect, buf: &mut buffer::Buffer) {
+        self.draw_view(area, buf);
+
+        // The tag rename input is drawn on top of everything else
+        if let Some(rename_area) = &self.rename_area {
+            let [_, input_area, _] = Layout::vertical([
+                Constraint::Fill(1),
+                Constraint::Length(3),
+                Constraint::Fill(1),
+            ])
+            .areas(area);
+            let [_, input_area, _] = Layout::horizontal([
+                Constraint::Fill(1),
+                Constraint::Percentage(60),
+                Constraint::Fill(1),
+            ])
+            .areas(input_area);
+
+            Widget::render(Clear, input_area, buf);
+            Widget::render(rename_area.widget(), input_area, buf);
+        }
+    }
+
+    fn keybindings(&self) -> Vec<(String, &'static str)> {
+        if self.rename_area.is_some() {
+            return vec![
+                ("Enter".to_owned(), "Preview renaming the tag"),
+                ("Esc".to_owned(), "Cancel the rename"),
+            ];
+        }
+        super::live_keybindings(&BINDINGS, |code| self.action(code))
+    }
+
+    fn update(&mut self, key: crossterm::event::KeyEvent) -> error::Result<ui::Message> {
+        // While entering a tag rename, all keys go to the text area
+        if let Some(rename_area) = &mut self.rename_area {
+            match key.code {
+                KeyCode::Esc => {
+                    self.rename_area = None;
+                }
+                KeyCode::Enter => {
+                    let input = rename_area.lines().first().cloned().unwrap_or_default();
+                    self.rename_area = None;
+                    self.preview_tag_rename(&input)?;
+                }
+                _ => {
+                    rename_area.input(key);
+                }
+            }
+            return Ok(ui::Message::None);
+        }
+
+        match self.action(key.code) {
+            // Back to the select screen
+            Some(Action::Close) => Ok(ui::Message::CloseStats),
+            Some(Action::Help) => Ok(ui::Message::DisplayHelp),
+            // Select broken links
+            Some(Action::SelectBrokenLink) => {
+                self.selected = match key.code {
+                    KeyCode::Char('j' | 'J') | KeyCode::Down => self
+                        .selected
+                        .saturating_add(1)
+                        .min(self.stats.broken_link_targets().len().saturating_sub(1)),
+                    _ => self.selected.saturating_sub(1),
+                };
+                Ok(ui::Message::None)
+            }
+            // Open the source of the selected broken link
+            Some(Action::OpenBrokenLink) => Ok(self
+                .stats
+                .broken_link_targets()
+                .get(self.selected)
+                .map(|(source, _)| ui::Message::DisplayStackPush(source.clone()))
+                .unwrap_or(ui::Message::None)),
+            // O: Switch between the environment's statistics and its overview compared to the whole vault
+            Some(Action::Overview) => {
+                self.toggle_view(StatsView::Overview);
+                Ok(ui::Message::None)
+            }
+            // T: Switch between the environment's statistics and the comparison of all top-level tags
+            Some(Action::CompareTags) => {
+                self.toggle_view(StatsView::Tags);
+                if self.tag_stats.is_none() {
+                    self.tag_stats = Some(data::EnvironmentStats::per_top_level_tag(&self.index));
+                }
+                Ok(ui::Message::None)
+            }
+            // F: Switch between the environment's statistics and the comparison of all folders
+            Some(Action::CompareFolders) => {
+                self.toggle_view(StatsView::Folders);
+                if self.folder_stats.is_none() {
+                    self.folder_stats = Some(data::EnvironmentStats::per_directory(
+                        &self.index,
+                        self.config.directory_depth,
+                    ));
+                }
+                Ok(ui::Message::None)
+            }
+            // A: Switch between the environment's statistics and the comparison of all note languages
+            Some(Action::CompareLanguages) => {
+                self.toggle_view(StatsView::Languages);
+                if self.language_stats.is_none() {
+                    self.language_stats = Some(data::EnvironmentStats::per_language(&self.index));
+                }
+                Ok(ui::Message::None)
+            }
+            // P: Switch between weekly and monthly growth
+            Some(Action::SwitchGrowth) => {
+                self.monthly = !self.monthly;
+                Ok(ui::Message::None)
+            }
+            // E/C/G/M: Export to JSON, CSV, DOT or GraphML
+            Some(Action::Export) => {
+                self.export(match key.code {
+                    KeyCode::Char('e' | 'E') => data::ExportFormat::Json,
+                    KeyCode::Char('c' | 'C') => data::ExportFormat::Csv,
+                    KeyCode::Char('g' | 'G') => data::ExportFormat::Dot,
+                    _ => data::ExportFormat::GraphMl,
+                })?;
+                Ok(ui::Message::None)
+            }
+            // S: Take a new snapshot of the index, replacing the last one
+            Some(Action::Snapshot) => {
+                let snapshot = data::IndexSnapshot::new(&self.index.borrow());
+                snapshot.store(self.index.borrow().vault_path())?;
+                self.changes = Some((snapshot.taken(), data::ChangeReport::default()));
+                Ok(ui::Message::None)
+            }
+            // D: Switch between the environment's statistics and the changes since the last snapshot
+            Some(Action::Changes) => {
+                self.load_changes()?;
+                self.toggle_view(StatsView::Changes);
+                Ok(ui::Message::None)
+            }
+            // N: Switch between the environment's statistics and the top notes
+            Some(Action::TopNotes) => {
+                self.toggle_view(StatsView::TopNotes);
+                Ok(ui::Message::None)
+            }
+            // R: Enter a tag rename to preview, or go back from the preview
+            Some(Action::RenameTag) => {
+                if self.view == StatsView::TagRename {
+                    self.view = StatsView::Environment;
+                } else {
+                    self.open_rename_area();
+                }
+                Ok(ui::Message::None)
+            }
+            // I: Switch between the environment's statistics and the id collisions
+            Some(Action::IdCollisions) => {
+                self.toggle_view(StatsView::IdCollisions);
+                Ok(ui::Message::None)
+            }
+            // U: Switch between external domains and full URLs
+            Some(Action::SwitchUrls) => {
+                self.show_urls = !self.show_urls;
+                Ok(ui::Message::None)
+            }
+            None => Ok(ui::Message::None),
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{error, io, ui};
+use crossterm::event::KeyCode;
+use ratatui::{prelude::*, widgets::*};
+use std::path;
+
+/// An action of the trash screen, triggered by the keys bound to it in [BINDINGS].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    Close,
+    Help,
+    Select,
+    Restore,
+    Delete,
+}
+
+/// The keybindings of the trash screen, used both to dispatch key presses and to list them in the help overlay.
+/// Each binding consists of its action, the keys triggering it, how these keys are shown and what the action does.
+const BINDINGS: [(Action, &[KeyCode], &str, &str); 5] = [
+    (
+        Action::Close,
+        &[KeyCode::Esc, KeyCode::Char('q'), KeyCode::Char('Q')],
+        "Esc / Q",
+        "Return to the select screen",
+    ),
+    (
+        Action::Select,
+        &[
+            KeyCode::Char('j'),
+            KeyCode::Char('J'),
+            KeyCode::Down,
+            KeyCode::Char('k'),
+            KeyCode::Char('K'),
+            KeyCode::Up,
+        ],
+        "J / K",
+        "Select the next / previous file",
+    ),
+    (
+        Action::Restore,
+        &[KeyCode::Enter, KeyCode::Char('r'), KeyCode::Char('R')],
+        "Enter / R",
+        "Restore the selected file",
+    ),
+    (
+        Action::Delete,
+        &[KeyCode::Char('d'), KeyCode::Char('D')],
+        "D",
+        "Delete the selected file permanently",
+    ),
+    (
+        Action::Help,
+        &[KeyCode::Char('?')],
+        "?",
+        "Show the keybindings",
+    ),
+];
+
+/// The trash screen lists all notes moved to the trash and allows the user to restore or permanently delete them.
+pub struct TrashScreen {
+    /// The paths of all trashed files, relative to the trash folder.
+    files: Vec<path::PathBuf>,
+    /// The index of the currently selected file.
+    selected: usize,
+    /// Wether the user was asked to confirm permanently deleting the selected file.
+    confirm_delete: bool,
+    /// The file manager used to restore and delete trashed files.
+    manager: io::FileManager,
+    /// The used styles.
+    styles: ui::UiStyles,
+}
+
+impl TrashScreen {
+    /// Creates a new trash screen listing the files currently in the trash of the given file manager.
+    pub fn new(manager: io::FileManager, styles: ui::UiStyles) -> Self {
+        Self {
+            files: manager.trashed_files(),
+            selected: 0,
+            confirm_delete: false,
+            manager,
+            styles,
+        }
+    }
+
+    /// Reads the files in the trash again, keeping the selection in bounds.
+    fn refresh(&mut self) {
+        self.files = self.manager.trashed_files();
+        self.selected = self.selected.min(self.files.len().saturating_sub(1));
+    }
+}
+
+impl super::Screen for TrashScreen {
+    fn draw(&self, area: layout::Rect, buf: &mut buffer::Buffer) {
+        let rows = self
+            .files
+            .iter()
+            .map(|path| {
+                Row::new(vec![
+                    path.file_stem()
+                        .map(|stem| stem.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    path.to_string_lossy().to_string(),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        let instructions_bot_left = block::Title::from(Line::from(if self.confirm_delete {
+            vec![
+                Span::styled("󰌑", self.styles.hotkey_style),
+                Span::styled(": Delete permanently──", self.styles.text_style),
+                Span::styled("Any", self.styles.hotkey_style),
+                Span::styled(": Cancel", self.styles.text_style),
+            ]
+        } else {
+            vec![
+                Span::styled("J", self.styles.hotkey_style),
+                Span::styled("/", self.styles.text_style),
+                Span::styled("K", self.styles.hotkey_style),
+                Span::styled(": Select──", self.styles.text_style),
+                Span::styled("R", self.styles.hotkey_style),
+                Span::styled("/", self.styles.text_style),
+                Span::styled("󰌑", self.styles.hotkey_style),
+                Span::styled(": Restore──", self.styles.text_style),
+                Span::styled("D", self.styles.hotkey_style),
+                Span::styled("elete──", self.styles.text_style),
+                Span::styled("Q", self.styles.hotkey_style),
+                Span::styled(": Back", self.styles.text_style),
+            ]
+        }))
+        .alignment(Alignment::Left)
+        .position(block::Position::Bottom);
+
+        let table = Table::new(rows, [Constraint::Percentage(30), Constraint::Fill(1)])
+            .style(self.styles.text_style)
+            .header(Row::new(vec![
+                Cell::from("Name").style(self.styles.subtitle_style),
+                Cell::from("Path").style(self.styles.subtitle_style),
+            ]))
+            .highlight_style(self.styles.selected_style)
+            .block(
+                Block::bordered()
+                    .title(style::Styled::set_style("Trash", self.styles.title_style))
+                    .title(instructions_bot_left),
+            );
+
+        let mut state = TableState::new().with_selected(Some(self.selected));
+
+        StatefulWidget::render(table, area, buf, &mut state);
+    }
+
+    fn keybindings(&self) -> Vec<(String, &'static str)> {
+        super::live_keybindings(&BINDINGS, |code| self.action(code))
+    }
+
+    fn update(&mut self, key: crossterm::event::KeyEvent) -> error::Result<ui::Message> {
+        // Confirm permanent deletion
+        if self.confirm_delete {
+            self.confirm_delete = false;
+            if key.code == KeyCode::Enter {
+                if let Some(path) = self.files.get(self.selected) {
+                    self.manager.delete_trashed_file(path)?;
+                }
+                self.refresh();
+            }
+            return Ok(ui::Message::None);
+        }
+
+        Ok(match self.action(key.code) {
+            // Back to the select screen
+            Some(Action::Close) => ui::Message::CloseTrash,
+            Some(Action::Help) => ui::Message::DisplayHelp,
+            // Selection
+            Some(Action::Select) => {
+                self.selected = match key.code {
+                    KeyCode::Char('j' | 'J') | KeyCode::Down => self
+                        .selected
+                        .saturating_add(1)
+                        .min(self.files.len().saturating_sub(1)),
+                    _ => self.selected.saturating_sub(1),
+                };
+                ui::Message::None
+            }
+            // Restore the selected file to its previous location, where the index picks it up again
+            Some(Action::Restore) => {
+                if let Some(path) = self.files.get(self.selected) {
+                    self.manager.restore_trashed_file(path)?;
+                }
+                self.refresh();
+                ui::Message::None
+            }
+            // Ask for confirmation to delete the selected file permanently
+            Some(Action::Delete) => {
+                self.confirm_delete = true;
+                ui::Message::None
+            }
+            None => ui::Message::None,
+        })
+    }
+}
+
+impl TrashScreen {
+    /// Returns the action the given key triggers, if any.
+    fn action(&self, code: KeyCode) -> Option<Action> {
+        BINDINGS
+            .iter()
+            .find(|(action, codes, _keys, _description)| {
+                codes.contains(&code) && self.is_available(*action)
+            })
+            .map(|(action, _codes, _keys, _description)| *action)
+    }
+
+    /// Checks wether the given action can currently be triggered.
+    fn is_available(&self, action: Action) -> bool {
+        match action {
+            Action::Select | Action::Restore | Action::Delete => !self.files.is_empty(),
+            Action::Close | Action::Help => true,
+        }
+    }
+}
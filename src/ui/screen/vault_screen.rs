@@ -0,0 +1,203 @@
+use crate::{error, ui};
+use crossterm::event::KeyCode;
+use ratatui::{prelude::*, widgets::*};
+use std::path;
+
+/// An action of the vault screen, triggered by the keys bound to it in [BINDINGS].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    Close,
+    Help,
+    Select,
+    Switch,
+}
+
+/// The keybindings of the vault screen, used both to dispatch key presses and to list them in the help overlay.
+/// Each binding consists of its action, the keys triggering it, how these keys are shown and what the action does.
+const BINDINGS: [(Action, &[KeyCode], &str, &str); 4] = [
+    (
+        Action::Close,
+        &[KeyCode::Esc, KeyCode::Char('q'), KeyCode::Char('Q')],
+        "Esc / Q",
+        "Return to the select screen",
+    ),
+    (
+        Action::Select,
+        &[
+            KeyCode::Char('j'),
+            KeyCode::Char('J'),
+            KeyCode::Down,
+            KeyCode::Char('k'),
+            KeyCode::Char('K'),
+            KeyCode::Up,
+        ],
+        "J / K",
+        "Select the next / previous vault",
+    ),
+    (
+        Action::Switch,
+        &[
+            KeyCode::Enter,
+            KeyCode::Char('l'),
+            KeyCode::Char('L'),
+            KeyCode::Right,
+        ],
+        "Enter / L",
+        "Switch to the selected vault",
+    ),
+    (
+        Action::Help,
+        &[KeyCode::Char('?')],
+        "?",
+        "Show the keybindings",
+    ),
+];
+
+/// The vault screen lists all configured vaults and allows the user to switch to one of them.
+pub struct VaultScreen {
+    /// The names and paths of all vaults to choose from.
+    vaults: Vec<(String, path::PathBuf)>,
+    /// The path of the currently opened vault.
+    current: path::PathBuf,
+    /// The paths of all vaults that have already been indexed during this session.
+    loaded: Vec<path::PathBuf>,
+    /// The index of the currently selected vault.
+    selected: usize,
+    /// The used styles.
+    styles: ui::UiStyles,
+}
+
+impl VaultScreen {
+    /// Creates a new vault screen from the configured vaults.
+    /// If the currently opened vault is not configured, it is listed first under the name of its folder.
+    pub fn new(
+        mut vaults: Vec<(String, path::PathBuf)>,
+        current: &path::Path,
+        loaded: Vec<path::PathBuf>,
+        styles: ui::UiStyles,
+    ) -> Self {
+        if !vaults.iter().any(|(_name, path)| path == current) {
+            vaults.insert(
+                0,
+                (
+                    current
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    current.to_path_buf(),
+                ),
+            );
+        }
+
+        Self {
+            selected: vaults
+                .iter()
+                .position(|(_name, path)| path == current)
+                .unwrap_or(0),
+            vaults,
+            current: current.to_path_buf(),
+            loaded,
+            styles,
+        }
+    }
+}
+
+impl super::Screen for VaultScreen {
+    fn draw(&self, area: layout::Rect, buf: &mut buffer::Buffer) {
+        let rows = self
+            .vaults
+            .iter()
+            .map(|(name, path)| {
+                Row::new(vec![
+                    name.clone(),
+                    path.to_string_lossy().to_string(),
+                    if *path == self.current {
+                        String::from("opened")
+                    } else if self.loaded.contains(path) {
+                        String::from("indexed")
+                    } else {
+                        String::new()
+                    },
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        let instructions_bot_left = block::Title::from(Line::from(vec![
+            Span::styled("J", self.styles.hotkey_style),
+            Span::styled("/", self.styles.text_style),
+            Span::styled("K", self.styles.hotkey_style),
+            Span::styled(": Select──", self.styles.text_style),
+            Span::styled("󰌑", self.styles.hotkey_style),
+            Span::styled(": Switch──", self.styles.text_style),
+            Span::styled("Q", self.styles.hotkey_style),
+            Span::styled(": Back", self.styles.text_style),
+        ]))
+        .alignment(Alignment::Left)
+        .position(block::Position::Bottom);
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(25),
+                Constraint::Fill(1),
+                Constraint::Length(8),
+            ],
+        )
+        .style(self.styles.text_style)
+        .header(Row::new(vec![
+            Cell::from("Name").style(self.styles.subtitle_style),
+            Cell::from("Path").style(self.styles.subtitle_style),
+            Cell::from("Status").style(self.styles.subtitle_style),
+        ]))
+        .highlight_style(self.styles.selected_style)
+        .block(
+            Block::bordered()
+                .title(style::Styled::set_style("Vaults", self.styles.title_style))
+                .title(instructions_bot_left),
+        );
+
+        let mut state = TableState::new().with_selected(Some(self.selected));
+
+        StatefulWidget::render(table, area, buf, &mut state);
+    }
+
+    fn keybindings(&self) -> Vec<(String, &'static str)> {
+        super::live_keybindings(&BINDINGS, |code| self.action(code))
+    }
+
+    fn update(&mut self, key: crossterm::event::KeyEvent) -> error::Result<ui::Message> {
+        Ok(match self.action(key.code) {
+            // Back to the select screen
+            Some(Action::Close) => ui::Message::CloseVaults,
+            Some(Action::Help) => ui::Message::DisplayHelp,
+            // Selection
+            Some(Action::Select) => {
+                self.selected = match key.code {
+                    KeyCode::Char('j' | 'J') | KeyCode::Down => self
+                        .selected
+                        .saturating_add(1)
+                        .min(self.vaults.len().saturating_sub(1)),
+                    _ => self.selected.saturating_sub(1),
+                };
+                ui::Message::None
+            }
+            // Switch to the selected vault
+            Some(Action::Switch) => self
+                .vaults
+                .get(self.selected)
+                .map(|(_name, path)| ui::Message::SwitchVault(path.clone()))
+                .unwrap_or(ui::Message::None),
+            None => ui::Message::None,
+        })
+    }
+}
+
+impl VaultScreen {
+    /// Returns the action the given key triggers, if any.
+    fn action(&self, code: KeyCode) -> Option<Action> {
+        BINDINGS
+            .iter()
+            .find(|(_action, codes, _keys, _description)| codes.contains(&code))
+            .map(|(action, _codes, _keys, _description)| *action)
+    }
+}
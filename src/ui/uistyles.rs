@@ -5,6 +5,7 @@ use crate::error;
 /// A struct that holds a collection of styles for a consistent looking UI.
 /// This is a pure data struct, having no methods and only public attributes.
 #[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct UiStyles {
     /// For titles of boxes.
     pub title_style: Style,
@@ -18,6 +19,8 @@ pub struct UiStyles {
     pub selected_style: Style,
     /// For text in an input area.
     pub input_style: Style,
+    /// For notes pinned to the top of the list.
+    pub pinned_style: Style,
 }
 
 impl Default for UiStyles {
@@ -37,6 +40,7 @@ impl Default for UiStyles {
                 .bg(ratatui::style::Color::Blue)
                 .add_modifier(Modifier::BOLD),
             input_style: Style::new().add_modifier(Modifier::ITALIC),
+            pinned_style: Style::new().fg(Color::Yellow),
         }
     }
 }